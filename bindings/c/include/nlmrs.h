@@ -6,6 +6,24 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+// No error.
+#define NLMRS_OK 0
+
+// A parameter was out of range or of the wrong kind.
+#define NLMRS_ERR_INVALID_PARAMETER 1
+
+// `rows` or `cols` was zero.
+#define NLMRS_ERR_INVALID_DIMENSIONS 2
+
+// Two grids that must have the same shape did not.
+#define NLMRS_ERR_SHAPE_MISMATCH 3
+
+// No algorithm with the given name exists.
+#define NLMRS_ERR_UNKNOWN_ALGORITHM 4
+
+// Reading or writing a file failed.
+#define NLMRS_ERR_IO 5
+
 // A 2-D grid returned by every NLM generator.
 //
 // `data` points to a heap-allocated row-major array of `rows * cols` doubles
@@ -17,10 +35,22 @@ typedef struct NlmGrid {
     uintptr_t cols;
 } NlmGrid;
 
-// Free a grid returned by any `nlmrs_*` function.
+// Error code of the most recent failed call on this thread, or `NLMRS_OK`
+// if the most recent call succeeded.
+int32_t nlmrs_last_error_code(void);
+
+// Human-readable description of the most recent error on this thread, or
+// NULL if the most recent call succeeded.
+//
+// The string is owned by the library and stays valid until the next
+// `nlmrs_*` call on the same thread.
+const char *nlmrs_last_error_message(void);
+
+// Free a grid returned by any `nlmrs_*` function. Grids with NULL `data`,
+// returned on error, may be passed safely.
 //
 // Must be called exactly once per grid. Behaviour is undefined if called on a
-// zeroed struct or a grid that has already been freed.
+// grid that has already been freed.
 void nlmrs_free(struct NlmGrid grid);
 
 // Spatially random NLM. Values in [0, 1).
@@ -30,10 +60,21 @@ struct NlmGrid nlmrs_random(uintptr_t rows, uintptr_t cols, const uint64_t *seed
 
 // Random element nearest-neighbour NLM. Values in [0, 1).
 //
-// @param n     Number of labelled seed elements to place.
-// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+// @param n         Number of labelled seed elements to place.
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 struct NlmGrid nlmrs_random_element(uintptr_t rows, uintptr_t cols, double n, const uint64_t *seed);
 
+// Like `nlmrs_random_element`, with a choice of edge handling.
+//
+// @param n         Number of labelled seed elements to place.
+// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_random_element_with_boundary(uintptr_t rows,
+                                                  uintptr_t cols,
+                                                  double n,
+                                                  bool periodic,
+                                                  const uint64_t *seed);
+
 // Linear planar gradient. Values in [0, 1).
 //
 // @param direction  Pointer to gradient direction in degrees [0, 360), or NULL for random.
@@ -54,9 +95,18 @@ struct NlmGrid nlmrs_edge_gradient(uintptr_t rows,
 
 // Radial distance gradient from a random centre point. Values in [0, 1).
 //
-// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 struct NlmGrid nlmrs_distance_gradient(uintptr_t rows, uintptr_t cols, const uint64_t *seed);
 
+// Like `nlmrs_distance_gradient`, with a choice of edge handling.
+//
+// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_distance_gradient_with_boundary(uintptr_t rows,
+                                                     uintptr_t cols,
+                                                     bool periodic,
+                                                     const uint64_t *seed);
+
 // Sinusoidal wave gradient. Values in [0, 1).
 //
 // @param period     Wave period — smaller values produce larger waves.
@@ -70,13 +120,24 @@ struct NlmGrid nlmrs_wave_gradient(uintptr_t rows,
 
 // Diamond-square (midpoint displacement) fractal terrain. Values in [0, 1).
 //
-// @param h     Spatial autocorrelation — 0 = rough, 1 = smooth.
-// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+// @param h         Spatial autocorrelation — 0 = rough, 1 = smooth.
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 struct NlmGrid nlmrs_midpoint_displacement(uintptr_t rows,
                                            uintptr_t cols,
                                            double h,
                                            const uint64_t *seed);
 
+// Like `nlmrs_midpoint_displacement`, with a choice of edge handling.
+//
+// @param h         Spatial autocorrelation — 0 = rough, 1 = smooth.
+// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_midpoint_displacement_with_boundary(uintptr_t rows,
+                                                         uintptr_t cols,
+                                                         double h,
+                                                         bool periodic,
+                                                         const uint64_t *seed);
+
 // Hill-grow NLM. Values in [0, 1).
 //
 // @param n            Number of iterations.
@@ -94,6 +155,25 @@ struct NlmGrid nlmrs_hill_grow(uintptr_t rows,
                                bool only_grow,
                                const uint64_t *seed);
 
+// Like `nlmrs_hill_grow`, with a choice of edge handling.
+//
+// @param n            Number of iterations.
+// @param runaway      If non-zero, hills cluster via weighted sampling.
+// @param kernel_data  Flat row-major convolution kernel, or NULL for the default 3×3 diamond.
+// @param kernel_size  Side length of the square kernel (ignored when kernel_data is NULL).
+// @param only_grow    If non-zero the surface only accumulates, never shrinks.
+// @param periodic     If non-zero, opposite edges wrap so the grid tiles seamlessly.
+// @param seed         Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_hill_grow_with_boundary(uintptr_t rows,
+                                             uintptr_t cols,
+                                             uintptr_t n,
+                                             bool runaway,
+                                             const double *kernel_data,
+                                             uintptr_t kernel_size,
+                                             bool only_grow,
+                                             bool periodic,
+                                             const uint64_t *seed);
+
 // Single-layer Perlin noise. Values in [0, 1).
 //
 // @param scale  Noise frequency — higher values produce more features per unit area.
@@ -159,22 +239,44 @@ struct NlmGrid nlmrs_worley_noise(uintptr_t rows,
 
 // Gaussian random field — spatially correlated noise. Values in [0, 1).
 //
-// @param sigma  Gaussian kernel standard deviation in cells (controls correlation length).
-// @param seed   Pointer to a u64 seed, or NULL for a random seed.
+// @param sigma     Gaussian kernel standard deviation in cells (controls correlation length).
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 struct NlmGrid nlmrs_gaussian_field(uintptr_t rows,
                                     uintptr_t cols,
                                     double sigma,
                                     const uint64_t *seed);
 
+// Like `nlmrs_gaussian_field`, with a choice of edge handling.
+//
+// @param sigma     Gaussian kernel standard deviation in cells (controls correlation length).
+// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_gaussian_field_with_boundary(uintptr_t rows,
+                                                  uintptr_t cols,
+                                                  double sigma,
+                                                  bool periodic,
+                                                  const uint64_t *seed);
+
 // Random cluster NLM via fault-line cuts. Values in [0, 1).
 //
-// @param n     Number of fault-line cuts. Higher values produce finer-grained clusters.
-// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+// @param n         Number of fault-line cuts. Higher values produce finer-grained clusters.
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 struct NlmGrid nlmrs_random_cluster(uintptr_t rows,
                                     uintptr_t cols,
                                     uintptr_t n,
                                     const uint64_t *seed);
 
+// Like `nlmrs_random_cluster`, with a choice of edge handling.
+//
+// @param n         Number of fault-line cuts. Higher values produce finer-grained clusters.
+// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_random_cluster_with_boundary(uintptr_t rows,
+                                                  uintptr_t cols,
+                                                  uintptr_t n,
+                                                  bool periodic,
+                                                  const uint64_t *seed);
+
 // Hybrid multifractal noise. Values in [0, 1).
 //
 // @param scale       Base noise frequency.
@@ -227,10 +329,39 @@ struct NlmGrid nlmrs_domain_warp(uintptr_t rows,
 
 // Mosaic NLM — discrete Voronoi patch map with flat-coloured regions. Values in [0, 1).
 //
-// @param n     Number of Voronoi seed points to place.
-// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+// @param n         Number of Voronoi seed points to place.
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 struct NlmGrid nlmrs_mosaic(uintptr_t rows, uintptr_t cols, uintptr_t n, const uint64_t *seed);
 
+// Like `nlmrs_mosaic`, with a choice of edge handling.
+//
+// @param n         Number of Voronoi seed points to place.
+// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_mosaic_with_boundary(uintptr_t rows,
+                                          uintptr_t cols,
+                                          uintptr_t n,
+                                          bool periodic,
+                                          const uint64_t *seed);
+
+// Binary percolation NLM. Values in {0.0, 1.0}.
+//
+// Each cell is independently habitat (1.0) with probability `p`.
+// The critical percolation threshold for 4-connectivity is ~0.593.
+//
+// @param p     Habitat probability (0.0–1.0).
+// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_percolation(uintptr_t rows, uintptr_t cols, double p, const uint64_t *seed);
+
+// Binary space partitioning NLM — hierarchical rectilinear partition. Values in [0, 1).
+//
+// @param n     Number of rectangles in the final partition.
+// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_binary_space_partitioning(uintptr_t rows,
+                                               uintptr_t cols,
+                                               uintptr_t n,
+                                               const uint64_t *seed);
+
 // Rectangular cluster NLM — overlapping random axis-aligned rectangles. Values in [0, 1).
 //
 // @param n     Number of rectangles to place.
@@ -240,4 +371,218 @@ struct NlmGrid nlmrs_rectangular_cluster(uintptr_t rows,
                                          uintptr_t n,
                                          const uint64_t *seed);
 
+// Cellular automaton NLM — binary cave-like patterns from birth/survival rules. Values in {0.0, 1.0}.
+//
+// @param p                  Initial alive probability.
+// @param iterations         Number of rule applications.
+// @param birth_threshold    Min live neighbours to birth a dead cell.
+// @param survival_threshold Min live neighbours for a live cell to survive.
+// @param seed               Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_cellular_automaton(uintptr_t rows,
+                                        uintptr_t cols,
+                                        double p,
+                                        uintptr_t iterations,
+                                        uintptr_t birth_threshold,
+                                        uintptr_t survival_threshold,
+                                        const uint64_t *seed);
+
+// Neighbourhood clustering NLM — iterative majority-vote patch clustering. Values in [0, 1).
+//
+// @param k           Number of distinct patch classes (>= 2).
+// @param iterations  Number of majority-vote passes.
+// @param seed        Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_neighbourhood_clustering(uintptr_t rows,
+                                              uintptr_t cols,
+                                              uintptr_t k,
+                                              uintptr_t iterations,
+                                              const uint64_t *seed);
+
+// Like `nlmrs_neighbourhood_clustering`, with a choice of edge handling.
+//
+// @param k           Number of distinct patch classes (>= 2).
+// @param iterations  Number of majority-vote passes.
+// @param periodic    If non-zero, opposite edges wrap so the grid tiles seamlessly.
+// @param seed        Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_neighbourhood_clustering_with_boundary(uintptr_t rows,
+                                                            uintptr_t cols,
+                                                            uintptr_t k,
+                                                            uintptr_t iterations,
+                                                            bool periodic,
+                                                            const uint64_t *seed);
+
+// Spectral synthesis NLM — 1/f^beta noise generated in the frequency domain. Values in [0, 1).
+//
+// @param beta  Spectral exponent: 0 = white noise, 1 = pink, 2 = brown/natural terrain.
+// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_spectral_synthesis(uintptr_t rows,
+                                        uintptr_t cols,
+                                        double beta,
+                                        const uint64_t *seed);
+
+// Gray-Scott reaction-diffusion NLM — Turing-pattern spots, stripes and labyrinths. Values in [0, 1).
+//
+// @param iterations Number of simulation steps.
+// @param feed       Feed rate for chemical A (controls pattern type).
+// @param kill       Kill rate for chemical B (controls pattern type).
+// @param seed       Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_reaction_diffusion(uintptr_t rows,
+                                        uintptr_t cols,
+                                        uintptr_t iterations,
+                                        double feed,
+                                        double kill,
+                                        const uint64_t *seed);
+
+// Eden growth model NLM — compact fractal blob grown from the centre. Values in {0.0, 1.0}.
+//
+// @param n     Number of cells to add to the cluster.
+// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_eden_growth(uintptr_t rows, uintptr_t cols, uintptr_t n, const uint64_t *seed);
+
+// Fractal Brownian surface NLM — parameterised by Hurst exponent. Values in [0, 1).
+//
+// @param h     Hurst exponent in (0, 1): 0 = rough, 1 = smooth.
+// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_fractal_brownian_surface(uintptr_t rows,
+                                              uintptr_t cols,
+                                              double h,
+                                              const uint64_t *seed);
+
+// Elliptical landscape gradient centred at the grid midpoint. Values in [0, 1).
+//
+// @param direction  Pointer to major-axis orientation in degrees [0, 360), or NULL for random.
+// @param aspect     Major-to-minor axis ratio (≥ 1.0). 1.0 = circular.
+// @param seed       Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_landscape_gradient(uintptr_t rows,
+                                        uintptr_t cols,
+                                        const double *direction,
+                                        double aspect,
+                                        const uint64_t *seed);
+
+// Diffusion-limited aggregation NLM — branching fractal cluster grown from the centre. Values in {0.0, 1.0}.
+//
+// @param n     Number of particles to release.
+// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_diffusion_limited_aggregation(uintptr_t rows,
+                                                   uintptr_t cols,
+                                                   uintptr_t n,
+                                                   const uint64_t *seed);
+
+// OpenSimplex noise NLM. Values in [0, 1).
+//
+// @param scale  Coordinate frequency.
+// @param seed   Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_simplex_noise(uintptr_t rows,
+                                   uintptr_t cols,
+                                   double scale,
+                                   const uint64_t *seed);
+
+// Invasion percolation NLM — lowest-weight boundary growth from centre. Values in {0.0, 1.0}.
+//
+// @param n    Number of cells to invade.
+// @param seed Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_invasion_percolation(uintptr_t rows,
+                                          uintptr_t cols,
+                                          uintptr_t n,
+                                          const uint64_t *seed);
+
+// Sum of random Gaussian blob kernels. Values in [0, 1).
+//
+// @param n     Number of blob centres.
+// @param sigma Gaussian width in cells.
+// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_gaussian_blobs(uintptr_t rows,
+                                    uintptr_t cols,
+                                    uintptr_t n,
+                                    double sigma,
+                                    const uint64_t *seed);
+
+// Ising model via Glauber dynamics. Binary values {0.0, 1.0}.
+//
+// @param beta       Inverse temperature (near 0.44 = critical point).
+// @param iterations Number of sweeps (each = rows × cols spin-flip attempts).
+// @param seed       Pointer to a u64 seed, or NULL for a random seed.
+struct NlmGrid nlmrs_ising_model(uintptr_t rows,
+                                 uintptr_t cols,
+                                 double beta,
+                                 uintptr_t iterations,
+                                 const uint64_t *seed);
+
+struct NlmGrid nlmrs_voronoi_distance(uintptr_t rows,
+                                      uintptr_t cols,
+                                      uintptr_t n,
+                                      const uint64_t *seed);
+
+struct NlmGrid nlmrs_sine_composite(uintptr_t rows,
+                                    uintptr_t cols,
+                                    uintptr_t waves,
+                                    const uint64_t *seed);
+
+struct NlmGrid nlmrs_curl_noise(uintptr_t rows, uintptr_t cols, double scale, const uint64_t *seed);
+
+struct NlmGrid nlmrs_hydraulic_erosion(uintptr_t rows,
+                                       uintptr_t cols,
+                                       uintptr_t n,
+                                       const uint64_t *seed);
+
+struct NlmGrid nlmrs_levy_flight(uintptr_t rows, uintptr_t cols, uintptr_t n, const uint64_t *seed);
+
+struct NlmGrid nlmrs_poisson_disk(uintptr_t rows,
+                                  uintptr_t cols,
+                                  double min_dist,
+                                  const uint64_t *seed);
+
+struct NlmGrid nlmrs_gabor_noise(uintptr_t rows,
+                                 uintptr_t cols,
+                                 double scale,
+                                 uintptr_t n,
+                                 const uint64_t *seed);
+
+struct NlmGrid nlmrs_spot_noise(uintptr_t rows, uintptr_t cols, uintptr_t n, const uint64_t *seed);
+
+struct NlmGrid nlmrs_anisotropic_noise(uintptr_t rows,
+                                       uintptr_t cols,
+                                       double scale,
+                                       uintptr_t octaves,
+                                       double direction,
+                                       double stretch,
+                                       const uint64_t *seed);
+
+struct NlmGrid nlmrs_tiled_noise(uintptr_t rows,
+                                 uintptr_t cols,
+                                 double scale,
+                                 const uint64_t *seed);
+
+struct NlmGrid nlmrs_brownian_motion(uintptr_t rows,
+                                     uintptr_t cols,
+                                     uintptr_t n,
+                                     const uint64_t *seed);
+
+struct NlmGrid nlmrs_forest_fire(uintptr_t rows,
+                                 uintptr_t cols,
+                                 double p_tree,
+                                 double p_lightning,
+                                 uintptr_t iterations,
+                                 const uint64_t *seed);
+
+struct NlmGrid nlmrs_river_network(uintptr_t rows, uintptr_t cols, const uint64_t *seed);
+
+struct NlmGrid nlmrs_hexagonal_voronoi(uintptr_t rows,
+                                       uintptr_t cols,
+                                       uintptr_t n,
+                                       const uint64_t *seed);
+
+// Classify a grid in place by rank into classes covering the given proportions.
+//
+// Cells are ordered by value; the lowest `weights[0] / sum(weights)` share
+// becomes class 0, the next share class 1, and so on. Class `k` is written as
+// `k / (n_weights - 1)`. Weights need not sum to 1.
+//
+// @param grid       Grid returned by any `nlmrs_*` generator; modified in place.
+// @param weights    Pointer to `n_weights` non-negative class weights.
+// @param n_weights  Number of classes.
+// @return 0 on success, -1 if `grid` or `weights` is NULL, or the weights are
+//         empty, negative or sum to zero. Invalid weights also set
+//         `NLMRS_ERR_INVALID_PARAMETER`.
+int32_t nlmrs_classify_weighted(struct NlmGrid *grid, const double *weights, uintptr_t n_weights);
+
 #endif /* NLMRS_H */
@@ -6,8 +6,16 @@
 //!     → target/release/libnlmrs_c.a    (Linux static)
 //!
 //! The generated header is written to include/nlmrs.h during the build.
+//!
+//! Generators never abort the host process on invalid input. Instead they
+//! return a grid with NULL `data` and record an error that can be queried with
+//! `nlmrs_last_error_code` and `nlmrs_last_error_message`.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::mem::ManuallyDrop;
 
-use nlmrs::Grid;
+use nlmrs::{Boundary, Grid, NlmError};
 
 // ── Return type ───────────────────────────────────────────────────────────────
 
@@ -35,15 +43,87 @@ impl NlmGrid {
     }
 }
 
+impl NlmGrid {
+    /// Converts a fallible result, recording the error and returning an empty
+    /// grid with NULL `data` on failure.
+    fn from_result(result: Result<Grid, NlmError>) -> Self {
+        match result {
+            Ok(grid) => {
+                clear_error();
+                NlmGrid::from_grid(grid)
+            }
+            Err(e) => {
+                set_error(&e);
+                NlmGrid { data: std::ptr::null_mut(), rows: 0, cols: 0 }
+            }
+        }
+    }
+}
+
 // Safety: NlmGrid is an owned pointer; the caller controls lifetime.
 unsafe impl Send for NlmGrid {}
 
+// ── Errors ────────────────────────────────────────────────────────────────────
+
+/// No error.
+pub const NLMRS_OK: i32 = 0;
+/// A parameter was out of range or of the wrong kind.
+pub const NLMRS_ERR_INVALID_PARAMETER: i32 = 1;
+/// `rows` or `cols` was zero.
+pub const NLMRS_ERR_INVALID_DIMENSIONS: i32 = 2;
+/// Two grids that must have the same shape did not.
+pub const NLMRS_ERR_SHAPE_MISMATCH: i32 = 3;
+/// No algorithm with the given name exists.
+pub const NLMRS_ERR_UNKNOWN_ALGORITHM: i32 = 4;
+/// Reading or writing a file failed.
+pub const NLMRS_ERR_IO: i32 = 5;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<(i32, CString)>> = const { RefCell::new(None) };
+}
+
+fn set_error(e: &NlmError) {
+    let code = match e {
+        NlmError::InvalidParameter { .. } | NlmError::Config(_) => NLMRS_ERR_INVALID_PARAMETER,
+        NlmError::InvalidDimensions { .. } => NLMRS_ERR_INVALID_DIMENSIONS,
+        NlmError::ShapeMismatch { .. } => NLMRS_ERR_SHAPE_MISMATCH,
+        NlmError::UnknownAlgorithm(_) => NLMRS_ERR_UNKNOWN_ALGORITHM,
+        NlmError::Io(_) => NLMRS_ERR_IO,
+    };
+    let message = CString::new(e.to_string().replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some((code, message)));
+}
+
+fn clear_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// Error code of the most recent failed call on this thread, or `NLMRS_OK`
+/// if the most recent call succeeded.
+#[no_mangle]
+pub extern "C" fn nlmrs_last_error_code() -> i32 {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(NLMRS_OK, |(code, _)| *code))
+}
+
+/// Human-readable description of the most recent error on this thread, or
+/// NULL if the most recent call succeeded.
+///
+/// The string is owned by the library and stays valid until the next
+/// `nlmrs_*` call on the same thread.
+#[no_mangle]
+pub extern "C" fn nlmrs_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow().as_ref().map_or(std::ptr::null(), |(_, message)| message.as_ptr())
+    })
+}
+
 // ── Memory management ─────────────────────────────────────────────────────────
 
-/// Free a grid returned by any `nlmrs_*` function.
+/// Free a grid returned by any `nlmrs_*` function. Grids with NULL `data`,
+/// returned on error, may be passed safely.
 ///
 /// Must be called exactly once per grid. Behaviour is undefined if called on a
-/// zeroed struct or a grid that has already been freed.
+/// grid that has already been freed.
 #[no_mangle]
 pub extern "C" fn nlmrs_free(grid: NlmGrid) {
     if !grid.data.is_null() {
@@ -59,11 +139,25 @@ fn opt_seed(seed: *const u64) -> Option<u64> {
     if seed.is_null() { None } else { Some(unsafe { *seed }) }
 }
 
+#[inline]
+fn boundary(periodic: bool) -> Boundary {
+    if periodic {
+        Boundary::Periodic
+    } else {
+        Boundary::Clamped
+    }
+}
+
 #[inline]
 fn opt_f64(ptr: *const f64) -> Option<f64> {
     if ptr.is_null() { None } else { Some(unsafe { *ptr }) }
 }
 
+#[inline]
+fn opt_slice<'a>(data: *const f64, len: usize) -> Option<&'a [f64]> {
+    if data.is_null() { None } else { Some(unsafe { std::slice::from_raw_parts(data, len) }) }
+}
+
 #[inline]
 fn opt_kernel(data: *const f64, size: usize) -> Option<Vec<Vec<f64>>> {
     if data.is_null() {
@@ -81,13 +175,13 @@ fn opt_kernel(data: *const f64, size: usize) -> Option<Vec<Vec<f64>>> {
 /// @param seed  Pointer to a u64 seed, or NULL for a random seed.
 #[no_mangle]
 pub extern "C" fn nlmrs_random(rows: usize, cols: usize, seed: *const u64) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::random(rows, cols, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_random(rows, cols, opt_seed(seed)))
 }
 
 /// Random element nearest-neighbour NLM. Values in [0, 1).
 ///
-/// @param n     Number of labelled seed elements to place.
-/// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+/// @param n         Number of labelled seed elements to place.
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 #[no_mangle]
 pub extern "C" fn nlmrs_random_element(
     rows: usize,
@@ -95,7 +189,23 @@ pub extern "C" fn nlmrs_random_element(
     n: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::random_element(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_random_element(rows, cols, n, opt_seed(seed)))
+}
+
+/// Like `nlmrs_random_element`, with a choice of edge handling.
+///
+/// @param n         Number of labelled seed elements to place.
+/// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+#[no_mangle]
+pub extern "C" fn nlmrs_random_element_with_boundary(
+    rows: usize,
+    cols: usize,
+    n: f64,
+    periodic: bool,
+    seed: *const u64,
+) -> NlmGrid {
+    NlmGrid::from_result(nlmrs::try_random_element_with_boundary(rows, cols, n, boundary(periodic), opt_seed(seed)))
 }
 
 /// Linear planar gradient. Values in [0, 1).
@@ -109,7 +219,7 @@ pub extern "C" fn nlmrs_planar_gradient(
     direction: *const f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::planar_gradient(rows, cols, opt_f64(direction), opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_planar_gradient(rows, cols, opt_f64(direction), opt_seed(seed)))
 }
 
 /// Symmetric edge gradient — zero at both edges, peak in the middle. Values in [0, 1).
@@ -123,19 +233,33 @@ pub extern "C" fn nlmrs_edge_gradient(
     direction: *const f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::edge_gradient(rows, cols, opt_f64(direction), opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_edge_gradient(rows, cols, opt_f64(direction), opt_seed(seed)))
 }
 
 /// Radial distance gradient from a random centre point. Values in [0, 1).
 ///
-/// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 #[no_mangle]
 pub extern "C" fn nlmrs_distance_gradient(
     rows: usize,
     cols: usize,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::distance_gradient(rows, cols, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_distance_gradient(rows, cols, opt_seed(seed)))
+}
+
+/// Like `nlmrs_distance_gradient`, with a choice of edge handling.
+///
+/// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+#[no_mangle]
+pub extern "C" fn nlmrs_distance_gradient_with_boundary(
+    rows: usize,
+    cols: usize,
+    periodic: bool,
+    seed: *const u64,
+) -> NlmGrid {
+    NlmGrid::from_result(nlmrs::try_distance_gradient_with_boundary(rows, cols, boundary(periodic), opt_seed(seed)))
 }
 
 /// Sinusoidal wave gradient. Values in [0, 1).
@@ -151,13 +275,13 @@ pub extern "C" fn nlmrs_wave_gradient(
     direction: *const f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::wave_gradient(rows, cols, period, opt_f64(direction), opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_wave_gradient(rows, cols, period, opt_f64(direction), opt_seed(seed)))
 }
 
 /// Diamond-square (midpoint displacement) fractal terrain. Values in [0, 1).
 ///
-/// @param h     Spatial autocorrelation — 0 = rough, 1 = smooth.
-/// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+/// @param h         Spatial autocorrelation — 0 = rough, 1 = smooth.
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 #[no_mangle]
 pub extern "C" fn nlmrs_midpoint_displacement(
     rows: usize,
@@ -165,7 +289,23 @@ pub extern "C" fn nlmrs_midpoint_displacement(
     h: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::midpoint_displacement(rows, cols, h, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_midpoint_displacement(rows, cols, h, opt_seed(seed)))
+}
+
+/// Like `nlmrs_midpoint_displacement`, with a choice of edge handling.
+///
+/// @param h         Spatial autocorrelation — 0 = rough, 1 = smooth.
+/// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+#[no_mangle]
+pub extern "C" fn nlmrs_midpoint_displacement_with_boundary(
+    rows: usize,
+    cols: usize,
+    h: f64,
+    periodic: bool,
+    seed: *const u64,
+) -> NlmGrid {
+    NlmGrid::from_result(nlmrs::try_midpoint_displacement_with_boundary(rows, cols, h, boundary(periodic), opt_seed(seed)))
 }
 
 /// Hill-grow NLM. Values in [0, 1).
@@ -187,13 +327,41 @@ pub extern "C" fn nlmrs_hill_grow(
     only_grow: bool,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::hill_grow(
+    NlmGrid::from_result(nlmrs::try_hill_grow(
         rows, cols, n, runaway,
         opt_kernel(kernel_data, kernel_size),
         only_grow, opt_seed(seed),
     ))
 }
 
+/// Like `nlmrs_hill_grow`, with a choice of edge handling.
+///
+/// @param n            Number of iterations.
+/// @param runaway      If non-zero, hills cluster via weighted sampling.
+/// @param kernel_data  Flat row-major convolution kernel, or NULL for the default 3×3 diamond.
+/// @param kernel_size  Side length of the square kernel (ignored when kernel_data is NULL).
+/// @param only_grow    If non-zero the surface only accumulates, never shrinks.
+/// @param periodic     If non-zero, opposite edges wrap so the grid tiles seamlessly.
+/// @param seed         Pointer to a u64 seed, or NULL for a random seed.
+#[no_mangle]
+pub extern "C" fn nlmrs_hill_grow_with_boundary(
+    rows: usize,
+    cols: usize,
+    n: usize,
+    runaway: bool,
+    kernel_data: *const f64,
+    kernel_size: usize,
+    only_grow: bool,
+    periodic: bool,
+    seed: *const u64,
+) -> NlmGrid {
+    NlmGrid::from_result(nlmrs::try_hill_grow_with_boundary(
+        rows, cols, n, runaway,
+        opt_kernel(kernel_data, kernel_size),
+        only_grow, boundary(periodic), opt_seed(seed),
+    ))
+}
+
 /// Single-layer Perlin noise. Values in [0, 1).
 ///
 /// @param scale  Noise frequency — higher values produce more features per unit area.
@@ -205,7 +373,7 @@ pub extern "C" fn nlmrs_perlin_noise(
     scale: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::perlin_noise(rows, cols, scale, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_perlin_noise(rows, cols, scale, opt_seed(seed)))
 }
 
 /// Fractal Brownian motion — layered Perlin noise. Values in [0, 1).
@@ -225,7 +393,7 @@ pub extern "C" fn nlmrs_fbm_noise(
     lacunarity: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::fbm_noise(rows, cols, scale, octaves, persistence, lacunarity, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_fbm_noise(rows, cols, scale, octaves, persistence, lacunarity, opt_seed(seed)))
 }
 
 /// Ridged multifractal noise. Values in [0, 1).
@@ -245,7 +413,7 @@ pub extern "C" fn nlmrs_ridged_noise(
     lacunarity: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::ridged_noise(rows, cols, scale, octaves, persistence, lacunarity, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_ridged_noise(rows, cols, scale, octaves, persistence, lacunarity, opt_seed(seed)))
 }
 
 /// Billow noise — rounded cloud- and hill-like patterns. Values in [0, 1).
@@ -265,7 +433,7 @@ pub extern "C" fn nlmrs_billow_noise(
     lacunarity: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::billow_noise(rows, cols, scale, octaves, persistence, lacunarity, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_billow_noise(rows, cols, scale, octaves, persistence, lacunarity, opt_seed(seed)))
 }
 
 /// Worley (cellular) noise — territory / patch patterns. Values in [0, 1).
@@ -279,13 +447,13 @@ pub extern "C" fn nlmrs_worley_noise(
     scale: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::worley_noise(rows, cols, scale, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_worley_noise(rows, cols, scale, opt_seed(seed)))
 }
 
 /// Gaussian random field — spatially correlated noise. Values in [0, 1).
 ///
-/// @param sigma  Gaussian kernel standard deviation in cells (controls correlation length).
-/// @param seed   Pointer to a u64 seed, or NULL for a random seed.
+/// @param sigma     Gaussian kernel standard deviation in cells (controls correlation length).
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 #[no_mangle]
 pub extern "C" fn nlmrs_gaussian_field(
     rows: usize,
@@ -293,13 +461,29 @@ pub extern "C" fn nlmrs_gaussian_field(
     sigma: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::gaussian_field(rows, cols, sigma, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_gaussian_field(rows, cols, sigma, opt_seed(seed)))
+}
+
+/// Like `nlmrs_gaussian_field`, with a choice of edge handling.
+///
+/// @param sigma     Gaussian kernel standard deviation in cells (controls correlation length).
+/// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+#[no_mangle]
+pub extern "C" fn nlmrs_gaussian_field_with_boundary(
+    rows: usize,
+    cols: usize,
+    sigma: f64,
+    periodic: bool,
+    seed: *const u64,
+) -> NlmGrid {
+    NlmGrid::from_result(nlmrs::try_gaussian_field_with_boundary(rows, cols, sigma, boundary(periodic), opt_seed(seed)))
 }
 
 /// Random cluster NLM via fault-line cuts. Values in [0, 1).
 ///
-/// @param n     Number of fault-line cuts. Higher values produce finer-grained clusters.
-/// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+/// @param n         Number of fault-line cuts. Higher values produce finer-grained clusters.
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 #[no_mangle]
 pub extern "C" fn nlmrs_random_cluster(
     rows: usize,
@@ -307,7 +491,23 @@ pub extern "C" fn nlmrs_random_cluster(
     n: usize,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::random_cluster(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_random_cluster(rows, cols, n, opt_seed(seed)))
+}
+
+/// Like `nlmrs_random_cluster`, with a choice of edge handling.
+///
+/// @param n         Number of fault-line cuts. Higher values produce finer-grained clusters.
+/// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+#[no_mangle]
+pub extern "C" fn nlmrs_random_cluster_with_boundary(
+    rows: usize,
+    cols: usize,
+    n: usize,
+    periodic: bool,
+    seed: *const u64,
+) -> NlmGrid {
+    NlmGrid::from_result(nlmrs::try_random_cluster_with_boundary(rows, cols, n, boundary(periodic), opt_seed(seed)))
 }
 
 /// Hybrid multifractal noise. Values in [0, 1).
@@ -327,7 +527,7 @@ pub extern "C" fn nlmrs_hybrid_noise(
     lacunarity: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::hybrid_noise(rows, cols, scale, octaves, persistence, lacunarity, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_hybrid_noise(rows, cols, scale, octaves, persistence, lacunarity, opt_seed(seed)))
 }
 
 /// Value noise — interpolated lattice noise. Values in [0, 1).
@@ -341,7 +541,7 @@ pub extern "C" fn nlmrs_value_noise(
     scale: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::value_noise(rows, cols, scale, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_value_noise(rows, cols, scale, opt_seed(seed)))
 }
 
 /// Turbulence — fBm with absolute-value fold per octave. Values in [0, 1).
@@ -361,7 +561,7 @@ pub extern "C" fn nlmrs_turbulence(
     lacunarity: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::turbulence(rows, cols, scale, octaves, persistence, lacunarity, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_turbulence(rows, cols, scale, octaves, persistence, lacunarity, opt_seed(seed)))
 }
 
 /// Domain-warped Perlin noise — organic, swirling patterns. Values in [0, 1).
@@ -377,13 +577,13 @@ pub extern "C" fn nlmrs_domain_warp(
     warp_strength: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::domain_warp(rows, cols, scale, warp_strength, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_domain_warp(rows, cols, scale, warp_strength, opt_seed(seed)))
 }
 
 /// Mosaic NLM — discrete Voronoi patch map with flat-coloured regions. Values in [0, 1).
 ///
-/// @param n     Number of Voronoi seed points to place.
-/// @param seed  Pointer to a u64 seed, or NULL for a random seed.
+/// @param n         Number of Voronoi seed points to place.
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
 #[no_mangle]
 pub extern "C" fn nlmrs_mosaic(
     rows: usize,
@@ -391,7 +591,23 @@ pub extern "C" fn nlmrs_mosaic(
     n: usize,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::mosaic(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_mosaic(rows, cols, n, opt_seed(seed)))
+}
+
+/// Like `nlmrs_mosaic`, with a choice of edge handling.
+///
+/// @param n         Number of Voronoi seed points to place.
+/// @param periodic  If non-zero, opposite edges wrap so the grid tiles seamlessly.
+/// @param seed      Pointer to a u64 seed, or NULL for a random seed.
+#[no_mangle]
+pub extern "C" fn nlmrs_mosaic_with_boundary(
+    rows: usize,
+    cols: usize,
+    n: usize,
+    periodic: bool,
+    seed: *const u64,
+) -> NlmGrid {
+    NlmGrid::from_result(nlmrs::try_mosaic_with_boundary(rows, cols, n, boundary(periodic), opt_seed(seed)))
 }
 
 /// Binary percolation NLM. Values in {0.0, 1.0}.
@@ -408,7 +624,7 @@ pub extern "C" fn nlmrs_percolation(
     p: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::percolation(rows, cols, p, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_percolation(rows, cols, p, opt_seed(seed)))
 }
 
 /// Binary space partitioning NLM — hierarchical rectilinear partition. Values in [0, 1).
@@ -422,7 +638,7 @@ pub extern "C" fn nlmrs_binary_space_partitioning(
     n: usize,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::binary_space_partitioning(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_binary_space_partitioning(rows, cols, n, opt_seed(seed)))
 }
 
 /// Rectangular cluster NLM — overlapping random axis-aligned rectangles. Values in [0, 1).
@@ -436,7 +652,7 @@ pub extern "C" fn nlmrs_rectangular_cluster(
     n: usize,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::rectangular_cluster(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_rectangular_cluster(rows, cols, n, opt_seed(seed)))
 }
 
 /// Cellular automaton NLM — binary cave-like patterns from birth/survival rules. Values in {0.0, 1.0}.
@@ -456,16 +672,16 @@ pub extern "C" fn nlmrs_cellular_automaton(
     survival_threshold: usize,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::cellular_automaton(
+    NlmGrid::from_result(nlmrs::try_cellular_automaton(
         rows, cols, p, iterations, birth_threshold, survival_threshold, opt_seed(seed),
     ))
 }
 
 /// Neighbourhood clustering NLM — iterative majority-vote patch clustering. Values in [0, 1).
 ///
-/// @param k          Number of distinct patch classes (>= 2).
-/// @param iterations Number of majority-vote passes.
-/// @param seed       Pointer to a u64 seed, or NULL for a random seed.
+/// @param k           Number of distinct patch classes (>= 2).
+/// @param iterations  Number of majority-vote passes.
+/// @param seed        Pointer to a u64 seed, or NULL for a random seed.
 #[no_mangle]
 pub extern "C" fn nlmrs_neighbourhood_clustering(
     rows: usize,
@@ -474,7 +690,25 @@ pub extern "C" fn nlmrs_neighbourhood_clustering(
     iterations: usize,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::neighbourhood_clustering(rows, cols, k, iterations, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_neighbourhood_clustering(rows, cols, k, iterations, opt_seed(seed)))
+}
+
+/// Like `nlmrs_neighbourhood_clustering`, with a choice of edge handling.
+///
+/// @param k           Number of distinct patch classes (>= 2).
+/// @param iterations  Number of majority-vote passes.
+/// @param periodic    If non-zero, opposite edges wrap so the grid tiles seamlessly.
+/// @param seed        Pointer to a u64 seed, or NULL for a random seed.
+#[no_mangle]
+pub extern "C" fn nlmrs_neighbourhood_clustering_with_boundary(
+    rows: usize,
+    cols: usize,
+    k: usize,
+    iterations: usize,
+    periodic: bool,
+    seed: *const u64,
+) -> NlmGrid {
+    NlmGrid::from_result(nlmrs::try_neighbourhood_clustering_with_boundary(rows, cols, k, iterations, boundary(periodic), opt_seed(seed)))
 }
 
 /// Spectral synthesis NLM — 1/f^beta noise generated in the frequency domain. Values in [0, 1).
@@ -488,7 +722,7 @@ pub extern "C" fn nlmrs_spectral_synthesis(
     beta: f64,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::spectral_synthesis(rows, cols, beta, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_spectral_synthesis(rows, cols, beta, opt_seed(seed)))
 }
 
 /// Gray-Scott reaction-diffusion NLM — Turing-pattern spots, stripes and labyrinths. Values in [0, 1).
@@ -501,7 +735,7 @@ pub extern "C" fn nlmrs_spectral_synthesis(
 pub extern "C" fn nlmrs_reaction_diffusion(
     rows: usize, cols: usize, iterations: usize, feed: f64, kill: f64, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::reaction_diffusion(rows, cols, iterations, feed, kill, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_reaction_diffusion(rows, cols, iterations, feed, kill, opt_seed(seed)))
 }
 
 /// Eden growth model NLM — compact fractal blob grown from the centre. Values in {0.0, 1.0}.
@@ -512,7 +746,7 @@ pub extern "C" fn nlmrs_reaction_diffusion(
 pub extern "C" fn nlmrs_eden_growth(
     rows: usize, cols: usize, n: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::eden_growth(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_eden_growth(rows, cols, n, opt_seed(seed)))
 }
 
 /// Fractal Brownian surface NLM — parameterised by Hurst exponent. Values in [0, 1).
@@ -523,7 +757,7 @@ pub extern "C" fn nlmrs_eden_growth(
 pub extern "C" fn nlmrs_fractal_brownian_surface(
     rows: usize, cols: usize, h: f64, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::fractal_brownian_surface(rows, cols, h, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_fractal_brownian_surface(rows, cols, h, opt_seed(seed)))
 }
 
 /// Elliptical landscape gradient centred at the grid midpoint. Values in [0, 1).
@@ -535,7 +769,7 @@ pub extern "C" fn nlmrs_fractal_brownian_surface(
 pub extern "C" fn nlmrs_landscape_gradient(
     rows: usize, cols: usize, direction: *const f64, aspect: f64, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::landscape_gradient(rows, cols, opt_f64(direction), aspect, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_landscape_gradient(rows, cols, opt_f64(direction), aspect, opt_seed(seed)))
 }
 
 /// Diffusion-limited aggregation NLM — branching fractal cluster grown from the centre. Values in {0.0, 1.0}.
@@ -549,7 +783,7 @@ pub extern "C" fn nlmrs_diffusion_limited_aggregation(
     n: usize,
     seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::diffusion_limited_aggregation(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_diffusion_limited_aggregation(rows, cols, n, opt_seed(seed)))
 }
 
 /// OpenSimplex noise NLM. Values in [0, 1).
@@ -560,7 +794,7 @@ pub extern "C" fn nlmrs_diffusion_limited_aggregation(
 pub extern "C" fn nlmrs_simplex_noise(
     rows: usize, cols: usize, scale: f64, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::simplex_noise(rows, cols, scale, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_simplex_noise(rows, cols, scale, opt_seed(seed)))
 }
 
 /// Invasion percolation NLM — lowest-weight boundary growth from centre. Values in {0.0, 1.0}.
@@ -571,7 +805,7 @@ pub extern "C" fn nlmrs_simplex_noise(
 pub extern "C" fn nlmrs_invasion_percolation(
     rows: usize, cols: usize, n: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::invasion_percolation(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_invasion_percolation(rows, cols, n, opt_seed(seed)))
 }
 
 /// Sum of random Gaussian blob kernels. Values in [0, 1).
@@ -583,7 +817,7 @@ pub extern "C" fn nlmrs_invasion_percolation(
 pub extern "C" fn nlmrs_gaussian_blobs(
     rows: usize, cols: usize, n: usize, sigma: f64, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::gaussian_blobs(rows, cols, n, sigma, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_gaussian_blobs(rows, cols, n, sigma, opt_seed(seed)))
 }
 
 /// Ising model via Glauber dynamics. Binary values {0.0, 1.0}.
@@ -595,103 +829,165 @@ pub extern "C" fn nlmrs_gaussian_blobs(
 pub extern "C" fn nlmrs_ising_model(
     rows: usize, cols: usize, beta: f64, iterations: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::ising_model(rows, cols, beta, iterations, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_ising_model(rows, cols, beta, iterations, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_voronoi_distance(
     rows: usize, cols: usize, n: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::voronoi_distance(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_voronoi_distance(rows, cols, n, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_sine_composite(
     rows: usize, cols: usize, waves: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::sine_composite(rows, cols, waves, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_sine_composite(rows, cols, waves, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_curl_noise(
     rows: usize, cols: usize, scale: f64, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::curl_noise(rows, cols, scale, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_curl_noise(rows, cols, scale, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_hydraulic_erosion(
     rows: usize, cols: usize, n: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::hydraulic_erosion(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_hydraulic_erosion(rows, cols, n, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_levy_flight(
     rows: usize, cols: usize, n: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::levy_flight(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_levy_flight(rows, cols, n, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_poisson_disk(
     rows: usize, cols: usize, min_dist: f64, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::poisson_disk(rows, cols, min_dist, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_poisson_disk(rows, cols, min_dist, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_gabor_noise(
     rows: usize, cols: usize, scale: f64, n: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::gabor_noise(rows, cols, scale, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_gabor_noise(rows, cols, scale, n, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_spot_noise(
     rows: usize, cols: usize, n: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::spot_noise(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_spot_noise(rows, cols, n, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_anisotropic_noise(
     rows: usize, cols: usize, scale: f64, octaves: usize, direction: f64, stretch: f64, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::anisotropic_noise(rows, cols, scale, octaves, direction, stretch, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_anisotropic_noise(rows, cols, scale, octaves, direction, stretch, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_tiled_noise(
     rows: usize, cols: usize, scale: f64, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::tiled_noise(rows, cols, scale, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_tiled_noise(rows, cols, scale, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_brownian_motion(
     rows: usize, cols: usize, n: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::brownian_motion(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_brownian_motion(rows, cols, n, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_forest_fire(
     rows: usize, cols: usize, p_tree: f64, p_lightning: f64, iterations: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::forest_fire(rows, cols, p_tree, p_lightning, iterations, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_forest_fire(rows, cols, p_tree, p_lightning, iterations, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_river_network(
     rows: usize, cols: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::river_network(rows, cols, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_river_network(rows, cols, opt_seed(seed)))
 }
 
 #[no_mangle]
 pub extern "C" fn nlmrs_hexagonal_voronoi(
     rows: usize, cols: usize, n: usize, seed: *const u64,
 ) -> NlmGrid {
-    NlmGrid::from_grid(nlmrs::hexagonal_voronoi(rows, cols, n, opt_seed(seed)))
+    NlmGrid::from_result(nlmrs::try_hexagonal_voronoi(rows, cols, n, opt_seed(seed)))
+}
+
+// ── Post-processing ───────────────────────────────────────────────────────────
+
+/// Borrows the Rust-owned buffer behind `grid` as a `Grid` for the duration of
+/// `f`, then hands ownership back to the caller. Returns `None` if `grid` is
+/// NULL or has no data.
+///
+/// The `Grid` is wrapped in `ManuallyDrop` so the buffer is never freed here,
+/// even if `f` panics; it stays owned by the caller until `nlmrs_free`. `f`
+/// must not change the grid's length, which would reallocate the buffer.
+fn with_grid_mut<R>(grid: *mut NlmGrid, f: impl FnOnce(&mut Grid) -> R) -> Option<R> {
+    if grid.is_null() {
+        return None;
+    }
+    let grid = unsafe { &mut *grid };
+    if grid.data.is_null() {
+        return None;
+    }
+    let len = grid.rows * grid.cols;
+    let mut g = ManuallyDrop::new(Grid {
+        data: unsafe { Vec::from_raw_parts(grid.data, len, len) },
+        rows: grid.rows,
+        cols: grid.cols,
+    });
+    let result = f(&mut g);
+    debug_assert_eq!(g.data.as_mut_ptr(), grid.data, "grid buffer was reallocated");
+    Some(result)
+}
+
+/// Classify a grid in place by rank into classes covering the given proportions.
+///
+/// Cells are ordered by value; the lowest `weights[0] / sum(weights)` share
+/// becomes class 0, the next share class 1, and so on. Class `k` is written as
+/// `k / (n_weights - 1)`. Weights need not sum to 1.
+///
+/// @param grid       Grid returned by any `nlmrs_*` generator; modified in place.
+/// @param weights    Pointer to `n_weights` non-negative class weights.
+/// @param n_weights  Number of classes.
+/// @return 0 on success, -1 if `grid` or `weights` is NULL, or the weights are
+///         empty, negative or sum to zero. Invalid weights also set
+///         `NLMRS_ERR_INVALID_PARAMETER`.
+#[no_mangle]
+pub extern "C" fn nlmrs_classify_weighted(
+    grid: *mut NlmGrid,
+    weights: *const f64,
+    n_weights: usize,
+) -> i32 {
+    let Some(weights) = opt_slice(weights, n_weights) else {
+        return -1;
+    };
+    match with_grid_mut(grid, |g| nlmrs::try_classify_weighted(g, weights)) {
+        Some(Ok(())) => {
+            clear_error();
+            0
+        }
+        Some(Err(e)) => {
+            set_error(&e);
+            -1
+        }
+        None => -1,
+    }
 }
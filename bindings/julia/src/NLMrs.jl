@@ -64,10 +64,10 @@ function edge_gradient(rows::Integer, cols::Integer;
 end
 
 function distance_gradient(rows::Integer, cols::Integer;
-                            seed=nothing)::Matrix{Float64}
-    _to_matrix(ccall((:nlmrs_distance_gradient, _libpath), _NlmGrid,
-        (Csize_t, Csize_t, Ptr{UInt64}),
-        rows, cols, _seed(seed)))
+                            periodic::Bool=false, seed=nothing)::Matrix{Float64}
+    _to_matrix(ccall((:nlmrs_distance_gradient_with_boundary, _libpath), _NlmGrid,
+        (Csize_t, Csize_t, Bool, Ptr{UInt64}),
+        rows, cols, periodic, _seed(seed)))
 end
 
 function wave_gradient(rows::Integer, cols::Integer;
@@ -245,16 +245,18 @@ function random(rows::Integer, cols::Integer;
 end
 
 function random_element(rows::Integer, cols::Integer;
-                         n::Real=50000.0, seed=nothing)::Matrix{Float64}
-    _to_matrix(ccall((:nlmrs_random_element, _libpath), _NlmGrid,
-        (Csize_t, Csize_t, Cdouble, Ptr{UInt64}),
-        rows, cols, Float64(n), _seed(seed)))
+                         n::Real=50000.0,
+                         periodic::Bool=false, seed=nothing)::Matrix{Float64}
+    _to_matrix(ccall((:nlmrs_random_element_with_boundary, _libpath), _NlmGrid,
+        (Csize_t, Csize_t, Cdouble, Bool, Ptr{UInt64}),
+        rows, cols, Float64(n), periodic, _seed(seed)))
 end
 
 function hill_grow(rows::Integer, cols::Integer;
                    n::Integer=10000, runaway::Bool=true,
                    kernel::Union{Matrix{Float64}, Nothing}=nothing,
-                   only_grow::Bool=false, seed=nothing)::Matrix{Float64}
+                   only_grow::Bool=false,
+                   periodic::Bool=false, seed=nothing)::Matrix{Float64}
     if isnothing(kernel)
         kdata = C_NULL
         ksize = Csize_t(0)
@@ -263,30 +265,32 @@ function hill_grow(rows::Integer, cols::Integer;
         ksize = Csize_t(size(kernel, 1))
         kdata = vec(permutedims(kernel))  # Julia column-major → row-major for C
     end
-    _to_matrix(ccall((:nlmrs_hill_grow, _libpath), _NlmGrid,
-        (Csize_t, Csize_t, Csize_t, Bool, Ptr{Cdouble}, Csize_t, Bool, Ptr{UInt64}),
-        rows, cols, n, runaway, kdata, ksize, only_grow, _seed(seed)))
+    _to_matrix(ccall((:nlmrs_hill_grow_with_boundary, _libpath), _NlmGrid,
+        (Csize_t, Csize_t, Csize_t, Bool, Ptr{Cdouble}, Csize_t, Bool, Bool, Ptr{UInt64}),
+        rows, cols, n, runaway, kdata, ksize, only_grow, periodic, _seed(seed)))
 end
 
 function midpoint_displacement(rows::Integer, cols::Integer;
-                                h::Real=1.0, seed=nothing)::Matrix{Float64}
-    _to_matrix(ccall((:nlmrs_midpoint_displacement, _libpath), _NlmGrid,
-        (Csize_t, Csize_t, Cdouble, Ptr{UInt64}),
-        rows, cols, h, _seed(seed)))
+                                h::Real=1.0,
+                                periodic::Bool=false, seed=nothing)::Matrix{Float64}
+    _to_matrix(ccall((:nlmrs_midpoint_displacement_with_boundary, _libpath), _NlmGrid,
+        (Csize_t, Csize_t, Cdouble, Bool, Ptr{UInt64}),
+        rows, cols, h, periodic, _seed(seed)))
 end
 
 function random_cluster(rows::Integer, cols::Integer;
-                         n::Integer=200, seed=nothing)::Matrix{Float64}
-    _to_matrix(ccall((:nlmrs_random_cluster, _libpath), _NlmGrid,
-        (Csize_t, Csize_t, Csize_t, Ptr{UInt64}),
-        rows, cols, n, _seed(seed)))
+                         n::Integer=200,
+                         periodic::Bool=false, seed=nothing)::Matrix{Float64}
+    _to_matrix(ccall((:nlmrs_random_cluster_with_boundary, _libpath), _NlmGrid,
+        (Csize_t, Csize_t, Csize_t, Bool, Ptr{UInt64}),
+        rows, cols, n, periodic, _seed(seed)))
 end
 
 function mosaic(rows::Integer, cols::Integer;
-                n::Integer=200, seed=nothing)::Matrix{Float64}
-    _to_matrix(ccall((:nlmrs_mosaic, _libpath), _NlmGrid,
-        (Csize_t, Csize_t, Csize_t, Ptr{UInt64}),
-        rows, cols, n, _seed(seed)))
+                n::Integer=200, periodic::Bool=false, seed=nothing)::Matrix{Float64}
+    _to_matrix(ccall((:nlmrs_mosaic_with_boundary, _libpath), _NlmGrid,
+        (Csize_t, Csize_t, Csize_t, Bool, Ptr{UInt64}),
+        rows, cols, n, periodic, _seed(seed)))
 end
 
 function rectangular_cluster(rows::Integer, cols::Integer;
@@ -322,10 +326,10 @@ end
 
 function neighbourhood_clustering(rows::Integer, cols::Integer;
                                    k::Integer=5, iterations::Integer=10,
-                                   seed=nothing)::Matrix{Float64}
-    _to_matrix(ccall((:nlmrs_neighbourhood_clustering, _libpath), _NlmGrid,
-        (Csize_t, Csize_t, Csize_t, Csize_t, Ptr{UInt64}),
-        rows, cols, k, iterations, _seed(seed)))
+                                   periodic::Bool=false, seed=nothing)::Matrix{Float64}
+    _to_matrix(ccall((:nlmrs_neighbourhood_clustering_with_boundary, _libpath), _NlmGrid,
+        (Csize_t, Csize_t, Csize_t, Csize_t, Bool, Ptr{UInt64}),
+        rows, cols, k, iterations, periodic, _seed(seed)))
 end
 
 function reaction_diffusion(rows::Integer, cols::Integer;
@@ -395,10 +399,11 @@ function poisson_disk(rows::Integer, cols::Integer;
 end
 
 function gaussian_field(rows::Integer, cols::Integer;
-                         sigma::Real=10.0, seed=nothing)::Matrix{Float64}
-    _to_matrix(ccall((:nlmrs_gaussian_field, _libpath), _NlmGrid,
-        (Csize_t, Csize_t, Cdouble, Ptr{UInt64}),
-        rows, cols, sigma, _seed(seed)))
+                         sigma::Real=10.0,
+                         periodic::Bool=false, seed=nothing)::Matrix{Float64}
+    _to_matrix(ccall((:nlmrs_gaussian_field_with_boundary, _libpath), _NlmGrid,
+        (Csize_t, Csize_t, Cdouble, Bool, Ptr{UInt64}),
+        rows, cols, sigma, periodic, _seed(seed)))
 end
 
 function brownian_motion(rows::Integer, cols::Integer;
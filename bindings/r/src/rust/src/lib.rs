@@ -1,5 +1,5 @@
 use extendr_api::prelude::*;
-use nlmrs::Grid;
+use nlmrs::{Boundary, Grid};
 
 // ── Helpers ──────────────────────────────────────────────────────────────────
 
@@ -25,6 +25,15 @@ fn seed_from_r(seed: Nullable<f64>) -> Option<u64> {
     }
 }
 
+/// Map an R logical `periodic` flag to a [`Boundary`].
+fn boundary_from_r(periodic: bool) -> Boundary {
+    if periodic {
+        Boundary::Periodic
+    } else {
+        Boundary::Clamped
+    }
+}
+
 /// Map an R numeric-or-NULL direction to `Option<f64>`.
 fn direction_from_r(dir: Nullable<f64>) -> Option<f64> {
     match dir {
@@ -44,8 +53,8 @@ fn r_random(rows: i32, cols: i32, seed: Nullable<f64>) -> RMatrix<f64> {
 
 /// Random element nearest-neighbour NLM. Values in [0, 1).
 #[extendr]
-fn r_random_element(rows: i32, cols: i32, n: f64, seed: Nullable<f64>) -> RMatrix<f64> {
-    let grid = nlmrs::random_element(rows as usize, cols as usize, n, seed_from_r(seed));
+fn r_random_element(rows: i32, cols: i32, n: f64, seed: Nullable<f64>, periodic: bool) -> RMatrix<f64> {
+    let grid = nlmrs::random_element_with_boundary(rows as usize, cols as usize, n, boundary_from_r(periodic), seed_from_r(seed));
     grid_to_rmatrix(grid)
 }
 
@@ -85,8 +94,8 @@ fn r_edge_gradient(
 
 /// Radial distance gradient from a random centre point. Values in [0, 1).
 #[extendr]
-fn r_distance_gradient(rows: i32, cols: i32, seed: Nullable<f64>) -> RMatrix<f64> {
-    let grid = nlmrs::distance_gradient(rows as usize, cols as usize, seed_from_r(seed));
+fn r_distance_gradient(rows: i32, cols: i32, seed: Nullable<f64>, periodic: bool) -> RMatrix<f64> {
+    let grid = nlmrs::distance_gradient_with_boundary(rows as usize, cols as usize, boundary_from_r(periodic), seed_from_r(seed));
     grid_to_rmatrix(grid)
 }
 
@@ -111,8 +120,8 @@ fn r_wave_gradient(
 
 /// Diamond-square (midpoint displacement) fractal terrain. Values in [0, 1).
 #[extendr]
-fn r_midpoint_displacement(rows: i32, cols: i32, h: f64, seed: Nullable<f64>) -> RMatrix<f64> {
-    let grid = nlmrs::midpoint_displacement(rows as usize, cols as usize, h, seed_from_r(seed));
+fn r_midpoint_displacement(rows: i32, cols: i32, h: f64, seed: Nullable<f64>, periodic: bool) -> RMatrix<f64> {
+    let grid = nlmrs::midpoint_displacement_with_boundary(rows as usize, cols as usize, h, boundary_from_r(periodic), seed_from_r(seed));
     grid_to_rmatrix(grid)
 }
 
@@ -129,6 +138,7 @@ fn r_hill_grow(
     kernel: Nullable<List>,
     only_grow: bool,
     seed: Nullable<f64>,
+    periodic: bool,
 ) -> RMatrix<f64> {
     let kernel_opt: Option<Vec<Vec<f64>>> = match kernel {
         Nullable::NotNull(list) => {
@@ -140,13 +150,14 @@ fn r_hill_grow(
         }
         Nullable::Null => None,
     };
-    let grid = nlmrs::hill_grow(
+    let grid = nlmrs::hill_grow_with_boundary(
         rows as usize,
         cols as usize,
         n as usize,
         runaway,
         kernel_opt,
         only_grow,
+        boundary_from_r(periodic),
         seed_from_r(seed),
     );
     grid_to_rmatrix(grid)
@@ -237,15 +248,15 @@ fn r_worley_noise(rows: i32, cols: i32, scale_factor: f64, seed: Nullable<f64>)
 
 /// Gaussian random field — spatially correlated noise. Values in [0, 1).
 #[extendr]
-fn r_gaussian_field(rows: i32, cols: i32, sigma: f64, seed: Nullable<f64>) -> RMatrix<f64> {
-    let grid = nlmrs::gaussian_field(rows as usize, cols as usize, sigma, seed_from_r(seed));
+fn r_gaussian_field(rows: i32, cols: i32, sigma: f64, seed: Nullable<f64>, periodic: bool) -> RMatrix<f64> {
+    let grid = nlmrs::gaussian_field_with_boundary(rows as usize, cols as usize, sigma, boundary_from_r(periodic), seed_from_r(seed));
     grid_to_rmatrix(grid)
 }
 
 /// Random cluster NLM via fault-line cuts. Values in [0, 1).
 #[extendr]
-fn r_random_cluster(rows: i32, cols: i32, n: i32, seed: Nullable<f64>) -> RMatrix<f64> {
-    let grid = nlmrs::random_cluster(rows as usize, cols as usize, n as usize, seed_from_r(seed));
+fn r_random_cluster(rows: i32, cols: i32, n: i32, seed: Nullable<f64>, periodic: bool) -> RMatrix<f64> {
+    let grid = nlmrs::random_cluster_with_boundary(rows as usize, cols as usize, n as usize, boundary_from_r(periodic), seed_from_r(seed));
     grid_to_rmatrix(grid)
 }
 
@@ -323,8 +334,8 @@ fn r_domain_warp(
 
 /// Mosaic NLM — discrete Voronoi patch map. Values in [0, 1).
 #[extendr]
-fn r_mosaic(rows: i32, cols: i32, n: i32, seed: Nullable<f64>) -> RMatrix<f64> {
-    let grid = nlmrs::mosaic(rows as usize, cols as usize, n as usize, seed_from_r(seed));
+fn r_mosaic(rows: i32, cols: i32, n: i32, seed: Nullable<f64>, periodic: bool) -> RMatrix<f64> {
+    let grid = nlmrs::mosaic_with_boundary(rows as usize, cols as usize, n as usize, boundary_from_r(periodic), seed_from_r(seed));
     grid_to_rmatrix(grid)
 }
 
@@ -588,10 +599,10 @@ fn r_cellular_automaton(
 /// Neighbourhood clustering NLM — iterative majority-vote patch clustering. Values in [0, 1).
 #[extendr]
 fn r_neighbourhood_clustering(
-    rows: i32, cols: i32, k: i32, iterations: i32, seed: Nullable<f64>,
+    rows: i32, cols: i32, k: i32, iterations: i32, seed: Nullable<f64>, periodic: bool,
 ) -> RMatrix<f64> {
-    let grid = nlmrs::neighbourhood_clustering(
-        rows as usize, cols as usize, k as usize, iterations as usize, seed_from_r(seed),
+    let grid = nlmrs::neighbourhood_clustering_with_boundary(
+        rows as usize, cols as usize, k as usize, iterations as usize, boundary_from_r(periodic), seed_from_r(seed),
     );
     grid_to_rmatrix(grid)
 }
@@ -1,5 +1,5 @@
 use wasm_bindgen::prelude::*;
-use nlmrs::Grid;
+use nlmrs::{Boundary, Grid};
 
 // ── Helpers ──────────────────────────────────────────────────────────────────
 
@@ -18,6 +18,15 @@ fn seed_from_js(seed: Option<u32>) -> Option<u64> {
     seed.map(|s| s as u64)
 }
 
+/// Map a JS boolean-or-undefined `periodic` flag to a [`Boundary`].
+fn boundary_from_js(periodic: Option<bool>) -> Boundary {
+    if periodic.unwrap_or(false) {
+        Boundary::Periodic
+    } else {
+        Boundary::Clamped
+    }
+}
+
 // ── Return type ───────────────────────────────────────────────────────────────
 
 /// A 2-D grid returned by every NLM generator.
@@ -63,13 +72,14 @@ pub fn random(rows: u32, cols: u32, seed: Option<u32>) -> WasmGrid {
 
 /// Random element nearest-neighbour NLM. Values in \[0, 1\).
 ///
-/// @param rows - Number of rows.
-/// @param cols - Number of columns.
-/// @param n    - Number of labelled seed elements to place (default 50 000).
-/// @param seed - Optional integer seed.
+/// @param rows     - Number of rows.
+/// @param cols     - Number of columns.
+/// @param n        - Number of labelled seed elements to place (default 50 000).
+/// @param seed     - Optional integer seed.
+/// @param periodic - If true, opposite edges wrap so the grid tiles seamlessly (default false).
 #[wasm_bindgen]
-pub fn random_element(rows: u32, cols: u32, n: f64, seed: Option<u32>) -> WasmGrid {
-    let grid = nlmrs::random_element(rows as usize, cols as usize, n, seed_from_js(seed));
+pub fn random_element(rows: u32, cols: u32, n: f64, seed: Option<u32>, periodic: Option<bool>) -> WasmGrid {
+    let grid = nlmrs::random_element_with_boundary(rows as usize, cols as usize, n, boundary_from_js(periodic), seed_from_js(seed));
     grid_to_wasm(grid)
 }
 
@@ -100,12 +110,13 @@ pub fn edge_gradient(rows: u32, cols: u32, direction: Option<f64>, seed: Option<
 
 /// Radial distance gradient from a random centre point. Values in \[0, 1\).
 ///
-/// @param rows - Number of rows.
-/// @param cols - Number of columns.
-/// @param seed - Optional integer seed.
+/// @param rows     - Number of rows.
+/// @param cols     - Number of columns.
+/// @param seed     - Optional integer seed.
+/// @param periodic - If true, opposite edges wrap so the grid tiles seamlessly (default false).
 #[wasm_bindgen]
-pub fn distance_gradient(rows: u32, cols: u32, seed: Option<u32>) -> WasmGrid {
-    let grid = nlmrs::distance_gradient(rows as usize, cols as usize, seed_from_js(seed));
+pub fn distance_gradient(rows: u32, cols: u32, seed: Option<u32>, periodic: Option<bool>) -> WasmGrid {
+    let grid = nlmrs::distance_gradient_with_boundary(rows as usize, cols as usize, boundary_from_js(periodic), seed_from_js(seed));
     grid_to_wasm(grid)
 }
 
@@ -130,13 +141,14 @@ pub fn wave_gradient(
 
 /// Diamond-square (midpoint displacement) fractal terrain. Values in \[0, 1\).
 ///
-/// @param rows - Number of rows.
-/// @param cols - Number of columns.
-/// @param h    - Hurst exponent: 0 = rough, 1 = smooth (default 1.0).
-/// @param seed - Optional integer seed.
+/// @param rows     - Number of rows.
+/// @param cols     - Number of columns.
+/// @param h        - Hurst exponent: 0 = rough, 1 = smooth (default 1.0).
+/// @param seed     - Optional integer seed.
+/// @param periodic - If true, opposite edges wrap so the grid tiles seamlessly (default false).
 #[wasm_bindgen]
-pub fn midpoint_displacement(rows: u32, cols: u32, h: f64, seed: Option<u32>) -> WasmGrid {
-    let grid = nlmrs::midpoint_displacement(rows as usize, cols as usize, h, seed_from_js(seed));
+pub fn midpoint_displacement(rows: u32, cols: u32, h: f64, seed: Option<u32>, periodic: Option<bool>) -> WasmGrid {
+    let grid = nlmrs::midpoint_displacement_with_boundary(rows as usize, cols as usize, h, boundary_from_js(periodic), seed_from_js(seed));
     grid_to_wasm(grid)
 }
 
@@ -152,6 +164,7 @@ pub fn midpoint_displacement(rows: u32, cols: u32, h: f64, seed: Option<u32>) ->
 ///                      Ignored when `kernel_flat` is empty.
 /// @param only_grow   - If true, surface only accumulates (default false).
 /// @param seed        - Optional integer seed.
+/// @param periodic    - If true, opposite edges wrap so the grid tiles seamlessly (default false).
 #[wasm_bindgen]
 pub fn hill_grow(
     rows: u32,
@@ -162,6 +175,7 @@ pub fn hill_grow(
     kernel_size: u32,
     only_grow: bool,
     seed: Option<u32>,
+    periodic: Option<bool>,
 ) -> WasmGrid {
     let kernel: Option<Vec<Vec<f64>>> = if kernel_size > 0 && !kernel_flat.is_empty() {
         let sz = kernel_size as usize;
@@ -169,13 +183,14 @@ pub fn hill_grow(
     } else {
         None
     };
-    let grid = nlmrs::hill_grow(
+    let grid = nlmrs::hill_grow_with_boundary(
         rows as usize,
         cols as usize,
         n as usize,
         runaway,
         kernel,
         only_grow,
+        boundary_from_js(periodic),
         seed_from_js(seed),
     );
     grid_to_wasm(grid)
@@ -300,25 +315,27 @@ pub fn worley_noise(rows: u32, cols: u32, scale_factor: f64, seed: Option<u32>)
 
 /// Gaussian random field — spatially correlated noise. Values in \[0, 1\).
 ///
-/// @param rows  - Number of rows.
-/// @param cols  - Number of columns.
-/// @param sigma - Gaussian kernel standard deviation in cells (default 10.0).
-/// @param seed  - Optional integer seed.
+/// @param rows     - Number of rows.
+/// @param cols     - Number of columns.
+/// @param sigma    - Gaussian kernel standard deviation in cells (default 10.0).
+/// @param seed     - Optional integer seed.
+/// @param periodic - If true, opposite edges wrap so the grid tiles seamlessly (default false).
 #[wasm_bindgen]
-pub fn gaussian_field(rows: u32, cols: u32, sigma: f64, seed: Option<u32>) -> WasmGrid {
-    let grid = nlmrs::gaussian_field(rows as usize, cols as usize, sigma, seed_from_js(seed));
+pub fn gaussian_field(rows: u32, cols: u32, sigma: f64, seed: Option<u32>, periodic: Option<bool>) -> WasmGrid {
+    let grid = nlmrs::gaussian_field_with_boundary(rows as usize, cols as usize, sigma, boundary_from_js(periodic), seed_from_js(seed));
     grid_to_wasm(grid)
 }
 
 /// Random cluster NLM via fault-line cuts. Values in \[0, 1\).
 ///
-/// @param rows - Number of rows.
-/// @param cols - Number of columns.
-/// @param n    - Number of fault-line cuts (default 200).
-/// @param seed - Optional integer seed.
+/// @param rows     - Number of rows.
+/// @param cols     - Number of columns.
+/// @param n        - Number of fault-line cuts (default 200).
+/// @param seed     - Optional integer seed.
+/// @param periodic - If true, opposite edges wrap so the grid tiles seamlessly (default false).
 #[wasm_bindgen]
-pub fn random_cluster(rows: u32, cols: u32, n: u32, seed: Option<u32>) -> WasmGrid {
-    let grid = nlmrs::random_cluster(rows as usize, cols as usize, n as usize, seed_from_js(seed));
+pub fn random_cluster(rows: u32, cols: u32, n: u32, seed: Option<u32>, periodic: Option<bool>) -> WasmGrid {
+    let grid = nlmrs::random_cluster_with_boundary(rows as usize, cols as usize, n as usize, boundary_from_js(periodic), seed_from_js(seed));
     grid_to_wasm(grid)
 }
 
@@ -423,13 +440,14 @@ pub fn domain_warp(
 
 /// Mosaic NLM — discrete Voronoi patch map. Values in \[0, 1\).
 ///
-/// @param rows - Number of rows.
-/// @param cols - Number of columns.
-/// @param n    - Number of Voronoi seed points (default 200).
-/// @param seed - Optional integer seed.
+/// @param rows     - Number of rows.
+/// @param cols     - Number of columns.
+/// @param n        - Number of Voronoi seed points (default 200).
+/// @param seed     - Optional integer seed.
+/// @param periodic - If true, opposite edges wrap so the grid tiles seamlessly (default false).
 #[wasm_bindgen]
-pub fn mosaic(rows: u32, cols: u32, n: u32, seed: Option<u32>) -> WasmGrid {
-    let grid = nlmrs::mosaic(rows as usize, cols as usize, n as usize, seed_from_js(seed));
+pub fn mosaic(rows: u32, cols: u32, n: u32, seed: Option<u32>, periodic: Option<bool>) -> WasmGrid {
+    let grid = nlmrs::mosaic_with_boundary(rows as usize, cols as usize, n as usize, boundary_from_js(periodic), seed_from_js(seed));
     grid_to_wasm(grid)
 }
 
@@ -505,10 +523,11 @@ pub fn cellular_automaton(
 /// @param k          - Number of distinct patch classes (default 5).
 /// @param iterations - Number of majority-vote passes (default 10).
 /// @param seed       - Optional integer seed.
+/// @param periodic   - If true, opposite edges wrap so the grid tiles seamlessly (default false).
 #[wasm_bindgen]
-pub fn neighbourhood_clustering(rows: u32, cols: u32, k: u32, iterations: u32, seed: Option<u32>) -> WasmGrid {
-    let grid = nlmrs::neighbourhood_clustering(
-        rows as usize, cols as usize, k as usize, iterations as usize, seed_from_js(seed),
+pub fn neighbourhood_clustering(rows: u32, cols: u32, k: u32, iterations: u32, seed: Option<u32>, periodic: Option<bool>) -> WasmGrid {
+    let grid = nlmrs::neighbourhood_clustering_with_boundary(
+        rows as usize, cols as usize, k as usize, iterations as usize, boundary_from_js(periodic), seed_from_js(seed),
     );
     grid_to_wasm(grid)
 }
@@ -0,0 +1,331 @@
+//! Conditional simulation: NLMs that pass through observed values.
+//!
+//! Each generator draws the unconditional NLM and adds the ordinary kriging
+//! interpolation of the residuals between the observations and the NLM at
+//! the observed cells. Kriging uses the exact covariance of the generator,
+//! so the result honours the observations while keeping the NLM's spatial
+//! structure, and its randomness, away from them. Repeating with different
+//! seeds gives an ensemble for uncertainty analysis around surveyed sites.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use rustfft::num_complex::Complex;
+
+use crate::error::{NlmError, Result};
+use crate::grid::{Boundary, Grid};
+
+use super::patch::gaussian_kernel;
+use super::{gaussian_field_with_boundary, spectral_synthesis};
+
+/// Returns a [`gaussian_field`](crate::gaussian_field) conditioned to hold
+/// `value` at every `(row, col, value)` of `observations`.
+///
+/// Without observations this is exactly `gaussian_field`. Otherwise values
+/// are in the units of the observations, which should be on the NLM's [0, 1]
+/// scale, and are not rescaled, so cells near observations outside that range
+/// leave it too. Far from every observation the field is the NLM shifted by
+/// the kriged mean of the residuals. The covariance is that of the field away
+/// from the edges.
+///
+/// Panics if [`check_observations`] fails.
+pub fn conditional_gaussian_field(
+    rows: usize,
+    cols: usize,
+    sigma: f64,
+    observations: &[(usize, usize, f64)],
+    seed: Option<u64>,
+) -> Grid {
+    conditional_gaussian_field_with_boundary(
+        rows,
+        cols,
+        sigma,
+        observations,
+        Boundary::Clamped,
+        seed,
+    )
+}
+
+/// [`conditional_gaussian_field`] with edges handled by `boundary`: the NLM
+/// is [`gaussian_field_with_boundary`], and with [`Boundary::Periodic`]
+/// observations also inform cells across the opposite edges.
+///
+/// Panics if [`check_observations`] fails.
+pub fn conditional_gaussian_field_with_boundary(
+    rows: usize,
+    cols: usize,
+    sigma: f64,
+    observations: &[(usize, usize, f64)],
+    boundary: Boundary,
+    seed: Option<u64>,
+) -> Grid {
+    if let Err(e) = check_observations(rows, cols, observations) {
+        panic!("{e}");
+    }
+    let mut grid = gaussian_field_with_boundary(rows, cols, sigma, boundary, seed);
+    if observations.is_empty() || rows == 0 || cols == 0 {
+        return grid;
+    }
+    if sigma <= 0.0 {
+        condition(&mut grid, observations, |di, dj| if di == 0 && dj == 0 { 1.0 } else { 0.0 });
+        return grid;
+    }
+    // The blur of white noise has the autocorrelation of its kernel as
+    // covariance, separable into a row and a column factor.
+    let (radius, kernel) = gaussian_kernel(sigma);
+    let r = radius as isize;
+    let factor = |len: usize| {
+        let size = if boundary.is_periodic() { len } else { 4 * radius + 1 };
+        let mut a = vec![0.0; size];
+        for u in -r..=r {
+            for v in -r..=r {
+                let d = if boundary.is_periodic() {
+                    (u - v).rem_euclid(len as isize)
+                } else {
+                    u - v + 2 * r
+                };
+                a[d as usize] += kernel[(u + r) as usize] * kernel[(v + r) as usize];
+            }
+        }
+        a
+    };
+    let (row_factor, col_factor) = (factor(rows), factor(cols));
+    let lookup = |a: &[f64], d: isize, len: usize| {
+        if boundary.is_periodic() {
+            a[d.rem_euclid(len as isize) as usize]
+        } else if d.abs() > 2 * r {
+            0.0
+        } else {
+            a[(d + 2 * r) as usize]
+        }
+    };
+    condition(&mut grid, observations, |di, dj| {
+        lookup(&row_factor, di, rows) * lookup(&col_factor, dj, cols)
+    });
+    grid
+}
+
+/// Returns a [`spectral_synthesis`] NLM conditioned to hold `value` at every
+/// `(row, col, value)` of `observations`.
+///
+/// Without observations this is exactly [`spectral_synthesis`]. The
+/// covariance is the periodic one of the `1/f^beta` spectrum, and values are
+/// in the units of the observations as for [`conditional_gaussian_field`].
+///
+/// There is no `_with_boundary` variant: the inverse FFT behind spectral
+/// synthesis makes the NLM periodic whatever the boundary, so observations
+/// always inform cells across the opposite edges.
+///
+/// Panics if [`check_observations`] fails.
+pub fn conditional_spectral_synthesis(
+    rows: usize,
+    cols: usize,
+    beta: f64,
+    observations: &[(usize, usize, f64)],
+    seed: Option<u64>,
+) -> Grid {
+    if let Err(e) = check_observations(rows, cols, observations) {
+        panic!("{e}");
+    }
+    let mut grid = spectral_synthesis(rows, cols, beta, seed);
+    if observations.is_empty() || rows == 0 || cols == 0 {
+        return grid;
+    }
+    // By Wiener-Khinchin the covariance is the inverse transform of the
+    // power spectrum, |f|^-beta without the DC term.
+    let spectrum: Vec<Complex<f64>> = (0..rows * cols)
+        .map(|k| {
+            let (i, j) = (k / cols, k % cols);
+            let fi = if i <= rows / 2 { i as f64 } else { i as f64 - rows as f64 };
+            let fj = if j <= cols / 2 { j as f64 } else { j as f64 - cols as f64 };
+            let f = (fi * fi + fj * fj).sqrt();
+            Complex::new(if k == 0 { 0.0 } else { f.powf(-beta) }, 0.0)
+        })
+        .collect();
+    let covariance = crate::spectrum::fft_2d(spectrum, rows, cols, true);
+    condition(&mut grid, observations, |di, dj| {
+        let i = di.rem_euclid(rows as isize) as usize;
+        let j = dj.rem_euclid(cols as isize) as usize;
+        covariance[i * cols + j].re
+    });
+    grid
+}
+
+/// Errors if an observation lies outside a (rows x cols) grid, holds a
+/// non-finite value, or repeats a cell with a different value.
+pub fn check_observations(rows: usize, cols: usize, observations: &[(usize, usize, f64)]) -> Result<()> {
+    let mut seen: Vec<(usize, usize, f64)> = Vec::with_capacity(observations.len());
+    for &(i, j, v) in observations {
+        if i >= rows || j >= cols {
+            return Err(NlmError::invalid(
+                "observations",
+                format!("cell ({i}, {j}) is outside the {rows}x{cols} grid"),
+            ));
+        }
+        if !v.is_finite() {
+            return Err(NlmError::invalid("observations", format!("cell ({i}, {j}) has value {v}")));
+        }
+        seen.push((i, j, v));
+    }
+    seen.sort_by_key(|a| (a.0, a.1));
+    match seen.windows(2).find(|w| (w[0].0, w[0].1) == (w[1].0, w[1].1) && w[0].2 != w[1].2) {
+        Some(w) => Err(NlmError::invalid(
+            "observations",
+            format!("cell ({}, {}) is observed as both {} and {}", w[0].0, w[0].1, w[0].2, w[1].2),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Adds to `grid` the ordinary kriging interpolation of the residuals
+/// between `observations` and `grid`, given the covariance of cells `di`
+/// rows and `dj` columns apart, then sets the observed cells exactly.
+fn condition(
+    grid: &mut Grid,
+    observations: &[(usize, usize, f64)],
+    covariance: impl Fn(isize, isize) -> f64 + Sync,
+) {
+    let mut points: Vec<(usize, usize, f64)> = observations.to_vec();
+    points.sort_by_key(|a| (a.0, a.1));
+    points.dedup_by(|a, b| (a.0, a.1) == (b.0, b.1));
+    let n = points.len();
+    let offset = |a: &(usize, usize, f64), i: usize, j: usize| {
+        covariance(i as isize - a.0 as isize, j as isize - a.1 as isize)
+    };
+
+    // Dual ordinary kriging system [C 1; 1ᵀ 0] [w; μ] = [r; 0]. A tiny
+    // nugget keeps it solvable when nearby observations are almost
+    // perfectly correlated; the observed cells are set exactly afterwards.
+    let m = n + 1;
+    let nugget = 1e-10 * covariance(0, 0).abs().max(f64::MIN_POSITIVE);
+    let mut a = vec![0.0; m * m];
+    let mut b = vec![0.0; m];
+    for (p, point) in points.iter().enumerate() {
+        for (q, other) in points.iter().enumerate() {
+            a[p * m + q] = offset(point, other.0, other.1);
+        }
+        a[p * m + p] += nugget;
+        a[p * m + n] = 1.0;
+        a[n * m + p] = 1.0;
+        b[p] = point.2 - grid[point.0][point.1];
+    }
+    let weights = solve(a, b);
+    let (w, mean) = (&weights[..n], weights[n]);
+
+    let cols = grid.cols;
+    let add = |(i, row): (usize, &mut [f64])| {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v += mean + points.iter().zip(w).map(|(p, &w)| w * offset(p, i, j)).sum::<f64>();
+        }
+    };
+    #[cfg(feature = "parallel")]
+    grid.data.par_chunks_mut(cols).enumerate().for_each(add);
+    #[cfg(not(feature = "parallel"))]
+    grid.data.chunks_mut(cols).enumerate().for_each(add);
+    for &(i, j, v) in &points {
+        grid[i][j] = v;
+    }
+}
+
+/// Solves the square system `a x = b` by Gaussian elimination with partial
+/// pivoting. Singular pivots are skipped, leaving those unknowns at zero.
+fn solve(mut a: Vec<f64>, mut b: Vec<f64>) -> Vec<f64> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&p, &q| a[p * n + col].abs().total_cmp(&a[q * n + col].abs()))
+            .unwrap();
+        if a[pivot * n + col] == 0.0 {
+            continue;
+        }
+        if pivot != col {
+            for k in 0..n {
+                a.swap(pivot * n + k, col * n + k);
+            }
+            b.swap(pivot, col);
+        }
+        for row in col + 1..n {
+            let f = a[row * n + col] / a[col * n + col];
+            if f != 0.0 {
+                for k in col..n {
+                    a[row * n + k] -= f * a[col * n + k];
+                }
+                b[row] -= f * b[col];
+            }
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let d = a[row * n + row];
+        if d != 0.0 {
+            let s: f64 = (row + 1..n).map(|k| a[row * n + k] * x[k]).sum();
+            x[row] = (b[row] - s) / d;
+        }
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sites() -> Vec<(usize, usize, f64)> {
+        vec![(5, 5, 0.9), (5, 6, 0.85), (30, 40, 0.1), (50, 10, 0.5), (0, 63, 0.0), (59, 0, 1.0)]
+    }
+
+    #[test]
+    fn test_conditional_fields_pass_through_observations() {
+        let grids = [
+            conditional_gaussian_field(60, 64, 4.0, &sites(), Some(1)),
+            conditional_gaussian_field_with_boundary(60, 64, 4.0, &sites(), Boundary::Periodic, Some(1)),
+            conditional_gaussian_field(60, 64, 0.0, &sites(), Some(1)),
+            conditional_spectral_synthesis(60, 64, 2.0, &sites(), Some(1)),
+        ];
+        for grid in &grids {
+            for &(i, j, v) in &sites() {
+                assert_eq!(grid[i][j], v);
+            }
+            assert!(grid.iter().all(|v| v.is_finite()));
+        }
+    }
+
+    #[test]
+    fn test_conditioning_is_smooth_near_observations() {
+        let grid = conditional_gaussian_field(60, 64, 4.0, &sites(), Some(2));
+        // Neighbours of an observation move towards its value.
+        assert!((grid[5][4] - 0.9).abs() < 0.1, "{}", grid[5][4]);
+        assert!((grid[31][40] - 0.1).abs() < 0.1, "{}", grid[31][40]);
+
+        let grid = conditional_spectral_synthesis(60, 64, 3.0, &sites(), Some(2));
+        assert!((grid[30][41] - 0.1).abs() < 0.1, "{}", grid[30][41]);
+    }
+
+    #[test]
+    fn test_without_observations_matches_unconditional() {
+        let g = conditional_gaussian_field_with_boundary(30, 20, 3.0, &[], Boundary::Periodic, Some(3));
+        assert_eq!(g, gaussian_field_with_boundary(30, 20, 3.0, Boundary::Periodic, Some(3)));
+        let s = conditional_spectral_synthesis(30, 20, 1.5, &[], Some(3));
+        assert_eq!(s, spectral_synthesis(30, 20, 1.5, Some(3)));
+    }
+
+    #[test]
+    fn test_realisations_differ_away_from_observations() {
+        let a = conditional_gaussian_field(60, 64, 3.0, &sites(), Some(4));
+        let b = conditional_gaussian_field(60, 64, 3.0, &sites(), Some(5));
+        assert_eq!(a[30][40], b[30][40]);
+        assert_ne!(a[20][20], b[20][20]);
+    }
+
+    #[test]
+    fn test_check_observations() {
+        assert!(check_observations(10, 10, &[(1, 1, 0.5), (1, 1, 0.5)]).is_ok());
+        assert!(check_observations(10, 10, &[(1, 1, 0.5), (1, 1, 0.6)]).is_err());
+        assert!(check_observations(10, 10, &[(10, 1, 0.5)]).is_err());
+        assert!(check_observations(10, 10, &[(1, 1, f64::NAN)]).is_err());
+    }
+
+    #[test]
+    fn test_solve() {
+        let x = solve(vec![0.0, 2.0, 1.0, 1.0], vec![4.0, 3.0]);
+        assert!((x[0] - 1.0).abs() < 1e-12 && (x[1] - 2.0).abs() < 1e-12);
+    }
+}
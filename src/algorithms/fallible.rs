@@ -0,0 +1,374 @@
+//! Fallible counterparts of every generator.
+//!
+//! Each `try_<name>` validates its arguments against the generator's
+//! [registry](crate::registry) schema and the grid dimensions, returning an
+//! [`NlmError`](crate::NlmError) instead of panicking or producing NaNs.
+
+use crate::error::Result;
+use crate::grid::{Boundary, Grid};
+use crate::labels::LabelGrid;
+use crate::registry::{self, Params, Value};
+
+use super::*;
+
+/// Validates `params` and the dimensions for the generator `name`.
+fn check(name: &str, rows: usize, cols: usize, params: &Params) -> Result<()> {
+    registry::try_get(name)?.validate_size(rows, cols, params)
+}
+
+/// A generator argument as seen by the registry schema.
+trait Arg {
+    /// The registry value of the argument, or `None` to leave the parameter
+    /// unset (an absent `Option`).
+    fn value(&self) -> Option<Value>;
+}
+
+impl Arg for f64 {
+    fn value(&self) -> Option<Value> {
+        Some(Value::Float(*self))
+    }
+}
+
+impl Arg for usize {
+    fn value(&self) -> Option<Value> {
+        Some(Value::Int(*self))
+    }
+}
+
+impl Arg for bool {
+    fn value(&self) -> Option<Value> {
+        Some(Value::Bool(*self))
+    }
+}
+
+impl<T: Arg> Arg for Option<T> {
+    fn value(&self) -> Option<Value> {
+        self.as_ref().and_then(Arg::value)
+    }
+}
+
+impl Arg for Vec<Vec<f64>> {
+    fn value(&self) -> Option<Value> {
+        Some(Value::Kernel(self.clone()))
+    }
+}
+
+/// The `periodic` flag.
+impl Arg for Boundary {
+    fn value(&self) -> Option<Value> {
+        Some(Value::Bool(self.is_periodic()))
+    }
+}
+
+/// Observations of a conditional generator, as `[row, col, value]` rows.
+impl Arg for &[(usize, usize, f64)] {
+    fn value(&self) -> Option<Value> {
+        let rows = self.iter().map(|&(i, j, v)| vec![i as f64, j as f64, v]).collect();
+        Some(Value::Kernel(rows))
+    }
+}
+
+/// Defines `try_<name>` wrappers. Each argument between `rows, cols` and
+/// `seed` is set as the registry parameter of the same name, or the one
+/// given after `as`, and the whole set is validated against the schema of
+/// the generator, or the one named after the function, before calling it.
+macro_rules! fallible {
+    ($(
+        $(#[$attr:meta])*
+        $try_name:ident => $name:ident $(as $registry:literal)? (
+            $($arg:ident $(as $param:literal)?: $ty:ty),* $(,)?
+        ) -> $output:ty;
+    )*) => {$(
+        #[doc = concat!("Fallible [`", stringify!($name), "`].")]
+        $(#[$attr])*
+        pub fn $try_name(
+            rows: usize,
+            cols: usize,
+            $($arg: $ty,)*
+            seed: Option<u64>,
+        ) -> Result<$output> {
+            #[allow(unused_mut)]
+            let mut params = Params::new();
+            $(
+                if let Some(value) = Arg::value(&$arg) {
+                    params.set(fallible!(@param $arg $($param)?), value);
+                }
+            )*
+            check(fallible!(@param $name $($registry)?), rows, cols, &params)?;
+            Ok($name(rows, cols, $($arg,)* seed))
+        }
+    )*};
+    (@param $name:ident) => {
+        stringify!($name)
+    };
+    (@param $name:ident $renamed:literal) => {
+        $renamed
+    };
+}
+
+// ── gradient ────────────────────────────────────────────────────────────────
+
+fallible! {
+    try_planar_gradient => planar_gradient(direction: Option<f64>) -> Grid;
+    try_edge_gradient => edge_gradient(direction: Option<f64>) -> Grid;
+    try_distance_gradient => distance_gradient() -> Grid;
+    try_distance_gradient_with_boundary => distance_gradient_with_boundary as "distance_gradient"(
+        boundary as "periodic": Boundary,
+    ) -> Grid;
+    try_wave_gradient => wave_gradient(period: f64, direction: Option<f64>) -> Grid;
+    try_landscape_gradient => landscape_gradient(direction: Option<f64>, aspect: f64) -> Grid;
+    try_concentric_rings => concentric_rings(frequency: f64) -> Grid;
+    try_checkerboard => checkerboard(scale: usize) -> Grid;
+    try_spiral_gradient => spiral_gradient(turns: f64) -> Grid;
+    try_radial_sweep => radial_sweep() -> Grid;
+}
+
+// ── noise ───────────────────────────────────────────────────────────────────
+
+fallible! {
+    try_perlin_noise => perlin_noise(scale_factor as "scale": f64) -> Grid;
+    try_fbm_noise => fbm_noise(
+        scale_factor as "scale": f64,
+        octaves: usize,
+        persistence: f64,
+        lacunarity: f64,
+    ) -> Grid;
+    try_ridged_noise => ridged_noise(
+        scale_factor as "scale": f64,
+        octaves: usize,
+        persistence: f64,
+        lacunarity: f64,
+    ) -> Grid;
+    try_billow_noise => billow_noise(
+        scale_factor as "scale": f64,
+        octaves: usize,
+        persistence: f64,
+        lacunarity: f64,
+    ) -> Grid;
+    try_worley_noise => worley_noise(scale_factor as "scale": f64) -> Grid;
+    try_hybrid_noise => hybrid_noise(
+        scale_factor as "scale": f64,
+        octaves: usize,
+        persistence: f64,
+        lacunarity: f64,
+    ) -> Grid;
+    try_value_noise => value_noise(scale_factor as "scale": f64) -> Grid;
+    try_turbulence => turbulence(
+        scale_factor as "scale": f64,
+        octaves: usize,
+        persistence: f64,
+        lacunarity: f64,
+    ) -> Grid;
+    try_domain_warp => domain_warp(scale_factor as "scale": f64, warp_strength: f64) -> Grid;
+    try_spectral_synthesis => spectral_synthesis(beta: f64) -> Grid;
+    try_conditional_spectral_synthesis => conditional_spectral_synthesis(
+        beta: f64,
+        observations: &[(usize, usize, f64)],
+    ) -> Grid;
+    try_fractal_brownian_surface => fractal_brownian_surface(h: f64) -> Grid;
+    try_simplex_noise => simplex_noise(scale_factor as "scale": f64) -> Grid;
+    try_voronoi_distance => voronoi_distance(n: usize) -> Grid;
+    try_sine_composite => sine_composite(waves: usize) -> Grid;
+    try_curl_noise => curl_noise(scale_factor as "scale": f64) -> Grid;
+    try_gabor_noise => gabor_noise(scale_factor as "scale": f64, n: usize) -> Grid;
+    try_spot_noise => spot_noise(n: usize) -> Grid;
+    try_anisotropic_noise => anisotropic_noise(
+        scale_factor as "scale": f64,
+        octaves: usize,
+        direction: f64,
+        stretch: f64,
+    ) -> Grid;
+    try_tiled_noise => tiled_noise(scale_factor as "scale": f64) -> Grid;
+    try_voronoi_crease => voronoi_crease(n: usize) -> Grid;
+    try_perlin_worley => perlin_worley(scale_factor as "scale": f64) -> Grid;
+    try_lognormal_field => lognormal_field(sigma: f64) -> Grid;
+    try_lognormal_field_with_boundary => lognormal_field_with_boundary as "lognormal_field"(
+        sigma: f64,
+        boundary as "periodic": Boundary,
+    ) -> Grid;
+    try_multifractal_terrain => multifractal_terrain(
+        scale_factor as "scale": f64,
+        octaves: usize,
+    ) -> Grid;
+    try_blue_noise => blue_noise() -> Grid;
+}
+
+// ── patch ───────────────────────────────────────────────────────────────────
+
+fallible! {
+    try_random => random() -> Grid;
+    try_random_element => random_element(n: f64) -> Grid;
+    try_random_element_with_boundary => random_element_with_boundary as "random_element"(
+        n: f64,
+        boundary as "periodic": Boundary,
+    ) -> Grid;
+    try_midpoint_displacement => midpoint_displacement(h: f64) -> Grid;
+    try_midpoint_displacement_with_boundary =>
+        midpoint_displacement_with_boundary as "midpoint_displacement"(
+            h: f64,
+            boundary as "periodic": Boundary,
+        ) -> Grid;
+    try_gaussian_field => gaussian_field(sigma: f64) -> Grid;
+    try_gaussian_field_with_boundary => gaussian_field_with_boundary as "gaussian_field"(
+        sigma: f64,
+        boundary as "periodic": Boundary,
+    ) -> Grid;
+    try_conditional_gaussian_field => conditional_gaussian_field(
+        sigma: f64,
+        observations: &[(usize, usize, f64)],
+    ) -> Grid;
+    try_conditional_gaussian_field_with_boundary =>
+        conditional_gaussian_field_with_boundary as "conditional_gaussian_field"(
+            sigma: f64,
+            observations: &[(usize, usize, f64)],
+            boundary as "periodic": Boundary,
+        ) -> Grid;
+    try_random_cluster => random_cluster(n: usize) -> Grid;
+    try_random_cluster_with_boundary => random_cluster_with_boundary as "random_cluster"(
+        n: usize,
+        boundary as "periodic": Boundary,
+    ) -> Grid;
+    try_mosaic => mosaic(n: usize) -> Grid;
+    try_mosaic_with_boundary => mosaic_with_boundary as "mosaic"(
+        n: usize,
+        boundary as "periodic": Boundary,
+    ) -> Grid;
+    try_mosaic_labels => mosaic_labels as "mosaic"(n: usize) -> LabelGrid<u32>;
+    try_mosaic_labels_with_boundary => mosaic_labels_with_boundary as "mosaic"(
+        n: usize,
+        boundary as "periodic": Boundary,
+    ) -> LabelGrid<u32>;
+    try_rectangular_cluster => rectangular_cluster(n: usize) -> Grid;
+    try_percolation => percolation(p: f64) -> Grid;
+    try_binary_space_partitioning => binary_space_partitioning(n: usize) -> Grid;
+    try_binary_space_partitioning_labels =>
+        binary_space_partitioning_labels as "binary_space_partitioning"(
+            n: usize,
+        ) -> LabelGrid<u32>;
+    try_neighbourhood_clustering => neighbourhood_clustering(k: usize, iterations: usize) -> Grid;
+    try_neighbourhood_clustering_with_boundary =>
+        neighbourhood_clustering_with_boundary as "neighbourhood_clustering"(
+            k: usize,
+            iterations: usize,
+            boundary as "periodic": Boundary,
+        ) -> Grid;
+    try_cellular_automaton => cellular_automaton(
+        p: f64,
+        iterations: usize,
+        birth_threshold: usize,
+        survival_threshold: usize,
+    ) -> Grid;
+    try_diffusion_limited_aggregation => diffusion_limited_aggregation(n: usize) -> Grid;
+    try_reaction_diffusion => reaction_diffusion(iterations: usize, feed: f64, kill: f64) -> Grid;
+    try_eden_growth => eden_growth(n: usize) -> Grid;
+    try_invasion_percolation => invasion_percolation(n: usize) -> Grid;
+    try_gaussian_blobs => gaussian_blobs(n: usize, sigma: f64) -> Grid;
+    try_ising_model => ising_model(beta: f64, iterations: usize) -> Grid;
+    try_levy_flight => levy_flight(n: usize) -> Grid;
+    try_hydraulic_erosion => hydraulic_erosion(n: usize) -> Grid;
+    try_poisson_disk => poisson_disk(min_dist: f64) -> Grid;
+    try_brownian_motion => brownian_motion(n: usize) -> Grid;
+    try_forest_fire => forest_fire(p_tree: f64, p_lightning: f64, iterations: usize) -> Grid;
+    try_river_network => river_network() -> Grid;
+    try_hexagonal_voronoi => hexagonal_voronoi(n: usize) -> Grid;
+    try_hexagonal_voronoi_labels => hexagonal_voronoi_labels as "hexagonal_voronoi"(
+        n: usize,
+    ) -> LabelGrid<u32>;
+    try_fault_uplift => fault_uplift(n: usize) -> Grid;
+    try_triangular_tessellation => triangular_tessellation(n: usize) -> Grid;
+    try_physarum => physarum(n: usize, iterations: usize) -> Grid;
+    try_cahn_hilliard => cahn_hilliard(iterations: usize) -> Grid;
+    try_crystal_growth => crystal_growth(iterations: usize, site_area: usize) -> Grid;
+    try_predator_prey => predator_prey(iterations: usize) -> Grid;
+    try_sandpile => sandpile(n: usize) -> Grid;
+    try_correlated_walk => correlated_walk(n: usize, kappa: f64) -> Grid;
+    try_schelling => schelling(tolerance: f64, iterations: usize) -> Grid;
+    #[allow(clippy::too_many_arguments)]
+    try_sir_epidemic => sir_epidemic(
+        beta: f64,
+        gamma: f64,
+        iterations: usize,
+        site_area: usize,
+        noise: f64,
+    ) -> Grid;
+    try_thermal_erosion => thermal_erosion(n: usize) -> Grid;
+    try_space_colonization => space_colonization(n: usize) -> Grid;
+    try_substrate => substrate(n: usize) -> Grid;
+    try_game_of_life => game_of_life(iterations: usize) -> Grid;
+    try_rock_paper_scissors => rock_paper_scissors(iterations: usize) -> Grid;
+    try_excitable_media => excitable_media(iterations: usize) -> Grid;
+    try_truchet => truchet(n: usize) -> Grid;
+}
+
+// ── hill_grow ───────────────────────────────────────────────────────────────
+
+fallible! {
+    try_hill_grow => hill_grow(
+        n: usize,
+        runaway: bool,
+        kernel: Option<Vec<Vec<f64>>>,
+        only_grow: bool,
+    ) -> Grid;
+    #[allow(clippy::too_many_arguments)]
+    try_hill_grow_with_boundary => hill_grow_with_boundary as "hill_grow"(
+        n: usize,
+        runaway: bool,
+        kernel: Option<Vec<Vec<f64>>>,
+        only_grow: bool,
+        boundary as "periodic": Boundary,
+    ) -> Grid;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NlmError;
+
+    #[test]
+    fn test_try_matches_plain_generator() {
+        let a = try_fbm_noise(30, 30, 2.0, 3, 0.5, 2.0, Some(5)).unwrap();
+        assert_eq!(a, fbm_noise(30, 30, 2.0, 3, 0.5, 2.0, Some(5)));
+        let b = try_planar_gradient(10, 10, Some(90.0), Some(1)).unwrap();
+        assert_eq!(b, planar_gradient(10, 10, Some(90.0), Some(1)));
+    }
+
+    #[test]
+    fn test_try_rejects_invalid_parameters() {
+        let err = try_percolation(10, 10, -0.1, Some(1)).unwrap_err();
+        assert!(matches!(&err, NlmError::InvalidParameter { name, .. } if name == "p"));
+        assert!(try_gaussian_field(10, 10, -1.0, Some(1)).is_err());
+        assert!(try_mosaic(10, 10, 0, Some(1)).is_err());
+        assert!(try_billow_noise(10, 10, 4.0, 6, 0.0, 2.0, Some(1)).is_err());
+        assert!(try_domain_warp(10, 10, 4.0, f64::NEG_INFINITY, Some(1)).is_err());
+        assert!(try_planar_gradient(10, 10, Some(f64::NAN), Some(1)).is_err());
+        let outside = [(3, 10, 0.5)];
+        let err = try_conditional_spectral_synthesis(10, 10, 2.0, &outside, Some(1)).unwrap_err();
+        assert!(matches!(&err, NlmError::InvalidParameter { name, .. } if name == "observations"));
+        assert!(try_conditional_gaussian_field(10, 10, -1.0, &[], Some(1)).is_err());
+        let periodic = try_conditional_gaussian_field_with_boundary(
+            10,
+            10,
+            2.0,
+            &[(9, 9, 0.5)],
+            Boundary::Periodic,
+            Some(1),
+        );
+        assert!(periodic.is_ok());
+    }
+
+    #[test]
+    fn test_try_rejects_empty_dimensions() {
+        assert!(matches!(
+            try_perlin_noise(0, 10, 4.0, Some(1)),
+            Err(NlmError::InvalidDimensions { rows: 0, cols: 10 })
+        ));
+        assert!(try_random(10, 0, Some(1)).is_err());
+    }
+
+    #[test]
+    fn test_try_hill_grow_rejects_even_kernel() {
+        let kernel = vec![vec![1.0; 2]; 2];
+        assert!(try_hill_grow(10, 10, 10, false, Some(kernel), false, Some(1)).is_err());
+        assert!(try_hill_grow(10, 10, 10, false, None, false, Some(1)).is_ok());
+    }
+}
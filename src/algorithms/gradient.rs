@@ -1,5 +1,5 @@
-use crate::grid::Grid;
-use crate::operation::{euclidean_distance_transform, invert, scale};
+use crate::grid::{Boundary, Element, Grid};
+use crate::operation::{euclidean_distance_transform_with_boundary, invert, scale};
 use super::make_rng;
 use rand::Rng;
 #[cfg(feature = "parallel")]
@@ -18,14 +18,33 @@ use rayon::prelude::*;
 ///
 /// Implementation ported from NLMpy.
 pub fn planar_gradient(rows: usize, cols: usize, direction: Option<f64>, seed: Option<u64>) -> Grid {
+    planar_gradient_as(rows, cols, direction, seed)
+}
+
+/// [`planar_gradient`] computed straight into a single-precision grid.
+pub fn planar_gradient_f32(
+    rows: usize,
+    cols: usize,
+    direction: Option<f64>,
+    seed: Option<u64>,
+) -> Grid<f32> {
+    planar_gradient_as(rows, cols, direction, seed)
+}
+
+fn planar_gradient_as<T: Element>(
+    rows: usize,
+    cols: usize,
+    direction: Option<f64>,
+    seed: Option<u64>,
+) -> Grid<T> {
     let mut rng = make_rng(seed);
     let d = direction.unwrap_or_else(|| rng.gen_range(0.0..360.0));
     let right = d.to_radians().sin();
     let down = -d.to_radians().cos();
 
-    let mut grid = Grid::new(rows, cols);
-    let fill = |(k, v): (usize, &mut f64)| {
-        *v = (k / cols) as f64 * down + (k % cols) as f64 * right;
+    let mut grid = Grid::from_value(rows, cols, T::ZERO);
+    let fill = |(k, v): (usize, &mut T)| {
+        *v = T::from_f64((k / cols) as f64 * down + (k % cols) as f64 * right);
     };
     #[cfg(feature = "parallel")]
     grid.data.par_iter_mut().enumerate().for_each(fill);
@@ -49,11 +68,31 @@ pub fn planar_gradient(rows: usize, cols: usize, direction: Option<f64>, seed: O
 ///
 /// Implementation ported from NLMpy.
 pub fn edge_gradient(rows: usize, cols: usize, direction: Option<f64>, seed: Option<u64>) -> Grid {
-    let mut grid = planar_gradient(rows, cols, direction, seed);
+    edge_gradient_as(rows, cols, direction, seed)
+}
+
+/// [`edge_gradient`] computed straight into a single-precision grid.
+pub fn edge_gradient_f32(
+    rows: usize,
+    cols: usize,
+    direction: Option<f64>,
+    seed: Option<u64>,
+) -> Grid<f32> {
+    edge_gradient_as(rows, cols, direction, seed)
+}
+
+fn edge_gradient_as<T: Element>(
+    rows: usize,
+    cols: usize,
+    direction: Option<f64>,
+    seed: Option<u64>,
+) -> Grid<T> {
+    let mut grid = planar_gradient_as::<T>(rows, cols, direction, seed);
+    let fold = |v: &mut T| *v = T::from_f64(1. - (2. * (v.to_f64() - 0.5).abs()));
     #[cfg(feature = "parallel")]
-    grid.data.par_iter_mut().for_each(|v| *v = 1. - (2. * (*v - 0.5).abs()));
+    grid.data.par_iter_mut().for_each(fold);
     #[cfg(not(feature = "parallel"))]
-    grid.data.iter_mut().for_each(|v| *v = 1. - (2. * (*v - 0.5).abs()));
+    grid.data.iter_mut().for_each(fold);
     scale(&mut grid);
     grid
 }
@@ -70,6 +109,17 @@ pub fn edge_gradient(rows: usize, cols: usize, direction: Option<f64>, seed: Opt
 ///
 /// Implementation ported from NLMpy.
 pub fn distance_gradient(rows: usize, cols: usize, seed: Option<u64>) -> Grid {
+    distance_gradient_with_boundary(rows, cols, Boundary::Clamped, seed)
+}
+
+/// [`distance_gradient`] with edges handled by `boundary`: with
+/// [`Boundary::Periodic`], distance is measured on the torus.
+pub fn distance_gradient_with_boundary(
+    rows: usize,
+    cols: usize,
+    boundary: Boundary,
+    seed: Option<u64>,
+) -> Grid {
     if rows == 0 || cols == 0 {
         return Grid::new(rows, cols);
     }
@@ -80,7 +130,7 @@ pub fn distance_gradient(rows: usize, cols: usize, seed: Option<u64>) -> Grid {
     let r = rng.gen_range(0..rows);
     let c = rng.gen_range(0..cols);
     grid[r][c] = 0.0;
-    euclidean_distance_transform(&mut grid);
+    euclidean_distance_transform_with_boundary(&mut grid, boundary);
     scale(&mut grid);
     grid
 }
@@ -105,11 +155,35 @@ pub fn wave_gradient(
     direction: Option<f64>,
     seed: Option<u64>,
 ) -> Grid {
-    let mut grid = planar_gradient(rows, cols, direction, seed);
+    wave_gradient_as(rows, cols, period, direction, seed)
+}
+
+/// [`wave_gradient`] computed straight into a single-precision grid.
+pub fn wave_gradient_f32(
+    rows: usize,
+    cols: usize,
+    period: f64,
+    direction: Option<f64>,
+    seed: Option<u64>,
+) -> Grid<f32> {
+    wave_gradient_as(rows, cols, period, direction, seed)
+}
+
+fn wave_gradient_as<T: Element>(
+    rows: usize,
+    cols: usize,
+    period: f64,
+    direction: Option<f64>,
+    seed: Option<u64>,
+) -> Grid<T> {
+    let mut grid = planar_gradient_as::<T>(rows, cols, direction, seed);
+    let wave = |v: &mut T| {
+        *v = T::from_f64((v.to_f64() * 2. * std::f64::consts::PI * period).sin());
+    };
     #[cfg(feature = "parallel")]
-    grid.data.par_iter_mut().for_each(|v| *v = (*v * 2. * std::f64::consts::PI * period).sin());
+    grid.data.par_iter_mut().for_each(wave);
     #[cfg(not(feature = "parallel"))]
-    grid.data.iter_mut().for_each(|v| *v = (*v * 2. * std::f64::consts::PI * period).sin());
+    grid.data.iter_mut().for_each(wave);
     scale(&mut grid);
     grid
 }
@@ -172,29 +246,32 @@ pub fn landscape_gradient(
 
 /// Returns a concentric rings NLM with values ranging [0, 1).
 ///
-/// Concentric sinusoidal bands radiate outward from the grid centre.
-/// `frequency` controls how many full rings span the grid radius.
+/// Concentric sinusoidal bands radiate outward from a random centre within
+/// the grid, starting at a random phase. `frequency` controls how many full
+/// rings span half the grid diagonal, so ring spacing does not depend on
+/// where the centre falls.
 ///
 /// # Arguments
 ///
 /// * `rows`      - Number of rows.
 /// * `cols`      - Number of columns.
-/// * `frequency` - Number of concentric ring pairs across the grid radius.
-/// * `seed`      - Optional RNG seed (unused; provided for API consistency).
-pub fn concentric_rings(rows: usize, cols: usize, frequency: f64, _seed: Option<u64>) -> Grid {
+/// * `frequency` - Number of concentric ring pairs across half the grid diagonal.
+/// * `seed`      - Optional RNG seed for reproducible results.
+pub fn concentric_rings(rows: usize, cols: usize, frequency: f64, seed: Option<u64>) -> Grid {
     if rows == 0 || cols == 0 {
         return Grid::new(0, 0);
     }
-    let cx = (cols as f64 - 1.0) / 2.0;
-    let cy = (rows as f64 - 1.0) / 2.0;
-    let max_r = cx.hypot(cy).max(1.0);
+    let mut rng = make_rng(seed);
+    let (cx, cy) = random_centre(&mut rng, rows, cols);
+    let phase = rng.gen::<f64>() * std::f64::consts::TAU;
+    let max_r = half_diagonal(rows, cols);
 
     let data: Vec<f64> = (0..rows * cols)
         .map(|idx| {
             let dx = (idx % cols) as f64 - cx;
             let dy = (idx / cols) as f64 - cy;
             let r = dx.hypot(dy) / max_r;
-            (r * frequency * std::f64::consts::PI).sin() * 0.5 + 0.5
+            (r * frequency * std::f64::consts::PI + phase).sin() * 0.5 + 0.5
         })
         .collect();
 
@@ -205,46 +282,57 @@ pub fn concentric_rings(rows: usize, cols: usize, frequency: f64, _seed: Option<
 
 /// Returns a checkerboard NLM with binary values {0.0, 1.0}.
 ///
-/// Deterministic alternating pattern of axis-aligned squares with side
-/// length `scale` cells. A canonical control landscape for ecological
-/// studies and spatial autocorrelation analysis.
+/// Alternating pattern of axis-aligned squares with side length `scale`
+/// cells, shifted by a random offset so that the squares cut by the grid
+/// edges and the colour of each square vary between replicates. A canonical
+/// control landscape for ecological studies and spatial autocorrelation
+/// analysis.
 ///
 /// # Arguments
 ///
 /// * `rows`  - Number of rows.
 /// * `cols`  - Number of columns.
 /// * `scale` - Side length of each square in cells (≥ 1).
-/// * `seed`  - Optional RNG seed (unused; provided for API consistency).
-pub fn checkerboard(rows: usize, cols: usize, scale: usize, _seed: Option<u64>) -> Grid {
+/// * `seed`  - Optional RNG seed for reproducible results.
+pub fn checkerboard(rows: usize, cols: usize, scale: usize, seed: Option<u64>) -> Grid {
     if rows == 0 || cols == 0 {
         return Grid::new(0, 0);
     }
     let s = scale.max(1);
+    let mut rng = make_rng(seed);
+    // One full period of the pattern is two squares in each direction.
+    let dr = rng.gen_range(0..2 * s);
+    let dc = rng.gen_range(0..2 * s);
     let data: Vec<f64> = (0..rows * cols)
-        .map(|idx| if ((idx / cols) / s + (idx % cols) / s) % 2 == 0 { 0.0 } else { 1.0 })
+        .map(|idx| {
+            let (i, j) = (idx / cols + dr, idx % cols + dc);
+            if (i / s + j / s).is_multiple_of(2) { 0.0 } else { 1.0 }
+        })
         .collect();
     Grid { data, rows, cols }
 }
 
 /// Returns a spiral gradient NLM with values ranging [0, 1).
 ///
-/// Values increase along an Archimedean spiral radiating outward from the
-/// centre of the grid. `turns` controls how many full rotations span the
-/// grid radius — higher values produce tighter, more closely-wound spirals.
+/// Values increase along an Archimedean spiral radiating outward from a
+/// random centre within the grid, rotated by a random angle. `turns` controls
+/// how many full rotations span half the grid diagonal — higher values
+/// produce tighter, more closely-wound spirals.
 ///
 /// # Arguments
 ///
 /// * `rows`  - Number of rows.
 /// * `cols`  - Number of columns.
-/// * `turns` - Number of spiral rotations across the grid radius.
-/// * `seed`  - Optional RNG seed (unused; provided for API consistency).
-pub fn spiral_gradient(rows: usize, cols: usize, turns: f64, _seed: Option<u64>) -> Grid {
+/// * `turns` - Number of spiral rotations across half the grid diagonal.
+/// * `seed`  - Optional RNG seed for reproducible results.
+pub fn spiral_gradient(rows: usize, cols: usize, turns: f64, seed: Option<u64>) -> Grid {
     if rows == 0 || cols == 0 {
         return Grid::new(0, 0);
     }
-    let cx = (cols as f64 - 1.0) / 2.0;
-    let cy = (rows as f64 - 1.0) / 2.0;
-    let max_r = cx.hypot(cy).max(1.0);
+    let mut rng = make_rng(seed);
+    let (cx, cy) = random_centre(&mut rng, rows, cols);
+    let phase = rng.gen::<f64>();
+    let max_r = half_diagonal(rows, cols);
 
     let data: Vec<f64> = (0..rows * cols)
         .map(|idx| {
@@ -255,7 +343,7 @@ pub fn spiral_gradient(rows: usize, cols: usize, turns: f64, _seed: Option<u64>)
             let r = dx.hypot(dy) / max_r;
             let theta = dy.atan2(dx); // [-π, π]
             let theta_norm = theta / (2.0 * std::f64::consts::PI) + 0.5; // [0, 1]
-            ((r * turns + theta_norm) % 1.0 + 1.0) % 1.0
+            ((r * turns + theta_norm + phase) % 1.0 + 1.0) % 1.0
         })
         .collect();
 
@@ -266,27 +354,30 @@ pub fn spiral_gradient(rows: usize, cols: usize, turns: f64, _seed: Option<u64>)
 
 /// Returns a radial sweep NLM with values ranging [0, 1).
 ///
-/// Each cell's value is the normalised clockwise angle from the grid centre,
-/// mapped from `atan2(dy, dx)` into `[0, 1)`. Produces a smooth rotation
-/// field useful as a directional covariate or combined with radial algorithms.
+/// Each cell's value is the normalised clockwise angle around a random
+/// centre within the grid, measured from a random starting direction and
+/// mapped into `[0, 1)`. Produces a smooth rotation field useful as a
+/// directional covariate or combined with radial algorithms.
 ///
 /// # Arguments
 ///
 /// * `rows` - Number of rows.
 /// * `cols` - Number of columns.
-/// * `seed` - Optional RNG seed (unused; provided for API consistency).
-pub fn radial_sweep(rows: usize, cols: usize, _seed: Option<u64>) -> Grid {
+/// * `seed` - Optional RNG seed for reproducible results.
+pub fn radial_sweep(rows: usize, cols: usize, seed: Option<u64>) -> Grid {
     if rows == 0 || cols == 0 {
         return Grid::new(0, 0);
     }
-    let cx = (cols as f64 - 1.0) / 2.0;
-    let cy = (rows as f64 - 1.0) / 2.0;
+    let mut rng = make_rng(seed);
+    let (cx, cy) = random_centre(&mut rng, rows, cols);
+    let start = rng.gen::<f64>();
 
     let data: Vec<f64> = (0..rows * cols)
         .map(|idx| {
             let dx = (idx % cols) as f64 - cx;
             let dy = (idx / cols) as f64 - cy;
-            (dy.atan2(dx) + std::f64::consts::PI) / (2.0 * std::f64::consts::PI)
+            let angle = (dy.atan2(dx) + std::f64::consts::PI) / (2.0 * std::f64::consts::PI);
+            (angle + start) % 1.0
         })
         .collect();
 
@@ -295,6 +386,18 @@ pub fn radial_sweep(rows: usize, cols: usize, _seed: Option<u64>) -> Grid {
     result
 }
 
+/// A uniformly random point (x, y) in continuous cell coordinates of a rows x cols grid.
+fn random_centre(rng: &mut impl Rng, rows: usize, cols: usize) -> (f64, f64) {
+    let x = rng.gen::<f64>() * (cols as f64 - 1.0);
+    let y = rng.gen::<f64>() * (rows as f64 - 1.0);
+    (x, y)
+}
+
+/// Distance from the grid centre to a corner, in cells (at least 1).
+fn half_diagonal(rows: usize, cols: usize) -> f64 {
+    ((cols as f64 - 1.0) / 2.0).hypot((rows as f64 - 1.0) / 2.0).max(1.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,6 +463,15 @@ mod tests {
         assert_eq!(zero_to_one_count(&grid), rows * cols);
     }
 
+    #[test]
+    fn test_distance_gradient_periodic_peaks_opposite_source() {
+        let (rows, cols) = (20, 30);
+        let grid = distance_gradient_with_boundary(rows, cols, Boundary::Periodic, Some(3));
+        let source = grid.data.iter().position(|&v| v == 0.0).unwrap();
+        let (r, c) = (source / cols, source % cols);
+        assert_eq!(grid[(r + rows / 2) % rows][(c + cols / 2) % cols], 1.0);
+    }
+
     #[rstest]
     #[case(0, 0)]
     #[case(1, 1)]
@@ -417,6 +529,12 @@ mod tests {
         assert_eq!(zero_to_one_count(&grid), rows * cols);
     }
 
+    #[test]
+    fn test_concentric_rings_seeded() {
+        assert_eq!(concentric_rings(40, 30, 4.0, Some(7)), concentric_rings(40, 30, 4.0, Some(7)));
+        assert_ne!(concentric_rings(40, 30, 4.0, Some(7)), concentric_rings(40, 30, 4.0, Some(8)));
+    }
+
     // ── checkerboard ──────────────────────────────────────────────────────────
 
     #[rstest]
@@ -432,6 +550,21 @@ mod tests {
         assert_eq!(zero_to_one_count(&grid), rows * cols);
     }
 
+    #[test]
+    fn test_checkerboard_seeded_offset() {
+        assert_eq!(checkerboard(30, 30, 4, Some(1)), checkerboard(30, 30, 4, Some(1)));
+        let boards: Vec<Grid> = (0..8).map(|s| checkerboard(30, 30, 4, Some(s))).collect();
+        assert!(boards.iter().any(|b| *b != boards[0]));
+        for b in &boards {
+            // Still a checkerboard: every cell equals the cell `scale` along, inverted.
+            for i in 0..30 {
+                for j in 0..26 {
+                    assert_eq!(b[i][j], 1.0 - b[i][j + 4]);
+                }
+            }
+        }
+    }
+
     // ── spiral_gradient ───────────────────────────────────────────────────────
 
     #[rstest]
@@ -451,6 +584,12 @@ mod tests {
         assert_eq!(zero_to_one_count(&grid), rows * cols);
     }
 
+    #[test]
+    fn test_spiral_gradient_seeded() {
+        assert_eq!(spiral_gradient(40, 30, 3.0, Some(7)), spiral_gradient(40, 30, 3.0, Some(7)));
+        assert_ne!(spiral_gradient(40, 30, 3.0, Some(7)), spiral_gradient(40, 30, 3.0, Some(8)));
+    }
+
     // ── radial_sweep ──────────────────────────────────────────────────────────
 
     #[rstest]
@@ -465,4 +604,10 @@ mod tests {
         assert_eq!(nan_count(&grid), 0);
         assert_eq!(zero_to_one_count(&grid), rows * cols);
     }
+
+    #[test]
+    fn test_radial_sweep_seeded() {
+        assert_eq!(radial_sweep(40, 30, Some(7)), radial_sweep(40, 30, Some(7)));
+        assert_ne!(radial_sweep(40, 30, Some(7)), radial_sweep(40, 30, Some(8)));
+    }
 }
@@ -1,36 +1,39 @@
 use crate::fenwick::WeightedSampler;
-use crate::grid::Grid;
+use crate::grid::{Boundary, Grid};
 use crate::operation::scale;
 use super::make_rng;
 use rand::Rng;
 
-/// Returns an iterator over `(row, col, kernel_value)` for every in-bounds cell
-/// covered by `kernel` centered at `(row, col)`.
+/// Returns an iterator over `(row, col, kernel_value)` for every cell covered
+/// by `kernel` centered at `(row, col)`. Cells beyond a clamped edge are
+/// skipped; a periodic grid wraps them onto the opposite side.
 fn kernel_cells(
     grid_rows: usize,
     grid_cols: usize,
     row: usize,
     col: usize,
     kernel: &[Vec<f64>],
+    boundary: Boundary,
 ) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
-    let half = (kernel.len() as i32 - 1) / 2;
-    let row_i = row as i32;
-    let col_i = col as i32;
+    let half = (kernel.len() as isize - 1) / 2;
     kernel.iter().enumerate().flat_map(move |(ki, krow)| {
-        let i = row_i - half + ki as i32;
+        let i = boundary.offset(row, ki as isize - half, grid_rows);
         krow.iter().copied().enumerate().filter_map(move |(kj, kv)| {
-            let j = col_i - half + kj as i32;
-            if i >= 0 && i < grid_rows as i32 && j >= 0 && j < grid_cols as i32 {
-                Some((i as usize, j as usize, kv))
-            } else {
-                None
-            }
+            let j = boundary.offset(col, kj as isize - half, grid_cols)?;
+            Some((i?, j, kv))
         })
     })
 }
 
-fn apply_kernel(grid: &mut Grid, row: usize, col: usize, kernel: &[Vec<f64>], factor: f64) {
-    for (iu, ju, kv) in kernel_cells(grid.rows, grid.cols, row, col, kernel) {
+fn apply_kernel(
+    grid: &mut Grid,
+    row: usize,
+    col: usize,
+    kernel: &[Vec<f64>],
+    factor: f64,
+    boundary: Boundary,
+) {
+    for (iu, ju, kv) in kernel_cells(grid.rows, grid.cols, row, col, kernel, boundary) {
         grid[iu][ju] = (grid[iu][ju] + kv * factor).max(0.);
     }
 }
@@ -42,10 +45,11 @@ fn apply_kernel_tracked(
     col: usize,
     kernel: &[Vec<f64>],
     factor: f64,
+    boundary: Boundary,
     changes: &mut Vec<(usize, f64)>,
 ) {
     let cols = grid.cols;
-    for (iu, ju, kv) in kernel_cells(grid.rows, cols, row, col, kernel) {
+    for (iu, ju, kv) in kernel_cells(grid.rows, cols, row, col, kernel, boundary) {
         let old = grid[iu][ju];
         grid[iu][ju] = (old + kv * factor).max(0.);
         let delta = grid[iu][ju] - old;
@@ -83,6 +87,22 @@ pub fn hill_grow(
     kernel: Option<Vec<Vec<f64>>>,
     only_grow: bool,
     seed: Option<u64>,
+) -> Grid {
+    hill_grow_with_boundary(rows, cols, n, runaway, kernel, only_grow, Boundary::Clamped, seed)
+}
+
+/// [`hill_grow`] with edges handled by `boundary`: with [`Boundary::Periodic`],
+/// hills near an edge wrap onto the opposite side.
+#[allow(clippy::too_many_arguments)]
+pub fn hill_grow_with_boundary(
+    rows: usize,
+    cols: usize,
+    n: usize,
+    runaway: bool,
+    kernel: Option<Vec<Vec<f64>>>,
+    only_grow: bool,
+    boundary: Boundary,
+    seed: Option<u64>,
 ) -> Grid {
     if rows == 0 || cols == 0 {
         return Grid::new(0, 0);
@@ -127,7 +147,7 @@ pub fn hill_grow(
             let factor = if grow { 0.1 } else { -0.1 };
 
             changes.clear();
-            apply_kernel_tracked(&mut grid, row, col, &k, factor, &mut changes);
+            apply_kernel_tracked(&mut grid, row, col, &k, factor, boundary, &mut changes);
 
             // Update the Fenwick tree for each modified cell — O(k * log n) total.
             for &(idx, delta) in &changes {
@@ -141,7 +161,7 @@ pub fn hill_grow(
             let col = rng.gen_range(0..cols);
             let grow = only_grow || rng.gen_bool(0.5);
             let factor = if grow { 0.1 } else { -0.1 };
-            apply_kernel(&mut grid, row, col, &k, factor);
+            apply_kernel(&mut grid, row, col, &k, factor, boundary);
         }
     }
 
@@ -180,4 +200,14 @@ mod tests {
         assert_eq!(nan_count(&grid), 0);
         assert_eq!(zero_to_one_count(&grid), rows * cols);
     }
+
+    #[rstest]
+    #[case(1, 1)]
+    #[case(2, 3)]
+    #[case(40, 25)]
+    fn test_hill_grow_periodic(#[case] rows: usize, #[case] cols: usize) {
+        let grid = hill_grow_with_boundary(rows, cols, 5000, true, None, false, Boundary::Periodic, Some(42));
+        assert_eq!(nan_count(&grid), 0);
+        assert_eq!(zero_to_one_count(&grid), rows * cols);
+    }
 }
@@ -1,24 +1,66 @@
-use rand::rngs::StdRng;
-use rand::{Rng, SeedableRng};
+use rand::{Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
 
+pub mod conditional;
+pub mod fallible;
 pub mod gradient;
 pub mod hill_grow;
 pub mod noise;
 pub mod patch;
 
+pub use conditional::*;
+pub use fallible::*;
 pub use gradient::*;
 pub use hill_grow::*;
 pub use noise::*;
 pub use patch::*;
 
-pub(crate) fn make_rng(seed: Option<u64>) -> StdRng {
-    StdRng::seed_from_u64(seed.unwrap_or_else(|| rand::thread_rng().gen()))
+/// Identifies the pseudo-random stream behind every seeded generator: ChaCha
+/// with 12 rounds from `rand_chacha`, seeded with `seed_from_u64` and sampled
+/// with rand 0.8. The same seed gives the same grid on every platform, with or
+/// without the `parallel` feature, for as long as this string is unchanged.
+///
+/// Since `v2`, per-cell draws come from a sub-stream per row and octave seeds
+/// from a sub-stream per octave.
+pub const RNG: &str = "chacha12-v2";
+
+/// Pseudo-random generator named by [`RNG`]. `rand_chacha` guarantees its
+/// output for a given seed, unlike `StdRng`.
+pub(crate) type NlmRng = ChaCha12Rng;
+
+pub(crate) fn make_rng(seed: Option<u64>) -> NlmRng {
+    NlmRng::seed_from_u64(seed.unwrap_or_else(|| rand::thread_rng().gen()))
 }
 
 /// Converts an optional 64-bit seed to a 32-bit Perlin seed, generating a random
 /// one from thread_rng when none is provided.
+///
+/// Seeds below 2^32 are used as is; larger seeds are hashed through [`NlmRng`]
+/// so that their high bits are not discarded.
 pub(crate) fn perlin_seed(seed: Option<u64>) -> u32 {
-    seed.map(|s| s as u32).unwrap_or_else(|| rand::thread_rng().gen())
+    match seed {
+        Some(s) => u32::try_from(s).unwrap_or_else(|_| NlmRng::seed_from_u64(s).next_u32()),
+        None => rand::thread_rng().gen(),
+    }
+}
+
+/// Perlin seed for octave or layer `index` of a generator whose own seed is
+/// `seed_val`, taken from stream `index + 1` of [`NlmRng`] seeded with it.
+///
+/// Adding `index` to the seed instead would make octave 1 of seed 42 the same
+/// noise as octave 0 of seed 43.
+pub(crate) fn sub_seed(seed_val: u32, index: usize) -> u32 {
+    let mut rng = NlmRng::seed_from_u64(seed_val as u64);
+    rng.set_stream(index as u64 + 1);
+    rng.next_u32()
+}
+
+/// Seed for layer or replicate `index` of a run whose own seed is `seed_val`,
+/// the 64-bit counterpart of [`sub_seed`].
+pub(crate) fn sub_seed64(seed_val: u64, index: usize) -> u64 {
+    let mut rng = NlmRng::seed_from_u64(seed_val);
+    rng.set_stream(index as u64 + 1);
+    rng.next_u64()
 }
 
 #[cfg(test)]
@@ -28,12 +70,31 @@ pub(crate) fn nan_count(grid: &crate::Grid) -> usize {
 
 #[cfg(test)]
 pub(crate) fn zero_to_one_count(grid: &crate::Grid) -> usize {
-    grid.iter().filter(|&&n| n >= 0. && n <= 1.).count()
+    grid.iter().filter(|&&n| (0. ..=1.).contains(&n)).count()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{classify, random, threshold, Grid};
+    use crate::{
+        apply_mask, classify, classify_weighted, edge_gradient, edge_gradient_f32, fbm_noise,
+        fbm_noise_f32, gaussian_field, gaussian_field_f32, label_patches, percolation,
+        perlin_noise, perlin_noise_f32, planar_gradient, planar_gradient_f32, polygon_mask,
+        random, ridged_noise, ridged_noise_f32, scale, simplex_noise, simplex_noise_f32,
+        threshold, try_add, try_apply_mask, try_classify, try_classify_weighted, try_multiply,
+        value_noise, value_noise_f32, wave_gradient, wave_gradient_f32, worley_noise,
+        worley_noise_f32, Boundary, Connectivity, Grid, NlmError,
+    };
+    use crate::operation::{
+        euclidean_distance_transform, euclidean_distance_transform_with_boundary, interpolate,
+        interpolate_with_boundary,
+    };
+
+    fn with_nodata(mut grid: Grid) -> Grid {
+        for j in 0..grid.cols {
+            grid[0][j] = f64::NAN;
+        }
+        grid
+    }
 
     // ── classify ─────────────────────────────────────────────────────────────
 
@@ -75,6 +136,356 @@ mod tests {
         assert_eq!(grid[0][0], 1.0);
     }
 
+    #[test]
+    fn test_classify_skips_nodata() {
+        let mut grid = with_nodata(random(10, 10, Some(13)));
+        classify(&mut grid, 3);
+        assert_eq!(grid.nodata_count(), 10);
+        assert!(grid.iter().filter(|v| !v.is_nan()).all(|&v| v == 0.0 || v == 0.5 || v == 1.0));
+    }
+
+    #[test]
+    fn test_try_classify_rejects_zero_classes() {
+        let mut grid = random(10, 10, Some(3));
+        let before = grid.clone();
+        assert!(try_classify(&mut grid, 0).is_err());
+        assert_eq!(grid, before);
+    }
+
+    // ── classify_weighted ────────────────────────────────────────────────────
+
+    #[test]
+    fn test_classify_weighted_exact_proportions() {
+        let mut grid = random(50, 40, Some(7));
+        classify_weighted(&mut grid, &[0.6, 0.3, 0.1]);
+        let count = |t: f64| grid.iter().filter(|&&v| v == t).count();
+        assert_eq!(count(0.0), 1200);
+        assert_eq!(count(0.5), 600);
+        assert_eq!(count(1.0), 200);
+    }
+
+    #[test]
+    fn test_classify_weighted_preserves_rank() {
+        let original = random(30, 30, Some(8));
+        let mut grid = original.clone();
+        classify_weighted(&mut grid, &[1.0, 1.0, 2.0]);
+        for (i, &a) in original.iter().enumerate() {
+            for (j, &b) in original.iter().enumerate() {
+                if a < b {
+                    assert!(grid.data[i] <= grid.data[j], "cells {i}, {j} out of order");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_classify_weighted_unnormalised_weights() {
+        let mut a = random(20, 20, Some(9));
+        let mut b = a.clone();
+        classify_weighted(&mut a, &[0.25, 0.75]);
+        classify_weighted(&mut b, &[1.0, 3.0]);
+        assert_eq!(a.data, b.data);
+        assert_eq!(a.iter().filter(|&&v| v == 0.0).count(), 100);
+    }
+
+    #[test]
+    fn test_classify_weighted_zero_weight_class() {
+        let mut grid = random(10, 10, Some(10));
+        classify_weighted(&mut grid, &[0.5, 0.0, 0.5]);
+        assert!(grid.iter().all(|&v| v == 0.0 || v == 1.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_classify_weighted_empty_weights() {
+        let mut grid = random(5, 5, Some(11));
+        classify_weighted(&mut grid, &[]);
+    }
+
+    #[test]
+    fn test_classify_weighted_skips_nodata() {
+        let mut grid = with_nodata(random(10, 10, Some(14)));
+        classify_weighted(&mut grid, &[0.5, 0.5]);
+        assert_eq!(grid.nodata_count(), 10);
+        assert_eq!(grid.iter().filter(|&&v| v == 0.0).count(), 45);
+        assert_eq!(grid.iter().filter(|&&v| v == 1.0).count(), 45);
+    }
+
+    #[test]
+    fn test_try_classify_weighted_rejects_bad_weights() {
+        let mut grid = random(10, 10, Some(3));
+        assert!(try_classify_weighted(&mut grid, &[]).is_err());
+        assert!(try_classify_weighted(&mut grid, &[1.0, -1.0]).is_err());
+        assert!(try_classify_weighted(&mut grid, &[0.0, 0.0]).is_err());
+        assert!(try_classify_weighted(&mut grid, &[1.0, f64::NAN]).is_err());
+        assert!(try_classify_weighted(&mut grid, &[1.0, 1.0]).is_ok());
+    }
+
+    // ── boundary ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_boundary_offset() {
+        assert_eq!(Boundary::Clamped.offset(2, 1, 5), Some(3));
+        assert_eq!(Boundary::Clamped.offset(0, -1, 5), None);
+        assert_eq!(Boundary::Clamped.offset(4, 1, 5), None);
+        assert_eq!(Boundary::Periodic.offset(0, -1, 5), Some(4));
+        assert_eq!(Boundary::Periodic.offset(4, 1, 5), Some(0));
+        assert_eq!(Boundary::Periodic.offset(1, -7, 5), Some(4));
+        assert_eq!(Boundary::Periodic.offset(0, 1, 0), None);
+    }
+
+    #[test]
+    fn test_boundary_resolve() {
+        assert_eq!(Boundary::Clamped.resolve(0, -2, 5), 0);
+        assert_eq!(Boundary::Clamped.resolve(3, 4, 5), 4);
+        assert_eq!(Boundary::Periodic.resolve(0, -2, 5), 3);
+        assert_eq!(Boundary::Periodic.resolve(3, 4, 5), 2);
+    }
+
+    // ── interpolate ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_interpolate_periodic_wraps() {
+        let mut seeds = Grid::new(1, 8);
+        seeds[0][0] = 1.0;
+        seeds[0][2] = 2.0;
+
+        let mut clamped = seeds.clone();
+        interpolate(&mut clamped);
+        assert_eq!(clamped[0][6], 2.0);
+        assert_eq!(clamped[0][7], 2.0);
+
+        let mut torus = seeds;
+        interpolate_with_boundary(&mut torus, Boundary::Periodic);
+        assert_eq!(torus[0][6], 1.0);
+        assert_eq!(torus[0][7], 1.0);
+    }
+
+    // ── euclidean_distance_transform ─────────────────────────────────────────
+
+    #[test]
+    fn test_edt_periodic_wraps() {
+        let mut clamped = Grid::filled(5, 7, 1.0);
+        clamped[0][0] = 0.0;
+        let mut torus = clamped.clone();
+        euclidean_distance_transform(&mut clamped);
+        euclidean_distance_transform_with_boundary(&mut torus, Boundary::Periodic);
+
+        assert_eq!(clamped[0][6], 6.0);
+        assert_eq!(torus[0][6], 1.0);
+        assert!((clamped[4][6] - 52f64.sqrt()).abs() < 1e-12);
+        assert!((torus[4][6] - 2f64.sqrt()).abs() < 1e-12);
+        assert!((torus[2][3] - 13f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_edt_periodic_matches_brute_force() {
+        let (rows, cols) = (17, 12);
+        let mut grid = percolation(rows, cols, 0.95, Some(5));
+        let zeros: Vec<(usize, usize)> = (0..rows * cols)
+            .filter(|&k| grid.data[k] == 0.0)
+            .map(|k| (k / cols, k % cols))
+            .collect();
+        assert!(!zeros.is_empty());
+        euclidean_distance_transform_with_boundary(&mut grid, Boundary::Periodic);
+
+        let wrap = |d: usize, len: usize| d.min(len - d) as f64;
+        for i in 0..rows {
+            for j in 0..cols {
+                let expected = zeros
+                    .iter()
+                    .map(|&(zi, zj)| {
+                        let di = wrap(i.abs_diff(zi), rows);
+                        let dj = wrap(j.abs_diff(zj), cols);
+                        (di * di + dj * dj).sqrt()
+                    })
+                    .fold(f64::INFINITY, f64::min);
+                assert!((grid[i][j] - expected).abs() < 1e-9, "({i}, {j}): {} vs {expected}", grid[i][j]);
+            }
+        }
+    }
+
+    // ── label_patches ────────────────────────────────────────────────────────
+
+    fn diagonal() -> Grid {
+        let mut grid = Grid::new(3, 3);
+        grid[0][0] = 1.0;
+        grid[1][1] = 1.0;
+        grid[2][2] = 1.0;
+        grid
+    }
+
+    #[test]
+    fn test_label_patches_rook_splits_diagonal() {
+        let (labels, patches) = label_patches(&diagonal(), Connectivity::Rook, Boundary::Clamped);
+        let ones: Vec<_> = patches.iter().filter(|p| p.value == 1.0).collect();
+        assert_eq!(ones.len(), 3);
+        assert!(ones.iter().all(|p| p.area == 1));
+        assert_ne!(labels[0][0], labels[1][1]);
+    }
+
+    #[test]
+    fn test_label_patches_queen_joins_diagonal() {
+        let (labels, patches) = label_patches(&diagonal(), Connectivity::Queen, Boundary::Clamped);
+        let ones: Vec<_> = patches.iter().filter(|p| p.value == 1.0).collect();
+        assert_eq!(ones.len(), 1);
+        assert_eq!(ones[0].area, 3);
+        assert_eq!((ones[0].min_row, ones[0].min_col), (0, 0));
+        assert_eq!((ones[0].max_row, ones[0].max_col), (2, 2));
+        assert_eq!(labels[0][0], labels[2][2]);
+    }
+
+    #[test]
+    fn test_label_patches_labels_index_patches() {
+        let grid = percolation(40, 40, 0.5, Some(12));
+        let (labels, patches) = label_patches(&grid, Connectivity::Rook, Boundary::Clamped);
+        assert_eq!(patches.iter().map(|p| p.area).sum::<usize>(), 1600);
+        for (&l, &v) in labels.iter().zip(grid.iter()) {
+            assert_eq!(patches[l as usize].value, v);
+        }
+    }
+
+    #[test]
+    fn test_label_patches_perimeter() {
+        let mut grid = Grid::new(4, 4);
+        grid[1][1] = 1.0;
+        grid[1][2] = 1.0;
+        let (_, patches) = label_patches(&grid, Connectivity::Rook, Boundary::Clamped);
+        let bar = patches.iter().find(|p| p.value == 1.0).unwrap();
+        assert_eq!(bar.perimeter, 6);
+        let matrix = patches.iter().find(|p| p.value == 0.0).unwrap();
+        // 6 sides facing the bar plus 16 along the grid boundary.
+        assert_eq!(matrix.perimeter, 22);
+    }
+
+    #[test]
+    fn test_label_patches_periodic_wraps() {
+        let mut grid = Grid::new(5, 5);
+        for i in 0..5 {
+            grid[i][0] = 1.0;
+            grid[i][4] = 1.0;
+        }
+        let (_, open) = label_patches(&grid, Connectivity::Rook, Boundary::Clamped);
+        assert_eq!(open.iter().filter(|p| p.value == 1.0).count(), 2);
+
+        let (labels, torus) = label_patches(&grid, Connectivity::Rook, Boundary::Periodic);
+        let ones: Vec<_> = torus.iter().filter(|p| p.value == 1.0).collect();
+        assert_eq!(ones.len(), 1);
+        assert_eq!(ones[0].area, 10);
+        assert_eq!(ones[0].perimeter, 10);
+        assert!(ones[0].spans_rows(5) && !ones[0].spans_cols(5));
+        assert_eq!(labels[2][0], labels[2][4]);
+    }
+
+    #[test]
+    fn test_label_patches_thin_wrapped_patch_does_not_span() {
+        let mut grid = Grid::new(5, 5);
+        for (r, c) in [(0, 0), (0, 4), (4, 0), (4, 4)] {
+            grid[r][c] = 1.0;
+        }
+        let (_, patches) = label_patches(&grid, Connectivity::Rook, Boundary::Periodic);
+        let corner = patches.iter().find(|p| p.value == 1.0).unwrap();
+        assert_eq!(corner.area, 4);
+        assert_eq!((corner.min_row, corner.max_row), (0, 4));
+        assert!(!corner.wraps_rows && !corner.wraps_cols);
+        assert!(!corner.spans_rows(5) && !corner.spans_cols(5));
+
+        let matrix = patches.iter().find(|p| p.value == 0.0).unwrap();
+        assert!(matrix.wraps_rows && matrix.wraps_cols);
+        assert!(matrix.spans_rows(5) && matrix.spans_cols(5));
+    }
+
+    #[test]
+    fn test_label_patches_spanning_cluster() {
+        let mut grid = Grid::new(4, 4);
+        for i in 0..4 {
+            grid[i][1] = 1.0;
+        }
+        let (_, patches) = label_patches(&grid, Connectivity::Rook, Boundary::Clamped);
+        let column = patches.iter().find(|p| p.value == 1.0).unwrap();
+        assert!(column.spans_rows(4));
+        assert!(!column.spans_cols(4));
+    }
+
+    #[test]
+    fn test_label_patches_empty() {
+        let (labels, patches) = label_patches(&Grid::new(0, 0), Connectivity::Queen, Boundary::Periodic);
+        assert!(labels.is_empty());
+        assert!(patches.is_empty());
+    }
+
+    #[test]
+    fn test_label_patches_skips_nodata() {
+        let mut grid = Grid::new(3, 3);
+        for i in 0..3 {
+            grid[i][1] = f64::NAN;
+        }
+        let (labels, patches) = label_patches(&grid, Connectivity::Queen, Boundary::Clamped);
+        assert_eq!(patches.len(), 2);
+        assert!(patches.iter().all(|p| p.area == 3 && p.perimeter == 8));
+        assert!(labels[0][1].is_nan());
+    }
+
+    // ── masks ────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_apply_mask() {
+        let mut grid = random(4, 4, Some(15));
+        let mut mask = Grid::filled(4, 4, 1.0);
+        mask[0][0] = 0.0;
+        mask[3][3] = f64::NAN;
+        apply_mask(&mut grid, &mask);
+        assert!(grid.is_nodata(0, 0) && grid.is_nodata(3, 3));
+        assert_eq!(grid.nodata_count(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_apply_mask_shape_mismatch() {
+        apply_mask(&mut Grid::new(3, 3), &Grid::new(3, 4));
+    }
+
+    #[test]
+    fn test_polygon_mask_square() {
+        let mask = polygon_mask(10, 10, &[(2.0, 2.0), (2.0, 6.0), (6.0, 6.0), (6.0, 2.0)]);
+        assert_eq!(mask.iter().filter(|&&v| v == 1.0).count(), 16);
+        assert_eq!(mask[2][2], 1.0);
+        assert_eq!(mask[6][6], 0.0);
+    }
+
+    #[test]
+    fn test_polygon_mask_triangle() {
+        let mask = polygon_mask(10, 10, &[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]);
+        // Cells strictly below the diagonal plus the 10 cells whose centres lie on it.
+        let inside = mask.iter().filter(|&&v| v == 1.0).count();
+        assert!((45..=55).contains(&inside), "{inside} cells inside");
+        assert_eq!(mask[9][0], 1.0);
+        assert_eq!(mask[0][9], 0.0);
+    }
+
+    #[test]
+    fn test_scale_skips_nodata() {
+        let mut grid = with_nodata(random(10, 10, Some(16)));
+        scale(&mut grid);
+        let valid: Vec<f64> = grid.iter().copied().filter(|v| !v.is_nan()).collect();
+        assert_eq!(valid.len(), 90);
+        assert_eq!(valid.iter().cloned().fold(f64::INFINITY, f64::min), 0.0);
+        assert_eq!(valid.iter().cloned().fold(f64::NEG_INFINITY, f64::max), 1.0);
+    }
+
+    #[test]
+    fn test_try_apply_mask_shape_mismatch() {
+        let mut grid = random(10, 10, Some(3));
+        let err = try_apply_mask(&mut grid, &Grid::filled(5, 10, 1.0)).unwrap_err();
+        assert!(matches!(
+            err,
+            NlmError::ShapeMismatch { expected: (10, 10), found: (5, 10) }
+        ));
+        assert!(try_add(&mut grid, &Grid::new(10, 9)).is_err());
+        assert!(try_multiply(&mut grid, &Grid::new(9, 10)).is_err());
+        assert!(try_add(&mut grid, &Grid::new(10, 10)).is_ok());
+    }
+
     // ── threshold ────────────────────────────────────────────────────────────
 
     #[test]
@@ -106,4 +517,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_threshold_skips_nodata() {
+        let mut grid = with_nodata(random(10, 10, Some(17)));
+        threshold(&mut grid, 0.5);
+        assert_eq!(grid.nodata_count(), 10);
+    }
+
+    // ── f32 grids ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_f32_round_trip() {
+        let grid = random(20, 30, Some(18));
+        let single = grid.to_f32();
+        assert_eq!((single.rows, single.cols), (20, 30));
+        for (&a, &b) in grid.iter().zip(single.iter()) {
+            assert_eq!(a as f32, b);
+        }
+        assert_eq!(single.to_f64().to_f32(), single);
+    }
+
+    #[test]
+    fn test_f32_operations_match_f64() {
+        let mut double = with_nodata(random(30, 30, Some(19)));
+        let mut single = double.to_f32();
+        scale(&mut double);
+        scale(&mut single);
+        assert_eq!(single.nodata_count(), 30);
+        for (&a, &b) in double.iter().zip(single.iter()) {
+            assert!(a.is_nan() && b.is_nan() || (a - b as f64).abs() < 1e-6);
+        }
+        classify_weighted(&mut single, &[1.0, 2.0]);
+        assert_eq!(single.iter().filter(|&&v| v == 1.0).count(), 580);
+
+        let mask = polygon_mask(30, 30, &[(0.0, 0.0), (30.0, 0.0), (0.0, 30.0)]);
+        let mut single = random(30, 30, Some(20)).to_f32();
+        apply_mask(&mut single, &mask);
+        threshold(&mut single, 0.5f32);
+        assert!(single.iter().all(|&v| v.is_nan() || v == 0.0 || v == 1.0));
+    }
+
+    #[test]
+    fn test_f32_generators_match_f64() {
+        let close = |double: Grid, single: Grid<f32>| {
+            assert_eq!((single.rows, single.cols), (double.rows, double.cols));
+            for (&a, &b) in double.iter().zip(single.iter()) {
+                assert!((a - b as f64).abs() < 1e-5, "{a} vs {b}");
+            }
+        };
+        let seed = Some(21);
+        close(perlin_noise(30, 40, 4.0, seed), perlin_noise_f32(30, 40, 4.0, seed));
+        close(
+            fbm_noise(30, 40, 4.0, 5, 0.5, 2.0, seed),
+            fbm_noise_f32(30, 40, 4.0, 5, 0.5, 2.0, seed),
+        );
+        close(
+            ridged_noise(30, 40, 4.0, 5, 0.5, 2.0, seed),
+            ridged_noise_f32(30, 40, 4.0, 5, 0.5, 2.0, seed),
+        );
+        close(worley_noise(30, 40, 4.0, seed), worley_noise_f32(30, 40, 4.0, seed));
+        close(value_noise(30, 40, 4.0, seed), value_noise_f32(30, 40, 4.0, seed));
+        close(simplex_noise(30, 40, 4.0, seed), simplex_noise_f32(30, 40, 4.0, seed));
+        close(planar_gradient(30, 40, None, seed), planar_gradient_f32(30, 40, None, seed));
+        close(edge_gradient(30, 40, None, seed), edge_gradient_f32(30, 40, None, seed));
+        close(
+            wave_gradient(30, 40, 2.5, None, seed),
+            wave_gradient_f32(30, 40, 2.5, None, seed),
+        );
+        close(gaussian_field(30, 40, 3.0, seed), gaussian_field_f32(30, 40, 3.0, seed));
+    }
 }
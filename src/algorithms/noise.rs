@@ -1,10 +1,20 @@
-use crate::grid::Grid;
+use crate::grid::{Boundary, Element, Grid};
 use crate::operation::scale;
-use super::{make_rng, perlin_seed};
+use crate::tiled::Window;
+use super::{make_rng, perlin_seed, sub_seed};
 use rand::Rng;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+/// Frequency and octave settings of the fractal Perlin samplers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Octaves {
+    pub scale_factor: f64,
+    pub octaves: usize,
+    pub persistence: f64,
+    pub lacunarity: f64,
+}
+
 /// Returns a Perlin noise NLM with values ranging [0, 1).
 ///
 /// # Arguments
@@ -14,26 +24,51 @@ use rayon::prelude::*;
 /// * `scale_factor` - Frequency of the noise (higher = more features per unit).
 /// * `seed` - Optional RNG seed for reproducible results.
 pub fn perlin_noise(rows: usize, cols: usize, scale_factor: f64, seed: Option<u64>) -> Grid {
+    let window = Window::all(rows, cols);
+    let mut grid = perlin_window(rows, cols, scale_factor, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// [`perlin_noise`] sampled straight into a single-precision grid, with no `f64`
+/// copy in between.
+pub fn perlin_noise_f32(
+    rows: usize,
+    cols: usize,
+    scale_factor: f64,
+    seed: Option<u64>,
+) -> Grid<f32> {
+    let window = Window::all(rows, cols);
+    let mut grid = perlin_window(rows, cols, scale_factor, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// Unscaled [`perlin_noise`] values over `window` of a (rows x cols) grid.
+pub(crate) fn perlin_window<T: Element>(
+    rows: usize,
+    cols: usize,
+    scale_factor: f64,
+    seed_val: u32,
+    window: Window,
+) -> Grid<T> {
     use noise::{NoiseFn, Perlin};
-    let seed_val = perlin_seed(seed);
     let perlin = Perlin::new(seed_val);
 
-    let mut grid = Grid::new(rows, cols);
+    let mut grid = Grid::from_value(window.rows, window.cols, T::ZERO);
     let inv_rows = 1.0 / rows as f64;
     let inv_cols = 1.0 / cols as f64;
-    let fill_row = |(i, row): (usize, &mut [f64])| {
-        let ny = i as f64 * inv_rows * scale_factor;
+    let fill_row = |(i, row): (usize, &mut [T])| {
+        let ny = (window.row + i) as f64 * inv_rows * scale_factor;
         for (j, cell) in row.iter_mut().enumerate() {
-            let nx = j as f64 * inv_cols * scale_factor;
-            *cell = perlin.get([nx, ny]);
+            let nx = (window.col + j) as f64 * inv_cols * scale_factor;
+            *cell = T::from_f64(perlin.get([nx, ny]));
         }
     };
     #[cfg(feature = "parallel")]
-    grid.data.par_chunks_mut(cols).enumerate().for_each(fill_row);
+    grid.data.par_chunks_mut(window.cols).enumerate().for_each(fill_row);
     #[cfg(not(feature = "parallel"))]
-    grid.data.chunks_mut(cols).enumerate().for_each(fill_row);
-
-    scale(&mut grid);
+    grid.data.chunks_mut(window.cols).enumerate().for_each(fill_row);
     grid
 }
 
@@ -59,10 +94,43 @@ pub fn fbm_noise(
     lacunarity: f64,
     seed: Option<u64>,
 ) -> Grid {
+    let octaves = Octaves { scale_factor, octaves, persistence, lacunarity };
+    let window = Window::all(rows, cols);
+    let mut grid = fbm_window(rows, cols, octaves, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// [`fbm_noise`] sampled straight into a single-precision grid, with no `f64`
+/// copy in between.
+pub fn fbm_noise_f32(
+    rows: usize,
+    cols: usize,
+    scale_factor: f64,
+    octaves: usize,
+    persistence: f64,
+    lacunarity: f64,
+    seed: Option<u64>,
+) -> Grid<f32> {
+    let octaves = Octaves { scale_factor, octaves, persistence, lacunarity };
+    let window = Window::all(rows, cols);
+    let mut grid = fbm_window(rows, cols, octaves, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// Unscaled [`fbm_noise`] values over `window` of a (rows x cols) grid.
+pub(crate) fn fbm_window<T: Element>(
+    rows: usize,
+    cols: usize,
+    octaves: Octaves,
+    seed_val: u32,
+    window: Window,
+) -> Grid<T> {
     use noise::{NoiseFn, Perlin};
-    let seed_val = perlin_seed(seed);
+    let Octaves { scale_factor, octaves, persistence, lacunarity } = octaves;
     let generators: Vec<Perlin> = (0..octaves)
-        .map(|o| Perlin::new(seed_val.wrapping_add(o as u32)))
+        .map(|o| Perlin::new(sub_seed(seed_val, o)))
         .collect();
 
     let mut freq_amp: Vec<(f64, f64)> = Vec::with_capacity(octaves);
@@ -82,28 +150,27 @@ pub fn fbm_noise(
         }
     }
 
-    let mut grid = Grid::new(rows, cols);
+    let mut grid = Grid::from_value(window.rows, window.cols, T::ZERO);
     let inv_rows = 1.0 / rows as f64;
     let inv_cols = 1.0 / cols as f64;
-    let fill_row = |(i, row): (usize, &mut [f64])| {
+    let fill_row = |(i, row): (usize, &mut [T])| {
+        let i = window.row + i;
         let nys: Vec<f64> =
             freq_amp.iter().map(|&(freq, _)| i as f64 * inv_rows * freq).collect();
         for (j, cell) in row.iter_mut().enumerate() {
-            let x = j as f64 * inv_cols;
+            let x = (window.col + j) as f64 * inv_cols;
             let mut value = 0.0;
             for (k, gen) in generators.iter().enumerate() {
                 let (freq, amp) = freq_amp[k];
                 value += gen.get([x * freq, nys[k]]) * amp;
             }
-            *cell = value;
+            *cell = T::from_f64(value);
         }
     };
     #[cfg(feature = "parallel")]
-    grid.data.par_chunks_mut(cols).enumerate().for_each(fill_row);
+    grid.data.par_chunks_mut(window.cols).enumerate().for_each(fill_row);
     #[cfg(not(feature = "parallel"))]
-    grid.data.chunks_mut(cols).enumerate().for_each(fill_row);
-
-    scale(&mut grid);
+    grid.data.chunks_mut(window.cols).enumerate().for_each(fill_row);
     grid
 }
 
@@ -131,29 +198,60 @@ pub fn ridged_noise(
     lacunarity: f64,
     seed: Option<u64>,
 ) -> Grid {
+    let octaves = Octaves { scale_factor, octaves, persistence, lacunarity };
+    let window = Window::all(rows, cols);
+    let mut grid = ridged_window(rows, cols, octaves, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// [`ridged_noise`] sampled straight into a single-precision grid, with no `f64`
+/// copy in between.
+pub fn ridged_noise_f32(
+    rows: usize,
+    cols: usize,
+    scale_factor: f64,
+    octaves: usize,
+    persistence: f64,
+    lacunarity: f64,
+    seed: Option<u64>,
+) -> Grid<f32> {
+    let octaves = Octaves { scale_factor, octaves, persistence, lacunarity };
+    let window = Window::all(rows, cols);
+    let mut grid = ridged_window(rows, cols, octaves, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// Unscaled [`ridged_noise`] values over `window` of a (rows x cols) grid.
+pub(crate) fn ridged_window<T: Element>(
+    rows: usize,
+    cols: usize,
+    octaves: Octaves,
+    seed_val: u32,
+    window: Window,
+) -> Grid<T> {
     use noise::{MultiFractal, NoiseFn, Perlin, RidgedMulti};
-    let seed_val = perlin_seed(seed);
+    let Octaves { scale_factor, octaves, persistence, lacunarity } = octaves;
     let ridged = RidgedMulti::<Perlin>::new(seed_val)
         .set_octaves(octaves)
         .set_persistence(persistence)
         .set_lacunarity(lacunarity);
 
-    let mut grid = Grid::new(rows, cols);
+    let mut grid = Grid::from_value(window.rows, window.cols, T::ZERO);
     let inv_rows = 1.0 / rows as f64;
     let inv_cols = 1.0 / cols as f64;
-    let fill_row = |(i, row): (usize, &mut [f64])| {
-        let ny = i as f64 * inv_rows * scale_factor;
+    let fill_row = |(i, row): (usize, &mut [T])| {
+        let ny = (window.row + i) as f64 * inv_rows * scale_factor;
         for (j, cell) in row.iter_mut().enumerate() {
-            let nx = j as f64 * inv_cols * scale_factor;
-            *cell = ridged.get([nx, ny]);
+            let nx = (window.col + j) as f64 * inv_cols * scale_factor;
+            *cell = T::from_f64(ridged.get([nx, ny]));
         }
     };
     #[cfg(feature = "parallel")]
-    grid.data.par_chunks_mut(cols).enumerate().for_each(fill_row);
+    grid.data.par_chunks_mut(window.cols).enumerate().for_each(fill_row);
     #[cfg(not(feature = "parallel"))]
-    grid.data.chunks_mut(cols).enumerate().for_each(fill_row);
-
-    scale(&mut grid);
+    grid.data.chunks_mut(window.cols).enumerate().for_each(fill_row);
     grid
 }
 
@@ -219,28 +317,53 @@ pub fn billow_noise(
 /// * `scale_factor` - Frequency of the seed points (higher = smaller cells).
 /// * `seed` - Optional RNG seed for reproducible results.
 pub fn worley_noise(rows: usize, cols: usize, scale_factor: f64, seed: Option<u64>) -> Grid {
+    let window = Window::all(rows, cols);
+    let mut grid = worley_window(rows, cols, scale_factor, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// [`worley_noise`] sampled straight into a single-precision grid, with no `f64`
+/// copy in between.
+pub fn worley_noise_f32(
+    rows: usize,
+    cols: usize,
+    scale_factor: f64,
+    seed: Option<u64>,
+) -> Grid<f32> {
+    let window = Window::all(rows, cols);
+    let mut grid = worley_window(rows, cols, scale_factor, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// Unscaled [`worley_noise`] values over `window` of a (rows x cols) grid.
+pub(crate) fn worley_window<T: Element>(
+    rows: usize,
+    cols: usize,
+    scale_factor: f64,
+    seed_val: u32,
+    window: Window,
+) -> Grid<T> {
     use noise::{NoiseFn, Worley};
-    let seed_val = perlin_seed(seed);
 
-    let mut grid = Grid::new(rows, cols);
+    let mut grid = Grid::from_value(window.rows, window.cols, T::ZERO);
     let inv_rows = 1.0 / rows as f64;
     let inv_cols = 1.0 / cols as f64;
     // Worley contains Rc<dyn Fn> and is not Sync, so each row constructs its
     // own instance.  The permutation table creation is O(256) — negligible.
-    let fill_row = |(i, row): (usize, &mut [f64])| {
+    let fill_row = |(i, row): (usize, &mut [T])| {
         let worley = Worley::new(seed_val);
-        let ny = i as f64 * inv_rows * scale_factor;
+        let ny = (window.row + i) as f64 * inv_rows * scale_factor;
         for (j, cell) in row.iter_mut().enumerate() {
-            let nx = j as f64 * inv_cols * scale_factor;
-            *cell = worley.get([nx, ny]);
+            let nx = (window.col + j) as f64 * inv_cols * scale_factor;
+            *cell = T::from_f64(worley.get([nx, ny]));
         }
     };
     #[cfg(feature = "parallel")]
-    grid.data.par_chunks_mut(cols).enumerate().for_each(fill_row);
+    grid.data.par_chunks_mut(window.cols).enumerate().for_each(fill_row);
     #[cfg(not(feature = "parallel"))]
-    grid.data.chunks_mut(cols).enumerate().for_each(fill_row);
-
-    scale(&mut grid);
+    grid.data.chunks_mut(window.cols).enumerate().for_each(fill_row);
     grid
 }
 
@@ -305,26 +428,51 @@ pub fn hybrid_noise(
 /// * `scale_factor` - Frequency of the noise (higher = more features per unit).
 /// * `seed` - Optional RNG seed for reproducible results.
 pub fn value_noise(rows: usize, cols: usize, scale_factor: f64, seed: Option<u64>) -> Grid {
+    let window = Window::all(rows, cols);
+    let mut grid = value_window(rows, cols, scale_factor, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// [`value_noise`] sampled straight into a single-precision grid, with no `f64`
+/// copy in between.
+pub fn value_noise_f32(
+    rows: usize,
+    cols: usize,
+    scale_factor: f64,
+    seed: Option<u64>,
+) -> Grid<f32> {
+    let window = Window::all(rows, cols);
+    let mut grid = value_window(rows, cols, scale_factor, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// Unscaled [`value_noise`] values over `window` of a (rows x cols) grid.
+pub(crate) fn value_window<T: Element>(
+    rows: usize,
+    cols: usize,
+    scale_factor: f64,
+    seed_val: u32,
+    window: Window,
+) -> Grid<T> {
     use noise::{NoiseFn, Value};
-    let seed_val = perlin_seed(seed);
     let v = Value::new(seed_val);
 
-    let mut grid = Grid::new(rows, cols);
+    let mut grid = Grid::from_value(window.rows, window.cols, T::ZERO);
     let inv_rows = 1.0 / rows as f64;
     let inv_cols = 1.0 / cols as f64;
-    let fill_row = |(i, row): (usize, &mut [f64])| {
-        let ny = i as f64 * inv_rows * scale_factor;
+    let fill_row = |(i, row): (usize, &mut [T])| {
+        let ny = (window.row + i) as f64 * inv_rows * scale_factor;
         for (j, cell) in row.iter_mut().enumerate() {
-            let nx = j as f64 * inv_cols * scale_factor;
-            *cell = v.get([nx, ny]);
+            let nx = (window.col + j) as f64 * inv_cols * scale_factor;
+            *cell = T::from_f64(v.get([nx, ny]));
         }
     };
     #[cfg(feature = "parallel")]
-    grid.data.par_chunks_mut(cols).enumerate().for_each(fill_row);
+    grid.data.par_chunks_mut(window.cols).enumerate().for_each(fill_row);
     #[cfg(not(feature = "parallel"))]
-    grid.data.chunks_mut(cols).enumerate().for_each(fill_row);
-
-    scale(&mut grid);
+    grid.data.chunks_mut(window.cols).enumerate().for_each(fill_row);
     grid
 }
 
@@ -354,7 +502,7 @@ pub fn turbulence(
     use noise::{NoiseFn, Perlin};
     let seed_val = perlin_seed(seed);
     let generators: Vec<Perlin> = (0..octaves)
-        .map(|o| Perlin::new(seed_val.wrapping_add(o as u32)))
+        .map(|o| Perlin::new(sub_seed(seed_val, o)))
         .collect();
 
     let mut freq_amp: Vec<(f64, f64)> = Vec::with_capacity(octaves);
@@ -421,7 +569,7 @@ pub fn domain_warp(
     use noise::{NoiseFn, Perlin};
     let seed_val = perlin_seed(seed);
     let warp = Perlin::new(seed_val);
-    let base = Perlin::new(seed_val.wrapping_add(1));
+    let base = Perlin::new(sub_seed(seed_val, 1));
 
     let mut grid = Grid::new(rows, cols);
     let inv_rows = 1.0 / rows as f64;
@@ -458,7 +606,7 @@ pub fn domain_warp(
 /// * `rows`  - Number of rows.
 /// * `cols`  - Number of columns.
 /// * `beta`  - Spectral exponent. 0 = white noise, 1 = pink noise,
-///             2 = red/brown noise (Brownian landscape), higher = smoother.
+///   2 = red/brown noise (Brownian landscape), higher = smoother.
 /// * `seed`  - Optional RNG seed for reproducible results.
 pub fn spectral_synthesis(rows: usize, cols: usize, beta: f64, seed: Option<u64>) -> Grid {
     use rustfft::{FftPlanner, num_complex::Complex};
@@ -549,26 +697,51 @@ pub fn fractal_brownian_surface(rows: usize, cols: usize, h: f64, seed: Option<u
 /// * `scale_factor` - Noise frequency (higher = more features per unit area).
 /// * `seed`         - Optional RNG seed for reproducible results.
 pub fn simplex_noise(rows: usize, cols: usize, scale_factor: f64, seed: Option<u64>) -> Grid {
+    let window = Window::all(rows, cols);
+    let mut grid = simplex_window(rows, cols, scale_factor, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// [`simplex_noise`] sampled straight into a single-precision grid, with no `f64`
+/// copy in between.
+pub fn simplex_noise_f32(
+    rows: usize,
+    cols: usize,
+    scale_factor: f64,
+    seed: Option<u64>,
+) -> Grid<f32> {
+    let window = Window::all(rows, cols);
+    let mut grid = simplex_window(rows, cols, scale_factor, perlin_seed(seed), window);
+    scale(&mut grid);
+    grid
+}
+
+/// Unscaled [`simplex_noise`] values over `window` of a (rows x cols) grid.
+pub(crate) fn simplex_window<T: Element>(
+    rows: usize,
+    cols: usize,
+    scale_factor: f64,
+    seed_val: u32,
+    window: Window,
+) -> Grid<T> {
     use noise::{NoiseFn, OpenSimplex};
-    let seed_val = perlin_seed(seed);
     let gen = OpenSimplex::new(seed_val);
 
-    let mut grid = Grid::new(rows, cols);
+    let mut grid = Grid::from_value(window.rows, window.cols, T::ZERO);
     let inv_rows = 1.0 / rows as f64;
     let inv_cols = 1.0 / cols as f64;
-    let fill_row = |(i, row): (usize, &mut [f64])| {
-        let ny = i as f64 * inv_rows * scale_factor;
+    let fill_row = |(i, row): (usize, &mut [T])| {
+        let ny = (window.row + i) as f64 * inv_rows * scale_factor;
         for (j, cell) in row.iter_mut().enumerate() {
-            let nx = j as f64 * inv_cols * scale_factor;
-            *cell = gen.get([nx, ny]);
+            let nx = (window.col + j) as f64 * inv_cols * scale_factor;
+            *cell = T::from_f64(gen.get([nx, ny]));
         }
     };
     #[cfg(feature = "parallel")]
-    grid.data.par_chunks_mut(cols).enumerate().for_each(fill_row);
+    grid.data.par_chunks_mut(window.cols).enumerate().for_each(fill_row);
     #[cfg(not(feature = "parallel"))]
-    grid.data.chunks_mut(cols).enumerate().for_each(fill_row);
-
-    scale(&mut grid);
+    grid.data.chunks_mut(window.cols).enumerate().for_each(fill_row);
     grid
 }
 
@@ -688,7 +861,7 @@ pub fn curl_noise(rows: usize, cols: usize, scale_factor: f64, seed: Option<u64>
     use noise::{NoiseFn, Perlin};
     let seed_val = perlin_seed(seed);
     let potential = Perlin::new(seed_val);
-    let base = Perlin::new(seed_val.wrapping_add(1));
+    let base = Perlin::new(sub_seed(seed_val, 1));
 
     let mut grid = Grid::new(rows, cols);
     let inv_rows = 1.0 / rows as f64;
@@ -870,7 +1043,7 @@ pub fn anisotropic_noise(
     let seed_val = perlin_seed(seed);
     let oct = octaves.max(1);
     let generators: Vec<Perlin> = (0..oct)
-        .map(|o| Perlin::new(seed_val.wrapping_add(o as u32)))
+        .map(|o| Perlin::new(sub_seed(seed_val, o)))
         .collect();
 
     let angle = direction.to_radians();
@@ -1110,10 +1283,22 @@ pub fn perlin_worley(rows: usize, cols: usize, scale_factor: f64, seed: Option<u
 /// * `sigma` - Gaussian kernel standard deviation in cells (correlation length).
 /// * `seed`  - Optional RNG seed for reproducible results.
 pub fn lognormal_field(rows: usize, cols: usize, sigma: f64, seed: Option<u64>) -> Grid {
+    lognormal_field_with_boundary(rows, cols, sigma, Boundary::Clamped, seed)
+}
+
+/// [`lognormal_field`] with `boundary` as the edge handling of the underlying
+/// [`gaussian_field_with_boundary`](super::gaussian_field_with_boundary).
+pub fn lognormal_field_with_boundary(
+    rows: usize,
+    cols: usize,
+    sigma: f64,
+    boundary: Boundary,
+    seed: Option<u64>,
+) -> Grid {
     // Build a Gaussian random field, re-centre it to span [-3, 3], then
     // exponentiate. The exponential of a normally-distributed variable is
     // lognormally distributed. scale() normalises the result to [0, 1].
-    let mut grid = super::gaussian_field(rows, cols, sigma, seed);
+    let mut grid = super::gaussian_field_with_boundary(rows, cols, sigma, boundary, seed);
     for v in grid.data.iter_mut() {
         // Map [0, 1] → [-3, 3] then exponentiate.
         *v = (*v * 6.0 - 3.0).exp();
@@ -1154,7 +1339,7 @@ pub fn multifractal_terrain(
     let offset = 1.0f64;
     let seed_val = perlin_seed(seed);
     let generators: Vec<Perlin> = (0..octaves.max(1))
-        .map(|o| Perlin::new(seed_val.wrapping_add(o as u32)))
+        .map(|o| Perlin::new(sub_seed(seed_val, o)))
         .collect();
 
     let mut grid = Grid::new(rows, cols);
@@ -1273,6 +1458,13 @@ mod tests {
         assert_eq!(a.data, b.data);
     }
 
+    #[test]
+    fn test_perlin_seed_keeps_high_bits() {
+        let a = perlin_noise(50, 50, 4.0, Some(42));
+        let b = perlin_noise(50, 50, 4.0, Some(42 + (1 << 32)));
+        assert_ne!(a.data, b.data);
+    }
+
     #[rstest]
     #[case(1, 1)]
     #[case(10, 10)]
@@ -1705,6 +1897,13 @@ mod tests {
         assert_eq!(a.data, b.data);
     }
 
+    #[test]
+    fn test_lognormal_field_periodic() {
+        let grid = lognormal_field_with_boundary(40, 30, 5.0, Boundary::Periodic, Some(42));
+        assert_eq!(nan_count(&grid), 0);
+        assert_eq!(zero_to_one_count(&grid), 40 * 30);
+    }
+
     // ── multifractal_terrain ──────────────────────────────────────────────────
 
     #[rstest]
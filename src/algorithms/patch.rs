@@ -1,13 +1,23 @@
-use crate::array::{diamond_square, rand_grid, rand_sub_grid};
-use crate::grid::Grid;
-use crate::operation::{interpolate, scale};
-use super::{make_rng, perlin_seed};
+use crate::array::{
+    diamond_square, diamond_square_periodic, rand_cells, rand_grid, rand_sub_grid,
+};
+use crate::error::{NlmError, Result};
+use crate::focal::separable;
+use crate::grid::{Boundary, Element, Grid};
+use crate::labels::{Label, LabelGrid};
+use crate::operation::{interpolate, interpolate_with_boundary, scale};
+use super::{make_rng, perlin_seed, sub_seed};
 use rand::Rng;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 // ── Private helpers ──────────────────────────────────────────────────────────
 
+/// Greatest common divisor, with `gcd(0, 0) == 0`.
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
 /// 4-point Laplacian with periodic (toroidal) boundary conditions.
 fn laplacian_periodic(v: &[f64], i: usize, j: usize, rows: usize, cols: usize) -> f64 {
     let get = |ri: isize, ci: isize| -> f64 {
@@ -20,7 +30,41 @@ fn laplacian_periodic(v: &[f64], i: usize, j: usize, rows: usize, cols: usize) -
         - 4.0 * get(ri, ci)
 }
 
+/// Values of a seed-filled grid whose cells hold `label + 1` of their patch
+/// (`0.0` where unlabelled), looked up in `classes`.
+fn patch_values(grid: &Grid, classes: &[f64]) -> Grid {
+    let data = grid
+        .iter()
+        .map(|&v| if v == 0.0 { 0.0 } else { classes[v as usize - 1] })
+        .collect();
+    Grid { data, rows: grid.rows, cols: grid.cols }
+}
+
+/// Labels of a seed-filled grid as for [`patch_values`], with unlabelled
+/// cells as NoData.
+fn patch_labels(grid: &Grid, classes: Vec<f64>) -> LabelGrid<u32> {
+    let data = grid
+        .iter()
+        .map(|&v| {
+            if v == 0.0 {
+                u32::NODATA
+            } else {
+                u32::from_index(v as usize - 1).unwrap_or(u32::NODATA)
+            }
+        })
+        .collect();
+    LabelGrid { data, rows: grid.rows, cols: grid.cols, classes }
+}
+
+/// Random cell indices in `0..n`, one per `site_area` cells and at least one.
+/// May repeat.
+fn random_sites(rng: &mut impl Rng, n: usize, site_area: usize) -> Vec<usize> {
+    let count = (n / site_area).max(1);
+    (0..count).map(|_| rng.gen_range(0..n)).collect()
+}
+
 /// True if point `d` lies inside the circumcircle of counter-clockwise triangle (a, b, c).
+#[allow(clippy::too_many_arguments)]
 fn circumcircle_contains(
     ax: f64, ay: f64, bx: f64, by: f64, cx: f64, cy: f64,
     dx: f64, dy: f64,
@@ -35,6 +79,7 @@ fn circumcircle_contains(
 }
 
 /// True if point `p` lies inside or on the edge of triangle (a, b, c).
+#[allow(clippy::too_many_arguments)]
 fn point_in_tri(
     px: f64, py: f64,
     ax: f64, ay: f64, bx: f64, by: f64, cx: f64, cy: f64,
@@ -115,6 +160,18 @@ pub fn random(rows: usize, cols: usize, seed: Option<u64>) -> Grid {
 ///
 /// Implementation ported from NLMpy.
 pub fn random_element(rows: usize, cols: usize, n: f64, seed: Option<u64>) -> Grid {
+    random_element_with_boundary(rows, cols, n, Boundary::Clamped, seed)
+}
+
+/// [`random_element`] with edges handled by `boundary`: with
+/// [`Boundary::Periodic`], elements extend across the edges.
+pub fn random_element_with_boundary(
+    rows: usize,
+    cols: usize,
+    n: f64,
+    boundary: Boundary,
+    seed: Option<u64>,
+) -> Grid {
     if rows == 0 || cols == 0 {
         return Grid::new(0, 0);
     }
@@ -136,7 +193,7 @@ pub fn random_element(rows: usize, cols: usize, n: f64, seed: Option<u64>) -> Gr
         i += 1.;
     }
 
-    interpolate(&mut grid);
+    interpolate_with_boundary(&mut grid, boundary);
     scale(&mut grid);
 
     grid
@@ -153,14 +210,39 @@ pub fn random_element(rows: usize, cols: usize, n: f64, seed: Option<u64>) -> Gr
 ///
 /// Implementation ported from NLMpy.
 pub fn midpoint_displacement(rows: usize, cols: usize, h: f64, seed: Option<u64>) -> Grid {
+    midpoint_displacement_with_boundary(rows, cols, h, Boundary::Clamped, seed)
+}
+
+/// [`midpoint_displacement`] with edges handled by `boundary`: with
+/// [`Boundary::Periodic`], the surface is generated on a torus so opposite
+/// edges join seamlessly.
+///
+/// Panics if [`check_periodic_midpoint`] fails for a periodic surface.
+pub fn midpoint_displacement_with_boundary(
+    rows: usize,
+    cols: usize,
+    h: f64,
+    boundary: Boundary,
+    seed: Option<u64>,
+) -> Grid {
     let max_dim = rows.max(cols);
-    if max_dim == 0 {
+    if max_dim == 0 || rows == 0 || cols == 0 {
         return Grid::new(0, 0);
     }
+    let mut rng = make_rng(seed);
+
+    if boundary.is_periodic() {
+        if let Err(e) = check_periodic_midpoint(rows, cols) {
+            panic!("{e}");
+        }
+        let mut surface = diamond_square_periodic(rows, cols, h, &mut rng);
+        scale(&mut surface);
+        return surface;
+    }
+
     let n = ((max_dim - 1) as f64).log2().ceil() as u32;
     let dim = usize::pow(2, n) + 1;
 
-    let mut rng = make_rng(seed);
     let mut surface = diamond_square(dim, h, &mut rng);
     surface = rand_sub_grid(surface, rows, cols, &mut rng);
 
@@ -168,6 +250,19 @@ pub fn midpoint_displacement(rows: usize, cols: usize, h: f64, seed: Option<u64>
     surface
 }
 
+/// Errors unless `rows` and `cols` are powers of two, the sizes of torus
+/// that periodic midpoint displacement can subdivide evenly.
+pub fn check_periodic_midpoint(rows: usize, cols: usize) -> Result<()> {
+    if rows.is_power_of_two() && cols.is_power_of_two() {
+        Ok(())
+    } else {
+        Err(NlmError::invalid(
+            "periodic",
+            format!("midpoint displacement wraps only powers of two, got {rows}x{cols}"),
+        ))
+    }
+}
+
 /// Returns a spatially correlated Gaussian random field NLM with values in [0, 1).
 ///
 /// Generates white noise then applies a separable Gaussian blur, so nearby
@@ -182,79 +277,73 @@ pub fn midpoint_displacement(rows: usize, cols: usize, h: f64, seed: Option<u64>
 ///   values produce larger, smoother patches.
 /// * `seed` - Optional RNG seed for reproducible results.
 pub fn gaussian_field(rows: usize, cols: usize, sigma: f64, seed: Option<u64>) -> Grid {
+    gaussian_field_with_boundary(rows, cols, sigma, Boundary::Clamped, seed)
+}
+
+/// [`gaussian_field`] with `boundary` as the edge handling of the blur.
+/// [`Boundary::Clamped`] repeats the edge cells; [`Boundary::Periodic`] wraps
+/// so the field is stationary on a torus.
+pub fn gaussian_field_with_boundary(
+    rows: usize,
+    cols: usize,
+    sigma: f64,
+    boundary: Boundary,
+    seed: Option<u64>,
+) -> Grid {
+    gaussian_field_as(rows, cols, sigma, boundary, seed)
+}
+
+/// [`gaussian_field`] blurred straight into single-precision grids, so the
+/// noise and both blur passes take half the memory of the `f64` version.
+pub fn gaussian_field_f32(rows: usize, cols: usize, sigma: f64, seed: Option<u64>) -> Grid<f32> {
+    gaussian_field_as(rows, cols, sigma, Boundary::Clamped, seed)
+}
+
+fn gaussian_field_as<T: Element>(
+    rows: usize,
+    cols: usize,
+    sigma: f64,
+    boundary: Boundary,
+    seed: Option<u64>,
+) -> Grid<T> {
     let mut rng = make_rng(seed);
 
     if rows == 0 || cols == 0 {
-        return Grid::new(0, 0);
+        return Grid::from_value(0, 0, T::ZERO);
     }
 
-    let mut grid = rand_grid(rows, cols, &mut rng);
+    let data = rand_cells(rows, cols, &mut rng, |r| T::from_f64(r.gen()));
+    let mut grid = Grid { data, rows, cols };
 
     if sigma <= 0.0 {
         scale(&mut grid);
         return grid;
     }
 
-    // Build a 1-D normalised Gaussian kernel with radius = ceil(3σ).
-    let radius = (3.0 * sigma).ceil() as usize;
-    let kernel: Vec<f64> = {
-        let size = 2 * radius + 1;
-        let mut k = vec![0.0f64; size];
-        let mut sum = 0.0f64;
-        for i in 0..size {
-            let x = i as f64 - radius as f64;
-            k[i] = (-0.5 * (x / sigma).powi(2)).exp();
-            sum += k[i];
-        }
-        k.iter_mut().for_each(|v| *v /= sum);
-        k
-    };
-
-    // Horizontal pass — row-wise, cache-friendly.
-    let mut row_conv = Grid::new(rows, cols);
-    {
-        let fill = |(i, out_row): (usize, &mut [f64])| {
-            let src_row = &grid[i];
-            for j in 0..cols {
-                let mut val = 0.0f64;
-                for (k, &w) in kernel.iter().enumerate() {
-                    let jj = (j as i64 + k as i64 - radius as i64)
-                        .clamp(0, cols as i64 - 1) as usize;
-                    val += src_row[jj] * w;
-                }
-                out_row[j] = val;
-            }
-        };
-        #[cfg(feature = "parallel")]
-        row_conv.data.par_chunks_mut(cols).enumerate().for_each(fill);
-        #[cfg(not(feature = "parallel"))]
-        row_conv.data.chunks_mut(cols).enumerate().for_each(fill);
-    }
-
-    // Vertical pass.
-    let mut result = Grid::new(rows, cols);
-    {
-        let fill = |(i, out_row): (usize, &mut [f64])| {
-            for j in 0..cols {
-                let mut val = 0.0f64;
-                for (k, &w) in kernel.iter().enumerate() {
-                    let ii = (i as i64 + k as i64 - radius as i64)
-                        .clamp(0, rows as i64 - 1) as usize;
-                    val += row_conv[ii][j] * w;
-                }
-                out_row[j] = val;
-            }
-        };
-        #[cfg(feature = "parallel")]
-        result.data.par_chunks_mut(cols).enumerate().for_each(fill);
-        #[cfg(not(feature = "parallel"))]
-        result.data.chunks_mut(cols).enumerate().for_each(fill);
-    }
+    let (radius, kernel) = gaussian_kernel(sigma);
+    let resolve = |i, offset, len| Some(boundary.resolve(i, offset, len));
+    let mut result = separable(&grid, radius, resolve, 0.0, |acc, v, k| acc + v * kernel[k]);
 
     scale(&mut result);
     result
 }
 
+/// The radius, ceil(3σ), and weights of the normalised 1-D Gaussian kernel
+/// that [`gaussian_field`] blurs with, for a positive `sigma`.
+pub(crate) fn gaussian_kernel(sigma: f64) -> (usize, Vec<f64>) {
+    let radius = (3.0 * sigma).ceil() as usize;
+    let size = 2 * radius + 1;
+    let mut k = vec![0.0f64; size];
+    let mut sum = 0.0f64;
+    for (i, w) in k.iter_mut().enumerate() {
+        let x = i as f64 - radius as f64;
+        *w = (-0.5 * (x / sigma).powi(2)).exp();
+        sum += *w;
+    }
+    k.iter_mut().for_each(|v| *v /= sum);
+    (radius, k)
+}
+
 /// Returns a random cluster NLM with values ranging [0, 1).
 ///
 /// Applies `n` random fault-line cuts; each cut adds +1 to all cells on one
@@ -269,6 +358,25 @@ pub fn gaussian_field(rows: usize, cols: usize, sigma: f64, seed: Option<u64>) -
 /// * `n` - Number of fault-line cuts (higher = finer-grained clustering).
 /// * `seed` - Optional RNG seed for reproducible results.
 pub fn random_cluster(rows: usize, cols: usize, n: usize, seed: Option<u64>) -> Grid {
+    random_cluster_with_boundary(rows, cols, n, Boundary::Clamped, seed)
+}
+
+/// [`random_cluster`] with edges handled by `boundary`: with
+/// [`Boundary::Periodic`], each cut is a pair of parallel closed lines on the
+/// torus, splitting it into two equal bands, instead of a single straight line.
+///
+/// A straight line only closes on a torus if its normal is a whole number of
+/// cycles across and down the grid, so periodic cuts have their normals
+/// snapped to the nearest primitive vector (a, b) with |a|, |b| ≤ 3. That
+/// leaves 12 orientations, measured in grid fractions rather than cells, and
+/// steep ones wind around the torus up to three times.
+pub fn random_cluster_with_boundary(
+    rows: usize,
+    cols: usize,
+    n: usize,
+    boundary: Boundary,
+    seed: Option<u64>,
+) -> Grid {
     if rows == 0 || cols == 0 {
         return Grid::new(0, 0);
     }
@@ -276,7 +384,7 @@ pub fn random_cluster(rows: usize, cols: usize, n: usize, seed: Option<u64>) ->
     let mut rng = make_rng(seed);
 
     // Pre-generate all cuts so the per-cell inner loop has no mutable state.
-    // Each cut: (px, py, sin θ, cos θ) where θ ∈ [0, π).
+    // Each cut: (px, py, sin θ, cos θ) where θ ∈ [0, π), i.e. a point and normal.
     let cuts: Vec<(f64, f64, f64, f64)> = (0..n)
         .map(|_| {
             let theta: f64 = rng.gen_range(0.0..std::f64::consts::PI);
@@ -286,9 +394,24 @@ pub fn random_cluster(rows: usize, cols: usize, n: usize, seed: Option<u64>) ->
         })
         .collect();
 
+    // On a torus a cut's normal must be an integer lattice direction for the
+    // cut to close on itself; snap each angle to the nearest primitive vector.
+    let cuts: Vec<(f64, f64, f64, f64)> = if boundary.is_periodic() {
+        cuts.into_iter()
+            .map(|(px, py, sin_t, cos_t)| {
+                let (a, b) = ((3.0 * sin_t).round() as i64, (3.0 * cos_t).round() as i64);
+                let g = gcd(a.unsigned_abs(), b.unsigned_abs()).max(1) as f64;
+                (px, py, a as f64 / g, b as f64 / g)
+            })
+            .collect()
+    } else {
+        cuts
+    };
+
     let mut grid = Grid::new(rows, cols);
     let inv_rows = 1.0 / rows as f64;
     let inv_cols = 1.0 / cols as f64;
+    let periodic = boundary.is_periodic();
 
     let fill = |(k, v): (usize, &mut f64)| {
         let row = k / cols;
@@ -297,8 +420,10 @@ pub fn random_cluster(rows: usize, cols: usize, n: usize, seed: Option<u64>) ->
         let y = row as f64 * inv_rows;
         *v = cuts
             .iter()
-            .map(|&(px, py, sin_t, cos_t)| {
-                if (x - px) * sin_t + (y - py) * cos_t > 0.0 { 1.0 } else { -1.0 }
+            .map(|&(px, py, nx, ny)| {
+                let d = (x - px) * nx + (y - py) * ny;
+                let side = if periodic { d.rem_euclid(1.0) < 0.5 } else { d > 0.0 };
+                if side { 1.0 } else { -1.0 }
             })
             .sum::<f64>();
     };
@@ -325,26 +450,71 @@ pub fn random_cluster(rows: usize, cols: usize, n: usize, seed: Option<u64>) ->
 /// * `n` - Number of Voronoi seed points to place.
 /// * `seed` - Optional RNG seed for reproducible results.
 pub fn mosaic(rows: usize, cols: usize, n: usize, seed: Option<u64>) -> Grid {
+    mosaic_with_boundary(rows, cols, n, Boundary::Clamped, seed)
+}
+
+/// [`mosaic`] with edges handled by `boundary`: with [`Boundary::Periodic`],
+/// patches extend across the edges.
+pub fn mosaic_with_boundary(
+    rows: usize,
+    cols: usize,
+    n: usize,
+    boundary: Boundary,
+    seed: Option<u64>,
+) -> Grid {
+    let (grid, classes) = mosaic_patches(rows, cols, n, boundary, seed);
+    patch_values(&grid, &classes)
+}
+
+/// [`mosaic`] as patch labels: each seed's patch is one class, and `classes`
+/// holds the patch values that [`mosaic`] returns for the same seed.
+pub fn mosaic_labels(rows: usize, cols: usize, n: usize, seed: Option<u64>) -> LabelGrid<u32> {
+    mosaic_labels_with_boundary(rows, cols, n, Boundary::Clamped, seed)
+}
+
+/// [`mosaic_labels`] with edges handled by `boundary`, as for
+/// [`mosaic_with_boundary`].
+pub fn mosaic_labels_with_boundary(
+    rows: usize,
+    cols: usize,
+    n: usize,
+    boundary: Boundary,
+    seed: Option<u64>,
+) -> LabelGrid<u32> {
+    let (grid, classes) = mosaic_patches(rows, cols, n, boundary, seed);
+    patch_labels(&grid, classes)
+}
+
+/// Mosaic patches as `label + 1` per cell, with the value of each label.
+fn mosaic_patches(
+    rows: usize,
+    cols: usize,
+    n: usize,
+    boundary: Boundary,
+    seed: Option<u64>,
+) -> (Grid, Vec<f64>) {
     if rows == 0 || cols == 0 {
-        return Grid::new(0, 0);
+        return (Grid::new(0, 0), Vec::new());
     }
 
     let mut rng = make_rng(seed);
     let mut grid = Grid::new(rows, cols);
+    let mut classes = Vec::new();
 
     for _ in 0..n {
         let row = rng.gen_range(0..rows);
         let col = rng.gen_range(0..cols);
         if grid[row][col] == 0.0 {
             // Use (0, 1) range but avoid 0.0 (reserved for "unlabelled")
-            grid[row][col] = rng.gen::<f64>() * 0.999 + 0.001;
+            classes.push(rng.gen::<f64>() * 0.999 + 0.001);
+            grid[row][col] = classes.len() as f64;
         }
     }
 
-    // BFS fills every unlabelled cell with the nearest seed's value.
+    // BFS fills every unlabelled cell with the nearest seed's label.
     // Values are already in (0.001, 1.0) — no scale() needed.
-    interpolate(&mut grid);
-    grid
+    interpolate_with_boundary(&mut grid, boundary);
+    (grid, classes)
 }
 
 /// Returns a rectangular cluster NLM with values ranging [0, 1).
@@ -406,9 +576,7 @@ pub fn percolation(rows: usize, cols: usize, p: f64, seed: Option<u64>) -> Grid
         return Grid::new(0, 0);
     }
     let mut rng = make_rng(seed);
-    let data = (0..rows * cols)
-        .map(|_| if rng.gen::<f64>() < p { 1.0 } else { 0.0 })
-        .collect();
+    let data = rand_cells(rows, cols, &mut rng, |r| if r.gen::<f64>() < p { 1.0 } else { 0.0 });
     Grid { data, rows, cols }
 }
 
@@ -437,8 +605,22 @@ pub fn binary_space_partitioning(rows: usize, cols: usize, n: usize, seed: Optio
     if rows == 0 || cols == 0 {
         return Grid::new(0, 0);
     }
+    binary_space_partitioning_labels(rows, cols, n, seed).to_grid()
+}
+
+/// [`binary_space_partitioning`] as rectangle labels: each leaf rectangle is
+/// one class, and `classes` holds the values that
+/// [`binary_space_partitioning`] returns for the same seed.
+pub fn binary_space_partitioning_labels(
+    rows: usize,
+    cols: usize,
+    n: usize,
+    seed: Option<u64>,
+) -> LabelGrid<u32> {
+    if rows == 0 || cols == 0 {
+        return LabelGrid { data: Vec::new(), rows: 0, cols: 0, classes: Vec::new() };
+    }
     let mut rng = make_rng(seed);
-    let mut grid = Grid::new(rows, cols);
 
     // (row_start, col_start, row_end, col_end)
     let mut rects: Vec<(usize, usize, usize, usize)> = vec![(0, 0, rows, cols)];
@@ -472,16 +654,21 @@ pub fn binary_space_partitioning(rows: usize, cols: usize, n: usize, seed: Optio
     }
 
     // Assign a unique random float to every leaf rectangle.
-    for (r0, c0, r1, c1) in rects {
-        let val: f64 = rng.gen();
+    let mut labels = LabelGrid {
+        data: vec![u32::NODATA; rows * cols],
+        rows,
+        cols,
+        classes: Vec::with_capacity(rects.len()),
+    };
+    for (k, (r0, c0, r1, c1)) in rects.into_iter().enumerate() {
+        labels.classes.push(rng.gen());
+        let label = u32::from_index(k).unwrap_or(u32::NODATA);
         for r in r0..r1 {
-            for c in c0..c1 {
-                grid[r][c] = val;
-            }
+            labels[r][c0..c1].fill(label);
         }
     }
 
-    grid
+    labels
 }
 
 /// Returns a neighbourhood clustering NLM with values ranging [0, 1).
@@ -505,6 +692,20 @@ pub fn neighbourhood_clustering(
     k: usize,
     iterations: usize,
     seed: Option<u64>,
+) -> Grid {
+    neighbourhood_clustering_with_boundary(rows, cols, k, iterations, Boundary::Clamped, seed)
+}
+
+/// [`neighbourhood_clustering`] with `boundary` as the edge handling of the
+/// 3×3 neighbourhood. [`Boundary::Clamped`] repeats edge cells;
+/// [`Boundary::Periodic`] wraps.
+pub fn neighbourhood_clustering_with_boundary(
+    rows: usize,
+    cols: usize,
+    k: usize,
+    iterations: usize,
+    boundary: Boundary,
+    seed: Option<u64>,
 ) -> Grid {
     if rows == 0 || cols == 0 {
         return Grid::new(0, 0);
@@ -513,7 +714,7 @@ pub fn neighbourhood_clustering(
     let k = k.max(2);
     let mut rng = make_rng(seed);
 
-    let mut classes: Vec<usize> = (0..rows * cols).map(|_| rng.gen_range(0..k)).collect();
+    let mut classes = rand_cells(rows, cols, &mut rng, |r| r.gen_range(0..k));
     let mut next = vec![0usize; rows * cols];
 
     for _ in 0..iterations {
@@ -521,10 +722,10 @@ pub fn neighbourhood_clustering(
             let i = idx / cols;
             let j = idx % cols;
             let mut counts = vec![0usize; k];
-            for di in -1i64..=1 {
-                let ni = (i as i64 + di).clamp(0, rows as i64 - 1) as usize;
-                for dj in -1i64..=1 {
-                    let nj = (j as i64 + dj).clamp(0, cols as i64 - 1) as usize;
+            for di in -1isize..=1 {
+                let ni = boundary.resolve(i, di, rows);
+                for dj in -1isize..=1 {
+                    let nj = boundary.resolve(j, dj, cols);
                     counts[classes[ni * cols + nj]] += 1;
                 }
             }
@@ -572,7 +773,7 @@ pub fn cellular_automaton(
     }
 
     let mut rng = make_rng(seed);
-    let mut state: Vec<bool> = (0..rows * cols).map(|_| rng.gen::<f64>() < p).collect();
+    let mut state = rand_cells(rows, cols, &mut rng, |r| r.gen::<f64>() < p);
     let mut next = vec![false; rows * cols];
 
     for _ in 0..iterations {
@@ -678,9 +879,9 @@ pub fn diffusion_limited_aggregation(rows: usize, cols: usize, n: usize, seed: O
 
             // 4-connected random walk step.
             match rng.gen_range(0..4u8) {
-                0 => { if pr > 0 { pr -= 1; } }
+                0 => pr = pr.saturating_sub(1),
                 1 => { if pr + 1 < rows { pr += 1; } }
-                2 => { if pc > 0 { pc -= 1; } }
+                2 => pc = pc.saturating_sub(1),
                 _ => { if pc + 1 < cols { pc += 1; } }
             }
         }
@@ -856,7 +1057,7 @@ pub fn invasion_percolation(rows: usize, cols: usize, n: usize, seed: Option<u64
     }
     let mut rng = make_rng(seed);
 
-    let weights: Vec<f64> = (0..rows * cols).map(|_| rng.gen::<f64>()).collect();
+    let weights: Vec<f64> = rand_cells(rows, cols, &mut rng, |r| r.gen());
     let mut invaded = vec![false; rows * cols];
     let cr = rows / 2;
     let cc = cols / 2;
@@ -1251,6 +1452,18 @@ mod tests {
     use super::super::{nan_count, zero_to_one_count};
     use rstest::rstest;
 
+    /// Mean absolute step across the wrap seam (last column to first) divided
+    /// by the mean absolute step between interior neighbours along rows.
+    fn seam_ratio(grid: &Grid) -> f64 {
+        let (rows, cols) = (grid.rows, grid.cols);
+        let seam: f64 = (0..rows).map(|i| (grid[i][cols - 1] - grid[i][0]).abs()).sum();
+        let interior: f64 = (0..rows)
+            .flat_map(|i| (1..cols).map(move |j| (i, j)))
+            .map(|(i, j)| (grid[i][j] - grid[i][j - 1]).abs())
+            .sum();
+        (seam / rows as f64) / (interior / (rows * (cols - 1)) as f64)
+    }
+
     #[rstest]
     #[case(0, 0)]
     #[case(1, 1)]
@@ -1302,6 +1515,14 @@ mod tests {
         assert_eq!(zero_to_one_count(&grid), rows * cols);
     }
 
+    #[test]
+    fn test_random_element_periodic() {
+        let grid = random_element_with_boundary(60, 40, 30., Boundary::Periodic, Some(7));
+        assert_eq!(nan_count(&grid), 0);
+        assert_eq!(zero_to_one_count(&grid), 60 * 40);
+        assert!(seam_ratio(&grid) < 2.0);
+    }
+
     #[rstest]
     #[case(0, 0)]
     #[case(1, 1)]
@@ -1330,6 +1551,47 @@ mod tests {
         assert_eq!(a.data, b.data);
     }
 
+    #[rstest]
+    #[case(0, 0)]
+    #[case(1, 1)]
+    #[case(4, 2)]
+    #[case(16, 16)]
+    #[case(128, 32)]
+    fn test_midpoint_displacement_periodic(#[case] rows: usize, #[case] cols: usize) {
+        let grid = midpoint_displacement_with_boundary(rows, cols, 0.8, Boundary::Periodic, Some(42));
+        assert_eq!(grid.rows, rows);
+        assert_eq!(grid.cols, cols);
+        assert_eq!(nan_count(&grid), 0);
+        assert_eq!(zero_to_one_count(&grid), rows * cols);
+    }
+
+    #[test]
+    #[should_panic(expected = "powers of two")]
+    fn test_midpoint_displacement_periodic_rejects_other_sizes() {
+        midpoint_displacement_with_boundary(100, 64, 0.8, Boundary::Periodic, Some(42));
+    }
+
+    #[test]
+    fn test_midpoint_displacement_periodic_is_seamless() {
+        let grid = midpoint_displacement_with_boundary(128, 128, 0.8, Boundary::Periodic, Some(42));
+        assert!(seam_ratio(&grid) < 2.0);
+        let clamped = midpoint_displacement(128, 128, 0.8, Some(42));
+        assert!(seam_ratio(&clamped) > seam_ratio(&grid));
+    }
+
+    #[test]
+    fn test_midpoint_displacement_periodic_rectangle_wraps_both_axes() {
+        let grid = midpoint_displacement_with_boundary(64, 128, 0.8, Boundary::Periodic, Some(7));
+        let mut transposed = Grid::new(grid.cols, grid.rows);
+        for i in 0..grid.rows {
+            for j in 0..grid.cols {
+                transposed[j][i] = grid[i][j];
+            }
+        }
+        assert!(seam_ratio(&grid) < 2.0);
+        assert!(seam_ratio(&transposed) < 2.0);
+    }
+
     // ── gaussian_field ────────────────────────────────────────────────────────
 
     #[rstest]
@@ -1352,6 +1614,15 @@ mod tests {
         assert_eq!(a.data, b.data);
     }
 
+    #[test]
+    fn test_gaussian_field_periodic_is_seamless() {
+        let grid = gaussian_field_with_boundary(64, 48, 5.0, Boundary::Periodic, Some(42));
+        assert_eq!(zero_to_one_count(&grid), 64 * 48);
+        assert!(seam_ratio(&grid) < 2.0);
+        let clamped = gaussian_field(64, 48, 5.0, Some(42));
+        assert!(seam_ratio(&clamped) > 5.0);
+    }
+
     // ── random_cluster ────────────────────────────────────────────────────────
 
     #[rstest]
@@ -1375,6 +1646,13 @@ mod tests {
         assert_eq!(a.data, b.data);
     }
 
+    #[test]
+    fn test_random_cluster_periodic_is_seamless() {
+        let grid = random_cluster_with_boundary(64, 48, 100, Boundary::Periodic, Some(42));
+        assert_eq!(zero_to_one_count(&grid), 64 * 48);
+        assert!(seam_ratio(&grid) < 2.0);
+    }
+
     // ── mosaic ────────────────────────────────────────────────────────────────
 
     #[rstest]
@@ -1397,6 +1675,22 @@ mod tests {
         assert_eq!(a.data, b.data);
     }
 
+    #[test]
+    fn test_mosaic_periodic_is_seamless() {
+        let grid = mosaic_with_boundary(64, 48, 20, Boundary::Periodic, Some(42));
+        assert_eq!(zero_to_one_count(&grid), 64 * 48);
+        assert!(seam_ratio(&grid) < 2.0);
+    }
+
+    #[test]
+    fn test_mosaic_labels_match_values() {
+        let labels = mosaic_labels_with_boundary(50, 40, 30, Boundary::Periodic, Some(42));
+        assert_eq!(labels.nodata_count(), 0);
+        assert!(labels.classes.len() <= 30);
+        assert!(labels.counts().iter().all(|&c| c > 0));
+        assert_eq!(labels.to_grid(), mosaic_with_boundary(50, 40, 30, Boundary::Periodic, Some(42)));
+    }
+
     // ── rectangular_cluster ───────────────────────────────────────────────────
 
     #[rstest]
@@ -1477,6 +1771,14 @@ mod tests {
         assert_eq!(a.data, b.data);
     }
 
+    #[test]
+    fn test_binary_space_partitioning_labels_match_values() {
+        let labels = binary_space_partitioning_labels(50, 40, 20, Some(42));
+        assert_eq!(labels.classes.len(), 20);
+        assert!(labels.counts().iter().all(|&c| c > 0));
+        assert_eq!(labels.to_grid(), binary_space_partitioning(50, 40, 20, Some(42)));
+    }
+
     // ── neighbourhood_clustering ──────────────────────────────────────────────
 
     #[rstest]
@@ -1498,6 +1800,13 @@ mod tests {
         assert_eq!(a.data, b.data);
     }
 
+    #[test]
+    fn test_neighbourhood_clustering_periodic_is_seamless() {
+        let grid = neighbourhood_clustering_with_boundary(64, 48, 5, 10, Boundary::Periodic, Some(42));
+        assert_eq!(zero_to_one_count(&grid), 64 * 48);
+        assert!(seam_ratio(&grid) < 2.0);
+    }
+
     // ── cellular_automaton ────────────────────────────────────────────────────
 
     #[rstest]
@@ -1921,8 +2230,27 @@ pub fn river_network(rows: usize, cols: usize, seed: Option<u64>) -> Grid {
 /// * `n`    - Approximate number of hexagonal cells.
 /// * `seed` - Optional RNG seed for reproducible results.
 pub fn hexagonal_voronoi(rows: usize, cols: usize, n: usize, seed: Option<u64>) -> Grid {
+    let (grid, classes) = hexagonal_patches(rows, cols, n, seed);
+    patch_values(&grid, &classes)
+}
+
+/// [`hexagonal_voronoi`] as cell labels: each hexagon is one class, and
+/// `classes` holds the values that [`hexagonal_voronoi`] returns for the same
+/// seed.
+pub fn hexagonal_voronoi_labels(
+    rows: usize,
+    cols: usize,
+    n: usize,
+    seed: Option<u64>,
+) -> LabelGrid<u32> {
+    let (grid, classes) = hexagonal_patches(rows, cols, n, seed);
+    patch_labels(&grid, classes)
+}
+
+/// Hexagonal patches as `label + 1` per cell, with the value of each label.
+fn hexagonal_patches(rows: usize, cols: usize, n: usize, seed: Option<u64>) -> (Grid, Vec<f64>) {
     if rows == 0 || cols == 0 {
-        return Grid::new(rows, cols);
+        return (Grid::new(rows, cols), Vec::new());
     }
     let mut rng = make_rng(seed);
     let n = n.max(1);
@@ -1936,10 +2264,11 @@ pub fn hexagonal_voronoi(rows: usize, cols: usize, n: usize, seed: Option<u64>)
     let hex_h = 1.5 * hex_r;          // row stride
 
     let mut grid = Grid::new(rows, cols);
+    let mut classes = Vec::new();
     let mut row_f = 0.0f64;
     let mut row_idx = 0usize;
     while row_f < rows as f64 + hex_h {
-        let offset = if row_idx % 2 == 0 { 0.0 } else { hex_w / 2.0 };
+        let offset = if row_idx.is_multiple_of(2) { 0.0 } else { hex_w / 2.0 };
         let mut col_f = -hex_w / 2.0 + offset;
         while col_f < cols as f64 + hex_w {
             let jr = rng.gen_range(-hex_r * 0.1..hex_r * 0.1);
@@ -1947,7 +2276,8 @@ pub fn hexagonal_voronoi(rows: usize, cols: usize, n: usize, seed: Option<u64>)
             let ri = ((row_f + jr) as isize).clamp(0, rows as isize - 1) as usize;
             let ci = ((col_f + jc) as isize).clamp(0, cols as isize - 1) as usize;
             if grid[ri][ci] == 0.0 {
-                grid[ri][ci] = rng.gen::<f64>() * 0.999 + 0.001;
+                classes.push(rng.gen::<f64>() * 0.999 + 0.001);
+                grid[ri][ci] = classes.len() as f64;
             }
             col_f += hex_w;
         }
@@ -1955,7 +2285,7 @@ pub fn hexagonal_voronoi(rows: usize, cols: usize, n: usize, seed: Option<u64>)
         row_idx += 1;
     }
     interpolate(&mut grid);
-    grid
+    (grid, classes)
 }
 
 /// Returns a fault uplift terrain NLM with values ranging [0, 1).
@@ -1991,12 +2321,12 @@ pub fn fault_uplift(rows: usize, cols: usize, n: usize, seed: Option<u64>) -> Gr
         let theta: f64 = rng.gen_range(0.0..std::f64::consts::PI);
         let (nnx, nny) = (theta.cos(), theta.sin());
 
-        for idx in 0..rows * cols {
+        for (idx, v) in data.iter_mut().enumerate() {
             let cy = (idx / cols) as f64 * inv_r;
             let cx = (idx % cols) as f64 * inv_c;
             // Perpendicular distance to the fault line.
             let d = ((cx - px) * nnx + (cy - py) * nny).abs();
-            data[idx] += (-d / width).exp();
+            *v += (-d / width).exp();
         }
     }
 
@@ -2163,7 +2493,7 @@ pub fn physarum(
 
         // Diffuse and decay.
         let old = trail.clone();
-        for idx in 0..rows * cols {
+        for (idx, t) in trail.iter_mut().enumerate() {
             let ri = idx / cols;
             let ci = idx % cols;
             let mut sum = 0.0;
@@ -2176,7 +2506,7 @@ pub fn physarum(
                     cnt += 1;
                 }
             }
-            trail[idx] = (sum / cnt as f64) * decay;
+            *t = (sum / cnt as f64) * decay;
         }
 
         std::mem::swap(&mut pos, &mut new_pos);
@@ -2210,7 +2540,7 @@ pub fn cahn_hilliard(rows: usize, cols: usize, iterations: usize, seed: Option<u
     let mut rng = make_rng(seed);
 
     // Small random perturbations around zero (the unstable mixed state).
-    let mut u: Vec<f64> = (0..rows * cols).map(|_| rng.gen_range(-0.05..0.05)).collect();
+    let mut u = rand_cells(rows, cols, &mut rng, |r| r.gen_range(-0.05..0.05));
     let mut mu = vec![0.0f64; rows * cols];
     let mut u_next = vec![0.0f64; rows * cols];
 
@@ -2242,20 +2572,28 @@ pub fn cahn_hilliard(rows: usize, cols: usize, iterations: usize, seed: Option<u
 /// Returns a crystal growth NLM with values ranging [0, 1).
 ///
 /// Simulates Reiter's (1996) snowflake cellular automaton on a
-/// hexagonal lattice. A single frozen seed at the centre grows by
-/// absorbing water vapour from surrounding cells; receptive cells (those
-/// adjacent to the crystal) accumulate vapour at rate `gamma` and freeze
-/// when their level reaches 1.  The background vapour density `beta`
-/// and the number of `iterations` control the final crystal complexity
-/// and size.
+/// hexagonal lattice. Frozen nuclei at random cells (one per `site_area`
+/// cells, at least one) grow by absorbing water vapour
+/// from surrounding cells; receptive cells (those adjacent to a crystal)
+/// accumulate vapour at rate `gamma` and freeze when their level reaches 1.
+/// The background vapour density `beta` and the number of `iterations`
+/// control the final crystal complexity and size.
 ///
 /// # Arguments
 ///
 /// * `rows`       - Number of rows.
 /// * `cols`       - Number of columns.
 /// * `iterations` - Number of growth steps.
-/// * `seed`       - Optional RNG seed (unused; growth is deterministic).
-pub fn crystal_growth(rows: usize, cols: usize, iterations: usize, _seed: Option<u64>) -> Grid {
+/// * `site_area`  - Grid cells per nucleus (2,500 by default in the
+///   registry). Panics if zero.
+/// * `seed`       - Optional RNG seed for reproducible results.
+pub fn crystal_growth(
+    rows: usize,
+    cols: usize,
+    iterations: usize,
+    site_area: usize,
+    seed: Option<u64>,
+) -> Grid {
     if rows == 0 || cols == 0 {
         return Grid::new(0, 0);
     }
@@ -2266,14 +2604,15 @@ pub fn crystal_growth(rows: usize, cols: usize, iterations: usize, _seed: Option
     let mut u = vec![beta; rows * cols];
     let mut frozen = vec![false; rows * cols];
 
-    let cr = rows / 2;
-    let cc = cols / 2;
-    frozen[cr * cols + cc] = true;
-    u[cr * cols + cc] = 0.0;
+    let mut rng = make_rng(seed);
+    for idx in random_sites(&mut rng, rows * cols, site_area) {
+        frozen[idx] = true;
+        u[idx] = 0.0;
+    }
 
     // 6-connectivity using offset-row hex grid.
     let hex_nb = |i: usize, j: usize| -> [(isize, isize); 6] {
-        if i % 2 == 0 {
+        if i.is_multiple_of(2) {
             [(-1,-1),(-1,0),(0,-1),(0,1),(1,-1),(1,0)]
         } else {
             [(-1,0),(-1,1),(0,-1),(0,1),(1,0),(1,1)]
@@ -2640,25 +2979,37 @@ pub fn schelling(
 ///   dI/dt =  β·S·I - γ·I + D_I·∇²I
 ///   dR/dt =  γ·I
 ///
-/// The grid is initialised with S≈1 everywhere and a small infection seed
-/// I=0.1 at the centre. Periodic boundaries are used. The output is the
-/// recovered field R, which maps the spatial footprint of the epidemic wave.
+/// The grid is initialised with S≈1 everywhere and infection foci with
+/// I=0.1 at random cells (one per `site_area` cells, at least one).
+/// Transmission is stochastic: each step, the infection term in every cell
+/// is scaled by an independent uniform factor in [1 - `noise`, 1 + `noise`),
+/// so waves spread unevenly while keeping the mean rate `beta`. Periodic
+/// boundaries are used.
+/// The output is the recovered field R, which maps the spatial footprint of
+/// the epidemic.
 ///
 /// # Arguments
 ///
 /// * `rows`       - Number of rows.
 /// * `cols`       - Number of columns.
-/// * `beta`       - Transmission rate.
+/// * `beta`       - Mean transmission rate.
 /// * `gamma`      - Recovery rate.
 /// * `iterations` - Number of forward-Euler integration steps.
-/// * `seed`       - Optional RNG seed (unused; provided for API consistency).
+/// * `site_area`  - Grid cells per infection focus (2,500 by default in the
+///   registry). Panics if zero.
+/// * `noise`      - Amplitude of the transmission noise in [0, 1]: 0 gives
+///   deterministic spread from the foci, 1 (the registry default) factors in [0, 2).
+/// * `seed`       - Optional RNG seed for reproducible results.
+#[allow(clippy::too_many_arguments)]
 pub fn sir_epidemic(
     rows: usize,
     cols: usize,
     beta: f64,
     gamma: f64,
     iterations: usize,
-    _seed: Option<u64>,
+    site_area: usize,
+    noise: f64,
+    seed: Option<u64>,
 ) -> Grid {
     if rows == 0 || cols == 0 {
         return Grid::new(0, 0);
@@ -2672,9 +3023,11 @@ pub fn sir_epidemic(
     let mut inf = vec![0.0f64; n];
     let mut r = vec![0.0f64; n];
 
-    let centre = (rows / 2) * cols + (cols / 2);
-    inf[centre] = 0.1;
-    s[centre]   = 0.9;
+    let mut rng = make_rng(seed);
+    for idx in random_sites(&mut rng, n, site_area) {
+        inf[idx] = 0.1;
+        s[idx]   = 0.9;
+    }
 
     let mut s_next = s.clone();
     let mut i_next = inf.clone();
@@ -2689,7 +3042,10 @@ pub fn sir_epidemic(
             let ri = r[idx];
             let lap_s = laplacian_periodic(&s, row, col, rows, cols);
             let lap_i = laplacian_periodic(&inf, row, col, rows, cols);
-            let infection = beta * si * ii;
+            let mut infection = beta * si * ii;
+            if infection > 0.0 && noise > 0.0 {
+                infection *= rng.gen_range(1.0 - noise..1.0 + noise);
+            }
             s_next[idx] = (si + dt * (-infection + d_s * lap_s)).max(0.0);
             i_next[idx] = (ii + dt * (infection - gamma * ii + d_i * lap_i)).max(0.0);
             r_next[idx] = (ri + dt * gamma * ii).clamp(0.0, 1.0);
@@ -2738,7 +3094,7 @@ pub fn thermal_erosion(rows: usize, cols: usize, n: usize, seed: Option<u64>) ->
         let mut amp  = 1.0f64;
         let mut total = 0.0f64;
         for o in 0..n_oct {
-            v.push((Perlin::new(seed_val.wrapping_add(o as u32)), freq, amp));
+            v.push((Perlin::new(sub_seed(seed_val, o)), freq, amp));
             total += amp;
             amp  *= persistence;
             freq *= lacunarity;
@@ -3012,7 +3368,7 @@ pub fn game_of_life(rows: usize, cols: usize, iterations: usize, seed: Option<u6
     }
     let mut rng = make_rng(seed);
 
-    let mut state: Vec<bool> = (0..rows * cols).map(|_| rng.gen::<f64>() < 0.45).collect();
+    let mut state = rand_cells(rows, cols, &mut rng, |r| r.gen::<f64>() < 0.45);
     let mut next  = state.clone();
     let mut visits: Vec<f64> = state.iter().map(|&b| if b { 1.0 } else { 0.0 }).collect();
 
@@ -3068,7 +3424,7 @@ pub fn rock_paper_scissors(
         return Grid::new(0, 0);
     }
     let mut rng = make_rng(seed);
-    let mut states: Vec<u8> = (0..rows * cols).map(|_| rng.gen_range(0..3u8)).collect();
+    let mut states = rand_cells(rows, cols, &mut rng, |r| r.gen_range(0..3u8));
 
     let n_trials = rows * cols;
     for _ in 0..iterations {
@@ -3121,9 +3477,7 @@ pub fn excitable_media(rows: usize, cols: usize, iterations: usize, seed: Option
     // Initialise every cell with a random state across the full cycle.
     // This immediately creates broken wave fronts that curl into self-sustaining
     // spiral waves; seeding only a few excited cells causes all waves to die out.
-    let mut states: Vec<u8> = (0..rows * cols)
-        .map(|_| rng.gen_range(0..n_states))
-        .collect();
+    let mut states = rand_cells(rows, cols, &mut rng, |r| r.gen_range(0..n_states));
     let mut next = states.clone();
 
     for _ in 0..iterations {
@@ -3180,8 +3534,8 @@ pub fn truchet(rows: usize, cols: usize, n: usize, seed: Option<u64>) -> Grid {
     }
     let mut rng = make_rng(seed);
     let tile = n.max(2);
-    let tiles_r = (rows + tile - 1) / tile;
-    let tiles_c = (cols + tile - 1) / tile;
+    let tiles_r = rows.div_ceil(tile);
+    let tiles_c = cols.div_ceil(tile);
 
     // Pre-assign a random orientation (0 or 1) to every macro-tile.
     let tile_types: Vec<u8> = (0..tiles_r * tiles_c)
@@ -3317,6 +3671,13 @@ mod new_tests {
         assert_eq!(a.data, b.data);
     }
 
+    #[test]
+    fn test_hexagonal_voronoi_labels_match_values() {
+        let labels = hexagonal_voronoi_labels(50, 40, 20, Some(42));
+        assert_eq!(labels.nodata_count(), 0);
+        assert_eq!(labels.to_grid(), hexagonal_voronoi(50, 40, 20, Some(42)));
+    }
+
     // ── sandpile ──────────────────────────────────────────────────────────────
 
     #[rstest]
@@ -3476,13 +3837,29 @@ mod new_tests {
     #[case(10, 10)]
     #[case(100, 100)]
     fn test_crystal_growth(#[case] rows: usize, #[case] cols: usize) {
-        let grid = crystal_growth(rows, cols, 50, None);
+        let grid = crystal_growth(rows, cols, 50, 2500, None);
         assert_eq!(grid.rows, rows);
         assert_eq!(grid.cols, cols);
         assert_eq!(nan_count(&grid), 0);
         assert_eq!(zero_to_one_count(&grid), rows * cols);
     }
 
+    #[test]
+    fn test_crystal_growth_seeded_determinism() {
+        let a = crystal_growth(120, 110, 50, 2500, Some(42));
+        let b = crystal_growth(120, 110, 50, 2500, Some(42));
+        assert_eq!(a.data, b.data);
+        assert_ne!(a.data, crystal_growth(120, 110, 50, 2500, Some(43)).data);
+        // 13,200 cells hold five nuclei; the receptive cells around them,
+        // at most six each, have absorbed the most vapour.
+        let receptive = a.data.iter().filter(|&&v| v == 1.0).count();
+        assert!((1..=30).contains(&receptive), "{receptive} receptive cells");
+
+        let dense = crystal_growth(120, 110, 50, 100, Some(42));
+        let receptive_dense = dense.data.iter().filter(|&&v| v == 1.0).count();
+        assert!(receptive_dense > receptive, "{receptive_dense} receptive cells");
+    }
+
     // ── predator_prey ─────────────────────────────────────────────────────────
 
     #[rstest]
@@ -3513,7 +3890,7 @@ mod new_tests {
     #[case(10, 10)]
     #[case(100, 100)]
     fn test_sir_epidemic(#[case] rows: usize, #[case] cols: usize) {
-        let grid = sir_epidemic(rows, cols, 0.3, 0.1, 50, None);
+        let grid = sir_epidemic(rows, cols, 0.3, 0.1, 50, 2500, 1.0, None);
         assert_eq!(grid.rows, rows);
         assert_eq!(grid.cols, cols);
         assert_eq!(nan_count(&grid), 0);
@@ -3521,10 +3898,24 @@ mod new_tests {
     }
 
     #[test]
-    fn test_sir_epidemic_determinism() {
-        let a = sir_epidemic(50, 50, 0.3, 0.1, 50, None);
-        let b = sir_epidemic(50, 50, 0.3, 0.1, 50, None);
+    fn test_sir_epidemic_seeded_determinism() {
+        let a = sir_epidemic(50, 50, 0.3, 0.1, 50, 2500, 1.0, Some(42));
+        let b = sir_epidemic(50, 50, 0.3, 0.1, 50, 2500, 1.0, Some(42));
         assert_eq!(a.data, b.data);
+        assert_ne!(a.data, sir_epidemic(50, 50, 0.3, 0.1, 50, 2500, 1.0, Some(43)).data);
+    }
+
+    #[test]
+    fn test_sir_epidemic_without_noise_only_moves_the_focus() {
+        // One focus on a periodic grid: without transmission noise, seeds only
+        // translate the same epidemic.
+        let sorted = |noise, seed| {
+            let mut v = sir_epidemic(50, 50, 0.3, 0.1, 50, 2500, noise, Some(seed)).data;
+            v.sort_by(f64::total_cmp);
+            v
+        };
+        assert_eq!(sorted(0.0, 42), sorted(0.0, 43));
+        assert_ne!(sorted(1.0, 42), sorted(1.0, 43));
     }
 
     // ── thermal_erosion ───────────────────────────────────────────────────────
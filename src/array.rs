@@ -1,27 +1,54 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
+use crate::algorithms::NlmRng;
 use crate::grid::Grid;
 
 /// Returns a grid of size (rows x cols) containing uniform random [0, 1) values.
 pub fn rand_grid(rows: usize, cols: usize, rng: &mut impl Rng) -> Grid {
-    let data = (0..rows * cols).map(|_| rng.gen()).collect();
+    let data = rand_cells(rows, cols, rng, |r| r.gen());
     Grid { data, rows, cols }
 }
 
+/// Returns (rows x cols) values in row-major order, each drawn by `draw`.
+///
+/// One key is taken from `rng`, and row `i` draws from stream `i` of the
+/// generator seeded with it. Rows are therefore independent of each other and
+/// of the order they are filled in, and are filled in parallel when the
+/// `parallel` feature is enabled.
+pub fn rand_cells<T: Clone + Default + Send>(
+    rows: usize,
+    cols: usize,
+    rng: &mut impl Rng,
+    draw: impl Fn(&mut NlmRng) -> T + Sync,
+) -> Vec<T> {
+    let key: u64 = rng.gen();
+    let mut data = vec![T::default(); rows * cols];
+    if cols == 0 {
+        return data;
+    }
+    let fill_row = |(i, row): (usize, &mut [T])| {
+        let mut row_rng = NlmRng::seed_from_u64(key);
+        row_rng.set_stream(i as u64);
+        row.iter_mut().for_each(|v| *v = draw(&mut row_rng));
+    };
+    #[cfg(feature = "parallel")]
+    data.par_chunks_mut(cols).enumerate().for_each(fill_row);
+    #[cfg(not(feature = "parallel"))]
+    data.chunks_mut(cols).enumerate().for_each(fill_row);
+    data
+}
+
 fn random_displace(disheight: f64, r: f64) -> f64 {
     r * disheight - 0.5 * disheight
 }
 
-fn displace_vals(vals: &[f64], disheight: f64, r: (f64, f64)) -> Option<f64> {
-    if vals.len() == 4 {
-        let sum: f64 = vals.iter().sum();
-        Some(sum * 0.25 + random_displace(disheight, r.0))
-    } else if vals.len() == 3 {
-        let sum: f64 = vals.iter().sum();
-        Some(sum / 3. + random_displace(disheight, r.1))
-    } else {
-        None
-    }
+/// Averages `vals` (one to four neighbours) and adds a random displacement.
+fn displace_vals(vals: &[f64], disheight: f64, r: (f64, f64)) -> f64 {
+    let mean = vals.iter().sum::<f64>() / vals.len() as f64;
+    let r = if vals.len() == 4 { r.0 } else { r.1 };
+    mean + random_displace(disheight, r)
 }
 
 fn check_diamond_coords(diax: i32, diay: i32, dim: i32, i2: i32) -> ([(i32, i32); 4], usize) {
@@ -66,13 +93,16 @@ fn diamond_step(
     diay: usize,
 ) {
     let (diaco, n) = check_diamond_coords(diax as i32, diay as i32, dim as i32, mid as i32);
+    if n == 0 {
+        return;
+    }
     let mut diavals = [0.0f64; 4];
     for k in 0..n {
         let (x, y) = diaco[k];
         diavals[k] = surface[x as usize][y as usize];
     }
     let r = rng.gen();
-    surface[diax][diay] = displace_vals(&diavals[..n], disheight, r).unwrap();
+    surface[diax][diay] = displace_vals(&diavals[..n], disheight, r);
 }
 
 /// Returns a diamond-square fractal surface of size (dim x dim).
@@ -106,7 +136,7 @@ pub fn diamond_square(dim: usize, h: f64, rng: &mut impl Rng) -> Grid {
                     arr_n += 1;
                 }
                 let r = rng.gen();
-                surface[i + mid][j + mid] = displace_vals(&arr[..arr_n], disheight, r).unwrap();
+                surface[i + mid][j + mid] = displace_vals(&arr[..arr_n], disheight, r);
             }
         }
 
@@ -120,12 +150,66 @@ pub fn diamond_square(dim: usize, h: f64, rng: &mut impl Rng) -> Grid {
             }
         }
 
-        disheight = disheight.powf(-h) * 2.;
+        disheight = next_disheight(disheight, h);
         inc /= 2;
     }
     surface
 }
 
+/// Displacement range for the next, finer level of diamond-square.
+fn next_disheight(disheight: f64, h: f64) -> f64 {
+    disheight.powf(-h) * 2.
+}
+
+/// Returns a periodic diamond-square fractal surface of size (rows x cols).
+///
+/// `rows` and `cols` must be powers of two. The surface lives on a torus: the
+/// square and diamond steps read their neighbours with wrap-around, so the
+/// last row and column continue smoothly into the first. A rectangular torus
+/// starts from a lattice of square cells whose side is the shorter dimension.
+pub fn diamond_square_periodic(rows: usize, cols: usize, h: f64, rng: &mut impl Rng) -> Grid {
+    let mut disheight = 2.;
+    let mut surface = Grid::new(rows, cols);
+    let mut inc = rows.min(cols);
+    for i in (0..rows).step_by(inc) {
+        for j in (0..cols).step_by(inc) {
+            surface[i][j] = random_displace(disheight, rng.gen());
+        }
+    }
+
+    while inc > 1 {
+        let mid = inc / 2;
+
+        // Square step: each square's centre from its four (wrapped) corners.
+        for i in (0..rows).step_by(inc) {
+            for j in (0..cols).step_by(inc) {
+                let (i2, j2) = ((i + inc) % rows, (j + inc) % cols);
+                let corners = [surface[i][j], surface[i2][j], surface[i][j2], surface[i2][j2]];
+                surface[i + mid][j + mid] = displace_vals(&corners, disheight, rng.gen());
+            }
+        }
+
+        // Diamond step: each edge midpoint from its four (wrapped) neighbours.
+        for i in (0..rows).step_by(inc) {
+            for j in (0..cols).step_by(inc) {
+                for (r, c) in [(i + mid, j), (i, j + mid)] {
+                    let neighbours = [
+                        surface[(r + rows - mid) % rows][c],
+                        surface[(r + mid) % rows][c],
+                        surface[r][(c + cols - mid) % cols],
+                        surface[r][(c + mid) % cols],
+                    ];
+                    surface[r][c] = displace_vals(&neighbours, disheight, rng.gen());
+                }
+            }
+        }
+
+        disheight = next_disheight(disheight, h);
+        inc = mid;
+    }
+    surface
+}
+
 /// Selects a random subgrid of size (rows x cols) from the grid.
 pub fn rand_sub_grid(grid: Grid, rows: usize, cols: usize, rng: &mut impl Rng) -> Grid {
     if rows >= grid.rows && cols >= grid.cols {
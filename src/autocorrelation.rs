@@ -0,0 +1,476 @@
+//! Spatial autocorrelation diagnostics for continuous grids.
+//!
+//! [`morans_i`] and [`gearys_c`] summarise how alike neighbouring cells are
+//! over a [`Neighbourhood`], and [`correlogram`] reports the semivariance,
+//! Moran's I and Geary's C of cell pairs by distance class, optionally along
+//! one direction only. Together they show whether `h`, `beta` or a kernel
+//! radius produce the intended spatial structure.
+//!
+//! ```
+//! use nlmrs::autocorrelation::{correlogram, morans_i, LagOptions, Neighbourhood};
+//! use nlmrs::{midpoint_displacement, Boundary, Connectivity};
+//!
+//! let rough = midpoint_displacement(65, 65, 0.2, Some(1));
+//! let smooth = midpoint_displacement(65, 65, 0.9, Some(1));
+//! let rook = Neighbourhood::Adjacent(Connectivity::Rook);
+//! assert!(morans_i(&smooth, &rook, Boundary::Clamped) > morans_i(&rough, &rook, Boundary::Clamped));
+//!
+//! let lags = LagOptions { width: 2.0, count: 8, ..LagOptions::default() };
+//! print!("{}", correlogram(&smooth, &lags, Boundary::Clamped).to_csv());
+//! ```
+//!
+//! Weights are binary: a pair of cells is either in the neighbourhood or lag
+//! class or not. NoData (`NaN`) cells are left out of every pair and of the
+//! mean and variance. Pairs are found by offset, so the cost grows with the
+//! number of cells times the number of offsets within the largest distance.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::error::{self, NlmError};
+use crate::grid::{Boundary, Grid};
+use crate::operation::Connectivity;
+
+/// Which pairs of cells count as neighbours for [`morans_i`] and [`gearys_c`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Neighbourhood {
+    /// Cells sharing a side, or with [`Connectivity::Queen`] a side or a corner.
+    Adjacent(Connectivity),
+    /// Cells whose centres are more than `min` and at most `max` cells apart.
+    Band { min: f64, max: f64 },
+}
+
+impl Neighbourhood {
+    /// Errors if a band's bounds are not finite or `0 <= min < max` fails.
+    pub fn check(&self) -> error::Result<()> {
+        match *self {
+            Neighbourhood::Adjacent(_) => Ok(()),
+            Neighbourhood::Band { min, max } if max.is_finite() && 0.0 <= min && min < max => Ok(()),
+            Neighbourhood::Band { .. } => {
+                Err(NlmError::invalid("band", "needs finite bounds with 0 <= min < max"))
+            }
+        }
+    }
+
+    /// Offsets to the neighbours, one of each opposite pair.
+    fn offsets(&self) -> Vec<(isize, isize)> {
+        match *self {
+            Neighbourhood::Adjacent(c) => c
+                .offsets()
+                .iter()
+                .map(|&(di, dj)| (di as isize, dj as isize))
+                .filter(|&o| forward(o))
+                .collect(),
+            Neighbourhood::Band { min, max } => within(max)
+                .filter(|&(di, dj)| distance(di, dj) > min)
+                .collect(),
+        }
+    }
+}
+
+/// Distance classes of a [`correlogram`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LagOptions {
+    /// Width of each distance class in cells. Class `k` holds the pairs more
+    /// than `k * width` and at most `(k + 1) * width` cells apart.
+    pub width: f64,
+    /// Number of classes.
+    pub count: usize,
+    /// Only pair cells along this bearing in degrees, clockwise from north
+    /// (up the rows) as for `planar_gradient`; `None` pairs them in every
+    /// direction. A bearing and its opposite are the same direction.
+    pub direction: Option<f64>,
+    /// Largest angle in degrees between a pair and `direction`.
+    pub tolerance: f64,
+}
+
+impl Default for LagOptions {
+    /// Ten isotropic classes one cell wide, with a 22.5° tolerance if a
+    /// direction is set.
+    fn default() -> Self {
+        LagOptions { width: 1.0, count: 10, direction: None, tolerance: 22.5 }
+    }
+}
+
+impl LagOptions {
+    /// Errors if `width` is not positive and finite, `count` is zero, or the
+    /// direction or a tolerance outside (0, 90] is invalid.
+    pub fn check(&self) -> error::Result<()> {
+        if !(self.width.is_finite() && self.width > 0.0) {
+            return Err(NlmError::invalid("width", "must be positive and finite"));
+        }
+        if self.count == 0 {
+            return Err(NlmError::invalid("count", "must be at least 1"));
+        }
+        if self.direction.is_some_and(|d| !d.is_finite()) {
+            return Err(NlmError::invalid("direction", "must be finite"));
+        }
+        if !(self.tolerance > 0.0 && self.tolerance <= 90.0) {
+            return Err(NlmError::invalid("tolerance", "must be in (0, 90]"));
+        }
+        Ok(())
+    }
+}
+
+/// One distance class of a [`Correlogram`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lag {
+    /// Exclusive lower bound of the class, in cells.
+    pub lower: f64,
+    /// Inclusive upper bound of the class, in cells.
+    pub upper: f64,
+    /// Mean distance between the paired cells; `NaN` without pairs.
+    pub distance: f64,
+    /// Number of distinct pairs of cells in the class.
+    pub pairs: usize,
+    /// Semivariance: half the mean squared difference of paired values.
+    pub semivariance: f64,
+    /// Moran's I of the pairs in the class.
+    pub morans_i: f64,
+    /// Geary's C of the pairs in the class.
+    pub gearys_c: f64,
+}
+
+/// Empirical semivariogram and correlogram returned by [`correlogram`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Correlogram {
+    /// Distance classes, nearest first.
+    pub lags: Vec<Lag>,
+    /// Variance of the grid's values, the sill a semivariogram of a
+    /// stationary surface levels off at.
+    pub variance: f64,
+}
+
+impl Correlogram {
+    /// The table as CSV with a header row:
+    /// `lower,upper,distance,pairs,semivariance,morans_i,gearys_c`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("lower,upper,distance,pairs,semivariance,morans_i,gearys_c\n");
+        for l in &self.lags {
+            csv += &format!(
+                "{},{},{},{},{},{},{}\n",
+                l.lower, l.upper, l.distance, l.pairs, l.semivariance, l.morans_i, l.gearys_c
+            );
+        }
+        csv
+    }
+
+    /// Writes [`to_csv`](Correlogram::to_csv) to `path`.
+    pub fn write_csv(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_csv())
+    }
+}
+
+/// Global Moran's I: near 1 where neighbours have similar values, near
+/// `-1 / (n - 1)` for spatially random values and negative where they
+/// alternate.
+///
+/// With [`Boundary::Periodic`] cells pair across opposite edges. Returns
+/// `NaN` if no pair of valid cells are neighbours or every value is equal.
+/// Panics if [`Neighbourhood::check`] fails.
+pub fn morans_i(grid: &Grid, neighbourhood: &Neighbourhood, boundary: Boundary) -> f64 {
+    if let Err(e) = neighbourhood.check() {
+        panic!("{e}");
+    }
+    let stats = Stats::of(grid);
+    let sums = pair_sums(grid, &neighbourhood.offsets(), stats.mean, boundary)
+        .into_iter()
+        .fold(Sums::default(), Sums::add);
+    stats.morans_i(&sums)
+}
+
+/// Fallible [`morans_i`]: errors instead of panicking on an invalid band.
+pub fn try_morans_i(grid: &Grid, neighbourhood: &Neighbourhood, boundary: Boundary) -> error::Result<f64> {
+    neighbourhood.check()?;
+    Ok(morans_i(grid, neighbourhood, boundary))
+}
+
+/// Global Geary's C: near 0 where neighbours have similar values, 1 for
+/// spatially random values and above 1 where they alternate. More
+/// sensitive than [`morans_i`] to differences between close neighbours.
+///
+/// Edges and NoData are handled as by [`morans_i`]. Panics if
+/// [`Neighbourhood::check`] fails.
+pub fn gearys_c(grid: &Grid, neighbourhood: &Neighbourhood, boundary: Boundary) -> f64 {
+    if let Err(e) = neighbourhood.check() {
+        panic!("{e}");
+    }
+    let stats = Stats::of(grid);
+    let sums = pair_sums(grid, &neighbourhood.offsets(), stats.mean, boundary)
+        .into_iter()
+        .fold(Sums::default(), Sums::add);
+    stats.gearys_c(&sums)
+}
+
+/// Fallible [`gearys_c`]: errors instead of panicking on an invalid band.
+pub fn try_gearys_c(grid: &Grid, neighbourhood: &Neighbourhood, boundary: Boundary) -> error::Result<f64> {
+    neighbourhood.check()?;
+    Ok(gearys_c(grid, neighbourhood, boundary))
+}
+
+/// Computes the empirical semivariogram, Moran's I correlogram and Geary's C
+/// correlogram of `grid` over the distance classes of `lags`.
+///
+/// Each distinct pair of valid cells is counted once, in the class of the
+/// distance between their centres. With [`Boundary::Periodic`] cells also
+/// pair across opposite edges; keep the classes within half the grid's
+/// width, as longer offsets wrap round to pairs already counted at a
+/// shorter distance. Classes without pairs hold `NaN`.
+/// Panics if [`LagOptions::check`] fails.
+pub fn correlogram(grid: &Grid, lags: &LagOptions, boundary: Boundary) -> Correlogram {
+    if let Err(e) = lags.check() {
+        panic!("{e}");
+    }
+    let reach = lags.width * lags.count as f64;
+    let offsets: Vec<(isize, isize)> = within(reach)
+        .filter(|&o| match lags.direction {
+            None => true,
+            Some(d) => {
+                // Bearing of the offset, clockwise from north, as an axis.
+                let bearing = (o.1 as f64).atan2(-o.0 as f64).to_degrees();
+                let diff = (bearing - d).rem_euclid(180.0);
+                diff.min(180.0 - diff) <= lags.tolerance
+            }
+        })
+        .collect();
+    let stats = Stats::of(grid);
+    let mut classes = vec![Sums::default(); lags.count];
+    for (&(di, dj), sums) in offsets.iter().zip(pair_sums(grid, &offsets, stats.mean, boundary)) {
+        let k = ((distance(di, dj) / lags.width).ceil() as usize).clamp(1, lags.count) - 1;
+        classes[k] = classes[k].add(sums);
+    }
+    let lags = classes
+        .iter()
+        .enumerate()
+        .map(|(k, sums)| {
+            let n = sums.pairs as f64;
+            let per_pair = |v: f64| if sums.pairs == 0 { f64::NAN } else { v / n };
+            Lag {
+                lower: k as f64 * lags.width,
+                upper: (k + 1) as f64 * lags.width,
+                distance: per_pair(sums.distances),
+                pairs: sums.pairs,
+                semivariance: per_pair(sums.squares) / 2.0,
+                morans_i: stats.morans_i(sums),
+                gearys_c: stats.gearys_c(sums),
+            }
+        })
+        .collect();
+    Correlogram { lags, variance: stats.deviations / stats.n as f64 }
+}
+
+/// Fallible [`correlogram`]: errors instead of panicking on invalid options.
+pub fn try_correlogram(grid: &Grid, lags: &LagOptions, boundary: Boundary) -> error::Result<Correlogram> {
+    lags.check()?;
+    Ok(correlogram(grid, lags, boundary))
+}
+
+/// True for one offset of each opposite pair.
+fn forward((di, dj): (isize, isize)) -> bool {
+    di > 0 || (di == 0 && dj > 0)
+}
+
+fn distance(di: isize, dj: isize) -> f64 {
+    ((di * di + dj * dj) as f64).sqrt()
+}
+
+/// Forward offsets at most `max` cells long, nearest rows first.
+fn within(max: f64) -> impl Iterator<Item = (isize, isize)> {
+    let reach = max.floor() as isize;
+    (0..=reach)
+        .flat_map(move |di| (-reach..=reach).map(move |dj| (di, dj)))
+        .filter(move |&(di, dj)| forward((di, dj)) && distance(di, dj) <= max)
+}
+
+/// Count, mean and sum of squared deviations of the valid cells.
+struct Stats {
+    n: usize,
+    mean: f64,
+    deviations: f64,
+}
+
+impl Stats {
+    fn of(grid: &Grid) -> Self {
+        let valid = || grid.iter().filter(|v| !v.is_nan());
+        let n = valid().count();
+        let mean = valid().sum::<f64>() / n as f64;
+        let deviations = valid().map(|v| (v - mean) * (v - mean)).sum();
+        Stats { n, mean, deviations }
+    }
+
+    /// Moran's I with binary weights: `n / W * Σ w (x - m)(y - m) / Σ (x - m)²`,
+    /// where both sums over ordered pairs are twice those over `sums`' pairs.
+    fn morans_i(&self, sums: &Sums) -> f64 {
+        if sums.pairs == 0 || self.deviations == 0.0 {
+            return f64::NAN;
+        }
+        self.n as f64 * sums.products / (sums.pairs as f64 * self.deviations)
+    }
+
+    /// Geary's C with binary weights: `(n - 1) / 2W * Σ w (x - y)² / Σ (x - m)²`.
+    fn gearys_c(&self, sums: &Sums) -> f64 {
+        if sums.pairs == 0 || self.deviations == 0.0 {
+            return f64::NAN;
+        }
+        (self.n as f64 - 1.0) * sums.squares / (2.0 * sums.pairs as f64 * self.deviations)
+    }
+}
+
+/// Totals over the distinct pairs of valid cells at some offsets.
+#[derive(Clone, Copy, Debug, Default)]
+struct Sums {
+    pairs: usize,
+    /// Σ (x - m)(y - m).
+    products: f64,
+    /// Σ (x - y)².
+    squares: f64,
+    /// Σ of the distance between the paired cells.
+    distances: f64,
+}
+
+impl Sums {
+    fn add(self, other: Sums) -> Sums {
+        Sums {
+            pairs: self.pairs + other.pairs,
+            products: self.products + other.products,
+            squares: self.squares + other.squares,
+            distances: self.distances + other.distances,
+        }
+    }
+}
+
+/// [`Sums`] of the pairs at each offset, in the order of `offsets`.
+fn pair_sums(grid: &Grid, offsets: &[(isize, isize)], mean: f64, boundary: Boundary) -> Vec<Sums> {
+    let (rows, cols) = (grid.rows, grid.cols);
+    let at = |&(di, dj): &(isize, isize)| {
+        let mut sums = Sums::default();
+        for i in 0..rows {
+            let Some(ii) = boundary.offset(i, di, rows) else {
+                continue;
+            };
+            for j in 0..cols {
+                let Some(jj) = boundary.offset(j, dj, cols) else {
+                    continue;
+                };
+                let (x, y) = (grid[i][j], grid[ii][jj]);
+                if x.is_nan() || y.is_nan() {
+                    continue;
+                }
+                sums.pairs += 1;
+                sums.products += (x - mean) * (y - mean);
+                sums.squares += (x - y) * (x - y);
+            }
+        }
+        sums.distances = sums.pairs as f64 * distance(di, dj);
+        sums
+    };
+    #[cfg(feature = "parallel")]
+    return offsets.par_iter().map(at).collect();
+    #[cfg(not(feature = "parallel"))]
+    return offsets.iter().map(at).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOK: Neighbourhood = Neighbourhood::Adjacent(Connectivity::Rook);
+
+    fn checkerboard(n: usize) -> Grid {
+        let mut grid = Grid::new(n, n);
+        for k in 0..n * n {
+            grid.data[k] = ((k / n + k % n) % 2) as f64;
+        }
+        grid
+    }
+
+    #[test]
+    fn test_checkerboard_is_perfectly_dispersed() {
+        let grid = checkerboard(10);
+        assert!((morans_i(&grid, &ROOK, Boundary::Clamped) + 1.0).abs() < 1e-12);
+        assert!((gearys_c(&grid, &ROOK, Boundary::Clamped) - 2.0 * 99.0 / 100.0).abs() < 1e-12);
+        let queen = Neighbourhood::Adjacent(Connectivity::Queen);
+        assert!(morans_i(&grid, &queen, Boundary::Clamped) < 0.0);
+    }
+
+    #[test]
+    fn test_gradient_is_clustered_and_noise_is_not() {
+        let gradient = crate::planar_gradient(50, 50, Some(45.0), Some(1));
+        assert!(morans_i(&gradient, &ROOK, Boundary::Clamped) > 0.95);
+        assert!(gearys_c(&gradient, &ROOK, Boundary::Clamped) < 0.05);
+
+        let noise = crate::random(100, 100, Some(2));
+        assert!(morans_i(&noise, &ROOK, Boundary::Clamped).abs() < 0.05);
+        assert!((gearys_c(&noise, &ROOK, Boundary::Clamped) - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_band_neighbourhood() {
+        let grid = checkerboard(10);
+        // Diagonal neighbours, at distance √2, share the same value.
+        let band = Neighbourhood::Band { min: 1.0, max: 1.5 };
+        assert!((morans_i(&grid, &band, Boundary::Clamped) - 1.0).abs() < 1e-12);
+        assert!(try_morans_i(&grid, &Neighbourhood::Band { min: 2.0, max: 1.0 }, Boundary::Clamped).is_err());
+    }
+
+    #[test]
+    fn test_correlogram_pairs_and_classes() {
+        let grid = crate::random(6, 8, Some(3));
+        let lags = LagOptions { width: 1.0, count: 2, ..LagOptions::default() };
+        let c = correlogram(&grid, &lags, Boundary::Clamped);
+        // Rook pairs, then diagonal pairs at √2 and pairs two apart at 2.
+        assert_eq!(c.lags[0].pairs, 6 * 7 + 5 * 8);
+        assert_eq!(c.lags[1].pairs, 2 * 5 * 7 + 6 * 6 + 4 * 8);
+        assert_eq!(c.lags[0].distance, 1.0);
+        assert_eq!(
+            c.lags[0].morans_i,
+            morans_i(&grid, &ROOK, Boundary::Clamped)
+        );
+
+        let torus = correlogram(&grid, &lags, Boundary::Periodic);
+        assert_eq!(torus.lags[0].pairs, 2 * 48);
+    }
+
+    #[test]
+    fn test_semivariance_rises_to_the_sill() {
+        let grid = crate::gaussian_field_with_boundary(120, 120, 6.0, Boundary::Periodic, Some(4));
+        let lags = LagOptions { width: 2.0, count: 20, ..LagOptions::default() };
+        let c = correlogram(&grid, &lags, Boundary::Periodic);
+        let gamma: Vec<f64> = c.lags.iter().map(|l| l.semivariance).collect();
+        assert!(gamma[..5].windows(2).all(|w| w[0] < w[1]), "{gamma:?}");
+        assert!(gamma[0] < 0.2 * c.variance);
+        assert!(c.lags[0].morans_i > c.lags[10].morans_i);
+    }
+
+    #[test]
+    fn test_directional_semivariogram() {
+        // Values change along the columns only.
+        let grid = crate::planar_gradient(30, 30, Some(90.0), Some(5));
+        let along = |d| {
+            let lags = LagOptions { direction: Some(d), tolerance: 10.0, count: 3, ..LagOptions::default() };
+            correlogram(&grid, &lags, Boundary::Clamped).lags[0].semivariance
+        };
+        assert!(along(0.0) < 1e-20);
+        assert!(along(180.0) < 1e-20);
+        assert!(along(90.0) > 1e-4);
+        assert_eq!(along(90.0), along(270.0));
+    }
+
+    #[test]
+    fn test_nodata_and_degenerate_grids() {
+        let mut grid = checkerboard(10);
+        for j in 0..10 {
+            grid[0][j] = f64::NAN;
+        }
+        assert!((morans_i(&grid, &ROOK, Boundary::Clamped) + 1.0).abs() < 1e-12);
+        assert!(morans_i(&Grid::filled(5, 5, 2.0), &ROOK, Boundary::Clamped).is_nan());
+
+        let lags = LagOptions { width: 10.0, count: 3, ..LagOptions::default() };
+        let c = correlogram(&crate::random(4, 4, Some(6)), &lags, Boundary::Clamped);
+        assert_eq!(c.lags[2].pairs, 0);
+        assert!(c.lags[2].semivariance.is_nan());
+        assert!(c.to_csv().starts_with("lower,upper,distance,pairs,semivariance,morans_i,gearys_c\n0,10,"));
+        assert_eq!(c.to_csv().lines().count(), 4);
+        assert!(try_correlogram(&grid, &LagOptions { count: 0, ..lags }, Boundary::Clamped).is_err());
+    }
+}
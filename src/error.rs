@@ -0,0 +1,84 @@
+use std::fmt;
+
+/// Errors returned by the fallible (`try_`) generator and operation API.
+#[derive(Debug)]
+pub enum NlmError {
+    /// A parameter is of the wrong kind or outside its valid range.
+    InvalidParameter { name: String, reason: String },
+    /// The requested grid has zero rows or columns.
+    InvalidDimensions { rows: usize, cols: usize },
+    /// Two grids that must have the same shape do not.
+    ShapeMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    /// No generator is registered under this name.
+    UnknownAlgorithm(String),
+    /// A pipeline or other configuration file is malformed.
+    Config(String),
+    /// Reading or writing a file failed.
+    Io(std::io::Error),
+}
+
+impl NlmError {
+    pub(crate) fn invalid(name: &str, reason: impl Into<String>) -> Self {
+        NlmError::InvalidParameter { name: name.to_string(), reason: reason.into() }
+    }
+}
+
+impl fmt::Display for NlmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NlmError::InvalidParameter { name, reason } => {
+                write!(f, "invalid parameter '{name}': {reason}")
+            }
+            NlmError::InvalidDimensions { rows, cols } => {
+                write!(f, "grid dimensions must be non-zero, got {rows}x{cols}")
+            }
+            NlmError::ShapeMismatch { expected, found } => write!(
+                f,
+                "grid shape mismatch: expected {}x{}, found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            NlmError::UnknownAlgorithm(name) => write!(f, "unknown algorithm '{name}'"),
+            NlmError::Config(message) => write!(f, "invalid configuration: {message}"),
+            NlmError::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for NlmError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NlmError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for NlmError {
+    fn from(e: std::io::Error) -> Self {
+        NlmError::Io(e)
+    }
+}
+
+/// Shorthand for `Result<T, NlmError>`.
+pub type Result<T> = std::result::Result<T, NlmError>;
+
+/// Checks that a grid to be generated has at least one cell.
+pub(crate) fn check_dimensions(rows: usize, cols: usize) -> Result<()> {
+    if rows == 0 || cols == 0 {
+        Err(NlmError::InvalidDimensions { rows, cols })
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that `found` has the same shape as `expected`.
+pub(crate) fn check_shape(expected: (usize, usize), found: (usize, usize)) -> Result<()> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(NlmError::ShapeMismatch { expected, found })
+    }
+}
@@ -0,0 +1,346 @@
+use std::fmt;
+
+use crate::error::{NlmError, Result};
+
+/// Names accepted by [`Colormap::named`] and [`Colormap::parse`].
+pub const COLORMAPS: &[&str] = &[
+    "terrain", "viridis", "magma", "cividis", "rdbu", "gray", "set1", "tab10",
+];
+
+/// Roughly the matplotlib 'terrain' colormap:
+/// deep water → shallow water → sand → grass → forest → rock → snow.
+const TERRAIN: &[(f64, [u8; 3])] = &[
+    (0.00, [51, 51, 153]),   // deep water
+    (0.20, [102, 153, 255]), // shallow water
+    (0.25, [230, 220, 160]), // beach / sand
+    (0.40, [80, 160, 50]),   // grass
+    (0.65, [60, 110, 40]),   // forest
+    (0.75, [150, 140, 110]), // rock
+    (0.90, [160, 150, 140]), // high rock
+    (1.00, [255, 255, 255]), // snow
+];
+
+// matplotlib's perceptually uniform maps sampled at nine even stops.
+const VIRIDIS: &[u32] = &[
+    0x440154, 0x472c7a, 0x3b518b, 0x2c718e, 0x21908d, 0x27ad81, 0x5cc863, 0xaadc32, 0xfde725,
+];
+const MAGMA: &[u32] = &[
+    0x000004, 0x1c1044, 0x4f127b, 0x812581, 0xb5367a, 0xe55064, 0xfb8761, 0xfec287, 0xfcfdbf,
+];
+const CIVIDIS: &[u32] = &[
+    0x00224e, 0x123570, 0x3b496c, 0x575d6d, 0x707173, 0x8a8678, 0xa59c74, 0xc3b369, 0xfee838,
+];
+// ColorBrewer's 11-class diverging red–blue, low values red.
+const RDBU: &[u32] = &[
+    0x67001f, 0xb2182b, 0xd6604d, 0xf4a582, 0xfddbc7, 0xf7f7f7, 0xd1e5f0, 0x92c5de, 0x4393c3,
+    0x2166ac, 0x053061,
+];
+const GRAY: &[u32] = &[0x000000, 0xffffff];
+// Qualitative palettes for categorical maps: ColorBrewer Set1 and Tableau 10.
+const SET1: &[u32] = &[
+    0xe41a1c, 0x377eb8, 0x4daf4a, 0x984ea3, 0xff7f00, 0xffff33, 0xa65628, 0xf781bf, 0x999999,
+];
+const TAB10: &[u32] = &[
+    0x1f77b4, 0xff7f0e, 0x2ca02c, 0xd62728, 0x9467bd, 0x8c564b, 0xe377c2, 0x7f7f7f, 0xbcbd22,
+    0x17becf,
+];
+
+fn rgb(hex: u32) -> [u8; 3] {
+    [(hex >> 16) as u8, (hex >> 8) as u8, hex as u8]
+}
+
+/// Colours spaced evenly over [0, 1].
+fn even_stops(colors: impl ExactSizeIterator<Item = [u8; 3]>) -> Vec<(f64, [u8; 3])> {
+    let last = colors.len().saturating_sub(1).max(1) as f64;
+    colors.enumerate().map(|(i, c)| (i as f64 / last, c)).collect()
+}
+
+fn invalid(reason: impl Into<String>) -> NlmError {
+    NlmError::invalid("colormap", reason)
+}
+
+/// Maps values in [0, 1] to colours for PNG output.
+///
+/// A continuous map interpolates linearly between colour stops; a qualitative
+/// map (`set1`, `tab10`) has one flat band per colour and colours label grids
+/// class by class. [`discrete`](Colormap::discrete) splits any map into equal
+/// bands, and [`reversed`](Colormap::reversed) flips it.
+///
+/// Colormaps are written as specs such as `viridis`, `rdbu_r` (reversed),
+/// `magma@5` (five bands) or a list of stops, `0:#2166ac,0.5:#f7f7f7,1:#b2182b`;
+/// [`parse`](Colormap::parse) reads them and `Display` writes them back.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Colormap {
+    /// Built-in name, or `None` for user stops.
+    name: Option<&'static str>,
+    reversed: bool,
+    stops: Vec<(f64, [u8; 3])>,
+    qualitative: bool,
+    steps: Option<usize>,
+}
+
+impl Default for Colormap {
+    /// The terrain map used by [`write_to_png`](super::write_to_png).
+    fn default() -> Self {
+        Colormap::named("terrain").expect("terrain is built in")
+    }
+}
+
+impl Colormap {
+    /// One of the built-in [`COLORMAPS`].
+    pub fn named(name: &str) -> Option<Self> {
+        let (name, hexes, qualitative) = match name {
+            "terrain" => {
+                return Some(Colormap {
+                    name: Some("terrain"),
+                    reversed: false,
+                    stops: TERRAIN.to_vec(),
+                    qualitative: false,
+                    steps: None,
+                })
+            }
+            "viridis" => ("viridis", VIRIDIS, false),
+            "magma" => ("magma", MAGMA, false),
+            "cividis" => ("cividis", CIVIDIS, false),
+            "rdbu" => ("rdbu", RDBU, false),
+            "gray" => ("gray", GRAY, false),
+            "set1" => ("set1", SET1, true),
+            "tab10" => ("tab10", TAB10, true),
+            _ => return None,
+        };
+        Some(Colormap {
+            name: Some(name),
+            reversed: false,
+            stops: even_stops(hexes.iter().map(|&h| rgb(h))),
+            qualitative,
+            steps: None,
+        })
+    }
+
+    /// A continuous map through `(position, colour)` stops. Positions must run
+    /// from 0 to 1 in non-decreasing order, and at least two stops are needed.
+    pub fn from_stops(stops: Vec<(f64, [u8; 3])>) -> Result<Self> {
+        if stops.len() < 2 {
+            return Err(invalid("needs at least two stops"));
+        }
+        if stops.first().map(|s| s.0) != Some(0.0) || stops.last().map(|s| s.0) != Some(1.0) {
+            return Err(invalid("stops must start at 0 and end at 1"));
+        }
+        if stops.windows(2).any(|w| w[1].0.is_nan() || w[0].0 > w[1].0) {
+            return Err(invalid("stop positions must be non-decreasing"));
+        }
+        Ok(Colormap { name: None, reversed: false, stops, qualitative: false, steps: None })
+    }
+
+    /// A continuous map through `colors` spaced evenly from 0 to 1.
+    pub fn from_colors(colors: &[[u8; 3]]) -> Result<Self> {
+        Colormap::from_stops(even_stops(colors.iter().copied()))
+    }
+
+    /// Parses a colormap spec: a built-in name, optionally suffixed `_r` to
+    /// reverse it, or comma-separated `#rrggbb` colours, each optionally
+    /// prefixed by its `position:`. Either may end in `@N` for `N` discrete
+    /// bands.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (body, steps) = match spec.rsplit_once('@') {
+            Some((body, n)) => match n.trim().parse::<usize>() {
+                Ok(n) if n > 0 => (body.trim(), Some(n)),
+                _ => return Err(invalid(format!("band count '{n}' is not a positive integer"))),
+            },
+            None => (spec.trim(), None),
+        };
+        let mut map = if body.contains('#') {
+            Colormap::parse_stops(body)?
+        } else {
+            let (name, reversed) = match body.strip_suffix("_r") {
+                Some(name) => (name, true),
+                None => (body, false),
+            };
+            let map = Colormap::named(name).ok_or_else(|| {
+                invalid(format!(
+                    "unknown colormap '{name}'; expected one of {} or a list of #rrggbb stops",
+                    COLORMAPS.join(", ")
+                ))
+            })?;
+            if reversed {
+                map.reversed()
+            } else {
+                map
+            }
+        };
+        map.steps = steps;
+        Ok(map)
+    }
+
+    fn parse_stops(body: &str) -> Result<Self> {
+        let items: Vec<&str> = body.split(',').map(str::trim).collect();
+        let positioned = items.iter().filter(|s| s.contains(':')).count();
+        if positioned != 0 && positioned != items.len() {
+            return Err(invalid("give a position for every stop or for none"));
+        }
+        let color = |s: &str| -> Result<[u8; 3]> {
+            let hex = s.strip_prefix('#').filter(|h| h.len() == 6);
+            hex.and_then(|h| u32::from_str_radix(h, 16).ok())
+                .map(rgb)
+                .ok_or_else(|| invalid(format!("'{s}' is not a #rrggbb colour")))
+        };
+        if positioned == 0 {
+            let colors = items.iter().map(|s| color(s)).collect::<Result<Vec<_>>>()?;
+            return Colormap::from_colors(&colors);
+        }
+        let stops = items
+            .iter()
+            .map(|s| {
+                let (pos, c) = s.split_once(':').expect("counted above");
+                let pos = pos
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| invalid(format!("'{pos}' is not a stop position")))?;
+                Ok((pos, color(c.trim())?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Colormap::from_stops(stops)
+    }
+
+    /// The same map running from high to low.
+    pub fn reversed(mut self) -> Self {
+        self.reversed = !self.reversed;
+        self.stops.reverse();
+        for stop in &mut self.stops {
+            stop.0 = 1.0 - stop.0;
+        }
+        self
+    }
+
+    /// The same map in `steps` flat bands of equal width, coloured as the map
+    /// is at 0, 1/(steps − 1), …, 1 so they line up with
+    /// [`classify`](crate::classify). `steps` of 0 is treated as 1.
+    pub fn discrete(mut self, steps: usize) -> Self {
+        self.steps = Some(steps.max(1));
+        self
+    }
+
+    /// Returns true for a palette of unrelated colours meant for categories.
+    pub fn is_qualitative(&self) -> bool {
+        self.qualitative
+    }
+
+    /// Colour of value `t`, clamped to [0, 1].
+    pub fn color(&self, t: f64) -> [u8; 3] {
+        let mut t = t.clamp(0.0, 1.0);
+        if let Some(n) = self.steps {
+            let band = (t * n as f64).floor().min(n as f64 - 1.0);
+            t = if n == 1 { 0.5 } else { band / (n - 1) as f64 };
+        }
+        if self.qualitative {
+            let n = self.stops.len();
+            let k = ((t * n as f64).floor() as usize).min(n - 1);
+            return self.stops[k].1;
+        }
+        for w in self.stops.windows(2) {
+            let (t0, c0) = w[0];
+            let (t1, c1) = w[1];
+            if t <= t1 {
+                let f = if t1 > t0 { (t - t0) / (t1 - t0) } else { 1.0 };
+                let mix = |a: u8, b: u8| (a as f64 + f * (b as f64 - a as f64)).round() as u8;
+                return [mix(c0[0], c1[0]), mix(c0[1], c1[1]), mix(c0[2], c1[2])];
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+
+    /// Colours for the classes of a [`LabelGrid`](crate::LabelGrid) with
+    /// these class values: a qualitative map assigns its colours in turn,
+    /// any other colours each class by its value.
+    pub fn class_colors(&self, classes: &[f64]) -> Vec<[u8; 3]> {
+        if self.qualitative {
+            (0..classes.len())
+                .map(|k| self.stops[k % self.stops.len()].1)
+                .collect()
+        } else {
+            classes.iter().map(|&v| self.color(v)).collect()
+        }
+    }
+}
+
+impl fmt::Display for Colormap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "{name}{}", if self.reversed { "_r" } else { "" })?,
+            None => {
+                for (i, (pos, [r, g, b])) in self.stops.iter().enumerate() {
+                    let sep = if i > 0 { "," } else { "" };
+                    write!(f, "{sep}{pos}:#{r:02x}{g:02x}{b:02x}")?;
+                }
+            }
+        }
+        match self.steps {
+            Some(n) => write!(f, "@{n}"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terrain_matches_stops() {
+        let map = Colormap::default();
+        assert_eq!(map.color(0.0), [51, 51, 153]);
+        assert_eq!(map.color(1.0), [255, 255, 255]);
+        assert_eq!(map.color(0.1), [77, 102, 204]);
+        assert_eq!(map.color(-3.0), map.color(0.0));
+    }
+
+    #[test]
+    fn test_reversed_and_discrete() {
+        let map = Colormap::named("viridis").unwrap();
+        let rev = map.clone().reversed();
+        for t in [0.0, 0.3, 0.77, 1.0] {
+            assert_eq!(rev.color(t), map.color(1.0 - t));
+        }
+        let bands = map.clone().discrete(3);
+        assert_eq!(bands.color(0.1), map.color(0.0));
+        assert_eq!(bands.color(0.5), map.color(0.5));
+        assert_eq!(bands.color(0.9), map.color(1.0));
+    }
+
+    #[test]
+    fn test_qualitative_colors() {
+        let map = Colormap::named("set1").unwrap();
+        assert!(map.is_qualitative());
+        assert_eq!(map.color(0.0), rgb(SET1[0]));
+        assert_eq!(map.color(1.0), rgb(SET1[8]));
+        let colors = map.class_colors(&[0.0; 11]);
+        assert_eq!(colors[1], rgb(SET1[1]));
+        assert_eq!(colors[10], rgb(SET1[1]));
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        for spec in ["viridis", "rdbu_r", "magma@5", "tab10_r@3", "0:#000000,0.25:#ff0000,1:#ffffff"] {
+            let map = Colormap::parse(spec).unwrap();
+            assert_eq!(map.to_string(), spec);
+            assert_eq!(Colormap::parse(&map.to_string()).unwrap(), map);
+        }
+        let even = Colormap::parse("#000000, #ffffff").unwrap();
+        assert_eq!(even.color(0.5), [128, 128, 128]);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_specs() {
+        for spec in [
+            "jet",
+            "viridis@0",
+            "#000000",
+            "#00000,#ffffff",
+            "0:#000000,#ffffff",
+            "0.2:#000000,1:#ffffff",
+            "0:#000000,0.6:#ff0000,0.4:#00ff00,1:#ffffff",
+        ] {
+            assert!(Colormap::parse(spec).is_err(), "{spec}");
+        }
+    }
+}
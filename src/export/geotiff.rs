@@ -0,0 +1,558 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Result, Seek, Write};
+
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::encoder::{colortype, DirectoryEncoder, TiffEncoder, TiffKind, TiffValue};
+use tiff::tags::Tag;
+
+use crate::grid::{Element, GeoGrid, GeoTransform, Grid};
+use crate::labels::{Label, LabelGrid};
+use crate::tiled::Window;
+
+use super::ASCII_NODATA;
+
+/// Sample type used for the cells of a GeoTIFF written by [`write_to_geotiff`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TiffSampleFormat {
+    /// 32-bit IEEE float — half the size, ~7 significant digits.
+    Float32,
+    /// 64-bit IEEE float — lossless for any `Grid`.
+    Float64,
+}
+
+// GeoKey IDs and values from the GeoTIFF 1.0 specification.
+const GT_MODEL_TYPE: u16 = 1024;
+const GT_RASTER_TYPE: u16 = 1025;
+const GT_CITATION: u16 = 1026;
+const GEOGRAPHIC_TYPE: u16 = 2048;
+const PROJECTED_CS_TYPE: u16 = 3072;
+const MODEL_TYPE_PROJECTED: u16 = 1;
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+const RASTER_PIXEL_IS_AREA: u16 = 1;
+const RASTER_PIXEL_IS_POINT: u16 = 2;
+
+/// EPSG codes of common geographic (latitude/longitude) CRSs. Codes in the
+/// 4000s are not all geographic, so the model type is not guessed from the range.
+const GEOGRAPHIC_CODES: &[u16] = &[
+    4148, // Hartebeesthoek94
+    4152, // NAD83(HARN)
+    4167, // NZGD2000
+    4171, // RGF93
+    4202, // AGD66
+    4203, // AGD84
+    4230, // ED50
+    4258, // ETRS89
+    4265, // Monte Mario
+    4267, // NAD27
+    4269, // NAD83
+    4275, // NTF
+    4277, // OSGB36
+    4283, // GDA94
+    4289, // Amersfoort
+    4301, // Tokyo
+    4314, // DHDN
+    4322, // WGS 72
+    4326, // WGS 84
+    4490, // CGCS2000
+    4612, // JGD2000
+    4617, // NAD83(CSRS)
+    4619, // SWEREF99
+    4674, // SIRGAS 2000
+    4759, // NAD83(NSRS2007)
+    6318, // NAD83(2011)
+    6668, // JGD2011
+    7844, // GDA2020
+];
+
+/// Projected CRSs among the EPSG codes 4000 to 4999, which otherwise hold
+/// geographic, geocentric and other CRS types.
+const PROJECTED_CODES_IN_4000S: &[u16] = &[
+    4087, // WGS 84 / World Equidistant Cylindrical
+    4088, // World Equidistant Cylindrical (Sphere)
+];
+
+fn tiff_err(e: tiff::TiffError) -> std::io::Error {
+    std::io::Error::other(e.to_string())
+}
+
+fn invalid_data(msg: impl ToString) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// Parses an `"EPSG:<code>"` CRS string into its numeric code.
+fn epsg_code(crs: &str) -> Option<u16> {
+    let (authority, code) = crs.trim().split_once(':')?;
+    if authority.eq_ignore_ascii_case("EPSG") {
+        code.trim().parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Builds the GeoKeyDirectory and GeoAsciiParams tag values for `crs`.
+///
+/// An `EPSG:<code>` CRS is stored as a geographic type for the codes in
+/// [`GEOGRAPHIC_CODES`] and as a projected type for the others, except for
+/// codes in the 4000s whose type is not known, such as the geocentric 4978.
+/// Those and any other CRS string, such as WKT, are kept verbatim in the
+/// citation key rather than declared with the wrong model type, so they
+/// survive a round trip.
+fn geo_keys(crs: Option<&str>) -> (Vec<u16>, Option<String>) {
+    let mut keys = vec![[GT_RASTER_TYPE, 0, 1, RASTER_PIXEL_IS_AREA]];
+    let mut ascii = None;
+    if let Some(crs) = crs {
+        match epsg_code(crs) {
+            Some(code) if GEOGRAPHIC_CODES.contains(&code) => {
+                keys.push([GT_MODEL_TYPE, 0, 1, MODEL_TYPE_GEOGRAPHIC]);
+                keys.push([GEOGRAPHIC_TYPE, 0, 1, code]);
+            }
+            Some(code)
+                if !(4000..5000).contains(&code) || PROJECTED_CODES_IN_4000S.contains(&code) =>
+            {
+                keys.push([GT_MODEL_TYPE, 0, 1, MODEL_TYPE_PROJECTED]);
+                keys.push([PROJECTED_CS_TYPE, 0, 1, code]);
+            }
+            _ => {
+                let text = format!("{}|", crs.replace('|', " "));
+                keys.push([
+                    GT_CITATION,
+                    Tag::GeoAsciiParamsTag.to_u16(),
+                    text.len() as u16,
+                    0,
+                ]);
+                ascii = Some(text);
+            }
+        }
+    }
+    keys.sort_by_key(|k| k[0]);
+    let mut dir = vec![1, 1, 0, keys.len() as u16];
+    dir.extend(keys.into_iter().flatten());
+    (dir, ascii)
+}
+
+/// Writes a georeferenced grid as a single-band GeoTIFF with raw float samples.
+///
+/// Unlike [`write_to_tiff`](super::write_to_tiff), values are stored unquantised.
+/// The transform is written as ModelPixelScale/ModelTiepoint tags, an
+/// `EPSG:<code>` CRS of known type as GeoKeys (other CRS strings go into the
+/// citation key),
+/// and NoData cells as [`ASCII_NODATA`] declared in the `GDAL_NODATA` tag, so
+/// the file opens correctly in GDAL, QGIS, rasterio and terra.
+pub fn write_to_geotiff<T: Element>(geo: &GeoGrid<T>, path: &str, format: TiffSampleFormat) -> Result<()> {
+    let (grid, t) = (&geo.grid, &geo.transform);
+    let (rows, cols) = (grid.rows, grid.cols);
+    let file = BufWriter::new(File::create(path)?);
+    let mut tiff = TiffEncoder::new(file).map_err(tiff_err)?;
+    let samples = grid
+        .iter()
+        .map(|&v| if v.is_nan() { ASCII_NODATA } else { v.to_f64() });
+
+    match format {
+        TiffSampleFormat::Float32 => {
+            let data: Vec<f32> = samples.map(|v| v as f32).collect();
+            write_band::<colortype::Gray32Float, _>(&mut tiff, rows, cols, t, ASCII_NODATA, &data)
+        }
+        TiffSampleFormat::Float64 => {
+            let data: Vec<f64> = samples.collect();
+            write_band::<colortype::Gray64Float, _>(&mut tiff, rows, cols, t, ASCII_NODATA, &data)
+        }
+    }
+}
+
+fn write_band<C, W>(
+    tiff: &mut TiffEncoder<W>,
+    rows: usize,
+    cols: usize,
+    t: &GeoTransform,
+    nodata: f64,
+    data: &[C::Inner],
+) -> Result<()>
+where
+    C: colortype::ColorType,
+    [C::Inner]: TiffValue,
+    W: Write + Seek,
+{
+    let mut image = tiff
+        .new_image::<C>(cols as u32, rows as u32)
+        .map_err(tiff_err)?;
+    write_geo_tags(image.encoder(), rows, t, nodata)?;
+    image.write_data(data).map_err(tiff_err)
+}
+
+/// Writes 16-bit gray levels as a plain TIFF with `nodata` declared in the
+/// `GDAL_NODATA` tag, for [`write_to_tiff`](super::write_to_tiff).
+pub(crate) fn write_gray16(
+    path: &str,
+    rows: usize,
+    cols: usize,
+    data: &[u16],
+    nodata: u16,
+) -> Result<()> {
+    let mut tiff = TiffEncoder::new(BufWriter::new(File::create(path)?)).map_err(tiff_err)?;
+    let mut image = tiff
+        .new_image::<colortype::Gray16>(cols as u32, rows as u32)
+        .map_err(tiff_err)?;
+    image
+        .encoder()
+        .write_tag(Tag::GdalNodata, nodata.to_string().as_str())
+        .map_err(tiff_err)?;
+    image.write_data(data).map_err(tiff_err)
+}
+
+/// Writes a label grid as a single-band unsigned integer GeoTIFF, 8, 16 or 32
+/// bits per sample to match `L`.
+///
+/// Georeferencing is written as by [`write_to_geotiff`]. NoData cells keep the
+/// label [`Label::NODATA`], which is declared in the `GDAL_NODATA` tag. The
+/// class table is not stored; [`read_from_geotiff`] returns the labels as
+/// values.
+pub fn write_labels_to_geotiff<L: Label>(
+    labels: &LabelGrid<L>,
+    transform: &GeoTransform,
+    path: &str,
+) -> Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut tiff = TiffEncoder::new(file).map_err(tiff_err)?;
+    let (rows, cols, t) = (labels.rows, labels.cols, transform);
+    let nodata = L::NODATA.index() as f64;
+    let samples = labels.iter().map(|l| l.index());
+    match L::BITS {
+        8 => {
+            let data: Vec<u8> = samples.map(|v| v as u8).collect();
+            write_band::<colortype::Gray8, _>(&mut tiff, rows, cols, t, nodata, &data)
+        }
+        16 => {
+            let data: Vec<u16> = samples.map(|v| v as u16).collect();
+            write_band::<colortype::Gray16, _>(&mut tiff, rows, cols, t, nodata, &data)
+        }
+        _ => {
+            let data: Vec<u32> = samples.map(|v| v as u32).collect();
+            write_band::<colortype::Gray32, _>(&mut tiff, rows, cols, t, nodata, &data)
+        }
+    }
+}
+
+/// Writes the transform, CRS and NoData tags for a grid with `rows` rows.
+fn write_geo_tags<W: Write + Seek, K: TiffKind>(
+    dir: &mut DirectoryEncoder<W, K>,
+    rows: usize,
+    t: &GeoTransform,
+    nodata: f64,
+) -> Result<()> {
+    dir.write_tag(
+        Tag::ModelPixelScaleTag,
+        &[t.cell_size, t.cell_size, 0.0][..],
+    )
+    .map_err(tiff_err)?;
+    dir.write_tag(
+        Tag::ModelTiepointTag,
+        &[0.0, 0.0, 0.0, t.x_min, t.y_max(rows), 0.0][..],
+    )
+    .map_err(tiff_err)?;
+    let (keys, ascii) = geo_keys(t.crs.as_deref());
+    dir.write_tag(Tag::GeoKeyDirectoryTag, &keys[..])
+        .map_err(tiff_err)?;
+    if let Some(ascii) = &ascii {
+        dir.write_tag(Tag::GeoAsciiParamsTag, ascii.as_str())
+            .map_err(tiff_err)?;
+    }
+    dir.write_tag(Tag::GdalNodata, nodata.to_string().as_str())
+        .map_err(tiff_err)?;
+    Ok(())
+}
+
+/// Largest file, in bytes, written as a classic TIFF before switching to BigTIFF.
+/// Classic TIFF offsets are 32-bit; the margin leaves room for tags.
+const CLASSIC_TIFF_LIMIT: u64 = u32::MAX as u64 - (1 << 20);
+
+/// Streams a (rows x cols) grid to a tiled float GeoTIFF, asking `tile` for
+/// each `size` x `size` window in row-major order.
+///
+/// Tags and NoData handling match [`write_to_geotiff`]. Edge tiles are padded
+/// with [`ASCII_NODATA`], and files too large for classic TIFF are written as
+/// BigTIFF.
+pub(crate) fn write_geotiff_tiles(
+    path: &str,
+    rows: usize,
+    cols: usize,
+    transform: &GeoTransform,
+    format: TiffSampleFormat,
+    size: usize,
+    tile: impl FnMut(Window) -> Grid,
+) -> Result<()> {
+    if u32::try_from(rows.max(cols)).is_err() {
+        return Err(invalid_data(format!("{rows}x{cols} is too large for a TIFF")));
+    }
+    let file = BufWriter::new(File::create(path)?);
+    let tiles = rows.div_ceil(size) as u64 * cols.div_ceil(size) as u64;
+    let sample_bytes = match format {
+        TiffSampleFormat::Float32 => 4,
+        TiffSampleFormat::Float64 => 8,
+    };
+    let layout = TileLayout { rows, cols, size, transform };
+    if tiles * (size * size) as u64 * sample_bytes <= CLASSIC_TIFF_LIMIT {
+        let mut tiff = TiffEncoder::new(file).map_err(tiff_err)?;
+        write_tiles(&mut tiff, &layout, format, tile)
+    } else {
+        let mut tiff = TiffEncoder::new_big(file).map_err(tiff_err)?;
+        write_tiles(&mut tiff, &layout, format, tile)
+    }
+}
+
+struct TileLayout<'a> {
+    rows: usize,
+    cols: usize,
+    size: usize,
+    transform: &'a GeoTransform,
+}
+
+fn write_tiles<W: Write + Seek, K: TiffKind>(
+    tiff: &mut TiffEncoder<W, K>,
+    layout: &TileLayout,
+    format: TiffSampleFormat,
+    mut tile: impl FnMut(Window) -> Grid,
+) -> Result<()> {
+    let TileLayout { rows, cols, size, transform: t } = *layout;
+    let mut dir = tiff.image_directory().map_err(tiff_err)?;
+    let mut offsets = Vec::new();
+    let mut byte_counts = Vec::new();
+    let mut f32s = Vec::new();
+    let mut f64s = Vec::new();
+    for window in Window::tiles(rows, cols, size) {
+        let grid = tile(window);
+        let mut padded = vec![ASCII_NODATA; size * size];
+        for i in 0..grid.rows {
+            for (cell, &v) in padded[i * size..].iter_mut().zip(&grid[i]) {
+                *cell = if v.is_nan() { ASCII_NODATA } else { v };
+            }
+        }
+        let (offset, bytes) = match format {
+            TiffSampleFormat::Float32 => {
+                f32s.clear();
+                f32s.extend(padded.iter().map(|&v| v as f32));
+                (dir.write_data(&f32s[..]), f32s.len() * 4)
+            }
+            TiffSampleFormat::Float64 => {
+                f64s.clear();
+                f64s.extend_from_slice(&padded);
+                (dir.write_data(&f64s[..]), f64s.len() * 8)
+            }
+        };
+        offsets.push(K::convert_offset(offset.map_err(tiff_err)?).map_err(tiff_err)?);
+        byte_counts.push(bytes as u32);
+    }
+
+    let bits: u16 = match format {
+        TiffSampleFormat::Float32 => 32,
+        TiffSampleFormat::Float64 => 64,
+    };
+    let tags: [(Tag, u32); 4] = [
+        (Tag::ImageWidth, cols as u32),
+        (Tag::ImageLength, rows as u32),
+        (Tag::TileWidth, size as u32),
+        (Tag::TileLength, size as u32),
+    ];
+    for (tag, value) in tags {
+        dir.write_tag(tag, value).map_err(tiff_err)?;
+    }
+    let tags: [(Tag, u16); 6] = [
+        (Tag::BitsPerSample, bits),
+        (Tag::Compression, 1),
+        (Tag::PhotometricInterpretation, 1),
+        (Tag::SamplesPerPixel, 1),
+        (Tag::PlanarConfiguration, 1),
+        (Tag::SampleFormat, 3),
+    ];
+    for (tag, value) in tags {
+        dir.write_tag(tag, value).map_err(tiff_err)?;
+    }
+    dir.write_tag(Tag::TileOffsets, K::convert_slice(&offsets))
+        .map_err(tiff_err)?;
+    dir.write_tag(Tag::TileByteCounts, &byte_counts[..])
+        .map_err(tiff_err)?;
+    write_geo_tags(&mut dir, rows, t, ASCII_NODATA)?;
+    dir.finish().map_err(tiff_err)
+}
+
+/// Reads a single-band GeoTIFF such as one written by [`write_to_geotiff`].
+///
+/// Integer and float sample types are converted to `f64`, cells equal to the
+/// `GDAL_NODATA` value become NoData (`NaN`), and the transform is recovered
+/// from ModelPixelScale/ModelTiepoint. The CRS is read back as `EPSG:<code>`
+/// when the GeoKeys carry one, otherwise from the citation key. Files without
+/// georeferencing get the default transform; rotated or non-square cells are
+/// rejected.
+pub fn read_from_geotiff(path: &str) -> Result<GeoGrid> {
+    let mut dec = Decoder::new(BufReader::new(File::open(path)?)).map_err(tiff_err)?;
+    let grid = read_band(&mut dec, false)?;
+    let rows = grid.rows;
+
+    let mut transform = GeoTransform::default();
+    let scale = dec.find_tag(Tag::ModelPixelScaleTag).map_err(tiff_err)?;
+    let tiepoint = dec.find_tag(Tag::ModelTiepointTag).map_err(tiff_err)?;
+    let keys = match dec.find_tag(Tag::GeoKeyDirectoryTag).map_err(tiff_err)? {
+        Some(v) => v.into_u16_vec().map_err(tiff_err)?,
+        None => Vec::new(),
+    };
+    let key = |id: u16| {
+        keys.get(4..)
+            .unwrap_or(&[])
+            .chunks_exact(4)
+            .find(|k| k[0] == id)
+    };
+
+    if let (Some(scale), Some(tiepoint)) = (scale, tiepoint) {
+        let scale = scale.into_f64_vec().map_err(tiff_err)?;
+        let tp = tiepoint.into_f64_vec().map_err(tiff_err)?;
+        if scale.len() < 2 || tp.len() < 6 {
+            return Err(invalid_data(
+                "malformed ModelPixelScale or ModelTiepoint tag",
+            ));
+        }
+        if tp.len() > 6 || (scale[0] - scale[1]).abs() > 1e-9 * scale[0].abs() {
+            return Err(invalid_data(
+                "only north-up rasters with square cells are supported",
+            ));
+        }
+        let cell_size = scale[0];
+        // Tiepoint maps raster (i, j) to model (x, y); PixelIsPoint refers to cell centres.
+        let half = match key(GT_RASTER_TYPE) {
+            Some(k) if k[3] == RASTER_PIXEL_IS_POINT => 0.5,
+            _ => 0.0,
+        };
+        let x_min = tp[3] - (tp[0] + half) * cell_size;
+        let y_max = tp[4] + (tp[1] + half) * cell_size;
+        transform = GeoTransform {
+            x_min,
+            y_min: y_max - rows as f64 * cell_size,
+            cell_size,
+            crs: None,
+        };
+    }
+
+    transform.crs = match key(PROJECTED_CS_TYPE).or_else(|| key(GEOGRAPHIC_TYPE)) {
+        Some(k) if k[1] == 0 => Some(format!("EPSG:{}", k[3])),
+        _ => match key(GT_CITATION) {
+            Some(k) if k[1] == Tag::GeoAsciiParamsTag.to_u16() => {
+                let ascii = dec
+                    .get_tag_ascii_string(Tag::GeoAsciiParamsTag)
+                    .map_err(tiff_err)?;
+                let (start, len) = (k[3] as usize, k[2] as usize);
+                ascii
+                    .get(start..start + len)
+                    .map(|s| s.trim_end_matches(['|', '\0']).to_string())
+            }
+            _ => None,
+        },
+    };
+
+    Ok(GeoGrid { grid, transform })
+}
+
+/// Reads a single-band TIFF such as one written by
+/// [`write_to_tiff`](super::write_to_tiff), scaling 8- and 16-bit samples to
+/// [0, 1] and keeping float samples as stored.
+///
+/// Cells equal to a `GDAL_NODATA` value become NoData (`NaN`) and any
+/// georeferencing is ignored. Other integer sample types are rejected; read
+/// them with [`read_from_geotiff`], which keeps every value as stored.
+pub fn read_from_tiff(path: &str) -> Result<Grid> {
+    let mut dec = Decoder::new(BufReader::new(File::open(path)?)).map_err(tiff_err)?;
+    read_band(&mut dec, true)
+}
+
+/// Reads the TIFF at `path` with [`read_from_geotiff`] if it carries
+/// georeferencing, and with [`read_from_tiff`] otherwise.
+pub(crate) fn read_tiff_any(path: &str) -> Result<GeoGrid> {
+    let mut dec = Decoder::new(BufReader::new(File::open(path)?)).map_err(tiff_err)?;
+    if dec.find_tag(Tag::ModelPixelScaleTag).map_err(tiff_err)?.is_some() {
+        return read_from_geotiff(path);
+    }
+    let grid = read_band(&mut dec, true)?;
+    Ok(GeoGrid { grid, transform: GeoTransform::default() })
+}
+
+/// Decodes the first band of a grayscale TIFF, with cells equal to the
+/// `GDAL_NODATA` value as `NaN`. With `normalise`, 8- and 16-bit samples are
+/// divided by their maximum and other integer types are rejected.
+fn read_band<R: std::io::Read + Seek>(dec: &mut Decoder<R>, normalise: bool) -> Result<Grid> {
+    match dec.colortype().map_err(tiff_err)? {
+        tiff::ColorType::Gray(_) => {}
+        other => {
+            return Err(invalid_data(format!(
+                "expected a single-band image, found {other:?}"
+            )))
+        }
+    }
+    let (w, h) = dec.dimensions().map_err(tiff_err)?;
+    let (rows, cols) = (h as usize, w as usize);
+
+    let nodata = match dec.find_tag(Tag::GdalNodata).map_err(tiff_err)? {
+        Some(v) => {
+            let s = v.into_string().map_err(tiff_err)?;
+            Some(
+                s.trim_matches(|c: char| c == '\0' || c.is_whitespace())
+                    .parse::<f64>()
+                    .map_err(invalid_data)?,
+            )
+        }
+        None => None,
+    };
+
+    let (data, max): (Vec<f64>, Option<f64>) = match dec.read_image().map_err(tiff_err)? {
+        DecodingResult::U8(v) => (v.into_iter().map(f64::from).collect(), Some(255.0)),
+        DecodingResult::U16(v) => (v.into_iter().map(f64::from).collect(), Some(65535.0)),
+        DecodingResult::F16(v) => (v.into_iter().map(f64::from).collect(), None),
+        DecodingResult::F32(v) => (v.into_iter().map(f64::from).collect(), None),
+        DecodingResult::F64(v) => (v, None),
+        _ if normalise => {
+            return Err(invalid_data(
+                "expected 8- or 16-bit unsigned or float samples",
+            ))
+        }
+        DecodingResult::U32(v) => (v.into_iter().map(f64::from).collect(), None),
+        DecodingResult::U64(v) => (v.into_iter().map(|x| x as f64).collect(), None),
+        DecodingResult::I8(v) => (v.into_iter().map(f64::from).collect(), None),
+        DecodingResult::I16(v) => (v.into_iter().map(f64::from).collect(), None),
+        DecodingResult::I32(v) => (v.into_iter().map(f64::from).collect(), None),
+        DecodingResult::I64(v) => (v.into_iter().map(|x| x as f64).collect(), None),
+    };
+    let scale = max.filter(|_| normalise).unwrap_or(1.0);
+    let data = data
+        .into_iter()
+        .map(|v| {
+            if nodata.is_some_and(|n| v == n || (n.is_nan() && v.is_nan())) {
+                f64::NAN
+            } else {
+                v / scale
+            }
+        })
+        .collect();
+    Ok(Grid { data, rows, cols })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The (key, value) pairs of a GeoKeyDirectory built by [`geo_keys`].
+    fn keys(crs: &str) -> Vec<(u16, u16)> {
+        geo_keys(Some(crs)).0[4..].chunks(4).map(|k| (k[0], k[3])).collect()
+    }
+
+    #[test]
+    fn test_geo_keys_model_type() {
+        let geographic = keys("EPSG:4258");
+        assert!(geographic.contains(&(GT_MODEL_TYPE, MODEL_TYPE_GEOGRAPHIC)));
+        assert!(geographic.contains(&(GEOGRAPHIC_TYPE, 4258)));
+        for code in [4087, 32633, 27700] {
+            let projected = keys(&format!("EPSG:{code}"));
+            assert!(projected.contains(&(GT_MODEL_TYPE, MODEL_TYPE_PROJECTED)), "{code}");
+            assert!(projected.contains(&(PROJECTED_CS_TYPE, code)), "{code}");
+        }
+        // Geocentric: neither type, so only the citation is written.
+        let (dir, ascii) = geo_keys(Some("EPSG:4978"));
+        assert_eq!(dir[3], 2);
+        assert_eq!(ascii.as_deref(), Some("EPSG:4978|"));
+    }
+}
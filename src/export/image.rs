@@ -1,80 +1,248 @@
-use std::io::Result;
+use std::fs::File;
+use std::io::{BufWriter, Result};
 
-use crate::grid::Grid;
+use crate::grid::{Element, Grid};
+use crate::labels::{Label, LabelGrid};
 
-/// Maps a normalised value [0, 1] to an RGB terrain colour.
+use super::Colormap;
+
+/// Error for a grid whose `data` length disagrees with `rows * cols`.
+fn buffer_mismatch() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        "grid data length does not match rows x cols",
+    )
+}
+
+/// Writes the grid as a PNG using a terrain colormap (water → sand → grass → rock → snow).
 ///
-/// Colours roughly approximate the matplotlib 'terrain' colormap:
-/// deep water → shallow water → sand → grass → forest → rock → snow.
-fn terrain_color(t: f64) -> [u8; 3] {
-    let t = t.clamp(0.0, 1.0);
-    let stops: &[(f64, [u8; 3])] = &[
-        (0.00, [51, 51, 153]),   // deep water
-        (0.20, [102, 153, 255]), // shallow water
-        (0.25, [230, 220, 160]), // beach / sand
-        (0.40, [80, 160, 50]),   // grass
-        (0.65, [60, 110, 40]),   // forest
-        (0.75, [150, 140, 110]), // rock
-        (0.90, [160, 150, 140]), // high rock
-        (1.00, [255, 255, 255]), // snow
-    ];
+/// If the grid contains NoData (`NaN`) cells the image is written as RGBA with
+/// those cells fully transparent; otherwise a plain RGB image is written.
+pub fn write_to_png<T: Element>(grid: &Grid<T>, path: &str) -> Result<()> {
+    write_to_png_colormap(grid, path, &Colormap::default(), false)
+}
 
-    for w in stops.windows(2) {
-        let (t0, c0) = w[0];
-        let (t1, c1) = w[1];
-        if t <= t1 {
-            let f = (t - t0) / (t1 - t0);
-            return [
-                (c0[0] as f64 + f * (c1[0] as f64 - c0[0] as f64)).round() as u8,
-                (c0[1] as f64 + f * (c1[1] as f64 - c0[1] as f64)).round() as u8,
-                (c0[2] as f64 + f * (c1[2] as f64 - c0[2] as f64)).round() as u8,
-            ];
+/// Writes the grid as a PNG coloured by `colormap`, with NoData cells
+/// transparent as in [`write_to_png`].
+///
+/// With `legend`, a colour bar running from 0 at the bottom to 1 at the top is
+/// drawn to the right of the grid, separated by a transparent gap.
+pub fn write_to_png_colormap<T: Element>(
+    grid: &Grid<T>,
+    path: &str,
+    colormap: &Colormap,
+    legend: bool,
+) -> Result<()> {
+    use image::{RgbImage, RgbaImage};
+    let (gap, bar) = if legend { legend_size(grid.cols) } else { (0, 0) };
+    let width = grid.cols + gap + bar;
+    let (w, h) = (width as u32, grid.rows as u32);
+    let pixel = |i: usize, j: usize| -> Option<[u8; 3]> {
+        if j < grid.cols {
+            let v = grid[i][j];
+            (!v.is_nan()).then(|| colormap.color(v.to_f64()))
+        } else if j >= grid.cols + gap {
+            Some(colormap.color(legend_value(i, grid.rows)))
+        } else {
+            None
         }
+    };
+    if grid.data.len() != grid.rows * grid.cols {
+        return Err(buffer_mismatch());
     }
-    [255, 255, 255]
+    let res = if legend || grid.nodata_count() > 0 {
+        let buf: Vec<u8> = (0..grid.rows)
+            .flat_map(|i| (0..width).map(move |j| (i, j)))
+            .flat_map(|(i, j)| match pixel(i, j) {
+                Some([r, g, b]) => [r, g, b, 255],
+                None => [0, 0, 0, 0],
+            })
+            .collect();
+        RgbaImage::from_raw(w, h, buf)
+            .ok_or_else(buffer_mismatch)?
+            .save(path)
+    } else {
+        let buf: Vec<u8> = grid.data.iter().flat_map(|&v| colormap.color(v.to_f64())).collect();
+        RgbImage::from_raw(w, h, buf)
+            .ok_or_else(buffer_mismatch)?
+            .save(path)
+    };
+    res.map_err(|e| std::io::Error::other(e.to_string()))?;
+    Ok(())
 }
 
-/// Writes the grid as a PNG using a terrain colormap (water → sand → grass → rock → snow).
-pub fn write_to_png(grid: &Grid, path: &str) -> Result<()> {
-    use image::RgbImage;
-    let buf: Vec<u8> = grid.data.iter().flat_map(|&v| terrain_color(v)).collect();
-    let img = RgbImage::from_raw(grid.cols as u32, grid.rows as u32, buf)
-        .expect("buffer size mismatch");
-    img.save(path)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
-    Ok(())
+/// Widths of the transparent gap and the legend strip beside a grid of `cols`
+/// columns.
+fn legend_size(cols: usize) -> (usize, usize) {
+    let bar = (cols / 16).max(8);
+    (bar / 2, bar)
+}
+
+/// Value shown by row `i` of a legend strip `rows` tall: 1 at the top, 0 at
+/// the bottom.
+fn legend_value(i: usize, rows: usize) -> f64 {
+    1.0 - i as f64 / rows.saturating_sub(1).max(1) as f64
 }
 
 /// Writes the grid as a grayscale PNG.
-pub fn write_to_png_grayscale(grid: &Grid, path: &str) -> Result<()> {
-    use image::GrayImage;
-    let buf: Vec<u8> = grid
-        .data
-        .iter()
-        .map(|&v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
-        .collect();
-    let img = GrayImage::from_raw(grid.cols as u32, grid.rows as u32, buf)
-        .expect("buffer size mismatch");
-    img.save(path)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+///
+/// As with [`write_to_png`], NoData cells become transparent (grayscale + alpha).
+pub fn write_to_png_grayscale<T: Element>(grid: &Grid<T>, path: &str) -> Result<()> {
+    use image::{GrayAlphaImage, GrayImage};
+    let (w, h) = (grid.cols as u32, grid.rows as u32);
+    let luma = |v: T| (v.to_f64().clamp(0.0, 1.0) * 255.0).round() as u8;
+    let res = if grid.nodata_count() > 0 {
+        let buf: Vec<u8> = grid
+            .data
+            .iter()
+            .flat_map(|&v| if v.is_nan() { [0, 0] } else { [luma(v), 255] })
+            .collect();
+        GrayAlphaImage::from_raw(w, h, buf)
+            .ok_or_else(buffer_mismatch)?
+            .save(path)
+    } else {
+        let buf: Vec<u8> = grid.data.iter().map(|&v| luma(v)).collect();
+        GrayImage::from_raw(w, h, buf)
+            .ok_or_else(buffer_mismatch)?
+            .save(path)
+    };
+    res.map_err(|e| std::io::Error::other(e.to_string()))?;
     Ok(())
 }
 
+/// Gray level that [`write_to_tiff`] reserves for NoData in grids that have any.
+pub const TIFF_NODATA: u16 = 0;
+
 /// Writes the grid as a 16-bit grayscale TIFF.
 ///
 /// Each cell value [0, 1] is mapped to the full u16 range [0, 65535], preserving
-/// far more precision than an 8-bit PNG.
-pub fn write_to_tiff(grid: &Grid, path: &str) -> Result<()> {
+/// far more precision than an 8-bit PNG. If the grid has NoData (`NaN`) cells,
+/// level [`TIFF_NODATA`] is reserved for them and declared in the `GDAL_NODATA`
+/// tag, and values that would round to it are written as 1 instead, so
+/// [`read_from_tiff`](super::read_from_tiff) and GIS tools read those cells
+/// back as NoData. Use [`write_to_geotiff`](super::write_to_geotiff) to keep
+/// values unquantised.
+pub fn write_to_tiff<T: Element>(grid: &Grid<T>, path: &str) -> Result<()> {
     use image::{ImageBuffer, Luma};
-    let buf: Vec<u16> = grid
-        .data
-        .iter()
-        .map(|&v| (v.clamp(0.0, 1.0) * 65535.0).round() as u16)
-        .collect();
+    if grid.data.len() != grid.rows * grid.cols {
+        return Err(buffer_mismatch());
+    }
+    let level = |v: T| (v.to_f64().clamp(0.0, 1.0) * 65535.0).round() as u16;
+    if grid.nodata_count() > 0 {
+        let buf: Vec<u16> = grid
+            .data
+            .iter()
+            .map(|&v| if v.is_nan() { TIFF_NODATA } else { level(v).max(TIFF_NODATA + 1) })
+            .collect();
+        return super::geotiff::write_gray16(path, grid.rows, grid.cols, &buf, TIFF_NODATA);
+    }
+    let buf: Vec<u16> = grid.data.iter().map(|&v| level(v)).collect();
     let img: ImageBuffer<Luma<u16>, Vec<u16>> =
         ImageBuffer::from_raw(grid.cols as u32, grid.rows as u32, buf)
-            .expect("buffer size mismatch");
+            .ok_or_else(buffer_mismatch)?;
     img.save(path)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
     Ok(())
 }
+
+/// Writes a label grid as an 8-bit indexed PNG, one palette entry per class.
+///
+/// `palette[k]` is the colour of class `k`; with `None` each class takes the
+/// terrain colour of its value, as [`write_to_png`] would draw it, and
+/// [`Colormap::class_colors`] gives the colours of any other map. NoData
+/// cells use one more, fully transparent, palette entry. With `legend`, a
+/// strip of class swatches, class 0 at the bottom, is drawn to the right of
+/// the grid. Fails if the grid has more than 256 palette entries or `palette`
+/// is shorter than the class table.
+pub fn write_labels_to_png<L: Label>(
+    labels: &LabelGrid<L>,
+    path: &str,
+    palette: Option<&[[u8; 3]]>,
+    legend: bool,
+) -> Result<()> {
+    let n = labels.classes.len();
+    let colours: Vec<[u8; 3]> = match palette {
+        Some(p) if p.len() < n => {
+            return Err(invalid_input(format!("palette has {} colours for {n} classes", p.len())))
+        }
+        Some(p) => p[..n].to_vec(),
+        None => Colormap::default().class_colors(&labels.classes),
+    };
+    // Labels past the class table have no colour and are drawn as NoData.
+    let is_class = |l: L| l != L::NODATA && l.index() < n;
+    let nodata = legend || labels.iter().any(|&l| !is_class(l));
+    let entries = n + usize::from(nodata);
+    if entries > 256 {
+        return Err(invalid_input(format!(
+            "{entries} palette entries do not fit in an indexed PNG (at most 256)"
+        )));
+    }
+    let (gap, bar) = if legend { legend_size(labels.cols) } else { (0, 0) };
+    let width = labels.cols + gap + bar;
+    if labels.data.len() != labels.rows * labels.cols {
+        return Err(buffer_mismatch());
+    }
+    let indices: Vec<u8> = (0..labels.rows)
+        .flat_map(|i| (0..width).map(move |j| (i, j)))
+        .map(|(i, j)| {
+            if j < labels.cols {
+                let l = labels[i][j];
+                if is_class(l) { l.index() as u8 } else { n as u8 }
+            } else if j >= labels.cols + gap && n > 0 {
+                // Equal swatches, class 0 at the bottom.
+                ((labels.rows - 1 - i) * n / labels.rows) as u8
+            } else {
+                n as u8
+            }
+        })
+        .collect();
+
+    let mut rgb: Vec<u8> = colours.concat();
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        width as u32,
+        labels.rows as u32,
+    );
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    if nodata {
+        rgb.extend_from_slice(&[0, 0, 0]);
+        let mut alpha = vec![255u8; n];
+        alpha.push(0);
+        encoder.set_trns(alpha);
+    }
+    encoder.set_palette(rgb);
+    let png_err = |e: png::EncodingError| std::io::Error::other(e.to_string());
+    let mut writer = encoder.write_header().map_err(png_err)?;
+    writer.write_image_data(&indices).map_err(png_err)?;
+    writer.finish().map_err(png_err)
+}
+
+/// Reads a PNG such as one written by [`write_to_png_grayscale`] as a grid of
+/// gray levels in [0, 1].
+///
+/// 16-bit images keep their full precision. Colour images are converted to
+/// luminance, so a [`write_to_png`] terrain image does not read back as its
+/// values. Fully transparent pixels become NoData (`NaN`).
+pub fn read_from_png(path: &str) -> Result<Grid> {
+    use image::DynamicImage::*;
+    let img = image::open(path).map_err(|e| std::io::Error::other(e.to_string()))?;
+    let (rows, cols) = (img.height() as usize, img.width() as usize);
+    let data = match img {
+        ImageLuma16(_) | ImageLumaA16(_) | ImageRgb16(_) | ImageRgba16(_) => img
+            .to_luma_alpha16()
+            .pixels()
+            .map(|p| if p[1] == 0 { f64::NAN } else { f64::from(p[0]) / 65535.0 })
+            .collect(),
+        _ => img
+            .to_luma_alpha8()
+            .pixels()
+            .map(|p| if p[1] == 0 { f64::NAN } else { f64::from(p[0]) / 255.0 })
+            .collect(),
+    };
+    Ok(Grid { data, rows, cols })
+}
+
+fn invalid_input(msg: impl ToString) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, msg.to_string())
+}
@@ -0,0 +1,100 @@
+use serde_json::{Map, Value as Json};
+
+use crate::error::{NlmError, Result};
+use crate::grid::Grid;
+use crate::pipeline::Pipeline;
+
+/// How an exported grid was made: the crate version and a recipe that
+/// regenerates it.
+///
+/// The recipe is a [`Pipeline`] without outputs. A grid from the CLI is a
+/// one-layer pipeline whose steps are the post-processing options; a grid from
+/// `nlmrs run` records the whole pipeline and which layer was written. Layers
+/// always carry a seed, so [`reproduce`](Metadata::reproduce) is deterministic.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metadata {
+    /// `nlmrs` version that wrote the grid. Other versions may produce different values.
+    pub version: String,
+    /// Random stream the seeds were drawn from ([`crate::RNG`]). Seeds only
+    /// reproduce the grid under the same stream.
+    pub rng: String,
+    /// Layer of `pipeline` that was written.
+    pub layer: String,
+    pub pipeline: Pipeline,
+}
+
+impl Metadata {
+    /// Metadata for `layer` of `pipeline`, stamped with the current crate
+    /// version and random stream.
+    pub fn new(pipeline: Pipeline, layer: &str) -> Self {
+        Metadata {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            rng: crate::RNG.to_string(),
+            layer: layer.to_string(),
+            pipeline,
+        }
+    }
+
+    /// Pretty-printed JSON, as written by [`write_metadata`].
+    pub fn to_json(&self) -> String {
+        let mut root = Map::new();
+        root.insert("nlmrs_version".into(), self.version.as_str().into());
+        root.insert("rng".into(), self.rng.as_str().into());
+        root.insert("layer".into(), self.layer.as_str().into());
+        root.insert("pipeline".into(), self.pipeline.to_value());
+        serde_json::to_string_pretty(&root).expect("JSON values always serialise")
+    }
+
+    /// Parses the JSON written by [`to_json`](Metadata::to_json).
+    pub fn from_json(source: &str) -> Result<Self> {
+        let invalid = |reason: &str| NlmError::Config(format!("metadata: {reason}"));
+        let json: Json = serde_json::from_str(source).map_err(|e| invalid(&e.to_string()))?;
+        let field = |key: &str| {
+            json.get(key)
+                .and_then(Json::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| invalid(&format!("missing '{key}'")))
+        };
+        let pipeline = json
+            .get("pipeline")
+            .ok_or_else(|| invalid("missing 'pipeline'"))?;
+        Ok(Metadata {
+            version: field("nlmrs_version")?,
+            rng: field("rng")?,
+            layer: field("layer")?,
+            pipeline: Pipeline::from_value(pipeline)?,
+        })
+    }
+
+    /// Regenerates the grid by running the recipe.
+    ///
+    /// Any `like` template and mask files it names must still exist.
+    pub fn reproduce(&self) -> Result<Grid> {
+        let mut layers = self.pipeline.run()?;
+        layers
+            .remove(&self.layer)
+            .ok_or_else(|| NlmError::Config(format!("metadata: unknown layer '{}'", self.layer)))
+    }
+}
+
+/// Path of the metadata sidecar for an exported file: `path` with `.json` appended.
+pub fn sidecar_path(path: &str) -> String {
+    format!("{path}.json")
+}
+
+/// Writes `metadata` to the sidecar of the exported file at `path`.
+pub fn write_metadata(path: &str, metadata: &Metadata) -> std::io::Result<()> {
+    std::fs::write(sidecar_path(path), metadata.to_json())
+}
+
+/// Reads the metadata for `path`, which may be the exported file (its sidecar
+/// is read) or the sidecar itself.
+pub fn read_metadata(path: &str) -> Result<Metadata> {
+    let sidecar = sidecar_path(path);
+    let path = if std::path::Path::new(&sidecar).exists() {
+        &sidecar
+    } else {
+        path
+    };
+    Metadata::from_json(&std::fs::read_to_string(path)?)
+}
@@ -1,7 +1,222 @@
+mod colormap;
+mod geotiff;
 mod image;
+mod metadata;
+mod npy;
 mod text;
 
-pub use image::{write_to_png, write_to_png_grayscale, write_to_tiff};
+pub use geotiff::{
+    read_from_geotiff, read_from_tiff, write_labels_to_geotiff, write_to_geotiff,
+    TiffSampleFormat,
+};
+pub use colormap::{Colormap, COLORMAPS};
+pub use image::{
+    read_from_png, write_labels_to_png, write_to_png, write_to_png_colormap, write_to_png_grayscale,
+    write_to_tiff, TIFF_NODATA,
+};
+pub use metadata::{read_metadata, sidecar_path, write_metadata, Metadata};
+pub use npy::{
+    read_from_npy, read_from_npz, write_labels_to_npy, write_to_npy, write_to_npz,
+};
 pub use text::{
-    read_from_ascii_grid, read_from_csv, write_to_ascii_grid, write_to_csv, write_to_json,
+    read_from_ascii_grid, read_from_ascii_grid_geo, read_from_csv, read_from_json,
+    write_labels_to_ascii_grid,
+    write_to_ascii_grid, write_to_ascii_grid_geo, write_to_csv, write_to_json, ASCII_NODATA,
 };
+
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Result};
+
+use crate::grid::{Element, GeoGrid, GeoTransform, Grid};
+use crate::labels::{Label, LabelGrid};
+use crate::tiled::Window;
+
+/// Choices for [`write`] where a file extension allows more than one encoding.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WriteOptions {
+    /// Write `.png` output in grayscale instead of `colormap`.
+    pub grayscale: bool,
+    /// Colours of `.png` output; terrain by default.
+    pub colormap: Colormap,
+    /// Draw a colour bar, or class swatches for `labels`, beside `.png` output.
+    pub legend: bool,
+    /// Write `.tif` output as a float GeoTIFF with this sample type; `None`
+    /// writes a 16-bit grayscale TIFF.
+    pub tiff_format: Option<TiffSampleFormat>,
+    /// Write the grid's distinct values as integer class labels with
+    /// [`write_labels`], in the narrowest of `u8`, `u16` and `u32` that holds
+    /// them. Overrides `grayscale` and `tiff_format`.
+    pub labels: bool,
+}
+
+/// Writes `geo` in the format implied by the extension of `path`: `.csv`,
+/// `.json`, `.asc`, `.tif`/`.tiff`, `.npy`, `.npz` (as a single array named
+/// `arr_0`, like `numpy.savez`), or PNG for anything else.
+///
+/// Georeferencing is kept by `.asc` and float `.tif` output and dropped elsewhere.
+pub fn write<T: Element>(geo: &GeoGrid<T>, path: &str, options: &WriteOptions) -> Result<()> {
+    let grid = &geo.grid;
+    if options.labels {
+        let invalid = |e: crate::NlmError| Error::new(ErrorKind::InvalidInput, e.to_string());
+        let labels = LabelGrid::<u32>::from_grid(grid).map_err(invalid)?;
+        let (n, t) = (labels.classes.len(), &geo.transform);
+        return if n <= u8::NODATA.index() {
+            write_labels(&labels.try_cast::<u8>().map_err(invalid)?, t, path, options)
+        } else if n <= u16::NODATA.index() {
+            write_labels(&labels.try_cast::<u16>().map_err(invalid)?, t, path, options)
+        } else {
+            write_labels(&labels, t, path, options)
+        };
+    }
+    match path.rsplit('.').next().unwrap_or("png") {
+        "csv" => write_to_csv(grid, path),
+        "json" => write_to_json(grid, path),
+        "asc" => write_to_ascii_grid_geo(geo, path),
+        "npy" => write_to_npy(grid, path),
+        "npz" => write_to_npz(&[("arr_0", grid)], path),
+        "tif" | "tiff" => match options.tiff_format {
+            Some(format) => write_to_geotiff(geo, path, format),
+            None => write_to_tiff(grid, path),
+        },
+        _ if options.grayscale => write_to_png_grayscale(grid, path),
+        _ => write_to_png_colormap(grid, path, &options.colormap, options.legend),
+    }
+}
+
+/// Reads a grid from any format [`write`] produces, telling them apart by the
+/// file's contents rather than its extension.
+///
+/// PNG files are read with [`read_from_png`], `.npy` files with
+/// [`read_from_npy`], `.npz` archives holding one array with
+/// [`read_from_npz`], JSON arrays with
+/// [`read_from_json`], ESRI ASCII grids with [`read_from_ascii_grid_geo`] and
+/// anything else that starts with a number with [`read_from_csv`]. TIFF files
+/// are read with [`read_from_geotiff`] if they carry georeferencing, which
+/// keeps integer samples such as class labels as stored, and with
+/// [`read_from_tiff`] otherwise. Formats without georeferencing get the
+/// default transform.
+pub fn read(path: &str) -> Result<GeoGrid> {
+    let mut head = Vec::with_capacity(64);
+    File::open(path)?.take(64).read_to_end(&mut head)?;
+    let plain = |grid| GeoGrid { grid, transform: GeoTransform::default() };
+    if head.starts_with(b"\x89PNG") {
+        return read_from_png(path).map(plain);
+    }
+    if head.starts_with(b"\x93NUMPY") {
+        return read_from_npy(path).map(plain);
+    }
+    if head.starts_with(b"PK\x03\x04") {
+        let mut arrays = read_from_npz(path)?;
+        return match arrays.len() {
+            1 => Ok(plain(arrays.remove(0).1)),
+            n => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{path}: holds {n} arrays; read them with read_from_npz"),
+            )),
+        };
+    }
+    if [b"II*\0", b"MM\0*", b"II+\0", b"MM\0+"].iter().any(|m| head.starts_with(*m)) {
+        return geotiff::read_tiff_any(path);
+    }
+    let text = head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(&head);
+    let start = text.iter().position(|b| !b.is_ascii_whitespace());
+    let word: String = text[start.unwrap_or(text.len())..]
+        .iter()
+        .take_while(|b| b.is_ascii_alphabetic())
+        .map(|&b| char::from(b).to_ascii_lowercase())
+        .collect();
+    const ASCII_KEYS: [&str; 8] =
+        ["ncols", "nrows", "xllcorner", "yllcorner", "xllcenter", "yllcenter", "cellsize", "nodata"];
+    match start.map(|i| text[i]) {
+        Some(b'[') => read_from_json(path).map(plain),
+        Some(_) if ASCII_KEYS.contains(&word.as_str()) => read_from_ascii_grid_geo(path),
+        // A CSV row may start with `NaN` or `inf` as written for such cells.
+        Some(c) if c.is_ascii_digit() || b"-+.".contains(&c) || word == "nan" || word == "inf" => {
+            read_from_csv(path).map(plain)
+        }
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("{path}: not a PNG, TIFF, NumPy, JSON, ESRI ASCII grid or CSV file"),
+        )),
+    }
+}
+
+/// Writes `labels` in the format implied by the extension of `path`: an
+/// integer `.asc`, `.tif`/`.tiff` or `.npy`, or an indexed PNG for anything
+/// else except `.csv`, `.json` and `.npz`, which are rejected.
+///
+/// PNG classes are coloured by [`Colormap::class_colors`] of
+/// `options.colormap`, with swatches beside them if `options.legend` is set.
+/// The other options do not apply to labels.
+pub fn write_labels<L: Label>(
+    labels: &LabelGrid<L>,
+    transform: &GeoTransform,
+    path: &str,
+    options: &WriteOptions,
+) -> Result<()> {
+    match path.rsplit('.').next().unwrap_or("png") {
+        "asc" => write_labels_to_ascii_grid(labels, transform, path),
+        "tif" | "tiff" => write_labels_to_geotiff(labels, transform, path),
+        "npy" => write_labels_to_npy(labels, path),
+        "csv" | "json" | "npz" => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{path}: label output must be .png, .asc, .tif or .npy"),
+        )),
+        _ => {
+            let palette = options.colormap.class_colors(&labels.classes);
+            write_labels_to_png(labels, path, Some(&palette), options.legend)
+        }
+    }
+}
+
+/// Choices for [`write_tiled`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileOptions {
+    /// Side of each tile in cells, a multiple of 16 as TIFF requires. About
+    /// `tile_size` full rows of the grid are in memory at once for `.asc`
+    /// output, and one tile for `.tif`.
+    pub tile_size: usize,
+    /// Sample type of `.tif` output.
+    pub tiff_format: TiffSampleFormat,
+}
+
+impl Default for TileOptions {
+    /// 256 x 256 tiles of 32-bit floats.
+    fn default() -> Self {
+        TileOptions { tile_size: 256, tiff_format: TiffSampleFormat::Float32 }
+    }
+}
+
+/// Streams a (rows x cols) grid to disk without holding it in memory, asking
+/// `tile` for one [`Window`] of cells at a time.
+///
+/// `.asc` output is written in full-width bands of `tile_size` rows, and
+/// `.tif`/`.tiff` output as a tiled float GeoTIFF (BigTIFF above 4 GiB) that
+/// [`read_from_geotiff`] and GDAL read like any other. Other extensions are
+/// rejected.
+pub fn write_tiled(
+    path: &str,
+    rows: usize,
+    cols: usize,
+    transform: &GeoTransform,
+    options: TileOptions,
+    tile: impl FnMut(Window) -> Grid,
+) -> Result<()> {
+    let size = options.tile_size;
+    if size == 0 || !size.is_multiple_of(16) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("tile size {size} is not a positive multiple of 16"),
+        ));
+    }
+    match path.rsplit('.').next().unwrap_or("") {
+        "asc" => text::write_ascii_grid_bands(path, rows, cols, transform, size, tile),
+        "tif" | "tiff" => {
+            geotiff::write_geotiff_tiles(path, rows, cols, transform, options.tiff_format, size, tile)
+        }
+        _ => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("{path}: tiled output must be .asc or .tif"),
+        )),
+    }
+}
@@ -0,0 +1,255 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Result, Seek, Write};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::grid::{Element, Grid};
+use crate::labels::{Label, LabelGrid};
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+fn invalid_data(msg: impl ToString) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// Writes the grid as a NumPy `.npy` file of shape (rows, cols), in `float64`
+/// for a `Grid<f64>` and `float32` for a `Grid<f32>`, for `numpy.load(path)`.
+///
+/// Values are stored exactly and NoData cells stay `NaN`.
+pub fn write_to_npy<T: Element>(grid: &Grid<T>, path: &str) -> Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write_grid(&mut w, grid)?;
+    w.flush()
+}
+
+/// Writes a label grid as an integer `.npy` file of `uint8`, `uint16` or
+/// `uint32` like `L`, with NoData cells as the type's maximum.
+pub fn write_labels_to_npy<L: Label>(labels: &LabelGrid<L>, path: &str) -> Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    let shape = (labels.rows, labels.cols);
+    let values = labels.iter().map(|l| l.index());
+    match L::BITS {
+        8 => write_array(&mut w, "|u1", shape, values.map(|l| [l as u8])),
+        16 => write_array(&mut w, "<u2", shape, values.map(|l| (l as u16).to_le_bytes())),
+        _ => write_array(&mut w, "<u4", shape, values.map(|l| (l as u32).to_le_bytes())),
+    }?;
+    w.flush()
+}
+
+/// Writes `arrays` as an uncompressed NumPy `.npz` archive, as `numpy.savez`
+/// does, with one `.npy` member per `(name, grid)` pair so that
+/// `numpy.load(path)[name]` returns that grid.
+///
+/// Suited to stacks of replicates of one size. Fails if a name repeats.
+pub fn write_to_npz<T: Element>(arrays: &[(&str, &Grid<T>)], path: &str) -> Result<()> {
+    let mut zip = ZipWriter::new(BufWriter::new(File::create(path)?));
+    for (name, grid) in arrays {
+        let bytes = grid.data.len() as u64 * std::mem::size_of::<T>() as u64;
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(bytes >= u32::MAX as u64 - 1024);
+        zip.start_file(format!("{name}.npy"), options)?;
+        write_grid(&mut zip, grid)?;
+    }
+    zip.finish()?.flush()
+}
+
+fn write_grid<T: Element>(w: &mut impl Write, grid: &Grid<T>) -> Result<()> {
+    let shape = (grid.rows, grid.cols);
+    let values = grid.iter().map(|v| v.to_f64());
+    if std::mem::size_of::<T>() == 4 {
+        write_array(w, "<f4", shape, values.map(|v| (v as f32).to_le_bytes()))
+    } else {
+        write_array(w, "<f8", shape, values.map(f64::to_le_bytes))
+    }
+}
+
+/// Writes a version 1.0 `.npy` header followed by the C-order samples.
+fn write_array<const N: usize>(
+    w: &mut impl Write,
+    descr: &str,
+    (rows, cols): (usize, usize),
+    values: impl Iterator<Item = [u8; N]>,
+) -> Result<()> {
+    let mut header =
+        format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': ({rows}, {cols}), }}");
+    // Magic, version and length take 10 bytes; the whole preamble ends in a
+    // newline on a 64-byte boundary so the data is aligned.
+    let len = (10 + header.len() + 1).next_multiple_of(64) - 10;
+    header.extend(std::iter::repeat_n(' ', len - header.len() - 1));
+    header.push('\n');
+    w.write_all(MAGIC)?;
+    w.write_all(&[1, 0])?;
+    let len = u16::try_from(len).map_err(|_| invalid_data("npy header is too long"))?;
+    w.write_all(&len.to_le_bytes())?;
+    w.write_all(header.as_bytes())?;
+    for v in values {
+        w.write_all(&v)?;
+    }
+    Ok(())
+}
+
+/// Reads a 2D array from a `.npy` file such as one written by [`write_to_npy`]
+/// or `numpy.save`.
+///
+/// Float, integer and boolean arrays of either byte order and either C or
+/// Fortran order are converted to `f64`. `NaN` cells are NoData; integer
+/// values, including label NoData, are kept as stored.
+pub fn read_from_npy(path: &str) -> Result<Grid> {
+    read_array(&mut BufReader::new(File::open(path)?))
+}
+
+/// Reads every array of a `.npz` archive written by [`write_to_npz`],
+/// `numpy.savez` or `numpy.savez_compressed`, in archive order, as
+/// `(name, grid)` pairs. Each array must be 2D, as for [`read_from_npy`].
+pub fn read_from_npz(path: &str) -> Result<Vec<(String, Grid)>> {
+    read_npz(BufReader::new(File::open(path)?))
+}
+
+fn read_npz(r: impl Read + Seek) -> Result<Vec<(String, Grid)>> {
+    let mut zip = ZipArchive::new(r)?;
+    let mut arrays = Vec::with_capacity(zip.len());
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        let Some(name) = file.name().strip_suffix(".npy").map(str::to_string) else {
+            continue;
+        };
+        let grid = read_array(&mut file).map_err(|e| invalid_data(format!("{name}: {e}")))?;
+        arrays.push((name, grid));
+    }
+    Ok(arrays)
+}
+
+fn read_array(r: &mut impl Read) -> Result<Grid> {
+    let mut preamble = [0u8; 8];
+    r.read_exact(&mut preamble)?;
+    if &preamble[..6] != MAGIC {
+        return Err(invalid_data("not a .npy file"));
+    }
+    let len = match preamble[6] {
+        1 => {
+            let mut len = [0u8; 2];
+            r.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        2 | 3 => {
+            let mut len = [0u8; 4];
+            r.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        v => return Err(invalid_data(format!("unsupported .npy version {v}"))),
+    };
+    let mut header = vec![0u8; len];
+    r.read_exact(&mut header)?;
+    let header = String::from_utf8_lossy(&header);
+
+    let descr = field(&header, "descr")?
+        .strip_prefix('\'')
+        .and_then(|s| s.split('\'').next())
+        .ok_or_else(|| invalid_data("malformed 'descr' in .npy header"))?;
+    let fortran = field(&header, "fortran_order")?.starts_with("True");
+    let dims: Vec<usize> = field(&header, "shape")?
+        .strip_prefix('(')
+        .and_then(|s| s.split(')').next())
+        .ok_or_else(|| invalid_data("malformed 'shape' in .npy header"))?
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| d.parse::<usize>().map_err(invalid_data))
+        .collect::<Result<_>>()?;
+    let [rows, cols] = dims[..] else {
+        return Err(invalid_data(format!("expected a 2D array, found shape {dims:?}")));
+    };
+
+    let (order, kind) = match descr.as_bytes() {
+        [o @ (b'<' | b'>' | b'|' | b'='), rest @ ..] => (*o, rest),
+        rest => (b'=', rest),
+    };
+    let big = order == b'>' || (order == b'=' && cfg!(target_endian = "big"));
+    macro_rules! num {
+        ($t:ty, $b:expr, $big:expr) => {{
+            let bytes = $b.try_into().expect("chunk matches the sample size");
+            (if $big { <$t>::from_be_bytes(bytes) } else { <$t>::from_le_bytes(bytes) }) as f64
+        }};
+    }
+    // Sample size in bytes and its conversion given the byte order.
+    type Convert = fn(&[u8], bool) -> f64;
+    let (size, convert): (usize, Convert) = match kind {
+        b"f8" => (8, |b, big| num!(f64, b, big)),
+        b"f4" => (4, |b, big| num!(f32, b, big)),
+        b"i8" => (8, |b, big| num!(i64, b, big)),
+        b"i4" => (4, |b, big| num!(i32, b, big)),
+        b"i2" => (2, |b, big| num!(i16, b, big)),
+        b"i1" => (1, |b, _| b[0] as i8 as f64),
+        b"u8" => (8, |b, big| num!(u64, b, big)),
+        b"u4" => (4, |b, big| num!(u32, b, big)),
+        b"u2" => (2, |b, big| num!(u16, b, big)),
+        b"u1" | b"b1" => (1, |b, _| b[0] as f64),
+        _ => return Err(invalid_data(format!("unsupported .npy dtype '{descr}'"))),
+    };
+
+    let n = rows
+        .checked_mul(cols)
+        .and_then(|n| n.checked_mul(size))
+        .ok_or_else(|| invalid_data("array is too large"))?;
+    let mut bytes = vec![0u8; n];
+    r.read_exact(&mut bytes)?;
+    let values: Vec<f64> = bytes.chunks_exact(size).map(|b| convert(b, big)).collect();
+    let data = if fortran {
+        (0..rows * cols).map(|k| values[(k % cols) * rows + k / cols]).collect()
+    } else {
+        values
+    };
+    Ok(Grid { data, rows, cols })
+}
+
+/// The text following `'key':` in a `.npy` header dictionary.
+fn field<'a>(header: &'a str, key: &str) -> Result<&'a str> {
+    let quoted = format!("'{key}':");
+    header
+        .find(&quoted)
+        .map(|i| header[i + quoted.len()..].trim_start())
+        .ok_or_else(|| invalid_data(format!("missing '{key}' in .npy header")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_header_is_aligned() {
+        let grid = crate::random(3, 5, Some(1));
+        let mut bytes = Vec::new();
+        write_grid(&mut bytes, &grid).unwrap();
+        let preamble = bytes.len() - 15 * 8;
+        assert_eq!(preamble % 64, 0);
+        assert_eq!(bytes[preamble - 1], b'\n');
+        assert_eq!(read_array(&mut Cursor::new(bytes)).unwrap(), grid);
+    }
+
+    #[test]
+    fn test_reads_fortran_order_and_big_endian() {
+        // numpy.array([[1, 2, 3], [4, 5, 6]], dtype='>i2', order='F')
+        let header = "{'descr': '>i2', 'fortran_order': True, 'shape': (2, 3), }";
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([1, 0, header.len() as u8, 0]);
+        bytes.extend(header.as_bytes());
+        for v in [1i16, 4, 2, 5, 3, 6] {
+            bytes.extend(v.to_be_bytes());
+        }
+        let grid = read_array(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(grid.data, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_rejects_other_shapes() {
+        let header = "{'descr': '<f8', 'fortran_order': False, 'shape': (4,), }";
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([1, 0, header.len() as u8, 0]);
+        bytes.extend(header.as_bytes());
+        bytes.extend([0u8; 32]);
+        assert!(read_array(&mut Cursor::new(bytes)).is_err());
+    }
+}
@@ -1,11 +1,18 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Result, Write};
+use std::path::Path;
 
 use csv::Writer;
 
-use crate::grid::Grid;
+use crate::grid::{Element, GeoGrid, GeoTransform, Grid};
+use crate::labels::{Label, LabelGrid};
+use crate::tiled::Window;
 
-pub fn write_to_csv(grid: &Grid, path: &str) -> Result<()> {
+/// Sentinel written for NoData cells by [`write_to_ascii_grid`].
+pub const ASCII_NODATA: f64 = -9999.0;
+
+pub fn write_to_csv<T: Element>(grid: &Grid<T>, path: &str) -> Result<()> {
     let mut wtr = Writer::from_path(path)?;
     for i in 0..grid.rows {
         let _ = wtr.serialize(&grid[i]);
@@ -13,11 +20,11 @@ pub fn write_to_csv(grid: &Grid, path: &str) -> Result<()> {
     Ok(())
 }
 
-pub fn write_to_json(grid: &Grid, path: &str) -> Result<()> {
+pub fn write_to_json<T: Element>(grid: &Grid<T>, path: &str) -> Result<()> {
     let file = File::create(path)?;
     let mut writer = BufWriter::new(file);
     // Serialise as a 2D array for compatibility
-    let rows: Vec<&[f64]> = (0..grid.rows).map(|i| &grid[i]).collect();
+    let rows: Vec<&[T]> = (0..grid.rows).map(|i| &grid[i]).collect();
     serde_json::to_writer(&mut writer, &rows)?;
     writer.flush()?;
     Ok(())
@@ -27,27 +34,129 @@ pub fn write_to_json(grid: &Grid, path: &str) -> Result<()> {
 ///
 /// The format is widely supported by GIS and ecology software (R `terra`/`raster`,
 /// QGIS, ArcGIS). No spatial reference is set — `xllcorner`, `yllcorner` default
-/// to 0.0 and `cellsize` to 1.0.
-pub fn write_to_ascii_grid(grid: &Grid, path: &str) -> Result<()> {
+/// to 0.0 and `cellsize` to 1.0; use [`write_to_ascii_grid_geo`] to place the grid
+/// on a map. NoData cells are written as [`ASCII_NODATA`].
+pub fn write_to_ascii_grid<T: Element>(grid: &Grid<T>, path: &str) -> Result<()> {
+    write_ascii_grid(grid, &GeoTransform::default(), path)
+}
+
+/// Writes a georeferenced grid as an ESRI ASCII Grid (`.asc`).
+///
+/// The header carries the transform's lower-left corner and cell size. The
+/// format has no field for a CRS, so a WKT CRS is written to a `.prj` sidecar
+/// next to `path`, as GDAL does. An authority code such as `"EPSG:4326"` is
+/// not valid `.prj` content and is not written; use
+/// [`write_to_geotiff`](super::write_to_geotiff) to keep it.
+pub fn write_to_ascii_grid_geo<T: Element>(geo: &GeoGrid<T>, path: &str) -> Result<()> {
+    write_ascii_grid(&geo.grid, &geo.transform, path)?;
+    write_prj(path, &geo.transform)
+}
+
+/// Writes the transform's CRS to a `.prj` sidecar next to `path` if it is WKT.
+fn write_prj(path: &str, transform: &GeoTransform) -> Result<()> {
+    match &transform.crs {
+        Some(crs) if is_wkt(crs) => std::fs::write(Path::new(path).with_extension("prj"), crs),
+        _ => Ok(()),
+    }
+}
+
+/// Whether `crs` looks like WKT, i.e. opens with a keyword such as `PROJCS`
+/// or `GEOGCRS` followed by `[`, rather than an authority code.
+fn is_wkt(crs: &str) -> bool {
+    let crs = crs.trim_start();
+    match crs.find('[') {
+        Some(i) => i > 0 && crs[..i].bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_'),
+        None => false,
+    }
+}
+
+fn write_ascii_grid<T: Element>(grid: &Grid<T>, transform: &GeoTransform, path: &str) -> Result<()> {
     let file = File::create(path)?;
     let mut w = BufWriter::new(file);
-    writeln!(w, "ncols         {}", grid.cols)?;
-    writeln!(w, "nrows         {}", grid.rows)?;
-    writeln!(w, "xllcorner     0.0")?;
-    writeln!(w, "yllcorner     0.0")?;
-    writeln!(w, "cellsize      1.0")?;
-    writeln!(w, "NODATA_value  -9999")?;
+    write_ascii_header(&mut w, grid.rows, grid.cols, transform)?;
     for i in 0..grid.rows {
-        let row = &grid[i];
-        for (j, &v) in row.iter().enumerate() {
+        write_ascii_row(&mut w, &grid[i])?;
+    }
+    w.flush()
+}
+
+/// Writes a label grid as an ESRI ASCII Grid of integer class labels.
+///
+/// The header and `.prj` sidecar are written as by [`write_to_ascii_grid_geo`],
+/// and NoData cells as [`ASCII_NODATA`].
+pub fn write_labels_to_ascii_grid<L: Label>(
+    labels: &LabelGrid<L>,
+    transform: &GeoTransform,
+    path: &str,
+) -> Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write_ascii_header(&mut w, labels.rows, labels.cols, transform)?;
+    for i in 0..labels.rows {
+        for (j, &l) in labels[i].iter().enumerate() {
             if j > 0 {
                 w.write_all(b" ")?;
             }
-            write!(w, "{:.6}", v)?;
+            if l == L::NODATA {
+                write!(w, "{ASCII_NODATA}")?;
+            } else {
+                write!(w, "{l}")?;
+            }
         }
         w.write_all(b"\n")?;
     }
-    w.flush()
+    w.flush()?;
+    write_prj(path, transform)
+}
+
+/// Streams a (rows x cols) grid to an ESRI ASCII Grid `band_rows` rows at a
+/// time, asking `band` for each full-width window in turn.
+pub(crate) fn write_ascii_grid_bands(
+    path: &str,
+    rows: usize,
+    cols: usize,
+    transform: &GeoTransform,
+    band_rows: usize,
+    mut band: impl FnMut(Window) -> Grid,
+) -> Result<()> {
+    let mut w = BufWriter::new(File::create(path)?);
+    write_ascii_header(&mut w, rows, cols, transform)?;
+    for row in (0..rows).step_by(band_rows) {
+        let window = Window { row, col: 0, rows: band_rows.min(rows - row), cols };
+        let grid = band(window);
+        for i in 0..grid.rows {
+            write_ascii_row(&mut w, &grid[i])?;
+        }
+    }
+    w.flush()?;
+    write_prj(path, transform)
+}
+
+fn write_ascii_header(
+    w: &mut impl Write,
+    rows: usize,
+    cols: usize,
+    transform: &GeoTransform,
+) -> Result<()> {
+    writeln!(w, "ncols         {}", cols)?;
+    writeln!(w, "nrows         {}", rows)?;
+    writeln!(w, "xllcorner     {:?}", transform.x_min)?;
+    writeln!(w, "yllcorner     {:?}", transform.y_min)?;
+    writeln!(w, "cellsize      {:?}", transform.cell_size)?;
+    writeln!(w, "NODATA_value  {ASCII_NODATA}")
+}
+
+fn write_ascii_row<T: Element>(w: &mut impl Write, row: &[T]) -> Result<()> {
+    for (j, &v) in row.iter().enumerate() {
+        if j > 0 {
+            w.write_all(b" ")?;
+        }
+        if v.is_nan() {
+            write!(w, "{ASCII_NODATA}")?;
+        } else {
+            write!(w, "{:.6}", v)?;
+        }
+    }
+    w.write_all(b"\n")
 }
 
 /// Reads a grid from a CSV file written by [`write_to_csv`].
@@ -58,7 +167,7 @@ pub fn read_from_csv(path: &str) -> Result<Grid> {
     let mut rdr = csv::ReaderBuilder::new()
         .has_headers(false)
         .from_path(path)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+        .map_err(|e| std::io::Error::other(e.to_string()))?;
 
     let mut data: Vec<f64> = Vec::new();
     let mut cols = 0usize;
@@ -86,67 +195,128 @@ pub fn read_from_csv(path: &str) -> Result<Grid> {
     Ok(Grid { data, rows, cols })
 }
 
-/// Parse one "KEY  VALUE" header line from an ESRI ASCII Grid; returns the value string.
-fn parse_asc_header(line: &str, expected_key: &str) -> Result<String> {
-    let mut parts = line.split_whitespace();
-    let key = parts.next().unwrap_or("");
-    if !key.eq_ignore_ascii_case(expected_key) {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("expected header '{expected_key}', found '{key}'"),
-        ));
+/// Reads a grid from a JSON file written by [`write_to_json`].
+///
+/// The file must hold an array of equal-length rows of numbers; `null` cells,
+/// which is how NoData (`NaN`) is written, are read back as `NaN`.
+pub fn read_from_json(path: &str) -> Result<Grid> {
+    let file = File::open(path)?;
+    let rows: Vec<Vec<Option<f64>>> =
+        serde_json::from_reader(BufReader::new(file)).map_err(invalid_data)?;
+    let cols = rows.first().map_or(0, Vec::len);
+    let mut data = Vec::with_capacity(rows.len() * cols);
+    for (i, row) in rows.iter().enumerate() {
+        if row.len() != cols {
+            return Err(invalid_data(format!(
+                "row {} has {} columns, expected {}",
+                i + 1,
+                row.len(),
+                cols
+            )));
+        }
+        data.extend(row.iter().map(|v| v.unwrap_or(f64::NAN)));
     }
-    parts.next().map(|s| s.to_string()).ok_or_else(|| {
-        std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("missing value for '{expected_key}'"),
-        )
-    })
+    Ok(Grid { data, rows: rows.len(), cols })
+}
+
+fn invalid_data(msg: impl ToString) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg.to_string())
 }
 
 /// Reads a grid from an ESRI ASCII Grid (`.asc`) file written by [`write_to_ascii_grid`].
 ///
-/// Spatial metadata (xllcorner, yllcorner, cellsize, NODATA_value) is parsed and
-/// discarded — only the grid dimensions and cell values are returned.
+/// Cells equal to the optional `NODATA_value` header are read as NoData (`NaN`),
+/// so the result can be used directly as a mask with [`apply_mask`](crate::apply_mask).
+/// Spatial metadata is discarded; use [`read_from_ascii_grid_geo`] to keep it.
 pub fn read_from_ascii_grid(path: &str) -> Result<Grid> {
+    read_ascii_grid(path).map(|(grid, _)| grid)
+}
+
+/// Reads a grid and its georeferencing from an ESRI ASCII Grid (`.asc`) file.
+///
+/// Both the `xllcorner`/`yllcorner` and `xllcenter`/`yllcenter` header forms
+/// are accepted; the transform always stores the lower-left corner. If a
+/// `.prj` sidecar exists next to `path` its contents become the CRS.
+pub fn read_from_ascii_grid_geo(path: &str) -> Result<GeoGrid> {
+    let (grid, mut transform) = read_ascii_grid(path)?;
+    let prj = Path::new(path).with_extension("prj");
+    if prj.is_file() {
+        transform.crs = Some(std::fs::read_to_string(prj)?.trim().to_string());
+    }
+    Ok(GeoGrid { grid, transform })
+}
+
+fn read_ascii_grid(path: &str) -> Result<(Grid, GeoTransform)> {
     let file = File::open(path)?;
-    let mut lines = BufReader::new(file).lines();
+    let mut lines = BufReader::new(file).lines().peekable();
 
-    let mut next_line = |key: &str| -> Result<String> {
-        let raw = lines
+    // Header lines are "KEY  VALUE" pairs in any order; the first line that
+    // does not start with a known key begins the data. Matching the keys
+    // exactly keeps rows that start with `nan` or `inf` in the data.
+    const KEYS: [&str; 8] = [
+        "ncols",
+        "nrows",
+        "xllcorner",
+        "yllcorner",
+        "xllcenter",
+        "yllcenter",
+        "cellsize",
+        "nodata_value",
+    ];
+    let mut header = HashMap::new();
+    while let Some(Ok(line)) = lines.peek() {
+        let mut parts = line.split_whitespace();
+        let key = match parts.next().map(str::to_ascii_lowercase) {
+            Some(k) if KEYS.contains(&k.as_str()) => k,
+            _ => break,
+        };
+        let value = parts
             .next()
-            .ok_or_else(|| {
-                std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "truncated header")
-            })??;
-        parse_asc_header(&raw, key)
-    };
+            .ok_or_else(|| invalid_data(format!("missing value for '{key}'")))?
+            .to_string();
+        header.insert(key, value);
+        lines.next();
+    }
 
-    let cols = next_line("ncols")?.parse::<usize>().map_err(|e| {
-        std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
-    })?;
-    let rows = next_line("nrows")?.parse::<usize>().map_err(|e| {
-        std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
-    })?;
-    next_line("xllcorner")?;
-    next_line("yllcorner")?;
-    next_line("cellsize")?;
-    next_line("NODATA_value")?;
+    let field = |key: &str| -> Result<&str> {
+        header
+            .get(key)
+            .map(String::as_str)
+            .ok_or_else(|| invalid_data(format!("missing header '{key}'")))
+    };
+    let cols = field("ncols")?.parse::<usize>().map_err(invalid_data)?;
+    let rows = field("nrows")?.parse::<usize>().map_err(invalid_data)?;
+    let cell_size = field("cellsize")?.parse::<f64>().map_err(invalid_data)?;
+    let corner = |axis: &str| -> Result<f64> {
+        match field(&format!("{axis}llcorner")) {
+            Ok(v) => v.parse::<f64>().map_err(invalid_data),
+            Err(_) => Ok(field(&format!("{axis}llcenter"))?
+                .parse::<f64>()
+                .map_err(invalid_data)?
+                - cell_size / 2.0),
+        }
+    };
+    let transform = GeoTransform { x_min: corner("x")?, y_min: corner("y")?, cell_size, crs: None };
+    let nodata = match header.get("nodata_value") {
+        Some(v) => Some(v.parse::<f64>().map_err(invalid_data)?),
+        None => None,
+    };
 
     let mut data = Vec::with_capacity(rows * cols);
     for line in lines {
         for tok in line?.split_whitespace() {
-            data.push(tok.parse::<f64>().map_err(|e| {
-                std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
-            })?);
+            let v = tok.parse::<f64>().map_err(invalid_data)?;
+            data.push(if Some(v) == nodata { f64::NAN } else { v });
         }
     }
 
     if data.len() != rows * cols {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            format!("expected {} values, found {}", rows * cols, data.len()),
-        ));
+        return Err(invalid_data(format!(
+            "expected {} values, found {}",
+            rows * cols,
+            data.len()
+        )));
     }
 
-    Ok(Grid { data, rows, cols })
+    Ok((Grid { data, rows, cols }, transform))
 }
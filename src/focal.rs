@@ -0,0 +1,449 @@
+//! Focal (moving-window) statistics.
+//!
+//! [`focal`] replaces every cell with a statistic of the cells in a window
+//! around it: the mean or median to smooth a surface, the majority to
+//! generalise a classified map, or the Shannon diversity of classes to map
+//! local heterogeneity.
+//!
+//! ```
+//! use nlmrs::focal::{focal, Kernel, Statistic};
+//! use nlmrs::{classify, fbm_noise, Boundary};
+//!
+//! let mut smooth = fbm_noise(100, 100, 4.0, 6, 0.5, 2.0, Some(42));
+//! focal(&mut smooth, Statistic::Mean, &Kernel::Square(2), Boundary::Clamped);
+//!
+//! let mut cover = fbm_noise(100, 100, 4.0, 6, 0.5, 2.0, Some(42));
+//! classify(&mut cover, 5);
+//! focal(&mut cover, Statistic::Majority, &Kernel::Circle(3.0), Boundary::Clamped);
+//! ```
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::error::{self, NlmError};
+use crate::grid::{Boundary, Element, Grid};
+
+/// Statistic computed over each window by [`focal`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Statistic {
+    /// Weighted mean.
+    Mean,
+    /// Weighted median; the mean of the two middle values when they split
+    /// the weight evenly.
+    Median,
+    /// Smallest value.
+    Min,
+    /// Largest value.
+    Max,
+    /// Weighted population standard deviation.
+    StdDev,
+    /// Value with the greatest total weight, the smallest such value on ties.
+    Majority,
+    /// Shannon diversity (natural log) of the distinct values, weighted by
+    /// kernel weight: 0 where the window holds a single class.
+    Diversity,
+}
+
+impl Statistic {
+    /// Every statistic, in declaration order.
+    pub const ALL: [Statistic; 7] = [
+        Statistic::Mean,
+        Statistic::Median,
+        Statistic::Min,
+        Statistic::Max,
+        Statistic::StdDev,
+        Statistic::Majority,
+        Statistic::Diversity,
+    ];
+
+    /// Lower-case name, as used in pipeline files: `"mean"`, `"std_dev"`, ...
+    pub fn name(self) -> &'static str {
+        match self {
+            Statistic::Mean => "mean",
+            Statistic::Median => "median",
+            Statistic::Min => "min",
+            Statistic::Max => "max",
+            Statistic::StdDev => "std_dev",
+            Statistic::Majority => "majority",
+            Statistic::Diversity => "diversity",
+        }
+    }
+
+    /// The statistic called `name`, as returned by [`Statistic::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Statistic::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    /// The statistic of `(value, weight)` pairs, or `NaN` if there are none.
+    /// Reorders `window`.
+    fn of(self, window: &mut [(f64, f64)]) -> f64 {
+        if window.is_empty() {
+            return f64::NAN;
+        }
+        let total: f64 = window.iter().map(|&(_, w)| w).sum();
+        let mean = || window.iter().map(|&(v, w)| v * w).sum::<f64>() / total;
+        match self {
+            Statistic::Mean => mean(),
+            Statistic::StdDev => {
+                let m = mean();
+                let ss: f64 = window.iter().map(|&(v, w)| w * (v - m) * (v - m)).sum();
+                (ss / total).sqrt()
+            }
+            Statistic::Min => window.iter().map(|&(v, _)| v).fold(f64::NAN, f64::min),
+            Statistic::Max => window.iter().map(|&(v, _)| v).fold(f64::NAN, f64::max),
+            Statistic::Median => {
+                window.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let mut cumulative = 0.0;
+                for (k, &(v, w)) in window.iter().enumerate() {
+                    cumulative += w;
+                    if cumulative * 2.0 == total {
+                        return (v + window[k + 1].0) / 2.0;
+                    }
+                    if cumulative * 2.0 > total {
+                        return v;
+                    }
+                }
+                window[window.len() - 1].0
+            }
+            Statistic::Majority | Statistic::Diversity => {
+                window.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let mut classes: Vec<(f64, f64)> = Vec::new();
+                for &(v, w) in window.iter() {
+                    match classes.last_mut() {
+                        Some((last, sum)) if *last == v => *sum += w,
+                        _ => classes.push((v, w)),
+                    }
+                }
+                if self == Statistic::Majority {
+                    classes
+                        .iter()
+                        .fold((f64::NAN, 0.0), |best, &(v, w)| if w > best.1 { (v, w) } else { best })
+                        .0
+                } else {
+                    let mut shannon = 0.0;
+                    for &(_, w) in &classes {
+                        let p = w / total;
+                        shannon -= p * p.ln();
+                    }
+                    shannon
+                }
+            }
+        }
+    }
+}
+
+/// The window of cells around each cell that [`focal`] summarises.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kernel {
+    /// The (2r + 1) x (2r + 1) square of cells within `r` rows and columns.
+    Square(usize),
+    /// The cells whose centres lie within `r` cells of the centre cell's.
+    Circle(f64),
+    /// Per-cell weights centred on the cell, with an odd number of rows and
+    /// columns. Cells with zero weight are left out; the others weight the
+    /// mean, median, standard deviation, majority and diversity.
+    Custom(Grid),
+}
+
+impl Kernel {
+    /// Errors if a circle radius is negative or not finite, or a custom
+    /// kernel has an even side, a negative or non-finite weight, or no
+    /// positive weight.
+    pub fn check(&self) -> error::Result<()> {
+        match self {
+            Kernel::Square(_) => Ok(()),
+            Kernel::Circle(r) if r.is_finite() && *r >= 0.0 => Ok(()),
+            Kernel::Circle(_) => Err(NlmError::invalid("radius", "must be finite and non-negative")),
+            Kernel::Custom(k) if k.rows % 2 == 0 || k.cols % 2 == 0 => Err(NlmError::invalid(
+                "kernel",
+                format!("must have an odd number of rows and columns, got {}x{}", k.rows, k.cols),
+            )),
+            Kernel::Custom(k) if !k.iter().all(|&w| w.is_finite() && w >= 0.0) => {
+                Err(NlmError::invalid("kernel", "weights must be finite and non-negative"))
+            }
+            Kernel::Custom(k) if !k.iter().any(|&w| w > 0.0) => {
+                Err(NlmError::invalid("kernel", "needs at least one positive weight"))
+            }
+            Kernel::Custom(_) => Ok(()),
+        }
+    }
+
+    /// Row and column offsets of the cells in the window, with their weights.
+    fn cells(&self) -> Vec<(isize, isize, f64)> {
+        match self {
+            Kernel::Square(r) => {
+                let r = *r as isize;
+                (-r..=r).flat_map(|di| (-r..=r).map(move |dj| (di, dj, 1.0))).collect()
+            }
+            Kernel::Circle(r) => {
+                let reach = r.floor() as isize;
+                (-reach..=reach)
+                    .flat_map(|di| (-reach..=reach).map(move |dj| (di, dj)))
+                    .filter(|&(di, dj)| ((di * di + dj * dj) as f64).sqrt() <= *r)
+                    .map(|(di, dj)| (di, dj, 1.0))
+                    .collect()
+            }
+            Kernel::Custom(k) => {
+                let (ri, rj) = ((k.rows / 2) as isize, (k.cols / 2) as isize);
+                (0..k.rows)
+                    .flat_map(|i| (0..k.cols).map(move |j| (i, j)))
+                    .filter(|&(i, j)| k[i][j] > 0.0)
+                    .map(|(i, j)| (i as isize - ri, j as isize - rj, k[i][j]))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Replaces every cell with `statistic` of the cells of `kernel` around it.
+///
+/// NoData (`NaN`) cells stay NoData and are left out of their neighbours'
+/// windows. With [`Boundary::Clamped`] the window shrinks at the edges of the
+/// grid; with [`Boundary::Periodic`] it wraps to the opposite side. Square
+/// windows use separable row and column passes for the mean, standard
+/// deviation, minimum and maximum, so their cost does not grow with the
+/// window's area. Panics if [`Kernel::check`] fails.
+pub fn focal(grid: &mut Grid, statistic: Statistic, kernel: &Kernel, boundary: Boundary) {
+    if let Err(e) = kernel.check() {
+        panic!("{e}");
+    }
+    if grid.rows == 0 || grid.cols == 0 {
+        return;
+    }
+    let result = match (kernel, statistic) {
+        (
+            Kernel::Square(r),
+            Statistic::Mean | Statistic::StdDev | Statistic::Min | Statistic::Max,
+        ) => square(grid, *r, statistic, boundary),
+        _ => windowed(grid, &kernel.cells(), statistic, boundary),
+    };
+    for (v, r) in grid.data.iter_mut().zip(result) {
+        if !v.is_nan() {
+            *v = r;
+        }
+    }
+}
+
+/// Fallible [`focal`]: errors instead of panicking on an invalid kernel.
+pub fn try_focal(
+    grid: &mut Grid,
+    statistic: Statistic,
+    kernel: &Kernel,
+    boundary: Boundary,
+) -> error::Result<()> {
+    kernel.check()?;
+    focal(grid, statistic, kernel, boundary);
+    Ok(())
+}
+
+/// Evaluates `statistic` over the listed cells of every window.
+fn windowed(
+    grid: &Grid,
+    cells: &[(isize, isize, f64)],
+    statistic: Statistic,
+    boundary: Boundary,
+) -> Vec<f64> {
+    let (rows, cols) = (grid.rows, grid.cols);
+    let mut out = vec![0.0; rows * cols];
+    let fill = |(i, out_row): (usize, &mut [f64])| {
+        let mut window = Vec::with_capacity(cells.len());
+        for (j, out) in out_row.iter_mut().enumerate() {
+            window.clear();
+            for &(di, dj, w) in cells {
+                let (Some(r), Some(c)) = (boundary.offset(i, di, rows), boundary.offset(j, dj, cols))
+                else {
+                    continue;
+                };
+                let v = grid[r][c];
+                if !v.is_nan() {
+                    window.push((v, w));
+                }
+            }
+            *out = statistic.of(&mut window);
+        }
+    };
+    #[cfg(feature = "parallel")]
+    out.par_chunks_mut(cols).enumerate().for_each(fill);
+    #[cfg(not(feature = "parallel"))]
+    out.chunks_mut(cols).enumerate().for_each(fill);
+    out
+}
+
+/// Square-window mean, standard deviation, minimum or maximum from
+/// separable passes.
+fn square(grid: &Grid, radius: usize, statistic: Statistic, boundary: Boundary) -> Vec<f64> {
+    let offset = |i, o, len| boundary.offset(i, o, len);
+    let sum = |g: &Grid| separable(g, radius, offset, 0.0, |acc, v, _| acc + v);
+    match statistic {
+        Statistic::Min => separable(grid, radius, offset, f64::NAN, |acc, v, _| acc.min(v)).data,
+        Statistic::Max => separable(grid, radius, offset, f64::NAN, |acc, v, _| acc.max(v)).data,
+        _ => {
+            let map = |f: fn(f64) -> f64| Grid {
+                data: grid.iter().map(|&v| if v.is_nan() { 0.0 } else { f(v) }).collect(),
+                rows: grid.rows,
+                cols: grid.cols,
+            };
+            let count = sum(&map(|_| 1.0));
+            let total = sum(&map(|v| v));
+            let squares = (statistic == Statistic::StdDev).then(|| sum(&map(|v| v * v)));
+            (0..grid.data.len())
+                .map(|k| {
+                    let n = count.data[k];
+                    if n == 0.0 {
+                        return f64::NAN;
+                    }
+                    let mean = total.data[k] / n;
+                    match &squares {
+                        Some(sq) => (sq.data[k] / n - mean * mean).max(0.0).sqrt(),
+                        None => mean,
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+/// Folds each cell's (2 `radius` + 1)-wide neighbourhood along rows, then
+/// folds those results along columns.
+///
+/// `resolve` maps an index, offset and axis length to the neighbour's index,
+/// or `None` to skip it, and `fold` receives the accumulator, the neighbour's
+/// value and its position `0..=2 * radius` in the window. Folds run in `f64`
+/// whatever the cell type.
+pub(crate) fn separable<T: Element>(
+    grid: &Grid<T>,
+    radius: usize,
+    resolve: impl Fn(usize, isize, usize) -> Option<usize> + Sync,
+    init: f64,
+    fold: impl Fn(f64, f64, usize) -> f64 + Sync,
+) -> Grid<T> {
+    let (rows, cols) = (grid.rows, grid.cols);
+    let width = 2 * radius + 1;
+
+    // Horizontal pass — row-wise, cache-friendly.
+    let mut row_pass = Grid::from_value(rows, cols, T::ZERO);
+    {
+        let fill = |(i, out_row): (usize, &mut [T])| {
+            let src_row = &grid[i];
+            for (j, out) in out_row.iter_mut().enumerate() {
+                let mut acc = init;
+                for k in 0..width {
+                    if let Some(jj) = resolve(j, k as isize - radius as isize, cols) {
+                        acc = fold(acc, src_row[jj].to_f64(), k);
+                    }
+                }
+                *out = T::from_f64(acc);
+            }
+        };
+        #[cfg(feature = "parallel")]
+        row_pass.data.par_chunks_mut(cols).enumerate().for_each(fill);
+        #[cfg(not(feature = "parallel"))]
+        row_pass.data.chunks_mut(cols).enumerate().for_each(fill);
+    }
+
+    // Vertical pass.
+    let mut result = Grid::from_value(rows, cols, T::ZERO);
+    {
+        let fill = |(i, out_row): (usize, &mut [T])| {
+            for (j, out) in out_row.iter_mut().enumerate() {
+                let mut acc = init;
+                for k in 0..width {
+                    if let Some(ii) = resolve(i, k as isize - radius as isize, rows) {
+                        acc = fold(acc, row_pass[ii][j].to_f64(), k);
+                    }
+                }
+                *out = T::from_f64(acc);
+            }
+        };
+        #[cfg(feature = "parallel")]
+        result.data.par_chunks_mut(cols).enumerate().for_each(fill);
+        #[cfg(not(feature = "parallel"))]
+        result.data.chunks_mut(cols).enumerate().for_each(fill);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{classify, random};
+
+    #[test]
+    fn test_square_fast_path_matches_window() {
+        let mut grid = random(23, 31, Some(4));
+        grid[5][6] = f64::NAN;
+        for boundary in [Boundary::Clamped, Boundary::Periodic] {
+            for statistic in [Statistic::Mean, Statistic::StdDev, Statistic::Min, Statistic::Max] {
+                let fast = square(&grid, 2, statistic, boundary);
+                let slow = windowed(&grid, &Kernel::Square(2).cells(), statistic, boundary);
+                for (a, b) in fast.iter().zip(&slow) {
+                    assert!((a - b).abs() < 1e-9, "{statistic:?} {boundary:?}: {a} vs {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_statistics_of_window() {
+        let of = |s: Statistic, values: &[f64]| {
+            let mut window: Vec<(f64, f64)> = values.iter().map(|&v| (v, 1.0)).collect();
+            s.of(&mut window)
+        };
+        let values = [3.0, 1.0, 2.0, 2.0];
+        assert_eq!(of(Statistic::Mean, &values), 2.0);
+        assert_eq!(of(Statistic::Median, &values), 2.0);
+        assert_eq!(of(Statistic::Median, &[4.0, 1.0, 2.0, 3.0]), 2.5);
+        assert_eq!(of(Statistic::Min, &values), 1.0);
+        assert_eq!(of(Statistic::Max, &values), 3.0);
+        assert!((of(Statistic::StdDev, &values) - 0.5f64.sqrt()).abs() < 1e-12);
+        assert_eq!(of(Statistic::Majority, &values), 2.0);
+        assert_eq!(of(Statistic::Majority, &[1.0, 2.0]), 1.0);
+        let h = -(0.5 * 0.5f64.ln() + 2.0 * 0.25 * 0.25f64.ln());
+        assert!((of(Statistic::Diversity, &values) - h).abs() < 1e-12);
+        assert_eq!(of(Statistic::Diversity, &[1.0, 1.0]), 0.0);
+        assert!(of(Statistic::Mean, &[]).is_nan());
+    }
+
+    #[test]
+    fn test_weighted_median_and_majority() {
+        let mut window = vec![(1.0, 3.0), (5.0, 1.0), (9.0, 1.0)];
+        assert_eq!(Statistic::Median.of(&mut window), 1.0);
+        let mut window = vec![(1.0, 1.0), (2.0, 1.5), (1.0, 1.0)];
+        assert_eq!(Statistic::Majority.of(&mut window), 1.0);
+    }
+
+    #[test]
+    fn test_kernel_cells_and_check() {
+        assert_eq!(Kernel::Square(1).cells().len(), 9);
+        assert_eq!(Kernel::Circle(1.0).cells().len(), 5);
+        assert_eq!(Kernel::Circle(1.5).cells().len(), 9);
+        assert_eq!(Kernel::Circle(0.0).cells(), vec![(0, 0, 1.0)]);
+        let ring = Grid { data: vec![1.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0], rows: 3, cols: 3 };
+        assert_eq!(Kernel::Custom(ring).cells().len(), 8);
+        assert!(Kernel::Custom(Grid::filled(2, 3, 1.0)).check().is_err());
+        assert!(Kernel::Custom(Grid::filled(3, 3, 0.0)).check().is_err());
+        assert!(Kernel::Circle(-1.0).check().is_err());
+    }
+
+    #[test]
+    fn test_focal_keeps_nodata_and_clamps_edges() {
+        let mut grid = Grid::filled(4, 4, 1.0);
+        grid[0][0] = 5.0;
+        grid[3][3] = f64::NAN;
+        let mut mean = grid.clone();
+        focal(&mut mean, Statistic::Mean, &Kernel::Square(1), Boundary::Clamped);
+        assert_eq!(mean[0][0], 2.0); // (5 + 1 + 1 + 1) / 4
+        assert!(mean[3][3].is_nan());
+        assert_eq!(mean[2][2], 1.0);
+
+        let mut periodic = grid.clone();
+        focal(&mut periodic, Statistic::Max, &Kernel::Square(1), Boundary::Periodic);
+        assert_eq!(periodic[3][0], 5.0);
+        assert_eq!(periodic[2][2], 1.0);
+
+        let mut classes = random(30, 30, Some(9));
+        classify(&mut classes, 3);
+        focal(&mut classes, Statistic::Majority, &Kernel::Circle(2.0), Boundary::Clamped);
+        assert!(classes.iter().all(|&v| v == 0.0 || v == 0.5 || v == 1.0));
+    }
+}
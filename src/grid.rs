@@ -1,12 +1,100 @@
+use std::cmp::Ordering;
 use std::fmt;
-use std::ops::{Index, IndexMut};
+use std::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, MulAssign, Sub};
 
-/// A 2D grid backed by a flat `Vec<f64>` for cache-friendly storage.
+/// Cell type of a [`Grid`]: `f64` (the default) or `f32`.
 ///
-/// Indexing with `grid[row][col]` works naturally via `Index<usize>`.
+/// Generators compute in `f64`, so seeded values agree at either precision to
+/// within `f32` rounding. The `_f32` generators fill a `Grid<f32>` directly;
+/// [`Grid::to_f32`] converts the output of the others. The element-wise
+/// operations and exporters work on either type.
+pub trait Element:
+    Copy
+    + Default
+    + PartialOrd
+    + fmt::Debug
+    + fmt::Display
+    + serde::Serialize
+    + Send
+    + Sync
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + AddAssign
+    + MulAssign
+    + 'static
+{
+    const NAN: Self;
+    const ZERO: Self;
+    const ONE: Self;
+    const INFINITY: Self;
+    const NEG_INFINITY: Self;
+
+    /// Nearest value of this type to `v`.
+    fn from_f64(v: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn is_nan(self) -> bool;
+    fn abs(self) -> Self;
+    fn floor(self) -> Self;
+    /// Smaller of `self` and `other`, ignoring NaN as [`f64::min`] does.
+    fn min(self, other: Self) -> Self;
+    /// Larger of `self` and `other`, ignoring NaN as [`f64::max`] does.
+    fn max(self, other: Self) -> Self;
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+macro_rules! impl_element {
+    ($t:ident) => {
+        impl Element for $t {
+            const NAN: Self = $t::NAN;
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+            const INFINITY: Self = $t::INFINITY;
+            const NEG_INFINITY: Self = $t::NEG_INFINITY;
+
+            fn from_f64(v: f64) -> Self {
+                v as $t
+            }
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+            fn is_nan(self) -> bool {
+                $t::is_nan(self)
+            }
+            fn abs(self) -> Self {
+                $t::abs(self)
+            }
+            fn floor(self) -> Self {
+                $t::floor(self)
+            }
+            fn min(self, other: Self) -> Self {
+                $t::min(self, other)
+            }
+            fn max(self, other: Self) -> Self {
+                $t::max(self, other)
+            }
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                $t::total_cmp(self, other)
+            }
+        }
+    };
+}
+
+impl_element!(f32);
+impl_element!(f64);
+
+/// A 2D grid backed by a flat `Vec` for cache-friendly storage.
+///
+/// Indexing with `grid[row][col]` works naturally via `Index<usize>`. Cells
+/// are `f64` unless another [`Element`] type is given, e.g. `Grid<f32>`.
+///
+/// Cells holding `NaN` are NoData: they lie outside the study area and are
+/// skipped by operations such as `scale`, `classify` and `threshold`, and
+/// written as the NoData value by exporters that support one.
 #[derive(Clone, Debug, PartialEq)]
-pub struct Grid {
-    pub data: Vec<f64>,
+pub struct Grid<T: Element = f64> {
+    pub data: Vec<T>,
     pub rows: usize,
     pub cols: usize,
 }
@@ -30,38 +118,80 @@ impl Grid {
         }
     }
 
+    /// Returns a single-precision copy, rounding each cell to the nearest `f32`.
+    pub fn to_f32(&self) -> Grid<f32> {
+        self.cast()
+    }
+}
+
+impl Grid<f32> {
+    /// Returns a double-precision copy. Every `f32` is exactly representable.
+    pub fn to_f64(&self) -> Grid {
+        self.cast()
+    }
+}
+
+impl<T: Element> Grid<T> {
+    /// Creates a new grid of any element type filled with `value`.
+    pub fn from_value(rows: usize, cols: usize, value: T) -> Self {
+        Grid {
+            data: vec![value; rows * cols],
+            rows,
+            cols,
+        }
+    }
+
+    /// Returns a copy converted to element type `U`.
+    pub fn cast<U: Element>(&self) -> Grid<U> {
+        Grid {
+            data: self.data.iter().map(|&v| U::from_f64(v.to_f64())).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
     /// Returns true if the grid has no cells.
     pub fn is_empty(&self) -> bool {
         self.rows == 0 || self.cols == 0
     }
 
+    /// Returns true if the cell at (`row`, `col`) is NoData.
+    pub fn is_nodata(&self, row: usize, col: usize) -> bool {
+        self[row][col].is_nan()
+    }
+
+    /// Returns the number of NoData cells.
+    pub fn nodata_count(&self) -> usize {
+        self.data.iter().filter(|v| v.is_nan()).count()
+    }
+
     /// Iterates over all cell values in row-major order.
-    pub fn iter(&self) -> impl Iterator<Item = &f64> {
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.data.iter()
     }
 
     /// Mutably iterates over all cell values in row-major order.
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut f64> {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
         self.data.iter_mut()
     }
 }
 
 /// `grid[row]` returns a row slice, enabling `grid[row][col]` indexing.
-impl Index<usize> for Grid {
-    type Output = [f64];
+impl<T: Element> Index<usize> for Grid<T> {
+    type Output = [T];
 
-    fn index(&self, row: usize) -> &[f64] {
+    fn index(&self, row: usize) -> &[T] {
         &self.data[row * self.cols..(row + 1) * self.cols]
     }
 }
 
-impl IndexMut<usize> for Grid {
-    fn index_mut(&mut self, row: usize) -> &mut [f64] {
+impl<T: Element> IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, row: usize) -> &mut [T] {
         &mut self.data[row * self.cols..(row + 1) * self.cols]
     }
 }
 
-impl fmt::Display for Grid {
+impl<T: Element> fmt::Display for Grid<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for i in 0..self.rows {
             write!(f, "[")?;
@@ -76,3 +206,97 @@ impl fmt::Display for Grid {
         Ok(())
     }
 }
+
+/// How generators and operations treat the edges of a grid.
+///
+/// With `Periodic` the grid is a torus: the last column neighbours the first
+/// and the last row neighbours the top, so patches, kernels and distances
+/// continue across the edges and tiled copies of the output join seamlessly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Boundary {
+    /// Hard edges: nothing lies beyond the first and last rows and columns.
+    #[default]
+    Clamped,
+    /// Toroidal edges: opposite sides of the grid are adjacent.
+    Periodic,
+}
+
+impl Boundary {
+    /// Returns `index + offset` along an axis of length `len`, wrapping when
+    /// periodic and returning `None` when it falls outside a clamped grid.
+    pub fn offset(self, index: usize, offset: isize, len: usize) -> Option<usize> {
+        let i = index as isize + offset;
+        match self {
+            Boundary::Periodic if len > 0 => Some(i.rem_euclid(len as isize) as usize),
+            _ if i >= 0 && (i as usize) < len => Some(i as usize),
+            _ => None,
+        }
+    }
+
+    /// Like [`offset`](Boundary::offset), but a clamped grid repeats its edge
+    /// cell instead of returning `None`. `len` must be non-zero.
+    pub fn resolve(self, index: usize, offset: isize, len: usize) -> usize {
+        let i = index as isize + offset;
+        match self {
+            Boundary::Clamped => i.clamp(0, len as isize - 1) as usize,
+            Boundary::Periodic => i.rem_euclid(len as isize) as usize,
+        }
+    }
+
+    /// Whether this is [`Boundary::Periodic`].
+    pub fn is_periodic(self) -> bool {
+        self == Boundary::Periodic
+    }
+}
+
+/// Georeferencing for a [`Grid`]: where its lower-left corner sits in map
+/// coordinates, how large each (square) cell is, and optionally the
+/// coordinate reference system.
+///
+/// Row 0 is the northernmost row, matching raster conventions, so cell
+/// (`row`, `col`) spans x in `[x_min + col * cell_size, x_min + (col + 1) * cell_size)`
+/// and y downwards from `y_max(rows) - row * cell_size`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoTransform {
+    /// X coordinate of the grid's lower-left corner.
+    pub x_min: f64,
+    /// Y coordinate of the grid's lower-left corner.
+    pub y_min: f64,
+    /// Width and height of a cell in map units.
+    pub cell_size: f64,
+    /// Coordinate reference system as WKT or an authority code such as `"EPSG:27700"`.
+    pub crs: Option<String>,
+}
+
+impl Default for GeoTransform {
+    /// The unreferenced transform: origin at (0, 0), unit cells, no CRS.
+    fn default() -> Self {
+        GeoTransform { x_min: 0.0, y_min: 0.0, cell_size: 1.0, crs: None }
+    }
+}
+
+impl GeoTransform {
+    /// Y coordinate of the top edge of a grid with `rows` rows.
+    pub fn y_max(&self, rows: usize) -> f64 {
+        self.y_min + rows as f64 * self.cell_size
+    }
+
+    /// Map coordinates `(x, y)` of the centre of cell (`row`, `col`) in a grid with `rows` rows.
+    pub fn cell_centre(&self, rows: usize, row: usize, col: usize) -> (f64, f64) {
+        (
+            self.x_min + (col as f64 + 0.5) * self.cell_size,
+            self.y_max(rows) - (row as f64 + 0.5) * self.cell_size,
+        )
+    }
+}
+
+/// A [`Grid`] together with the [`GeoTransform`] that places it on a map.
+///
+/// Returned by the georeferenced readers in [`export`](crate::export) so an
+/// NLM can be generated to overlay an existing raster and written back with
+/// the same header.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoGrid<T: Element = f64> {
+    pub grid: Grid<T>,
+    pub transform: GeoTransform,
+}
@@ -0,0 +1,241 @@
+//! Integer label grids for classified landscapes.
+//!
+//! [`classify`](crate::classify) and [`threshold`](crate::threshold) leave
+//! class values such as 1/3 and 2/3 in a float [`Grid`]. A [`LabelGrid`]
+//! stores the class index of each cell instead, with a lookup table from
+//! index back to value, and is written by the `export::write_labels_*`
+//! functions as an indexed PNG, an integer GeoTIFF or an integer ASCII grid.
+
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Index, IndexMut};
+
+use crate::error::{NlmError, Result};
+use crate::grid::{Element, Grid};
+
+/// Cell type of a [`LabelGrid`]: `u8`, `u16` or `u32`.
+///
+/// The largest value of each type is reserved for NoData, so a `u8` grid
+/// holds up to 255 classes.
+pub trait Label:
+    Copy + Eq + Ord + Hash + fmt::Debug + fmt::Display + Send + Sync + 'static
+{
+    /// Label of NoData cells: the type's maximum value.
+    const NODATA: Self;
+    /// Width of the type in bits.
+    const BITS: u32;
+
+    /// Label for class `index`, or `None` if the type cannot hold it.
+    fn from_index(index: usize) -> Option<Self>;
+    /// Class index of this label.
+    fn index(self) -> usize;
+}
+
+macro_rules! impl_label {
+    ($t:ident) => {
+        impl Label for $t {
+            const NODATA: Self = $t::MAX;
+            const BITS: u32 = $t::BITS;
+
+            fn from_index(index: usize) -> Option<Self> {
+                $t::try_from(index).ok().filter(|&l| l != Self::NODATA)
+            }
+            fn index(self) -> usize {
+                self as usize
+            }
+        }
+    };
+}
+
+impl_label!(u8);
+impl_label!(u16);
+impl_label!(u32);
+
+/// A 2D grid of class labels, stored row-major like [`Grid`].
+///
+/// `classes[k]` is the value that class `k` stands for, so [`to_grid`]
+/// recovers the float grid the labels were made from. Cells equal to
+/// [`Label::NODATA`] are NoData.
+///
+/// [`to_grid`]: LabelGrid::to_grid
+#[derive(Clone, Debug, PartialEq)]
+pub struct LabelGrid<L: Label> {
+    pub data: Vec<L>,
+    pub rows: usize,
+    pub cols: usize,
+    pub classes: Vec<f64>,
+}
+
+impl<L: Label> LabelGrid<L> {
+    /// Labels each distinct value of `grid` by its rank, so the lowest value is
+    /// class 0 and `classes` lists the values in ascending order. NoData cells
+    /// stay NoData.
+    ///
+    /// Intended for grids that hold a few class values, such as the output of
+    /// [`classify`](crate::classify). Fails if `grid` has more distinct values
+    /// than `L` can label.
+    pub fn from_grid<T: Element>(grid: &Grid<T>) -> Result<Self> {
+        let mut classes: Vec<f64> = grid
+            .iter()
+            .filter(|v| !v.is_nan())
+            .map(|v| v.to_f64())
+            .collect();
+        classes.sort_by(f64::total_cmp);
+        classes.dedup_by(|a, b| a == b);
+        if !classes.is_empty() && L::from_index(classes.len() - 1).is_none() {
+            return Err(NlmError::invalid(
+                "grid",
+                format!(
+                    "has {} distinct values but {}-bit labels hold at most {}",
+                    classes.len(),
+                    L::BITS,
+                    L::NODATA.index()
+                ),
+            ));
+        }
+        let data = grid
+            .iter()
+            .map(|v| {
+                if v.is_nan() {
+                    return L::NODATA;
+                }
+                let v = v.to_f64();
+                let k = classes.partition_point(|&c| c < v);
+                L::from_index(k).expect("checked against the class count")
+            })
+            .collect();
+        Ok(LabelGrid { data, rows: grid.rows, cols: grid.cols, classes })
+    }
+
+    /// Value of class `label`, or `None` for NoData and labels past the table.
+    pub fn value(&self, label: L) -> Option<f64> {
+        if label == L::NODATA {
+            return None;
+        }
+        self.classes.get(label.index()).copied()
+    }
+
+    /// Float grid of class values, with NoData (and labels missing from the
+    /// table) as `NaN`.
+    pub fn to_grid(&self) -> Grid {
+        Grid {
+            data: self
+                .data
+                .iter()
+                .map(|&l| self.value(l).unwrap_or(f64::NAN))
+                .collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
+    /// Copy with labels of type `M`. Fails if `M` cannot hold every class.
+    pub fn try_cast<M: Label>(&self) -> Result<LabelGrid<M>> {
+        if !self.classes.is_empty() && M::from_index(self.classes.len() - 1).is_none() {
+            return Err(NlmError::invalid(
+                "labels",
+                format!(
+                    "{} classes do not fit in {}-bit labels",
+                    self.classes.len(),
+                    M::BITS
+                ),
+            ));
+        }
+        let data = self
+            .data
+            .iter()
+            .map(|&l| {
+                if l == L::NODATA {
+                    M::NODATA
+                } else {
+                    M::from_index(l.index()).unwrap_or(M::NODATA)
+                }
+            })
+            .collect();
+        Ok(LabelGrid { data, rows: self.rows, cols: self.cols, classes: self.classes.clone() })
+    }
+
+    /// Number of cells in each class, indexed by label. NoData cells and
+    /// labels past the table are not counted.
+    pub fn counts(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.classes.len()];
+        for &l in &self.data {
+            if let Some(c) = counts.get_mut(l.index()).filter(|_| l != L::NODATA) {
+                *c += 1;
+            }
+        }
+        counts
+    }
+
+    /// Returns true if the grid has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0 || self.cols == 0
+    }
+
+    /// Returns true if the cell at (`row`, `col`) is NoData.
+    pub fn is_nodata(&self, row: usize, col: usize) -> bool {
+        self[row][col] == L::NODATA
+    }
+
+    /// Returns the number of NoData cells.
+    pub fn nodata_count(&self) -> usize {
+        self.data.iter().filter(|&&l| l == L::NODATA).count()
+    }
+
+    /// Iterates over all labels in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &L> {
+        self.data.iter()
+    }
+}
+
+/// `labels[row]` returns a row slice, enabling `labels[row][col]` indexing.
+impl<L: Label> Index<usize> for LabelGrid<L> {
+    type Output = [L];
+
+    fn index(&self, row: usize) -> &[L] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+}
+
+impl<L: Label> IndexMut<usize> for LabelGrid<L> {
+    fn index_mut(&mut self, row: usize) -> &mut [L] {
+        &mut self.data[row * self.cols..(row + 1) * self.cols]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{classify, random};
+
+    #[test]
+    fn test_from_grid_round_trip() {
+        let mut grid = random(20, 20, Some(1));
+        classify(&mut grid, 4);
+        grid[0][0] = f64::NAN;
+        let labels = LabelGrid::<u8>::from_grid(&grid).unwrap();
+        assert_eq!(labels.classes, vec![0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0]);
+        assert!(labels.is_nodata(0, 0));
+        assert!(labels.iter().all(|&l| l < 4 || l == u8::NODATA));
+        assert_eq!(labels.counts().iter().sum::<usize>(), 399);
+        assert_eq!(labels.to_grid().to_string(), grid.to_string());
+    }
+
+    #[test]
+    fn test_from_grid_too_many_classes() {
+        let grid = random(20, 20, Some(2));
+        assert!(LabelGrid::<u8>::from_grid(&grid).is_err());
+        let labels = LabelGrid::<u16>::from_grid(&grid).unwrap();
+        assert_eq!(labels.classes.len(), 400);
+        assert!(labels.try_cast::<u8>().is_err());
+        assert_eq!(labels.try_cast::<u32>().unwrap().to_grid(), grid);
+    }
+
+    #[test]
+    fn test_from_index_reserves_nodata() {
+        assert_eq!(u8::from_index(254), Some(254));
+        assert_eq!(u8::from_index(255), None);
+        assert_eq!(u16::from_index(70_000), None);
+        assert_eq!(u32::NODATA, u32::MAX);
+    }
+}
@@ -1,15 +1,31 @@
+pub mod autocorrelation;
+pub mod error;
 pub mod export;
+pub mod focal;
 pub mod grid;
+pub mod labels;
+pub mod metrics;
+pub mod morphology;
 pub mod operation;
+pub mod pipeline;
+pub mod registry;
+pub mod spectrum;
+pub mod sweep;
+pub mod tiled;
 mod algorithms;
 mod array;
 mod fenwick;
 #[cfg(feature = "python")]
 mod python;
 
-pub use grid::Grid;
+pub use error::NlmError;
+pub use focal::{focal, try_focal, Kernel, Statistic};
+pub use grid::{Boundary, Element, GeoGrid, GeoTransform, Grid};
+pub use labels::{Label, LabelGrid};
+pub use morphology::{close, dilate, erode, fill_holes, open, sieve, skeletonize};
 pub use operation::{
-    abs, add, add_value, classify, invert, max, min, min_and_max, multiply, multiply_value, scale,
-    threshold,
+    abs, add, add_value, apply_mask, classify, classify_weighted, invert, label_patches, max,
+    min, min_and_max, multiply, multiply_value, polygon_mask, scale, threshold, try_add,
+    try_apply_mask, try_classify, try_classify_weighted, try_multiply, Connectivity, Patch,
 };
 pub use algorithms::*;
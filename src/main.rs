@@ -1,17 +1,24 @@
-use clap::{Parser, Subcommand};
-use nlmrs::export;
+use std::fmt::Display;
+use std::path::Path;
+
+use clap::{Arg, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use nlmrs::export::{self, Colormap, Metadata, TiffSampleFormat};
+use nlmrs::pipeline::{Layer, MaskSource, Output, Pipeline, Step};
+use nlmrs::registry::{self, Generator, ParamKind, Params, Value};
+use nlmrs::sweep::{self, Sweep};
+use nlmrs::tiled::TiledNoise;
+use nlmrs::{GeoGrid, GeoTransform, Grid};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 #[derive(Parser)]
 #[command(
     name = "nlmrs",
     about = "Generate Neutral Landscape Models",
-    long_about = "Generate 2D spatial grids using various NLM algorithms.\nOutput format is inferred from the file extension (.png, .csv, .json)."
+    long_about = "Generate 2D spatial grids using various NLM algorithms.\nOutput format is inferred from the file extension (.png, .csv, .json, .asc, .tif, .npy, .npz)."
 )]
 struct Cli {
-    #[command(subcommand)]
-    command: Commands,
-
-    /// Output file path (extension determines format: .png, .csv, .json)
+    /// Output file path (extension determines format: .png, .csv, .json, .asc, .tif, .npy, .npz)
     #[arg(long, short, default_value = "output.png", global = true)]
     output: String,
 
@@ -23,934 +30,553 @@ struct Cli {
     #[arg(long, global = true)]
     grayscale: bool,
 
+    /// PNG colormap: terrain, viridis, magma, cividis, rdbu, gray, set1 or tab10, "_r"
+    /// to reverse (viridis_r), or #rrggbb stops (0:#2166ac,0.5:#f7f7f7,1:#b2182b);
+    /// append @N for N discrete bands (magma@5)
+    #[arg(long, global = true, conflicts_with = "grayscale", value_name = "SPEC")]
+    colormap: Option<String>,
+
+    /// Draw a colour bar (class swatches with --labels) to the right of PNG output
+    #[arg(long, global = true)]
+    legend: bool,
+
     /// Classify output into N equal-width classes
     #[arg(long, global = true)]
     classify: Option<usize>,
 
+    /// Classify output by rank into classes covering the given proportions (e.g. 0.6,0.3,0.1)
+    #[arg(long, global = true, value_delimiter = ',', conflicts_with = "classify")]
+    weights: Option<Vec<f64>>,
+
     /// Threshold output at T: values below T → 0.0, at or above → 1.0
     #[arg(long, global = true)]
     threshold: Option<f64>,
+
+    /// Restrict output to a study area read from a raster (.asc, .tif, .png, .csv or .json);
+    /// cells that are 0 or NODATA in the mask become NoData and the rest are rescaled.
+    /// Its georeferencing is also used for the output unless --like is given
+    #[arg(long, global = true)]
+    mask: Option<String>,
+
+    /// Copy georeferencing (origin, cell size, CRS) from an existing ESRI ASCII grid
+    /// or GeoTIFF of the same dimensions, so .asc and float .tif output overlays it.
+    /// Other formats carry none and give the default
+    #[arg(long, global = true)]
+    like: Option<String>,
+
+    /// Write integer class labels instead of values: an indexed .png, or an integer .tif
+    /// or .asc. Each distinct value becomes a class, in ascending order, so use it with
+    /// --classify, --weights, --threshold or a patch generator such as mosaic
+    #[arg(long, global = true)]
+    labels: bool,
+
+    /// Sample format for .tif output: 16-bit grayscale, or a GeoTIFF of raw float values
+    #[arg(long, global = true, value_enum, default_value = "u16")]
+    tiff_format: TiffFormat,
+
+    /// Generate N replicates with seeds derived from SEED; --output becomes a file name
+    /// template (see --sweep), or a .npz holding every run as arr_0, arr_1, ..., and a
+    /// manifest CSV of seeds and parameters is written
+    #[arg(long, global = true, default_value = "1")]
+    replicates: usize,
+
+    /// Generate every value of a parameter, as a list (p=0.3,0.5) or an inclusive range
+    /// (h=0.1:0.9:0.2). Repeat for a full factorial design. --output may use the
+    /// placeholders {run}, {replicate}, {seed}, {algorithm} and {<parameter>}
+    #[arg(long, global = true, value_name = "NAME=VALUES")]
+    sweep: Vec<String>,
+
+    /// Manifest path for --replicates and --sweep [default: manifest.csv beside the outputs]
+    #[arg(long, global = true)]
+    manifest: Option<String>,
+
+    /// Also write <output>.json recording the algorithm, parameters, seed, grid size,
+    /// nlmrs version and post-processing, so `nlmrs reproduce` can regenerate the grid
+    #[arg(long, global = true)]
+    metadata: bool,
+
+    /// Generate and write the grid in NxN tiles instead of holding it in memory, for
+    /// grids too large to fit. Only for perlin, fbm, ridged, simplex-noise, worley and
+    /// value-noise with .asc or float .tif output; N must be a multiple of 16
+    #[arg(long, global = true, value_name = "N")]
+    tile_size: Option<usize>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum TiffFormat {
+    U16,
+    F32,
+    F64,
 }
 
+// The subcommands besides the generators, which `command` adds from the registry.
 #[derive(Subcommand)]
 enum Commands {
-    /// Spatially random noise
-    Random {
-        /// Number of rows
-        rows: usize,
-        /// Number of columns
-        cols: usize,
-    },
-    /// Random element nearest-neighbour interpolation
-    RandomElement {
-        rows: usize,
-        cols: usize,
-        /// Number of seed elements to place
-        #[arg(long, default_value = "50000")]
-        n: f64,
-    },
-    /// Linear gradient at a given angle
-    PlanarGradient {
-        rows: usize,
-        cols: usize,
-        /// Gradient direction in degrees [0, 360)
-        #[arg(long)]
-        direction: Option<f64>,
-    },
-    /// Symmetric gradient (zero at edges, peak in middle)
-    EdgeGradient {
-        rows: usize,
-        cols: usize,
-        /// Gradient direction in degrees [0, 360)
-        #[arg(long)]
-        direction: Option<f64>,
-    },
-    /// Radial gradient from a random centre point
-    DistanceGradient {
-        rows: usize,
-        cols: usize,
-    },
-    /// Sinusoidal wave gradient
-    WaveGradient {
-        rows: usize,
-        cols: usize,
-        /// Wave period (smaller = larger waves)
-        #[arg(long, default_value = "2.5")]
-        period: f64,
-        /// Wave direction in degrees [0, 360)
-        #[arg(long)]
-        direction: Option<f64>,
-    },
-    /// Diamond-square fractal terrain (midpoint displacement)
-    MidpointDisplacement {
-        rows: usize,
-        cols: usize,
-        /// Spatial autocorrelation (0 = rough, 1 = smooth)
-        #[arg(long, default_value = "1.0")]
-        h: f64,
-    },
-    /// Hill-grow algorithm
-    HillGrow {
-        rows: usize,
-        cols: usize,
-        /// Number of iterations
-        #[arg(long, default_value = "10000")]
-        n: usize,
-        /// Hills grow in clusters (weighted random selection)
-        #[arg(long)]
-        runaway: bool,
-        /// Surface only grows, never shrinks
-        #[arg(long)]
-        only_grow: bool,
-    },
-    /// Perlin noise
-    Perlin {
-        rows: usize,
-        cols: usize,
-        /// Noise frequency (higher = more features)
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-    },
-    /// Fractal Brownian motion (layered Perlin noise)
-    Fbm {
-        rows: usize,
-        cols: usize,
-        /// Base noise frequency
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-        /// Number of octaves
-        #[arg(long, default_value = "6")]
-        octaves: usize,
-        /// Amplitude scaling per octave
-        #[arg(long, default_value = "0.5")]
-        persistence: f64,
-        /// Frequency scaling per octave
-        #[arg(long, default_value = "2.0")]
-        lacunarity: f64,
-    },
-    /// Ridged multifractal noise — sharp ridges and mountain-like terrain
-    Ridged {
-        rows: usize,
-        cols: usize,
-        /// Base noise frequency
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-        /// Number of octaves
-        #[arg(long, default_value = "6")]
-        octaves: usize,
-        /// Amplitude scaling per octave
-        #[arg(long, default_value = "0.5")]
-        persistence: f64,
-        /// Frequency scaling per octave
-        #[arg(long, default_value = "2.0")]
-        lacunarity: f64,
-    },
-    /// Billow noise — rounded cloud- and hill-like patterns
-    Billow {
-        rows: usize,
-        cols: usize,
-        /// Base noise frequency
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-        /// Number of octaves
-        #[arg(long, default_value = "6")]
-        octaves: usize,
-        /// Amplitude scaling per octave
-        #[arg(long, default_value = "0.5")]
-        persistence: f64,
-        /// Frequency scaling per octave
-        #[arg(long, default_value = "2.0")]
-        lacunarity: f64,
-    },
-    /// Worley (cellular) noise — territory / patch patterns
-    Worley {
-        rows: usize,
-        cols: usize,
-        /// Seed-point frequency (higher = smaller cells)
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-    },
-    /// Gaussian random field — spatially correlated noise
-    GaussianField {
-        rows: usize,
-        cols: usize,
-        /// Gaussian kernel standard deviation in cells (correlation length)
-        #[arg(long, default_value = "10.0")]
-        sigma: f64,
-    },
-    /// Random cluster via fault-line cuts
-    RandomCluster {
-        rows: usize,
-        cols: usize,
-        /// Number of fault-line cuts
-        #[arg(long, default_value = "200")]
-        n: usize,
-    },
-    /// Hybrid multifractal noise — blends smooth and ridged characteristics
-    HybridNoise {
-        rows: usize,
-        cols: usize,
-        /// Base noise frequency
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-        /// Number of octaves
-        #[arg(long, default_value = "6")]
-        octaves: usize,
-        /// Amplitude scaling per octave
-        #[arg(long, default_value = "0.5")]
-        persistence: f64,
-        /// Frequency scaling per octave
-        #[arg(long, default_value = "2.0")]
-        lacunarity: f64,
-    },
-    /// Value noise — interpolated lattice noise
-    ValueNoise {
-        rows: usize,
-        cols: usize,
-        /// Noise frequency (higher = more features)
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-    },
-    /// Turbulence — fBm with absolute-value fold per octave
-    Turbulence {
-        rows: usize,
-        cols: usize,
-        /// Base noise frequency
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-        /// Number of octaves
-        #[arg(long, default_value = "6")]
-        octaves: usize,
-        /// Amplitude scaling per octave
-        #[arg(long, default_value = "0.5")]
-        persistence: f64,
-        /// Frequency scaling per octave
-        #[arg(long, default_value = "2.0")]
-        lacunarity: f64,
-    },
-    /// Domain-warped Perlin noise — organic, swirling patterns
-    DomainWarp {
-        rows: usize,
-        cols: usize,
-        /// Coordinate frequency (higher = more features)
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-        /// Displacement magnitude applied to sample coordinates
-        #[arg(long, default_value = "1.0")]
-        warp_strength: f64,
-    },
-    /// Mosaic — discrete Voronoi patch map with flat-coloured regions
-    Mosaic {
-        rows: usize,
-        cols: usize,
-        /// Number of Voronoi seed points
-        #[arg(long, default_value = "200")]
-        n: usize,
-    },
-    /// Rectangular cluster — overlapping random axis-aligned rectangles
-    RectangularCluster {
-        rows: usize,
-        cols: usize,
-        /// Number of rectangles to place
-        #[arg(long, default_value = "200")]
-        n: usize,
-    },
-    /// Percolation — binary Bernoulli lattice (Gardner 1987)
-    Percolation {
-        rows: usize,
-        cols: usize,
-        /// Probability a cell is habitat (0.0–1.0)
-        #[arg(long, default_value = "0.5")]
-        p: f64,
-    },
-    /// Binary space partitioning — hierarchical rectilinear partition
-    BinarySpacePartitioning {
-        rows: usize,
-        cols: usize,
-        /// Number of rectangles in the final partition
-        #[arg(long, default_value = "100")]
-        n: usize,
-    },
-    /// Cellular automaton — binary cave-like patterns from birth/survival rules
-    CellularAutomaton {
-        rows: usize,
-        cols: usize,
-        /// Initial probability of a cell being alive
-        #[arg(long, default_value = "0.45")]
-        p: f64,
-        /// Number of rule iterations
-        #[arg(long, default_value = "5")]
-        iterations: usize,
-        /// Min live neighbours for a dead cell to become alive
-        #[arg(long, default_value = "5")]
-        birth_threshold: usize,
-        /// Min live neighbours for a live cell to stay alive
-        #[arg(long, default_value = "4")]
-        survival_threshold: usize,
-    },
-    /// Neighbourhood clustering — iterative majority-vote patch clustering
-    NeighbourhoodClustering {
-        rows: usize,
-        cols: usize,
-        /// Number of distinct patch classes
-        #[arg(long, default_value = "5")]
-        k: usize,
-        /// Number of majority-vote iterations
-        #[arg(long, default_value = "10")]
-        iterations: usize,
-    },
-    /// Spectral synthesis — 1/f^beta noise generated in the frequency domain
-    SpectralSynthesis {
-        rows: usize,
-        cols: usize,
-        /// Spectral exponent: 0 = white noise, 1 = pink, 2 = brown/natural terrain
-        #[arg(long, default_value = "2.0")]
-        beta: f64,
-    },
-    /// Diffusion-limited aggregation — branching fractal cluster grown from centre
-    DiffusionLimitedAggregation {
-        rows: usize,
-        cols: usize,
-        /// Number of particles to release
-        #[arg(long, default_value = "2000")]
-        n: usize,
-    },
-    /// Gray-Scott reaction-diffusion — Turing-pattern spots, stripes and labyrinths
-    ReactionDiffusion {
-        rows: usize,
-        cols: usize,
-        /// Number of simulation steps
-        #[arg(long, default_value = "1000")]
-        iterations: usize,
-        /// Feed rate for chemical A (controls pattern type)
-        #[arg(long, default_value = "0.055")]
-        feed: f64,
-        /// Kill rate for chemical B (controls pattern type)
-        #[arg(long, default_value = "0.062")]
-        kill: f64,
-    },
-    /// Eden growth model — compact fractal blob grown from the centre
-    EdenGrowth {
-        rows: usize,
-        cols: usize,
-        /// Number of cells to add to the cluster
-        #[arg(long, default_value = "2000")]
-        n: usize,
-    },
-    /// Fractal Brownian surface parameterised by the Hurst exponent
-    FractalBrownianSurface {
-        rows: usize,
-        cols: usize,
-        /// Hurst exponent in (0, 1): 0 = rough, 1 = smooth
-        #[arg(long, default_value = "0.5")]
-        h: f64,
-    },
-    /// Elliptical landscape gradient centred at the grid midpoint
-    LandscapeGradient {
-        rows: usize,
-        cols: usize,
-        /// Major-axis orientation in degrees [0, 360). Random if omitted.
-        #[arg(long)]
-        direction: Option<f64>,
-        /// Major-to-minor axis ratio (≥ 1.0). 1.0 = circular.
-        #[arg(long, default_value = "1.0")]
-        aspect: f64,
-    },
-    /// OpenSimplex noise
-    SimplexNoise {
-        rows: usize,
-        cols: usize,
-        /// Coordinate frequency (higher = more features per unit)
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-    },
-    /// Invasion percolation (lowest-weight boundary growth from centre)
-    InvasionPercolation {
-        rows: usize,
-        cols: usize,
-        /// Number of cells to invade
-        #[arg(long, default_value = "2000")]
-        n: usize,
-    },
-    /// Sum of random Gaussian blob kernels
-    GaussianBlobs {
-        rows: usize,
-        cols: usize,
-        /// Number of blob centres
-        #[arg(long, default_value = "50")]
-        n: usize,
-        /// Gaussian width (in cells)
-        #[arg(long, default_value = "5.0")]
-        sigma: f64,
-    },
-    /// Ising model via Glauber dynamics (binary spin lattice)
-    IsingModel {
-        rows: usize,
-        cols: usize,
-        /// Inverse temperature (near 0.44 = critical point)
-        #[arg(long, default_value = "0.4")]
-        beta: f64,
-        /// Number of sweeps (each sweep = rows × cols spin-flip attempts)
-        #[arg(long, default_value = "1000")]
-        iterations: usize,
-    },
-    /// Voronoi distance field from random feature points
-    VoronoiDistance {
-        rows: usize,
-        cols: usize,
-        /// Number of feature points
-        #[arg(long, default_value = "50")]
-        n: usize,
-    },
-    /// Superposition of sinusoidal plane waves
-    SineComposite {
-        rows: usize,
-        cols: usize,
-        /// Number of sinusoidal waves to superpose
-        #[arg(long, default_value = "8")]
-        waves: usize,
-    },
-    /// Divergence-free curl-warped Perlin noise
-    CurlNoise {
-        rows: usize,
-        cols: usize,
-        /// Coordinate frequency (higher = more features per unit)
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-    },
-    /// Hydraulic erosion simulation on a random heightmap
-    HydraulicErosion {
-        rows: usize,
-        cols: usize,
-        /// Number of erosion droplets to simulate
-        #[arg(long, default_value = "500")]
-        n: usize,
-    },
-    /// Levy flight random walk density map
-    LevyFlight {
-        rows: usize,
-        cols: usize,
-        /// Number of flight steps
-        #[arg(long, default_value = "1000")]
-        n: usize,
-    },
-    /// Poisson disk sampling (binary inhibition pattern)
-    PoissonDisk {
-        rows: usize,
-        cols: usize,
-        /// Minimum distance in cells between any two sample points
-        #[arg(long, default_value = "5.0")]
-        min_dist: f64,
-    },
-    /// Gabor noise — oriented sinusoidal kernel superposition
-    GaborNoise {
-        rows: usize,
-        cols: usize,
-        /// Controls carrier frequency and envelope width (higher = finer features)
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-        /// Number of Gabor kernels to place
-        #[arg(long, default_value = "500")]
-        n: usize,
-    },
-    /// Spot noise — random anisotropic elliptical Gaussian blobs
-    SpotNoise {
-        rows: usize,
-        cols: usize,
-        /// Number of spots to place
-        #[arg(long, default_value = "200")]
-        n: usize,
-    },
-    /// Anisotropic noise — fBm stretched along a dominant axis
-    AnisotropicNoise {
-        rows: usize,
-        cols: usize,
-        /// Base noise frequency along the primary axis
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-        /// Number of noise layers to combine
-        #[arg(long, default_value = "6")]
-        octaves: usize,
-        /// Orientation of elongation in degrees [0, 360)
-        #[arg(long, default_value = "45.0")]
-        direction: f64,
-        /// Compression ratio for the perpendicular axis (≥ 1.0)
-        #[arg(long, default_value = "4.0")]
-        stretch: f64,
-    },
-    /// Tiled noise — seamlessly repeating Perlin noise via 4-D torus mapping
-    TiledNoise {
-        rows: usize,
-        cols: usize,
-        /// Number of noise cycles per tile (higher = more features)
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-    },
-    /// Brownian motion — Gaussian random-walk visit density
-    BrownianMotion {
-        rows: usize,
-        cols: usize,
-        /// Number of walk steps
-        #[arg(long, default_value = "5000")]
-        n: usize,
-    },
-    /// Forest fire — Drossel-Schwabl cellular automaton burn-history map
-    ForestFire {
-        rows: usize,
-        cols: usize,
-        /// Per-step probability an empty cell becomes a tree
-        #[arg(long, default_value = "0.02")]
-        p_tree: f64,
-        /// Per-step probability a tree ignites spontaneously
-        #[arg(long, default_value = "0.001")]
-        p_lightning: f64,
-        /// Number of simulation steps
-        #[arg(long, default_value = "500")]
-        iterations: usize,
-    },
-    /// River network — D8 flow accumulation on fBm terrain
-    RiverNetwork {
-        rows: usize,
-        cols: usize,
-    },
-    /// Hexagonal Voronoi — BFS mosaic from a regular hexagonal seed lattice
-    HexagonalVoronoi {
-        rows: usize,
-        cols: usize,
-        /// Approximate number of hexagonal cells
-        #[arg(long, default_value = "50")]
-        n: usize,
-    },
-    /// Archimedean spiral gradient emanating from the grid centre
-    SpiralGradient {
-        rows: usize,
-        cols: usize,
-        /// Number of full spiral rotations across the grid radius
-        #[arg(long, default_value = "3.0")]
-        turns: f64,
-    },
-    /// Lognormal random field — exponential-transformed Gaussian field
-    LognormalField {
-        rows: usize,
-        cols: usize,
-        /// Gaussian kernel standard deviation in cells (correlation length)
-        #[arg(long, default_value = "10.0")]
-        sigma: f64,
-    },
-    /// Bak-Tang-Wiesenfeld sandpile — self-organized criticality grain-count map
-    Sandpile {
-        rows: usize,
-        cols: usize,
-        /// Number of grains to drop
-        #[arg(long, default_value = "5000")]
-        n: usize,
-    },
-    /// Correlated random walk visit-density map
-    CorrelatedWalk {
-        rows: usize,
-        cols: usize,
-        /// Number of walk steps
-        #[arg(long, default_value = "5000")]
-        n: usize,
-        /// Directional persistence (0 = isotropic, higher = straighter)
-        #[arg(long, default_value = "2.0")]
-        kappa: f64,
-    },
-    /// Schelling segregation — self-organising binary spatial patches
-    Schelling {
-        rows: usize,
-        cols: usize,
-        /// Minimum fraction of same-type neighbours for a cell to be happy (0–1)
-        #[arg(long, default_value = "0.5")]
-        tolerance: f64,
-        /// Number of relocation sweeps
-        #[arg(long, default_value = "50")]
-        iterations: usize,
-    },
-    /// Concentric sinusoidal rings
-    ConcentricRings {
-        rows: usize,
-        cols: usize,
-        /// Number of ring oscillations across the radius
-        #[arg(long, default_value = "5.0")]
-        frequency: f64,
-    },
-    /// Deterministic alternating checkerboard
-    Checkerboard {
-        rows: usize,
-        cols: usize,
-        /// Side length of each square in cells
-        #[arg(long, default_value = "10")]
-        scale: usize,
-    },
-    /// Voronoi crease (F2-F1) — highlights cell boundaries
-    VoronoiCrease {
-        rows: usize,
-        cols: usize,
-        /// Number of Voronoi seed points
-        #[arg(long, default_value = "30")]
-        n: usize,
-    },
-    /// Combined Perlin-Worley noise
-    PerlinWorley {
-        rows: usize,
-        cols: usize,
-        /// Spatial frequency scale
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-    },
-    /// Fault uplift ridges from random fault lines
-    FaultUplift {
-        rows: usize,
-        cols: usize,
-        /// Number of fault lines
-        #[arg(long, default_value = "50")]
-        n: usize,
-    },
-    /// Triangular tessellation via Delaunay triangulation
-    TriangularTessellation {
-        rows: usize,
-        cols: usize,
-        /// Number of seed points (triangles ≈ 2n)
-        #[arg(long, default_value = "30")]
-        n: usize,
-    },
-    /// Physarum slime mould transport network
-    Physarum {
-        rows: usize,
-        cols: usize,
-        /// Number of agents
-        #[arg(long, default_value = "1000")]
-        n: usize,
-        /// Number of simulation steps
-        #[arg(long, default_value = "300")]
-        iterations: usize,
-    },
-    /// Cahn-Hilliard spinodal decomposition
-    CahnHilliard {
-        rows: usize,
-        cols: usize,
-        /// Number of PDE time steps
-        #[arg(long, default_value = "2000")]
-        iterations: usize,
-    },
-    /// Crystal growth (Reiter's snowflake model)
-    CrystalGrowth {
-        rows: usize,
-        cols: usize,
-        /// Number of growth steps
-        #[arg(long, default_value = "300")]
-        iterations: usize,
-    },
-    /// Predator-prey spatial pattern (Lotka-Volterra PDE)
-    PredatorPrey {
-        rows: usize,
-        cols: usize,
-        /// Number of PDE time steps
-        #[arg(long, default_value = "500")]
-        iterations: usize,
-    },
-    /// Radial sweep — clockwise angle from grid centre
-    RadialSweep {
-        rows: usize,
-        cols: usize,
-    },
-    /// Musgrave heterogeneous multifractal terrain
-    MultifractalTerrain {
-        rows: usize,
-        cols: usize,
-        /// Base noise frequency (higher = more features)
-        #[arg(long, default_value = "4.0")]
-        scale: f64,
-        /// Number of octaves
-        #[arg(long, default_value = "6")]
-        octaves: usize,
-    },
-    /// Spectral blue noise (high-frequency energy, uniform point distribution)
-    BlueNoise {
-        rows: usize,
-        cols: usize,
-    },
-    /// Spatial SIR epidemic model (reaction-diffusion PDE)
-    SirEpidemic {
-        rows: usize,
-        cols: usize,
-        /// Infection rate
-        #[arg(long, default_value = "0.3")]
-        beta: f64,
-        /// Recovery rate
-        #[arg(long, default_value = "0.1")]
-        gamma: f64,
-        /// Number of PDE time steps
-        #[arg(long, default_value = "200")]
-        iterations: usize,
-    },
-    /// Thermal erosion of a Perlin heightmap via talus-slope diffusion
-    ThermalErosion {
-        rows: usize,
-        cols: usize,
-        /// Number of erosion passes
-        #[arg(long, default_value = "50")]
-        n: usize,
-    },
-    /// Space colonisation — auxin-based vascular branching network
-    SpaceColonization {
-        rows: usize,
-        cols: usize,
-        /// Number of auxin attractors
-        #[arg(long, default_value = "200")]
-        n: usize,
-    },
-    /// Substrate — Jared Tarbell crack propagation
-    Substrate {
-        rows: usize,
-        cols: usize,
-        /// Number of simulation steps
-        #[arg(long, default_value = "10")]
-        n: usize,
-    },
-    /// Conway's Game of Life visit-density map
-    GameOfLife {
-        rows: usize,
-        cols: usize,
-        /// Number of generations
-        #[arg(long, default_value = "200")]
-        iterations: usize,
-    },
-    /// Cyclic dominance (rock-paper-scissors) spiral domains
-    RockPaperScissors {
-        rows: usize,
-        cols: usize,
-        /// Number of synchronous update steps
-        #[arg(long, default_value = "200")]
-        iterations: usize,
-    },
-    /// Greenberg-Hastings excitable media — spiral waves and target patterns
-    ExcitableMedia {
-        rows: usize,
-        cols: usize,
-        /// Number of synchronous update steps
-        #[arg(long, default_value = "200")]
-        iterations: usize,
-    },
-    /// Truchet quarter-circle tile pattern
-    Truchet {
-        rows: usize,
-        cols: usize,
-        /// Tile side length in cells
-        #[arg(long, default_value = "10")]
-        n: usize,
+    /// List every algorithm with its parameters, defaults and valid ranges
+    List,
+    /// Run a pipeline of layers, steps and outputs described in a TOML or JSON file
+    Run {
+        /// Pipeline file (.toml or .json)
+        config: String,
+    },
+    /// Regenerate a grid written with --metadata, to --output
+    Reproduce {
+        /// The grid written with --metadata, or its .json sidecar
+        file: String,
     },
 }
 
 fn main() {
-    let cli = Cli::parse();
-    let seed = cli.seed;
-
-    let grid = match cli.command {
-        Commands::Random { rows, cols } => nlmrs::random(rows, cols, seed),
-        Commands::RandomElement { rows, cols, n } => nlmrs::random_element(rows, cols, n, seed),
-        Commands::PlanarGradient { rows, cols, direction } => {
-            nlmrs::planar_gradient(rows, cols, direction, seed)
-        }
-        Commands::EdgeGradient { rows, cols, direction } => {
-            nlmrs::edge_gradient(rows, cols, direction, seed)
-        }
-        Commands::DistanceGradient { rows, cols } => nlmrs::distance_gradient(rows, cols, seed),
-        Commands::WaveGradient { rows, cols, period, direction } => {
-            nlmrs::wave_gradient(rows, cols, period, direction, seed)
-        }
-        Commands::MidpointDisplacement { rows, cols, h } => {
-            nlmrs::midpoint_displacement(rows, cols, h, seed)
-        }
-        Commands::HillGrow { rows, cols, n, runaway, only_grow } => {
-            nlmrs::hill_grow(rows, cols, n, runaway, None, only_grow, seed)
-        }
-        Commands::Perlin { rows, cols, scale } => nlmrs::perlin_noise(rows, cols, scale, seed),
-        Commands::Fbm { rows, cols, scale, octaves, persistence, lacunarity } => {
-            nlmrs::fbm_noise(rows, cols, scale, octaves, persistence, lacunarity, seed)
-        }
-        Commands::Ridged { rows, cols, scale, octaves, persistence, lacunarity } => {
-            nlmrs::ridged_noise(rows, cols, scale, octaves, persistence, lacunarity, seed)
-        }
-        Commands::Billow { rows, cols, scale, octaves, persistence, lacunarity } => {
-            nlmrs::billow_noise(rows, cols, scale, octaves, persistence, lacunarity, seed)
-        }
-        Commands::Worley { rows, cols, scale } => nlmrs::worley_noise(rows, cols, scale, seed),
-        Commands::GaussianField { rows, cols, sigma } => {
-            nlmrs::gaussian_field(rows, cols, sigma, seed)
-        }
-        Commands::RandomCluster { rows, cols, n } => nlmrs::random_cluster(rows, cols, n, seed),
-        Commands::HybridNoise { rows, cols, scale, octaves, persistence, lacunarity } => {
-            nlmrs::hybrid_noise(rows, cols, scale, octaves, persistence, lacunarity, seed)
-        }
-        Commands::ValueNoise { rows, cols, scale } => nlmrs::value_noise(rows, cols, scale, seed),
-        Commands::Turbulence { rows, cols, scale, octaves, persistence, lacunarity } => {
-            nlmrs::turbulence(rows, cols, scale, octaves, persistence, lacunarity, seed)
-        }
-        Commands::DomainWarp { rows, cols, scale, warp_strength } => {
-            nlmrs::domain_warp(rows, cols, scale, warp_strength, seed)
-        }
-        Commands::Mosaic { rows, cols, n } => nlmrs::mosaic(rows, cols, n, seed),
-        Commands::RectangularCluster { rows, cols, n } => {
-            nlmrs::rectangular_cluster(rows, cols, n, seed)
-        }
-        Commands::Percolation { rows, cols, p } => nlmrs::percolation(rows, cols, p, seed),
-        Commands::BinarySpacePartitioning { rows, cols, n } => {
-            nlmrs::binary_space_partitioning(rows, cols, n, seed)
-        }
-        Commands::CellularAutomaton { rows, cols, p, iterations, birth_threshold, survival_threshold } => {
-            nlmrs::cellular_automaton(rows, cols, p, iterations, birth_threshold, survival_threshold, seed)
-        }
-        Commands::NeighbourhoodClustering { rows, cols, k, iterations } => {
-            nlmrs::neighbourhood_clustering(rows, cols, k, iterations, seed)
-        }
-        Commands::SpectralSynthesis { rows, cols, beta } => {
-            nlmrs::spectral_synthesis(rows, cols, beta, seed)
-        }
-        Commands::DiffusionLimitedAggregation { rows, cols, n } => {
-            nlmrs::diffusion_limited_aggregation(rows, cols, n, seed)
-        }
-        Commands::ReactionDiffusion { rows, cols, iterations, feed, kill } => {
-            nlmrs::reaction_diffusion(rows, cols, iterations, feed, kill, seed)
-        }
-        Commands::EdenGrowth { rows, cols, n } => nlmrs::eden_growth(rows, cols, n, seed),
-        Commands::FractalBrownianSurface { rows, cols, h } => {
-            nlmrs::fractal_brownian_surface(rows, cols, h, seed)
-        }
-        Commands::LandscapeGradient { rows, cols, direction, aspect } => {
-            nlmrs::landscape_gradient(rows, cols, direction, aspect, seed)
-        }
-        Commands::SimplexNoise { rows, cols, scale } => {
-            nlmrs::simplex_noise(rows, cols, scale, seed)
-        }
-        Commands::InvasionPercolation { rows, cols, n } => {
-            nlmrs::invasion_percolation(rows, cols, n, seed)
-        }
-        Commands::GaussianBlobs { rows, cols, n, sigma } => {
-            nlmrs::gaussian_blobs(rows, cols, n, sigma, seed)
-        }
-        Commands::IsingModel { rows, cols, beta, iterations } => {
-            nlmrs::ising_model(rows, cols, beta, iterations, seed)
-        }
-        Commands::VoronoiDistance { rows, cols, n } => {
-            nlmrs::voronoi_distance(rows, cols, n, seed)
-        }
-        Commands::SineComposite { rows, cols, waves } => {
-            nlmrs::sine_composite(rows, cols, waves, seed)
-        }
-        Commands::CurlNoise { rows, cols, scale } => nlmrs::curl_noise(rows, cols, scale, seed),
-        Commands::HydraulicErosion { rows, cols, n } => {
-            nlmrs::hydraulic_erosion(rows, cols, n, seed)
-        }
-        Commands::LevyFlight { rows, cols, n } => nlmrs::levy_flight(rows, cols, n, seed),
-        Commands::PoissonDisk { rows, cols, min_dist } => {
-            nlmrs::poisson_disk(rows, cols, min_dist, seed)
-        }
-        Commands::GaborNoise { rows, cols, scale, n } => {
-            nlmrs::gabor_noise(rows, cols, scale, n, seed)
-        }
-        Commands::SpotNoise { rows, cols, n } => nlmrs::spot_noise(rows, cols, n, seed),
-        Commands::AnisotropicNoise { rows, cols, scale, octaves, direction, stretch } => {
-            nlmrs::anisotropic_noise(rows, cols, scale, octaves, direction, stretch, seed)
-        }
-        Commands::TiledNoise { rows, cols, scale } => nlmrs::tiled_noise(rows, cols, scale, seed),
-        Commands::BrownianMotion { rows, cols, n } => nlmrs::brownian_motion(rows, cols, n, seed),
-        Commands::ForestFire { rows, cols, p_tree, p_lightning, iterations } => {
-            nlmrs::forest_fire(rows, cols, p_tree, p_lightning, iterations, seed)
-        }
-        Commands::RiverNetwork { rows, cols } => nlmrs::river_network(rows, cols, seed),
-        Commands::HexagonalVoronoi { rows, cols, n } => {
-            nlmrs::hexagonal_voronoi(rows, cols, n, seed)
-        }
-        Commands::SpiralGradient { rows, cols, turns } => {
-            nlmrs::spiral_gradient(rows, cols, turns, seed)
-        }
-        Commands::LognormalField { rows, cols, sigma } => {
-            nlmrs::lognormal_field(rows, cols, sigma, seed)
-        }
-        Commands::Sandpile { rows, cols, n } => nlmrs::sandpile(rows, cols, n, seed),
-        Commands::CorrelatedWalk { rows, cols, n, kappa } => {
-            nlmrs::correlated_walk(rows, cols, n, kappa, seed)
-        }
-        Commands::Schelling { rows, cols, tolerance, iterations } => {
-            nlmrs::schelling(rows, cols, tolerance, iterations, seed)
-        }
-        Commands::ConcentricRings { rows, cols, frequency } => {
-            nlmrs::concentric_rings(rows, cols, frequency, seed)
-        }
-        Commands::Checkerboard { rows, cols, scale } => {
-            nlmrs::checkerboard(rows, cols, scale, seed)
-        }
-        Commands::VoronoiCrease { rows, cols, n } => nlmrs::voronoi_crease(rows, cols, n, seed),
-        Commands::PerlinWorley { rows, cols, scale } => {
-            nlmrs::perlin_worley(rows, cols, scale, seed)
-        }
-        Commands::FaultUplift { rows, cols, n } => nlmrs::fault_uplift(rows, cols, n, seed),
-        Commands::TriangularTessellation { rows, cols, n } => {
-            nlmrs::triangular_tessellation(rows, cols, n, seed)
-        }
-        Commands::Physarum { rows, cols, n, iterations } => {
-            nlmrs::physarum(rows, cols, n, iterations, seed)
-        }
-        Commands::CahnHilliard { rows, cols, iterations } => {
-            nlmrs::cahn_hilliard(rows, cols, iterations, seed)
-        }
-        Commands::CrystalGrowth { rows, cols, iterations } => {
-            nlmrs::crystal_growth(rows, cols, iterations, seed)
-        }
-        Commands::PredatorPrey { rows, cols, iterations } => {
-            nlmrs::predator_prey(rows, cols, iterations, seed)
-        }
-        Commands::RadialSweep { rows, cols } => nlmrs::radial_sweep(rows, cols, seed),
-        Commands::MultifractalTerrain { rows, cols, scale, octaves } => {
-            nlmrs::multifractal_terrain(rows, cols, scale, octaves, seed)
-        }
-        Commands::BlueNoise { rows, cols } => nlmrs::blue_noise(rows, cols, seed),
-        Commands::SirEpidemic { rows, cols, beta, gamma, iterations } => {
-            nlmrs::sir_epidemic(rows, cols, beta, gamma, iterations, seed)
-        }
-        Commands::ThermalErosion { rows, cols, n } => nlmrs::thermal_erosion(rows, cols, n, seed),
-        Commands::SpaceColonization { rows, cols, n } => {
-            nlmrs::space_colonization(rows, cols, n, seed)
-        }
-        Commands::Substrate { rows, cols, n } => nlmrs::substrate(rows, cols, n, seed),
-        Commands::GameOfLife { rows, cols, iterations } => {
-            nlmrs::game_of_life(rows, cols, iterations, seed)
-        }
-        Commands::RockPaperScissors { rows, cols, iterations } => {
-            nlmrs::rock_paper_scissors(rows, cols, iterations, seed)
-        }
-        Commands::ExcitableMedia { rows, cols, iterations } => {
-            nlmrs::excitable_media(rows, cols, iterations, seed)
-        }
-        Commands::Truchet { rows, cols, n } => nlmrs::truchet(rows, cols, n, seed),
+    let matches = command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // A grid is only reproducible from its metadata if its seed is known.
+    let seed = match cli.seed {
+        None if cli.metadata => Some(rand::random()),
+        seed => seed,
     };
 
-    let mut grid = grid;
-    if let Some(n) = cli.classify {
-        nlmrs::classify(&mut grid, n);
+    let (name, _) = matches.subcommand().expect("subcommand is required");
+    if Commands::has_subcommand(name) {
+        match Commands::from_arg_matches(&matches).unwrap_or_else(|e| e.exit()) {
+            Commands::List => print_registry(),
+            Commands::Run { config } => run_pipeline(&config, cli.seed, cli.metadata),
+            Commands::Reproduce { file } => reproduce(&cli, &file),
+        }
+        return;
     }
-    if let Some(t) = cli.threshold {
-        nlmrs::threshold(&mut grid, t);
+
+    if let Some(tile_size) = cli.tile_size {
+        run_tiled(&cli, &matches, tile_size, seed);
+        return;
+    }
+
+    if cli.replicates != 1 || !cli.sweep.is_empty() {
+        run_batch(&cli, &matches);
+        return;
     }
 
+    let (generator, rows, cols, params) = subcommand_params(&matches);
+    let mut grid = or_exit(generator.try_generate(rows, cols, &params, seed));
+    let (mask, transform) = read_templates(&cli, grid.rows, grid.cols);
+    post_process(&cli, mask.as_ref(), &mut grid);
+
+    let geo = GeoGrid { grid, transform };
+    let grid = &geo.grid;
     let path = &cli.output;
-    let ext = path.rsplit('.').next().unwrap_or("png");
-
-    let result = match ext {
-        "csv" => export::write_to_csv(&grid, path),
-        "json" => export::write_to_json(&grid, path),
-        "asc" => export::write_to_ascii_grid(&grid, path),
-        "tif" | "tiff" => export::write_to_tiff(&grid, path),
-        "png" if cli.grayscale => export::write_to_png_grayscale(&grid, path),
-        "png" | _ => export::write_to_png(&grid, path),
-    };
+    or_exit(create_parent_dir(path));
+    let result = export::write(&geo, path, &write_options(&cli));
 
     if let Err(e) = result {
         eprintln!("Error writing output: {e}");
         std::process::exit(1);
     }
+    if cli.metadata {
+        let recipe = recipe(&cli, generator, rows, cols, params, seed.expect("seeded above"));
+        or_exit(export::write_metadata(path, &Metadata::new(recipe, LAYER)));
+    }
 
     println!("Written {}×{} grid to {}", grid.rows, grid.cols, path);
 }
+
+/// The command line: the global options, the subcommands in [`Commands`] and
+/// one subcommand per registered generator.
+fn command() -> clap::Command {
+    Commands::augment_subcommands(Cli::command())
+        .subcommands(registry::generators().map(generator_command))
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+}
+
+/// A subcommand for `generator`, named after it in kebab case, taking the
+/// grid size and an option per parameter in its schema.
+fn generator_command(generator: &'static dyn Generator) -> clap::Command {
+    let name = generator.name().replace('_', "-");
+    let mut command = clap::Command::new(name.clone())
+        .about(generator.description())
+        .arg(size_arg("rows", "Number of rows"))
+        .arg(size_arg("cols", "Number of columns"));
+    // Earlier releases named the noise subcommands without the suffix.
+    if let Some(short) = name.strip_suffix("-noise") {
+        command = command.visible_alias(short.to_string());
+    }
+    command = command.next_help_heading("Parameters");
+    for p in generator.params() {
+        let mut arg = Arg::new(p.name)
+            .long(p.name.replace('_', "-"))
+            .value_name(p.name.to_uppercase())
+            .help(p.description);
+        if p.kind == ParamKind::Bool {
+            // `--periodic` switches a flag on; `--periodic=false` switches it off.
+            arg = arg
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("true");
+        }
+        if let Some(default) = &p.default {
+            arg = arg.default_value(default.to_string());
+        }
+        command = command.arg(arg);
+    }
+    command
+}
+
+fn size_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .required(true)
+        .value_parser(clap::value_parser!(usize))
+        .help(help)
+}
+
+/// Looks up the registry generator behind the chosen subcommand and reads its
+/// grid size and parameters from the command line.
+fn subcommand_params(matches: &ArgMatches) -> (&'static dyn Generator, usize, usize, Params) {
+    let (name, args) = matches.subcommand().expect("subcommand is required");
+    let generator = or_exit(registry::try_get(&name.replace('-', "_")));
+    let rows = *args.get_one::<usize>("rows").expect("rows is required");
+    let cols = *args.get_one::<usize>("cols").expect("cols is required");
+
+    // Subcommand options share their names with the registry parameters.
+    let mut params = Params::new();
+    for p in generator.params() {
+        if let Ok(Some(mut raw)) = args.try_get_raw(p.name) {
+            if let Some(text) = raw.next().and_then(|v| v.to_str()) {
+                params.set(p.name, or_exit(p.parse(text)));
+            }
+        }
+    }
+    (generator, rows, cols, params)
+}
+
+/// Name of the single layer in a --metadata recipe.
+const LAYER: &str = "grid";
+
+/// The recipe recorded by --metadata: the generator as a one-layer pipeline
+/// followed by the post-processing options, with file paths made absolute.
+fn recipe(
+    cli: &Cli,
+    generator: &dyn Generator,
+    rows: usize,
+    cols: usize,
+    params: Params,
+    seed: u64,
+) -> Pipeline {
+    let absolute = |path: &String| {
+        std::fs::canonicalize(path)
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| path.clone())
+    };
+    let target = || LAYER.to_string();
+    let mut steps = Vec::new();
+    if let Some(path) = &cli.mask {
+        steps.push(Step::Mask { target: target(), mask: MaskSource::File(absolute(path)) });
+        steps.push(Step::Scale { target: target() });
+    }
+    if let Some(classes) = cli.classify {
+        steps.push(Step::Classify { target: target(), classes });
+    }
+    if let Some(weights) = &cli.weights {
+        steps.push(Step::ClassifyWeighted { target: target(), weights: weights.clone() });
+    }
+    if let Some(value) = cli.threshold {
+        steps.push(Step::Threshold { target: target(), value });
+    }
+    Pipeline {
+        rows,
+        cols,
+        seed: None,
+        like: cli.like.as_ref().or(cli.mask.as_ref()).map(absolute),
+        layers: vec![Layer {
+            name: target(),
+            algorithm: generator.name().to_string(),
+            params,
+            seed: Some(seed),
+        }],
+        steps,
+        outputs: Vec::new(),
+    }
+}
+
+/// Generates every combination of --sweep values --replicates times through the
+/// registry, writing one file per run and a manifest of seeds and parameters.
+fn run_batch(cli: &Cli, matches: &ArgMatches) {
+    if cli.replicates == 0 {
+        exit_with("--replicates must be at least 1");
+    }
+    let (generator, rows, cols, base) = subcommand_params(matches);
+    let sweeps: Vec<Sweep> = cli
+        .sweep
+        .iter()
+        .map(|spec| or_exit(Sweep::parse(spec, generator)))
+        .collect();
+    let seed = cli.seed.unwrap_or_else(rand::random);
+    let runs = or_exit(sweep::runs(generator, &base, &sweeps, cli.replicates, seed));
+    // A .npz --output without placeholders collects every run into one
+    // archive, as arrays arr_0, arr_1, ... in run order.
+    let stack = cli.output.ends_with(".npz") && !cli.output.contains('{');
+    let paths = if stack {
+        if cli.metadata || cli.labels {
+            exit_with("--metadata and --labels need one file per run, not a .npz stack");
+        }
+        (0..runs.len()).map(|i| format!("{}:arr_{i}", cli.output)).collect()
+    } else {
+        or_exit(sweep::output_paths(&cli.output, generator, &runs))
+    };
+
+    let (mask, transform) = read_templates(cli, rows, cols);
+    let options = write_options(cli);
+    let generate = |run: &sweep::Run| -> nlmrs::error::Result<Grid> {
+        let mut grid = generator.try_generate(rows, cols, &run.params, Some(run.seed))?;
+        post_process(cli, mask.as_ref(), &mut grid);
+        Ok(grid)
+    };
+    if stack {
+        #[cfg(feature = "parallel")]
+        let grids: Vec<_> = runs.par_iter().map(generate).collect();
+        #[cfg(not(feature = "parallel"))]
+        let grids: Vec<_> = runs.iter().map(generate).collect();
+        let grids = or_exit(grids.into_iter().collect::<nlmrs::error::Result<Vec<_>>>());
+        let names: Vec<String> = (0..grids.len()).map(|i| format!("arr_{i}")).collect();
+        let arrays: Vec<(&str, &Grid)> = names.iter().map(String::as_str).zip(&grids).collect();
+        or_exit(create_parent_dir(&cli.output));
+        if let Err(e) = export::write_to_npz(&arrays, &cli.output) {
+            exit_with(format!("writing {}: {e}", cli.output));
+        }
+    } else {
+        let job = |(run, path): (&sweep::Run, &String)| -> nlmrs::error::Result<()> {
+            let grid = generate(run)?;
+            create_parent_dir(path)?;
+            let geo = GeoGrid { grid, transform: transform.clone() };
+            export::write(&geo, path, &options)?;
+            if cli.metadata {
+                let recipe = recipe(cli, generator, rows, cols, run.params.clone(), run.seed);
+                export::write_metadata(path, &Metadata::new(recipe, LAYER))?;
+            }
+            Ok(())
+        };
+        #[cfg(feature = "parallel")]
+        let results: Vec<_> = runs.par_iter().zip(paths.par_iter()).map(job).collect();
+        #[cfg(not(feature = "parallel"))]
+        let results: Vec<_> = runs.iter().zip(paths.iter()).map(job).collect();
+        for (result, path) in results.into_iter().zip(&paths) {
+            if let Err(e) = result {
+                exit_with(format!("writing {path}: {e}"));
+            }
+        }
+    }
+
+    let manifest = cli.manifest.clone().unwrap_or_else(|| {
+        let dir = Path::new(&cli.output).parent().unwrap_or(Path::new(""));
+        dir.join("manifest.csv").to_string_lossy().into_owned()
+    });
+    or_exit(create_parent_dir(&manifest));
+    or_exit(sweep::write_manifest(&manifest, generator, rows, cols, &runs, &paths));
+    println!(
+        "Written {} {}×{} grids ({} replicates from seed {seed}) and manifest {manifest}",
+        runs.len(),
+        rows,
+        cols,
+        cli.replicates
+    );
+}
+
+/// Streams the chosen noise generator to --output tile by tile.
+fn run_tiled(cli: &Cli, matches: &ArgMatches, tile_size: usize, seed: Option<u64>) {
+    let unsupported = [
+        ("--mask", cli.mask.is_some()),
+        ("--like", cli.like.is_some()),
+        ("--classify", cli.classify.is_some()),
+        ("--weights", cli.weights.is_some()),
+        ("--threshold", cli.threshold.is_some()),
+        ("--labels", cli.labels),
+        ("--replicates", cli.replicates != 1),
+        ("--sweep", !cli.sweep.is_empty()),
+    ];
+    if let Some((flag, _)) = unsupported.iter().find(|(_, set)| *set) {
+        exit_with(format!("{flag} cannot be combined with --tile-size"));
+    }
+    let tiff_format = write_options(cli).tiff_format;
+    let path = &cli.output;
+    if matches!(path.rsplit('.').next(), Some("tif" | "tiff")) && tiff_format.is_none() {
+        exit_with("tiled .tif output needs --tiff-format f32 or f64");
+    }
+
+    let (generator, rows, cols, params) = subcommand_params(matches);
+    let noise = or_exit(TiledNoise::new(generator.name(), rows, cols, &params, seed));
+    let options = export::TileOptions {
+        tile_size,
+        tiff_format: tiff_format.unwrap_or(TiffSampleFormat::Float32),
+    };
+    or_exit(create_parent_dir(path));
+    or_exit(noise.write(path, &GeoTransform::default(), options));
+    if cli.metadata {
+        let recipe = recipe(cli, generator, rows, cols, params, noise.seed);
+        or_exit(export::write_metadata(path, &Metadata::new(recipe, LAYER)));
+    }
+    println!("Written {rows}×{cols} grid to {path} in {tile_size}×{tile_size} tiles");
+}
+
+/// Reads the --mask grid and the output georeferencing (from --like, else
+/// --mask, else the default) for a rows x cols grid.
+fn read_templates(cli: &Cli, rows: usize, cols: usize) -> (Option<Grid>, GeoTransform) {
+    let mut transform = cli
+        .like
+        .as_deref()
+        .map(|path| read_template(path, "template", rows, cols).transform);
+    let mask = cli.mask.as_deref().map(|path| {
+        let mask = read_template(path, "mask", rows, cols);
+        transform.get_or_insert(mask.transform);
+        mask.grid
+    });
+    (mask, transform.unwrap_or_default())
+}
+
+/// Applies --mask, --classify, --weights and --threshold in that order.
+fn post_process(cli: &Cli, mask: Option<&Grid>, grid: &mut Grid) {
+    if let Some(mask) = mask {
+        nlmrs::apply_mask(grid, mask);
+        nlmrs::scale(grid);
+    }
+    if let Some(n) = cli.classify {
+        nlmrs::classify(grid, n);
+    }
+    if let Some(weights) = &cli.weights {
+        nlmrs::classify_weighted(grid, weights);
+    }
+    if let Some(t) = cli.threshold {
+        nlmrs::threshold(grid, t);
+    }
+}
+
+fn write_options(cli: &Cli) -> export::WriteOptions {
+    export::WriteOptions {
+        grayscale: cli.grayscale,
+        colormap: cli
+            .colormap
+            .as_deref()
+            .map(|spec| or_exit(Colormap::parse(spec)))
+            .unwrap_or_default(),
+        legend: cli.legend,
+        labels: cli.labels,
+        tiff_format: match cli.tiff_format {
+            TiffFormat::U16 => None,
+            TiffFormat::F32 => Some(TiffSampleFormat::Float32),
+            TiffFormat::F64 => Some(TiffSampleFormat::Float64),
+        },
+    }
+}
+
+fn create_parent_dir(path: &str) -> std::io::Result<()> {
+    match Path::new(path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => std::fs::create_dir_all(dir),
+        _ => Ok(()),
+    }
+}
+
+fn or_exit<T, E: Display>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|e| exit_with(e))
+}
+
+fn exit_with(message: impl Display) -> ! {
+    eprintln!("Error: {message}");
+    std::process::exit(1);
+}
+
+/// Reads a raster in any format [`export::read`] recognises that must match
+/// the generated grid's dimensions, exiting with an error message otherwise.
+fn read_template(path: &str, what: &str, rows: usize, cols: usize) -> GeoGrid {
+    let geo = match export::read(path) {
+        Ok(geo) => geo,
+        Err(e) => {
+            eprintln!("Error reading {what}: {e}");
+            std::process::exit(1);
+        }
+    };
+    if (geo.grid.rows, geo.grid.cols) != (rows, cols) {
+        eprintln!(
+            "Error: {what} is {}x{} but the grid is {rows}x{cols}",
+            geo.grid.rows, geo.grid.cols
+        );
+        std::process::exit(1);
+    }
+    geo
+}
+
+/// Loads, runs and writes a pipeline file, exiting with an error message on failure.
+fn run_pipeline(config: &str, seed: Option<u64>, metadata: bool) {
+    let result = Pipeline::load(config).and_then(|mut pipeline| {
+        if seed.is_some() {
+            pipeline.seed = seed;
+        }
+        for output in &mut pipeline.outputs {
+            output.metadata |= metadata;
+        }
+        pipeline.execute().map(|layers| (pipeline, layers))
+    });
+    let (pipeline, layers) = match result {
+        Ok(done) => done,
+        Err(e) => {
+            eprintln!("Error running {config}: {e}");
+            std::process::exit(1);
+        }
+    };
+    for output in &pipeline.outputs {
+        let grid = &layers[&output.layer];
+        println!("Written {}×{} grid '{}' to {}", grid.rows, grid.cols, output.layer, output.path);
+    }
+}
+
+/// Regenerates the grid described by a --metadata sidecar and writes it to --output.
+fn reproduce(cli: &Cli, path: &str) {
+    let metadata = or_exit(export::read_metadata(path));
+    let version = env!("CARGO_PKG_VERSION");
+    if metadata.version != version {
+        eprintln!(
+            "Warning: {path} was written by nlmrs {}, this is {version}; values may differ",
+            metadata.version
+        );
+    }
+    if metadata.rng != nlmrs::RNG {
+        eprintln!(
+            "Warning: {path} was seeded from random stream {}, this is {}; values will differ",
+            metadata.rng,
+            nlmrs::RNG
+        );
+    }
+    let output = Output {
+        layer: metadata.layer.clone(),
+        path: cli.output.clone(),
+        options: write_options(cli),
+        metadata: cli.metadata,
+    };
+    let pipeline = Pipeline { outputs: vec![output], ..metadata.pipeline };
+    let layers = or_exit(pipeline.execute());
+    let grid = &layers[&metadata.layer];
+    println!("Written {}×{} grid to {}", grid.rows, grid.cols, cli.output);
+}
+
+fn print_registry() {
+    for g in registry::generators() {
+        let periodic = if g.supports_periodic() { " [periodic]" } else { "" };
+        println!("{} ({}){periodic} — {}", g.name(), g.category(), g.description());
+        for p in g.params() {
+            let default = match &p.default {
+                Some(Value::Float(v)) => v.to_string(),
+                Some(Value::Int(v)) => v.to_string(),
+                Some(Value::Bool(v)) => v.to_string(),
+                Some(Value::Kernel(_)) | None => "none".to_string(),
+            };
+            let range = match p.kind {
+                ParamKind::Float | ParamKind::Int => format!("[{}, {}]", p.min, p.max),
+                _ => String::new(),
+            };
+            println!(
+                "    {:<20} {:<7} default {:<8} {:<14} {}",
+                p.name,
+                format!("{:?}", p.kind).to_lowercase(),
+                default,
+                range,
+                p.description
+            );
+        }
+    }
+}
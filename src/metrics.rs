@@ -0,0 +1,388 @@
+//! FRAGSTATS-style landscape metrics for classified grids.
+//!
+//! Every distinct cell value is treated as a class, so grids are expected to have
+//! been passed through [`classify`](crate::classify), [`threshold`](crate::threshold)
+//! or produced by a categorical generator such as `percolation` or `mosaic`.
+//!
+//! Patches are 8-connected (the FRAGSTATS default). Areas are measured in cells
+//! and edge lengths in cell sides, i.e. a cell size of 1. Edges along the
+//! landscape boundary count towards patch perimeter but not towards edge density.
+//! NoData (`NaN`) cells are excluded from the landscape: they form no class,
+//! do not count towards its area, and edges facing them are treated like the
+//! landscape boundary. Metrics that are undefined for a configuration (e.g.
+//! contagion with a single class) are returned as `NaN`.
+//!
+//! Based on: McGarigal, K., Cushman, S. A. & Ene, E. (2012). FRAGSTATS v4:
+//! Spatial Pattern Analysis Program for Categorical and Continuous Maps.
+
+use crate::grid::{Boundary, Grid};
+use crate::operation::{label_patches, Connectivity};
+
+/// Patch-level metrics for a single 8-connected patch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatchMetrics {
+    /// Cell value shared by every cell in the patch.
+    pub class: f64,
+    /// Number of cells in the patch.
+    pub area: usize,
+    /// Number of cell sides bordering another class or the landscape boundary.
+    pub perimeter: usize,
+}
+
+/// Class-level metrics for one cell value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassMetrics {
+    /// Cell value identifying the class.
+    pub class: f64,
+    /// Fraction of the landscape covered by the class (PLAND / 100).
+    pub proportion: f64,
+    /// Number of patches (NP).
+    pub number_of_patches: usize,
+    /// Mean patch area in cells (AREA_MN).
+    pub mean_patch_area: f64,
+    /// Class edge length per landscape cell (ED).
+    pub edge_density: f64,
+    /// Largest patch as a percentage of the landscape (LPI).
+    pub largest_patch_index: f64,
+    /// Like adjacencies as a percentage of the maximum possible (AI).
+    pub aggregation_index: f64,
+    /// Patch cohesion index (COHESION).
+    pub cohesion: f64,
+}
+
+/// Landscape-level metrics summarising all classes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LandscapeMetrics {
+    /// Number of distinct classes.
+    pub number_of_classes: usize,
+    /// Total number of patches across all classes (NP).
+    pub number_of_patches: usize,
+    /// Mean patch area in cells (AREA_MN).
+    pub mean_patch_area: f64,
+    /// Total edge length per landscape cell (ED).
+    pub edge_density: f64,
+    /// Largest patch as a percentage of the landscape (LPI).
+    pub largest_patch_index: f64,
+    /// Contagion index, 0–100 (CONTAG).
+    pub contagion: f64,
+    /// Shannon diversity index (SHDI).
+    pub shannon_diversity: f64,
+    /// Area-weighted aggregation index, 0–100 (AI).
+    pub aggregation_index: f64,
+    /// Patch cohesion index over all patches (COHESION).
+    pub cohesion: f64,
+}
+
+/// Grid cells mapped to dense class indices plus the patches found in it.
+struct Analysis {
+    classes: Vec<f64>,
+    /// Per-patch (class index, area, perimeter).
+    patches: Vec<(usize, usize, usize)>,
+    /// Row-major `classes.len()²` matrix of rook adjacencies, counted from both sides.
+    adjacency: Vec<usize>,
+    /// Number of cells in each class.
+    counts: Vec<usize>,
+    /// Number of cells that are not NoData.
+    total: usize,
+}
+
+fn analyse(grid: &Grid) -> Analysis {
+    let rows = grid.rows;
+    let cols = grid.cols;
+
+    let mut classes: Vec<f64> = grid.data.iter().copied().filter(|v| !v.is_nan()).collect();
+    let total = classes.len();
+    classes.sort_by(|a, b| a.total_cmp(b));
+    classes.dedup_by(|a, b| a.total_cmp(b).is_eq());
+    let class_of: Vec<Option<usize>> = grid
+        .data
+        .iter()
+        .map(|v| if v.is_nan() { None } else { classes.binary_search_by(|c| c.total_cmp(v)).ok() })
+        .collect();
+
+    let m = classes.len();
+    let mut counts = vec![0usize; m];
+    let mut adjacency = vec![0usize; m * m];
+    let mut add_pair = |k: Option<usize>, o: Option<usize>| {
+        if let (Some(k), Some(o)) = (k, o) {
+            adjacency[k * m + o] += 1;
+            adjacency[o * m + k] += 1;
+        }
+    };
+    for (idx, &k) in class_of.iter().enumerate() {
+        if let Some(k) = k {
+            counts[k] += 1;
+        }
+        let (r, c) = (idx / cols, idx % cols);
+        if c + 1 < cols {
+            add_pair(k, class_of[idx + 1]);
+        }
+        if r + 1 < rows {
+            add_pair(k, class_of[idx + cols]);
+        }
+    }
+
+    let patches = label_patches(grid, Connectivity::Queen, Boundary::Clamped)
+        .1
+        .into_iter()
+        .map(|p| {
+            let k = classes.binary_search_by(|c| c.total_cmp(&p.value)).unwrap();
+            (k, p.area, p.perimeter)
+        })
+        .collect();
+
+    Analysis { classes, patches, adjacency, counts, total }
+}
+
+/// Maximum possible number of like adjacencies for a class of `area` cells,
+/// attained when the cells form the most compact (near-square) shape.
+fn max_like_adjacencies(area: usize) -> usize {
+    let n = (area as f64).sqrt().floor() as usize;
+    let m = area - n * n;
+    if m == 0 {
+        2 * n * (n.saturating_sub(1))
+    } else if m <= n {
+        2 * n * (n - 1) + 2 * m - 1
+    } else {
+        2 * n * (n - 1) + 2 * m - 2
+    }
+}
+
+/// Patch cohesion over a set of (area, perimeter) pairs in a landscape of `total` cells.
+fn cohesion(patches: impl Iterator<Item = (usize, usize)>, total: usize) -> f64 {
+    let (mut sum_p, mut sum_pa) = (0.0f64, 0.0f64);
+    for (a, p) in patches {
+        sum_p += p as f64;
+        sum_pa += p as f64 * (a as f64).sqrt();
+    }
+    let z = total as f64;
+    if sum_pa == 0.0 || z <= 1.0 {
+        return f64::NAN;
+    }
+    (1.0 - sum_p / sum_pa) / (1.0 - 1.0 / z.sqrt()) * 100.0
+}
+
+/// Returns the area and perimeter of every 8-connected patch in row-major
+/// order of each patch's first cell.
+pub fn patch_metrics(grid: &Grid) -> Vec<PatchMetrics> {
+    let a = analyse(grid);
+    a.patches
+        .iter()
+        .map(|&(k, area, perimeter)| PatchMetrics { class: a.classes[k], area, perimeter })
+        .collect()
+}
+
+/// Returns class-level metrics for every distinct cell value, in ascending
+/// order of value.
+pub fn class_metrics(grid: &Grid) -> Vec<ClassMetrics> {
+    let a = analyse(grid);
+    let m = a.classes.len();
+    let z = a.total as f64;
+
+    (0..m)
+        .map(|k| {
+            let patches: Vec<(usize, usize)> = a
+                .patches
+                .iter()
+                .filter(|p| p.0 == k)
+                .map(|&(_, area, perim)| (area, perim))
+                .collect();
+            let np = patches.len();
+            let largest = patches.iter().map(|p| p.0).max().unwrap_or(0);
+            let edge: usize = (0..m).filter(|&o| o != k).map(|o| a.adjacency[k * m + o]).sum();
+            let like = a.adjacency[k * m + k] / 2;
+            let max_like = max_like_adjacencies(a.counts[k]);
+            ClassMetrics {
+                class: a.classes[k],
+                proportion: a.counts[k] as f64 / z,
+                number_of_patches: np,
+                mean_patch_area: a.counts[k] as f64 / np as f64,
+                edge_density: edge as f64 / z,
+                largest_patch_index: largest as f64 / z * 100.0,
+                aggregation_index: if max_like == 0 {
+                    f64::NAN
+                } else {
+                    like as f64 / max_like as f64 * 100.0
+                },
+                cohesion: cohesion(patches.into_iter(), a.total),
+            }
+        })
+        .collect()
+}
+
+/// Returns landscape-level metrics summarising every class in the grid.
+///
+/// Returns `NaN` for every ratio when the grid is empty.
+pub fn landscape_metrics(grid: &Grid) -> LandscapeMetrics {
+    let a = analyse(grid);
+    let m = a.classes.len();
+    let z = a.total as f64;
+    let np = a.patches.len();
+
+    let mut edge = 0usize;
+    for k in 0..m {
+        for o in (k + 1)..m {
+            edge += a.adjacency[k * m + o];
+        }
+    }
+
+    let mut shannon = 0.0f64;
+    let mut aggregation = 0.0f64;
+    let mut contagion_sum = 0.0f64;
+    for k in 0..m {
+        let p = a.counts[k] as f64 / z;
+        shannon -= p * p.ln();
+
+        let max_like = max_like_adjacencies(a.counts[k]);
+        if max_like > 0 {
+            aggregation += (a.adjacency[k * m + k] / 2) as f64 / max_like as f64 * p;
+        }
+
+        let row_total: usize = a.adjacency[k * m..(k + 1) * m].iter().sum();
+        if row_total > 0 {
+            for o in 0..m {
+                let g = a.adjacency[k * m + o];
+                if g > 0 {
+                    let q = p * g as f64 / row_total as f64;
+                    contagion_sum += q * q.ln();
+                }
+            }
+        }
+    }
+
+    LandscapeMetrics {
+        number_of_classes: m,
+        number_of_patches: np,
+        mean_patch_area: z / np as f64,
+        edge_density: edge as f64 / z,
+        largest_patch_index: a.patches.iter().map(|p| p.1).max().unwrap_or(0) as f64 / z * 100.0,
+        contagion: if m < 2 {
+            f64::NAN
+        } else {
+            (1.0 + contagion_sum / (2.0 * (m as f64).ln())) * 100.0
+        },
+        shannon_diversity: if m == 0 { f64::NAN } else { shannon },
+        aggregation_index: if m == 0 { f64::NAN } else { aggregation * 100.0 },
+        cohesion: cohesion(a.patches.iter().map(|p| (p.1, p.2)), a.total),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{percolation, random_cluster, threshold};
+
+    fn halves() -> Grid {
+        // Left half 0.0, right half 1.0.
+        let mut grid = Grid::new(10, 10);
+        for i in 0..10 {
+            for j in 5..10 {
+                grid[i][j] = 1.0;
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_patch_metrics_halves() {
+        let patches = patch_metrics(&halves());
+        assert_eq!(patches.len(), 2);
+        for p in &patches {
+            assert_eq!(p.area, 50);
+            // 5 + 5 along top/bottom, 10 along the outer side, 10 along the shared edge.
+            assert_eq!(p.perimeter, 30);
+        }
+    }
+
+    #[test]
+    fn test_patch_metrics_diagonal_is_one_patch() {
+        let mut grid = Grid::new(3, 3);
+        grid[0][0] = 1.0;
+        grid[1][1] = 1.0;
+        grid[2][2] = 1.0;
+        let ones = patch_metrics(&grid).iter().filter(|p| p.class == 1.0).count();
+        assert_eq!(ones, 1);
+    }
+
+    #[test]
+    fn test_class_metrics_halves() {
+        let metrics = class_metrics(&halves());
+        assert_eq!(metrics.len(), 2);
+        for c in &metrics {
+            assert_eq!(c.number_of_patches, 1);
+            assert_eq!(c.proportion, 0.5);
+            assert_eq!(c.mean_patch_area, 50.0);
+            assert_eq!(c.largest_patch_index, 50.0);
+            assert!((c.edge_density - 0.1).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_aggregation_index_square_is_maximal() {
+        let mut grid = Grid::new(10, 10);
+        for i in 0..4 {
+            for j in 0..4 {
+                grid[i][j] = 1.0;
+            }
+        }
+        let ones = class_metrics(&grid).into_iter().find(|c| c.class == 1.0).unwrap();
+        assert!((ones.aggregation_index - 100.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_landscape_metrics_uniform() {
+        let m = landscape_metrics(&Grid::filled(10, 10, 0.3));
+        assert_eq!(m.number_of_classes, 1);
+        assert_eq!(m.number_of_patches, 1);
+        assert_eq!(m.edge_density, 0.0);
+        assert_eq!(m.largest_patch_index, 100.0);
+        assert_eq!(m.shannon_diversity, 0.0);
+        assert!(m.contagion.is_nan());
+    }
+
+    #[test]
+    fn test_landscape_metrics_halves() {
+        let m = landscape_metrics(&halves());
+        assert_eq!(m.number_of_patches, 2);
+        assert!((m.shannon_diversity - 2f64.ln()).abs() < 1e-12);
+        assert!((m.edge_density - 0.1).abs() < 1e-12);
+        // Two equally abundant classes cap contagion at 50 even when fully clumped.
+        assert!(m.contagion > 30.0 && m.contagion < 50.0);
+    }
+
+    #[test]
+    fn test_contagion_orders_clustered_above_random() {
+        let random = percolation(100, 100, 0.5, Some(1));
+        let mut clustered = random_cluster(100, 100, 20, Some(1));
+        threshold(&mut clustered, 0.5);
+        let a = landscape_metrics(&random);
+        let b = landscape_metrics(&clustered);
+        assert!(b.contagion > a.contagion);
+        assert!(b.aggregation_index > a.aggregation_index);
+        assert!(b.number_of_patches < a.number_of_patches);
+    }
+
+    #[test]
+    fn test_metrics_ignore_nodata() {
+        // Masking out the right half leaves a single class over 50 cells.
+        let mut grid = halves();
+        for i in 0..10 {
+            for j in 5..10 {
+                grid[i][j] = f64::NAN;
+            }
+        }
+        let m = landscape_metrics(&grid);
+        assert_eq!(m.number_of_classes, 1);
+        assert_eq!(m.number_of_patches, 1);
+        assert_eq!(m.largest_patch_index, 100.0);
+        assert_eq!(class_metrics(&grid)[0].proportion, 1.0);
+        assert_eq!(patch_metrics(&grid)[0].area, 50);
+    }
+
+    #[test]
+    fn test_metrics_empty_grid() {
+        assert!(patch_metrics(&Grid::new(0, 0)).is_empty());
+        assert!(class_metrics(&Grid::new(0, 0)).is_empty());
+        assert_eq!(landscape_metrics(&Grid::new(0, 0)).number_of_patches, 0);
+    }
+}
@@ -0,0 +1,462 @@
+//! Morphological operations for cleaning binary and classified grids.
+//!
+//! [`erode`], [`dilate`], [`open`] and [`close`] use a 3 x 3 structuring
+//! element shaped by a [`Connectivity`]: the cell and its 4 rook neighbours,
+//! or all 8 queen neighbours. On binary 0/1 grids they are the usual binary
+//! operations; on other grids they take the minimum and maximum of each
+//! neighbourhood, so on a classified grid erosion shrinks the higher classes
+//! into the lower ones. [`fill_holes`] and [`skeletonize`] treat non-zero
+//! cells as foreground, and [`sieve`] merges small patches of any class into
+//! their neighbours.
+//!
+//! ```
+//! use nlmrs::morphology::{close, fill_holes, sieve};
+//! use nlmrs::{percolation, Boundary, Connectivity};
+//!
+//! let mut habitat = percolation(100, 100, 0.6, Some(42));
+//! close(&mut habitat, Connectivity::Rook, Boundary::Clamped);
+//! fill_holes(&mut habitat, Connectivity::Rook);
+//! sieve(&mut habitat, 10, Connectivity::Rook, Boundary::Clamped);
+//! ```
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::focal::{focal, Kernel, Statistic};
+use crate::grid::{Boundary, Grid};
+use crate::operation::{label_patches, Connectivity};
+
+/// Erosion, dilation, opening or closing, as a value for [`Morphology::apply`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Morphology {
+    /// [`erode`].
+    Erode,
+    /// [`dilate`].
+    Dilate,
+    /// [`open`].
+    Open,
+    /// [`close`].
+    Close,
+}
+
+impl Morphology {
+    /// Every operation, in declaration order.
+    pub const ALL: [Morphology; 4] =
+        [Morphology::Erode, Morphology::Dilate, Morphology::Open, Morphology::Close];
+
+    /// Lower-case name, as used in pipeline files: `"erode"`, `"dilate"`,
+    /// `"open"` or `"close"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Morphology::Erode => "erode",
+            Morphology::Dilate => "dilate",
+            Morphology::Open => "open",
+            Morphology::Close => "close",
+        }
+    }
+
+    /// The operation called `name`, as returned by [`Morphology::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Morphology::ALL.into_iter().find(|m| m.name() == name)
+    }
+
+    /// Applies the operation to `grid`.
+    pub fn apply(self, grid: &mut Grid, connectivity: Connectivity, boundary: Boundary) {
+        match self {
+            Morphology::Erode => erode(grid, connectivity, boundary),
+            Morphology::Dilate => dilate(grid, connectivity, boundary),
+            Morphology::Open => open(grid, connectivity, boundary),
+            Morphology::Close => close(grid, connectivity, boundary),
+        }
+    }
+}
+
+/// The 3 x 3 structuring element of `connectivity`.
+fn element(connectivity: Connectivity) -> Kernel {
+    match connectivity {
+        Connectivity::Queen => Kernel::Square(1),
+        Connectivity::Rook => {
+            let mut k = Grid::new(3, 3);
+            k[1][1] = 1.0;
+            for &(di, dj) in connectivity.offsets() {
+                k[(1 + di) as usize][(1 + dj) as usize] = 1.0;
+            }
+            Kernel::Custom(k)
+        }
+    }
+}
+
+/// Replaces every cell with the minimum of itself and its `connectivity`
+/// neighbours, shrinking foreground patches by one cell.
+///
+/// NoData cells stay NoData. They and, with [`Boundary::Clamped`], cells
+/// beyond the edge are ignored rather than treated as background, so patches
+/// are not eroded from the side of the study area's boundary.
+pub fn erode(grid: &mut Grid, connectivity: Connectivity, boundary: Boundary) {
+    focal(grid, Statistic::Min, &element(connectivity), boundary);
+}
+
+/// Replaces every cell with the maximum of itself and its `connectivity`
+/// neighbours, growing foreground patches by one cell. NoData cells stay
+/// NoData and are not grown into.
+pub fn dilate(grid: &mut Grid, connectivity: Connectivity, boundary: Boundary) {
+    focal(grid, Statistic::Max, &element(connectivity), boundary);
+}
+
+/// [`erode`] then [`dilate`]: removes foreground specks and spurs narrower
+/// than the structuring element while keeping larger patches' shapes.
+pub fn open(grid: &mut Grid, connectivity: Connectivity, boundary: Boundary) {
+    erode(grid, connectivity, boundary);
+    dilate(grid, connectivity, boundary);
+}
+
+/// [`dilate`] then [`erode`]: fills background specks and gaps narrower than
+/// the structuring element while keeping larger patches' shapes.
+pub fn close(grid: &mut Grid, connectivity: Connectivity, boundary: Boundary) {
+    dilate(grid, connectivity, boundary);
+    erode(grid, connectivity, boundary);
+}
+
+/// Sets background (`0.0`) cells enclosed by foreground to `1.0`.
+///
+/// Background cells are joined to their `connectivity` neighbours, and those
+/// connected to the edge of the grid or to a NoData cell are outside; the
+/// rest are holes. With [`Connectivity::Queen`] background escapes through
+/// diagonal gaps in the foreground that [`Connectivity::Rook`] treats as
+/// closed. Foreground and NoData cells are left unchanged.
+pub fn fill_holes(grid: &mut Grid, connectivity: Connectivity) {
+    let (rows, cols) = (grid.rows, grid.cols);
+    let neighbours = |k: usize| {
+        let (i, j) = (k / cols, k % cols);
+        connectivity.offsets().iter().map(move |&(di, dj)| {
+            let r = Boundary::Clamped.offset(i, di as isize, rows)?;
+            let c = Boundary::Clamped.offset(j, dj as isize, cols)?;
+            Some(r * cols + c)
+        })
+    };
+    let mut outside = vec![false; grid.data.len()];
+    let mut stack: Vec<usize> = (0..grid.data.len())
+        .filter(|&k| grid.data[k] == 0.0)
+        .filter(|&k| neighbours(k).any(|n| n.is_none_or(|n| grid.data[n].is_nan())))
+        .collect();
+    for &k in &stack {
+        outside[k] = true;
+    }
+    while let Some(k) = stack.pop() {
+        for n in neighbours(k).flatten() {
+            if !outside[n] && grid.data[n] == 0.0 {
+                outside[n] = true;
+                stack.push(n);
+            }
+        }
+    }
+    for (v, &out) in grid.data.iter_mut().zip(&outside) {
+        if *v == 0.0 && !out {
+            *v = 1.0;
+        }
+    }
+}
+
+/// The 8 neighbours P2..P9 of Zhang and Suen, clockwise from north.
+const RING: [(isize, isize); 8] = [(-1, 0), (-1, 1), (0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1)];
+
+/// Thins foreground (non-zero) patches to 1-cell-wide, 8-connected centre
+/// lines with the Zhang-Suen algorithm, returning a 0/1 grid.
+///
+/// NoData cells stay NoData and count as background, as do cells beyond the
+/// edge with [`Boundary::Clamped`]; with [`Boundary::Periodic`] patches
+/// continue across opposite edges.
+pub fn skeletonize(grid: &mut Grid, boundary: Boundary) {
+    let (rows, cols) = (grid.rows, grid.cols);
+    let mut fg: Vec<bool> = grid.iter().map(|&v| v != 0.0 && !v.is_nan()).collect();
+    loop {
+        let mut changed = false;
+        for pass in 0..2 {
+            let fg_ref = &fg;
+            let removable = |&k: &usize| {
+                if !fg_ref[k] {
+                    return false;
+                }
+                let (i, j) = (k / cols, k % cols);
+                let p = RING.map(|(di, dj)| {
+                    match (boundary.offset(i, di, rows), boundary.offset(j, dj, cols)) {
+                        (Some(r), Some(c)) => fg_ref[r * cols + c],
+                        _ => false,
+                    }
+                });
+                let count = p.iter().filter(|&&x| x).count();
+                let transitions = (0..8).filter(|&n| !p[n] && p[(n + 1) % 8]).count();
+                let (n, e, s, w) = (p[0], p[2], p[4], p[6]);
+                let side = if pass == 0 {
+                    !(e && s && (n || w))
+                } else {
+                    !(n && w && (e || s))
+                };
+                (2..=6).contains(&count) && transitions == 1 && side
+            };
+            #[cfg(feature = "parallel")]
+            let removed: Vec<usize> = (0..fg.len()).into_par_iter().filter(removable).collect();
+            #[cfg(not(feature = "parallel"))]
+            let removed: Vec<usize> = (0..fg.len()).filter(removable).collect();
+            changed |= !removed.is_empty();
+            for k in removed {
+                fg[k] = false;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    for (v, &f) in grid.data.iter_mut().zip(&fg) {
+        if !v.is_nan() {
+            *v = if f { 1.0 } else { 0.0 };
+        }
+    }
+}
+
+/// Merges every patch of fewer than `min_area` equal-valued cells into the
+/// neighbouring patch it shares the most `connectivity` adjacencies with.
+///
+/// Patches are found as by [`label_patches`] and merged smallest first, so a
+/// patch grows as it absorbs others; ties go to the larger neighbour, then
+/// to the first in row-major order. A merge also joins any other neighbour
+/// of the same class. Patches with no neighbours, such as one covering the
+/// whole grid, are kept. NoData cells are left unchanged and never merged
+/// into.
+pub fn sieve(grid: &mut Grid, min_area: usize, connectivity: Connectivity, boundary: Boundary) {
+    let (rows, cols) = (grid.rows, grid.cols);
+    let (labels, patches) = label_patches(grid, connectivity, boundary);
+    let mut cells: Vec<Vec<usize>> = patches.iter().map(|p| Vec::with_capacity(p.area)).collect();
+    for (k, &l) in labels.iter().enumerate() {
+        if !l.is_nan() {
+            cells[l as usize].push(k);
+        }
+    }
+    let mut parent: Vec<usize> = (0..patches.len()).collect();
+    let mut area: Vec<usize> = patches.iter().map(|p| p.area).collect();
+    fn root(parent: &mut [usize], mut p: usize) -> usize {
+        while parent[p] != p {
+            parent[p] = parent[parent[p]];
+            p = parent[p];
+        }
+        p
+    }
+
+    let mut order: Vec<usize> = (0..patches.len()).filter(|&p| area[p] < min_area).collect();
+    order.sort_by_key(|&p| area[p]);
+    // Neighbouring patches of the patch being merged, with the number of
+    // adjacencies shared with each.
+    let mut shared: Vec<(usize, usize)> = Vec::new();
+    for p in order {
+        if parent[p] != p || area[p] >= min_area {
+            continue;
+        }
+        shared.clear();
+        for &k in &cells[p] {
+            let (i, j) = (k / cols, k % cols);
+            for &(di, dj) in connectivity.offsets() {
+                let (Some(r), Some(c)) =
+                    (boundary.offset(i, di as isize, rows), boundary.offset(j, dj as isize, cols))
+                else {
+                    continue;
+                };
+                let l = labels.data[r * cols + c];
+                if l.is_nan() {
+                    continue;
+                }
+                let q = root(&mut parent, l as usize);
+                if q == p {
+                    continue;
+                }
+                match shared.iter_mut().find(|(r, _)| *r == q) {
+                    Some((_, n)) => *n += 1,
+                    None => shared.push((q, 1)),
+                }
+            }
+        }
+        let Some(&(q, _)) = shared
+            .iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then(area[a.0].cmp(&area[b.0])).then(b.0.cmp(&a.0)))
+        else {
+            continue;
+        };
+        let value = patches[q].value;
+        let joined: Vec<usize> = std::iter::once(p)
+            .chain(shared.iter().map(|&(r, _)| r).filter(|&r| r != q && patches[r].value == value))
+            .collect();
+        for x in joined {
+            parent[x] = q;
+            area[q] += area[x];
+            let moved = std::mem::take(&mut cells[x]);
+            cells[q].extend(moved);
+        }
+    }
+
+    let values: Vec<f64> = (0..patches.len()).map(|p| patches[root(&mut parent, p)].value).collect();
+    for (v, &l) in grid.data.iter_mut().zip(labels.iter()) {
+        if !l.is_nan() {
+            *v = values[l as usize];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_rows(rows: &[&[f64]]) -> Grid {
+        Grid {
+            data: rows.iter().flat_map(|r| r.iter().copied()).collect(),
+            rows: rows.len(),
+            cols: rows[0].len(),
+        }
+    }
+
+    fn block(n: usize, from: usize, to: usize) -> Grid {
+        let mut grid = Grid::new(n, n);
+        for i in from..to {
+            for j in from..to {
+                grid[i][j] = 1.0;
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn test_erode_and_dilate_follow_the_element() {
+        let mut grid = block(7, 2, 5);
+        erode(&mut grid, Connectivity::Queen, Boundary::Clamped);
+        assert_eq!(grid, block(7, 3, 4));
+
+        dilate(&mut grid, Connectivity::Rook, Boundary::Clamped);
+        let cross: Vec<(usize, usize)> =
+            (0..49).filter(|&k| grid.data[k] == 1.0).map(|k| (k / 7, k % 7)).collect();
+        assert_eq!(cross, vec![(2, 3), (3, 2), (3, 3), (3, 4), (4, 3)]);
+
+        let mut grid = block(7, 3, 4);
+        dilate(&mut grid, Connectivity::Queen, Boundary::Clamped);
+        assert_eq!(grid, block(7, 2, 5));
+    }
+
+    #[test]
+    fn test_open_and_close() {
+        let mut specks = block(12, 2, 10);
+        specks[0][0] = 1.0;
+        specks[5][5] = 0.0;
+        let mut opened = specks.clone();
+        open(&mut opened, Connectivity::Queen, Boundary::Clamped);
+        assert_eq!(opened[0][0], 0.0);
+        assert_eq!(opened[2][2], 1.0);
+
+        let mut closed = specks.clone();
+        close(&mut closed, Connectivity::Queen, Boundary::Clamped);
+        assert_eq!(closed[5][5], 1.0);
+        assert_eq!(closed[11][11], 0.0);
+    }
+
+    #[test]
+    fn test_erode_ignores_edges_and_nodata() {
+        let mut grid = Grid::filled(4, 4, 1.0);
+        grid[0][0] = f64::NAN;
+        erode(&mut grid, Connectivity::Queen, Boundary::Clamped);
+        assert!(grid[0][0].is_nan());
+        assert_eq!(grid.iter().filter(|&&v| v == 1.0).count(), 15);
+
+        let mut torus = block(5, 0, 2);
+        erode(&mut torus, Connectivity::Rook, Boundary::Periodic);
+        assert!(torus.iter().all(|&v| v == 0.0));
+    }
+
+    #[test]
+    fn test_fill_holes_connectivity() {
+        let ring = |corner: bool| {
+            let mut grid = block(5, 1, 4);
+            grid[2][2] = 0.0;
+            if corner {
+                grid[1][1] = 0.0;
+            }
+            grid
+        };
+        let mut grid = ring(false);
+        fill_holes(&mut grid, Connectivity::Queen);
+        assert_eq!(grid[2][2], 1.0);
+        assert_eq!(grid[0][0], 0.0);
+
+        let mut rook = ring(true);
+        fill_holes(&mut rook, Connectivity::Rook);
+        assert_eq!(rook[2][2], 1.0);
+        assert_eq!(rook[1][1], 0.0);
+
+        let mut queen = ring(true);
+        fill_holes(&mut queen, Connectivity::Queen);
+        assert_eq!(queen[2][2], 0.0);
+
+        let mut nodata = ring(false);
+        nodata[0][2] = f64::NAN;
+        nodata[1][2] = 0.0;
+        fill_holes(&mut nodata, Connectivity::Rook);
+        assert_eq!(nodata[2][2], 0.0);
+        assert!(nodata[0][2].is_nan());
+    }
+
+    #[test]
+    fn test_skeletonize_bar_to_centre_line() {
+        let mut grid = Grid::new(9, 20);
+        for i in 2..7 {
+            for j in 2..18 {
+                grid[i][j] = 1.0;
+            }
+        }
+        grid[0][0] = f64::NAN;
+        skeletonize(&mut grid, Boundary::Clamped);
+        assert!(grid[0][0].is_nan());
+        let kept: Vec<(usize, usize)> =
+            (0..grid.data.len()).filter(|&k| grid.data[k] == 1.0).map(|k| (k / 20, k % 20)).collect();
+        assert!(kept.len() >= 10);
+        assert!(kept.iter().all(|&(i, _)| i == 4), "{kept:?}");
+        assert!(kept.windows(2).all(|w| w[1].1 == w[0].1 + 1));
+    }
+
+    #[test]
+    fn test_skeletonize_keeps_thin_lines() {
+        let mut grid = Grid::new(5, 5);
+        for k in 0..5 {
+            grid[k][k] = 2.0;
+        }
+        skeletonize(&mut grid, Boundary::Clamped);
+        assert_eq!(grid.iter().filter(|&&v| v == 1.0).count(), 5);
+    }
+
+    #[test]
+    fn test_sieve_merges_into_majority_neighbour() {
+        let mut grid = from_rows(&[
+            &[1.0, 2.0, 2.0],
+            &[1.0, 5.0, 2.0],
+            &[1.0, 2.0, 2.0],
+        ]);
+        sieve(&mut grid, 2, Connectivity::Rook, Boundary::Clamped);
+        assert_eq!(grid[1][1], 2.0);
+        assert_eq!(grid[0][0], 1.0);
+    }
+
+    #[test]
+    fn test_sieve_joins_patches_of_the_same_class() {
+        let mut grid = from_rows(&[&[1.0, 1.0, 3.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0, 2.0]]);
+        sieve(&mut grid, 5, Connectivity::Rook, Boundary::Clamped);
+        assert_eq!(grid.data, vec![1.0, 1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn test_sieve_leaves_large_patches_and_nodata() {
+        let mut grid = crate::percolation(40, 40, 0.5, Some(3));
+        grid[0][0] = f64::NAN;
+        sieve(&mut grid, 8, Connectivity::Queen, Boundary::Clamped);
+        assert!(grid[0][0].is_nan());
+        let (_, patches) = label_patches(&grid, Connectivity::Queen, Boundary::Clamped);
+        assert!(patches.iter().all(|p| p.area >= 8));
+
+        let mut whole = Grid::filled(3, 3, 4.0);
+        sieve(&mut whole, 100, Connectivity::Rook, Boundary::Clamped);
+        assert!(whole.iter().all(|&v| v == 4.0));
+    }
+}
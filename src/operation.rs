@@ -1,22 +1,23 @@
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
-use crate::grid::Grid;
+use crate::error::{self, check_shape, NlmError};
+use crate::grid::{Boundary, Element, Grid};
 
-pub fn max(grid: &Grid) -> f64 {
+pub fn max<T: Element>(grid: &Grid<T>) -> T {
     min_and_max(grid).1
 }
 
-pub fn min(grid: &Grid) -> f64 {
+pub fn min<T: Element>(grid: &Grid<T>) -> T {
     min_and_max(grid).0
 }
 
-pub fn min_and_max(grid: &Grid) -> (f64, f64) {
+pub fn min_and_max<T: Element>(grid: &Grid<T>) -> (T, T) {
     grid.data
         .iter()
         .copied()
         .fold(
-            (f64::INFINITY, f64::NEG_INFINITY),
+            (T::INFINITY, T::NEG_INFINITY),
             |(mn, mx), v| (mn.min(v), mx.max(v)),
         )
 }
@@ -27,6 +28,12 @@ pub fn min_and_max(grid: &Grid) -> (f64, f64) {
 /// cells are flooded in BFS order so each inherits the label of the nearest
 /// seed.  Running time is O(rows × cols).
 pub fn interpolate(grid: &mut Grid) {
+    interpolate_with_boundary(grid, Boundary::Clamped);
+}
+
+/// [`interpolate`] with edges handled by `boundary`: with
+/// [`Boundary::Periodic`] the flood continues across opposite edges.
+pub fn interpolate_with_boundary(grid: &mut Grid, boundary: Boundary) {
     use std::collections::VecDeque;
     let cols = grid.cols;
     let rows = grid.rows;
@@ -44,33 +51,14 @@ pub fn interpolate(grid: &mut Grid) {
         let row = idx / cols;
         let col = idx % cols;
 
-        // Up
-        if row > 0 {
-            let ni = idx - cols;
-            if grid.data[ni] == 0.0 {
-                grid.data[ni] = val;
-                queue.push_back(ni);
-            }
-        }
-        // Down
-        if row + 1 < rows {
-            let ni = idx + cols;
-            if grid.data[ni] == 0.0 {
-                grid.data[ni] = val;
-                queue.push_back(ni);
-            }
-        }
-        // Left
-        if col > 0 {
-            let ni = idx - 1;
-            if grid.data[ni] == 0.0 {
-                grid.data[ni] = val;
-                queue.push_back(ni);
-            }
-        }
-        // Right
-        if col + 1 < cols {
-            let ni = idx + 1;
+        // Up, down, left, right.
+        for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (Some(nr), Some(nc)) =
+                (boundary.offset(row, dr, rows), boundary.offset(col, dc, cols))
+            else {
+                continue;
+            };
+            let ni = nr * cols + nc;
             if grid.data[ni] == 0.0 {
                 grid.data[ni] = val;
                 queue.push_back(ni);
@@ -79,11 +67,22 @@ pub fn interpolate(grid: &mut Grid) {
     }
 }
 
-pub fn scale(grid: &mut Grid) {
-    let (min, max) = min_and_max(grid);
+/// Linearly rescales every cell to [0, 1]. NoData cells are left untouched and
+/// do not contribute to the range.
+pub fn scale<T: Element>(grid: &mut Grid<T>) {
+    let range = min_and_max(grid);
+    scale_from(grid, range);
+}
+
+/// Rescales `grid` as [`scale`] would if its minimum and maximum were `min` and `max`.
+pub(crate) fn scale_from<T: Element>(grid: &mut Grid<T>, (min, max): (T, T)) {
     let range = max - min;
-    let scale_v = |v: &mut f64| {
-        *v = if range == 0.0 { 0.5 } else { (*v - min) / range };
+    let half = T::from_f64(0.5);
+    let scale_v = |v: &mut T| {
+        if v.is_nan() {
+            return;
+        }
+        *v = if range == T::ZERO { half } else { (*v - min) / range };
     };
     #[cfg(feature = "parallel")]
     grid.data.par_iter_mut().for_each(scale_v);
@@ -97,6 +96,13 @@ pub fn scale(grid: &mut Grid) {
 /// is non-contiguous and not easily parallelised without transposition overhead.
 /// Phase 2 (row passes) is parallelised via rayon when the `parallel` feature is enabled.
 pub fn euclidean_distance_transform(grid: &mut Grid) {
+    euclidean_distance_transform_with_boundary(grid, Boundary::Clamped);
+}
+
+/// [`euclidean_distance_transform`] with edges handled by `boundary`: with
+/// [`Boundary::Periodic`] distances are measured on the torus, so a cell near
+/// one edge may be closest to a zero near the opposite edge.
+pub fn euclidean_distance_transform_with_boundary(grid: &mut Grid, boundary: Boundary) {
     let rows = grid.rows;
     let cols = grid.cols;
 
@@ -106,23 +112,26 @@ pub fn euclidean_distance_transform(grid: &mut Grid) {
 
     let inf = f64::INFINITY;
     let mut g = vec![inf; rows * cols];
+    // A periodic column is walked twice so distances propagate across the wrap.
+    let passes = if boundary.is_periodic() { 2 * rows } else { rows };
 
     // Phase 1: per-column vertical squared-distance to nearest zero — sequential.
     for j in 0..cols {
-        g[j] = if grid[0][j] == 0.0 { 0.0 } else { inf };
-        for i in 1..rows {
-            g[i * cols + j] = if grid[i][j] == 0.0 {
-                0.0
-            } else if g[(i - 1) * cols + j] < inf {
-                g[(i - 1) * cols + j] + 1.0
-            } else {
-                inf
-            };
+        for i in 0..rows {
+            g[i * cols + j] = if grid[i][j] == 0.0 { 0.0 } else { inf };
+        }
+        for step in 1..passes {
+            let (i, above) = (step % rows, (step - 1) % rows);
+            let from_above = g[above * cols + j] + 1.0;
+            if from_above < g[i * cols + j] {
+                g[i * cols + j] = from_above;
+            }
         }
-        for i in (0..rows - 1).rev() {
-            let below = g[(i + 1) * cols + j];
-            if below < g[i * cols + j] {
-                g[i * cols + j] = below + 1.0;
+        for step in (0..passes - 1).rev() {
+            let (i, below) = (step % rows, (step + 1) % rows);
+            let from_below = g[below * cols + j] + 1.0;
+            if from_below < g[i * cols + j] {
+                g[i * cols + j] = from_below;
             }
         }
         for i in 0..rows {
@@ -132,94 +141,123 @@ pub fn euclidean_distance_transform(grid: &mut Grid) {
     }
 
     // Phase 2: per-row parabola DT — rows are fully independent, parallelise with rayon.
-    // Each row needs its own v/z scratch buffers.
+    // A periodic row is tiled three times so parabolas from the neighbouring
+    // copies can win, and the middle copy is read back.
     #[cfg(feature = "parallel")]
     let chunks = grid.data.par_chunks_mut(cols);
     #[cfg(not(feature = "parallel"))]
     let chunks = grid.data.chunks_mut(cols);
     chunks.enumerate().for_each(|(i, row_data)| {
-            let row_g: &[f64] = &g[i * cols..(i + 1) * cols];
-
-            let mut v = vec![0usize; cols];
-            let mut z = vec![0.0f64; cols + 1];
-
-            let mut k: usize = 0;
-            v[0] = 0;
-            z[0] = f64::NEG_INFINITY;
-            z[1] = f64::INFINITY;
-
-            for q in 1..cols {
-                let gq = row_g[q];
-                loop {
-                    let vk = v[k];
-                    let gvk = row_g[vk];
-                    let s = ((gq + (q * q) as f64) - (gvk + (vk * vk) as f64))
-                        / (2.0 * (q as f64 - vk as f64));
-                    if s > z[k] {
-                        k += 1;
-                        v[k] = q;
-                        z[k] = s;
-                        z[k + 1] = f64::INFINITY;
-                        break;
-                    }
-                    if k == 0 {
-                        v[0] = q;
-                        z[0] = f64::NEG_INFINITY;
-                        z[1] = f64::INFINITY;
-                        break;
-                    }
-                    k -= 1;
-                }
+        let row_g = &g[i * cols..(i + 1) * cols];
+        match boundary {
+            Boundary::Clamped => lower_envelope(row_g, 0, row_data),
+            Boundary::Periodic => {
+                let tiled: Vec<f64> = row_g.iter().chain(row_g).chain(row_g).copied().collect();
+                lower_envelope(&tiled, cols, row_data);
             }
+        }
+    });
+}
 
-            k = 0;
-            for q in 0..cols {
-                while z[k + 1] < q as f64 {
-                    k += 1;
-                }
-                let vk = v[k];
-                let dist_sq = (q as f64 - vk as f64).powi(2) + row_g[vk];
-                row_data[q] = if dist_sq < inf { dist_sq.sqrt() } else { 0.0 };
+/// Evaluates the lower envelope of the parabolas `(q - p)² + f[p]` at
+/// positions `start..start + out.len()`, writing the square root to `out`.
+fn lower_envelope(f: &[f64], start: usize, out: &mut [f64]) {
+    let n = f.len();
+    // Each call needs its own v/z scratch buffers.
+    let mut v = vec![0usize; n];
+    let mut z = vec![0.0f64; n + 1];
+
+    let mut k: usize = 0;
+    v[0] = 0;
+    z[0] = f64::NEG_INFINITY;
+    z[1] = f64::INFINITY;
+
+    for q in 1..n {
+        let fq = f[q];
+        loop {
+            let vk = v[k];
+            let fvk = f[vk];
+            let s = ((fq + (q * q) as f64) - (fvk + (vk * vk) as f64))
+                / (2.0 * (q as f64 - vk as f64));
+            if s > z[k] {
+                k += 1;
+                v[k] = q;
+                z[k] = s;
+                z[k + 1] = f64::INFINITY;
+                break;
+            }
+            if k == 0 {
+                v[0] = q;
+                z[0] = f64::NEG_INFINITY;
+                z[1] = f64::INFINITY;
+                break;
             }
-        });
+            k -= 1;
+        }
+    }
+
+    k = 0;
+    for (offset, o) in out.iter_mut().enumerate() {
+        let q = start + offset;
+        while z[k + 1] < q as f64 {
+            k += 1;
+        }
+        let vk = v[k];
+        let dist_sq = (q as f64 - vk as f64).powi(2) + f[vk];
+        *o = if dist_sq < f64::INFINITY { dist_sq.sqrt() } else { 0.0 };
+    }
 }
 
-pub fn invert(grid: &mut Grid) {
+pub fn invert<T: Element>(grid: &mut Grid<T>) {
     #[cfg(feature = "parallel")]
-    grid.data.par_iter_mut().for_each(|v| *v = 1.0 - *v);
+    grid.data.par_iter_mut().for_each(|v| *v = T::ONE - *v);
     #[cfg(not(feature = "parallel"))]
-    grid.data.iter_mut().for_each(|v| *v = 1.0 - *v);
+    grid.data.iter_mut().for_each(|v| *v = T::ONE - *v);
 }
 
-pub fn multiply(grid: &mut Grid, other: &Grid) {
+pub fn multiply<T: Element>(grid: &mut Grid<T>, other: &Grid<T>) {
     #[cfg(feature = "parallel")]
     grid.data.par_iter_mut().zip(other.data.par_iter()).for_each(|(v, &o)| *v *= o);
     #[cfg(not(feature = "parallel"))]
     grid.data.iter_mut().zip(other.data.iter()).for_each(|(v, &o)| *v *= o);
 }
 
-pub fn multiply_value(grid: &mut Grid, value: f64) {
+/// Fallible [`multiply`]: errors if the grid shapes differ.
+pub fn try_multiply<T: Element>(grid: &mut Grid<T>, other: &Grid<T>) -> error::Result<()> {
+    check_shape((grid.rows, grid.cols), (other.rows, other.cols))?;
+    multiply(grid, other);
+    Ok(())
+}
+
+pub fn multiply_value<T: Element>(grid: &mut Grid<T>, value: T) {
     #[cfg(feature = "parallel")]
     grid.data.par_iter_mut().for_each(|v| *v *= value);
     #[cfg(not(feature = "parallel"))]
     grid.data.iter_mut().for_each(|v| *v *= value);
 }
 
-pub fn add(grid: &mut Grid, other: &Grid) {
+pub fn add<T: Element>(grid: &mut Grid<T>, other: &Grid<T>) {
     #[cfg(feature = "parallel")]
     grid.data.par_iter_mut().zip(other.data.par_iter()).for_each(|(v, &o)| *v += o);
     #[cfg(not(feature = "parallel"))]
     grid.data.iter_mut().zip(other.data.iter()).for_each(|(v, &o)| *v += o);
 }
 
-pub fn add_value(grid: &mut Grid, value: f64) {
+/// Fallible [`add`]: errors if the grid shapes differ.
+pub fn try_add<T: Element>(grid: &mut Grid<T>, other: &Grid<T>) -> error::Result<()> {
+    check_shape((grid.rows, grid.cols), (other.rows, other.cols))?;
+    add(grid, other);
+    Ok(())
+}
+
+pub fn add_value<T: Element>(grid: &mut Grid<T>, value: T) {
     #[cfg(feature = "parallel")]
     grid.data.par_iter_mut().for_each(|v| *v += value);
     #[cfg(not(feature = "parallel"))]
     grid.data.iter_mut().for_each(|v| *v += value);
 }
 
-pub fn abs(grid: &mut Grid) {
+pub fn abs<T: Element>(grid: &mut Grid<T>) {
     #[cfg(feature = "parallel")]
     grid.data.par_iter_mut().for_each(|v| *v = v.abs());
     #[cfg(not(feature = "parallel"))]
@@ -229,14 +267,18 @@ pub fn abs(grid: &mut Grid) {
 /// Quantises each cell into one of `n` equal-width classes over [0, 1].
 ///
 /// Class `k` (0-indexed) is assigned the output value `k / (n − 1)`,
-/// evenly spacing the `n` classes across [0, 1]. Panics if `n == 0`.
-pub fn classify(grid: &mut Grid, n: usize) {
+/// evenly spacing the `n` classes across [0, 1]. NoData cells are left
+/// untouched. Panics if `n == 0`.
+pub fn classify<T: Element>(grid: &mut Grid<T>, n: usize) {
     assert!(n >= 1, "n must be at least 1");
-    let n_f = n as f64;
-    let max_class = (n - 1) as f64;
-    let op = |v: &mut f64| {
+    let n_f = T::from_f64(n as f64);
+    let max_class = T::from_f64((n - 1) as f64);
+    let op = |v: &mut T| {
+        if v.is_nan() {
+            return;
+        }
         let class = (*v * n_f).floor().min(max_class);
-        *v = if n == 1 { 0.0 } else { class / max_class };
+        *v = if n == 1 { T::ZERO } else { class / max_class };
     };
     #[cfg(feature = "parallel")]
     grid.data.par_iter_mut().for_each(op);
@@ -244,10 +286,334 @@ pub fn classify(grid: &mut Grid, n: usize) {
     grid.data.iter_mut().for_each(op);
 }
 
+/// Fallible [`classify`]: errors instead of panicking when `n == 0`.
+pub fn try_classify<T: Element>(grid: &mut Grid<T>, n: usize) -> error::Result<()> {
+    if n == 0 {
+        return Err(NlmError::invalid("n", "must be at least 1"));
+    }
+    classify(grid, n);
+    Ok(())
+}
+
+/// Assigns cells to classes by rank so each class covers the share of the grid
+/// given by its weight.
+///
+/// Cells are ordered by value and the lowest `weights[0] / Σweights` fraction
+/// becomes class 0, the next `weights[1] / Σweights` fraction class 1, and so
+/// on. Class `k` is written as `k / (n − 1)`, matching [`classify`]. Ties are
+/// broken by cell position so the result is deterministic. NoData cells are
+/// left untouched and shares are taken of the remaining cells only.
+///
+/// Panics if `weights` is empty, contains a negative value, or sums to zero.
+///
+/// Implementation ported from NLMpy's `classifyArray`.
+pub fn classify_weighted<T: Element>(grid: &mut Grid<T>, weights: &[f64]) {
+    assert!(!weights.is_empty(), "weights must not be empty");
+    assert!(weights.iter().all(|&w| w >= 0.0), "weights must be non-negative");
+    let total: f64 = weights.iter().sum();
+    assert!(total > 0.0, "weights must sum to a positive value");
+
+    let mut order: Vec<usize> = (0..grid.data.len()).filter(|&i| !grid.data[i].is_nan()).collect();
+    order.sort_by(|&a, &b| grid.data[a].total_cmp(&grid.data[b]));
+    let len = order.len();
+
+    // Cumulative cut-offs in cell counts; rounding the running total rather than
+    // each class individually keeps the counts summing to exactly `len`.
+    let n = weights.len();
+    let mut cum = 0.0f64;
+    let cutoffs: Vec<usize> = weights
+        .iter()
+        .map(|&w| {
+            cum += w;
+            ((cum / total) * len as f64).round() as usize
+        })
+        .collect();
+
+    let max_class = (n - 1) as f64;
+    let mut class = 0usize;
+    for (rank, &idx) in order.iter().enumerate() {
+        while class < n - 1 && rank >= cutoffs[class] {
+            class += 1;
+        }
+        grid.data[idx] = T::from_f64(if n == 1 { 0.0 } else { class as f64 / max_class });
+    }
+}
+
+/// Fallible [`classify_weighted`]: errors instead of panicking on empty,
+/// negative, non-finite or all-zero weights.
+pub fn try_classify_weighted<T: Element>(grid: &mut Grid<T>, weights: &[f64]) -> error::Result<()> {
+    if weights.is_empty() {
+        return Err(NlmError::invalid("weights", "must not be empty"));
+    }
+    if !weights.iter().all(|&w| w.is_finite() && w >= 0.0) {
+        return Err(NlmError::invalid("weights", "must be finite and non-negative"));
+    }
+    if weights.iter().sum::<f64>() <= 0.0 {
+        return Err(NlmError::invalid("weights", "must sum to a positive value"));
+    }
+    classify_weighted(grid, weights);
+    Ok(())
+}
+
+/// Neighbourhood used to decide whether two cells belong to the same patch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Connectivity {
+    /// 4-neighbourhood: cells sharing a side.
+    Rook,
+    /// 8-neighbourhood: cells sharing a side or a corner.
+    Queen,
+}
+
+impl Connectivity {
+    /// Lower-case name, as used in pipeline files: `"rook"` or `"queen"`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Connectivity::Rook => "rook",
+            Connectivity::Queen => "queen",
+        }
+    }
+
+    /// The connectivity called `name`, as returned by [`Connectivity::name`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Connectivity::Rook, Connectivity::Queen].into_iter().find(|c| c.name() == name)
+    }
+
+    /// Row/column offsets of the neighbouring cells.
+    pub fn offsets(self) -> &'static [(i64, i64)] {
+        match self {
+            Connectivity::Rook => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+            Connectivity::Queen => &[
+                (-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1),
+            ],
+        }
+    }
+}
+
+/// A connected patch of equal-valued cells found by [`label_patches`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Patch {
+    /// Cell value shared by every cell in the patch.
+    pub value: f64,
+    /// Number of cells in the patch.
+    pub area: usize,
+    /// Number of cell sides bordering a different value or, with a clamped
+    /// boundary, the edge of the grid.
+    pub perimeter: usize,
+    /// Smallest row index occupied by the patch.
+    pub min_row: usize,
+    /// Smallest column index occupied by the patch.
+    pub min_col: usize,
+    /// Largest row index occupied by the patch.
+    pub max_row: usize,
+    /// Largest column index occupied by the patch.
+    pub max_col: usize,
+    /// Boundary the patch was labelled with.
+    pub boundary: Boundary,
+    /// True if the patch joins itself around the torus top to bottom, so that
+    /// walking through it returns to a cell one or more grid heights away.
+    /// Always false with a clamped boundary.
+    pub wraps_rows: bool,
+    /// True if the patch joins itself around the torus left to right. Always
+    /// false with a clamped boundary.
+    pub wraps_cols: bool,
+}
+
+impl Patch {
+    /// True if the patch connects the top and bottom of a grid with `rows` rows:
+    /// with a clamped boundary it touches both the first and last rows, with a
+    /// periodic one it [wraps](Patch::wraps_rows) around the torus. A thin patch
+    /// crossing the top edge of a torus touches both rows without spanning.
+    pub fn spans_rows(&self, rows: usize) -> bool {
+        match self.boundary {
+            Boundary::Clamped => self.min_row == 0 && self.max_row + 1 == rows,
+            Boundary::Periodic => self.wraps_rows,
+        }
+    }
+
+    /// True if the patch connects the left and right of a grid with `cols`
+    /// columns, in the same sense as [`spans_rows`](Patch::spans_rows).
+    pub fn spans_cols(&self, cols: usize) -> bool {
+        match self.boundary {
+            Boundary::Clamped => self.min_col == 0 && self.max_col + 1 == cols,
+            Boundary::Periodic => self.wraps_cols,
+        }
+    }
+}
+
+/// Labels connected patches of equal-valued cells.
+///
+/// Returns a grid of the same shape where each cell holds the index of its
+/// patch in the returned `Vec<Patch>`, in row-major order of each patch's
+/// first cell. Every distinct value is labelled, so binary grids yield patches
+/// for both the 0.0 matrix and the 1.0 habitat — filter on [`Patch::value`].
+/// NoData cells belong to no patch and are labelled `NaN`; sides facing them
+/// count towards perimeter.
+///
+/// With [`Boundary::Periodic`] the grid is treated as a torus: patches continue
+/// across opposite edges and no perimeter is counted along the boundary.
+/// Bounding boxes are always reported in grid coordinates, so a patch crossing
+/// an edge covers that whole axis; [`Patch::wraps_rows`] and
+/// [`Patch::wraps_cols`] tell whether it also joins itself around the torus.
+pub fn label_patches(grid: &Grid, connectivity: Connectivity, boundary: Boundary) -> (Grid, Vec<Patch>) {
+    let rows = grid.rows;
+    let cols = grid.cols;
+    let total = rows * cols;
+    let offsets = connectivity.offsets();
+
+    // Resolves a neighbour offset to a flat index, wrapping or rejecting
+    // out-of-bounds coordinates.
+    let neighbour = |r: usize, c: usize, dr: i64, dc: i64| -> Option<usize> {
+        let nr = boundary.offset(r, dr as isize, rows)?;
+        let nc = boundary.offset(c, dc as isize, cols)?;
+        Some(nr * cols + nc)
+    };
+
+    const UNLABELLED: usize = usize::MAX;
+    let mut labels = vec![UNLABELLED; total];
+    // Coordinates of each labelled cell along the path that reached it, not
+    // reduced modulo the grid size. Reaching a cell of the same patch at other
+    // coordinates means the patch wraps around the torus.
+    let mut unwrapped = vec![(0i64, 0i64); total];
+    let mut patches: Vec<Patch> = Vec::new();
+    let mut stack = Vec::new();
+
+    for start in 0..total {
+        if labels[start] != UNLABELLED || grid.data[start].is_nan() {
+            continue;
+        }
+        let label = patches.len();
+        let value = grid.data[start];
+        let mut patch = Patch {
+            value,
+            area: 0,
+            perimeter: 0,
+            min_row: start / cols,
+            min_col: start % cols,
+            max_row: start / cols,
+            max_col: start % cols,
+            boundary,
+            wraps_rows: false,
+            wraps_cols: false,
+        };
+        labels[start] = label;
+        unwrapped[start] = ((start / cols) as i64, (start % cols) as i64);
+        stack.push(start);
+
+        while let Some(idx) = stack.pop() {
+            let (r, c) = (idx / cols, idx % cols);
+            let (ur, uc) = unwrapped[idx];
+            patch.area += 1;
+            patch.min_row = patch.min_row.min(r);
+            patch.max_row = patch.max_row.max(r);
+            patch.min_col = patch.min_col.min(c);
+            patch.max_col = patch.max_col.max(c);
+
+            for &(dr, dc) in Connectivity::Rook.offsets() {
+                match neighbour(r, c, dr, dc) {
+                    Some(ni) if grid.data[ni] == value => {}
+                    _ => patch.perimeter += 1,
+                }
+            }
+            for &(dr, dc) in offsets {
+                let Some(ni) = neighbour(r, c, dr, dc) else { continue };
+                let reached = (ur + dr, uc + dc);
+                if labels[ni] == label {
+                    patch.wraps_rows |= unwrapped[ni].0 != reached.0;
+                    patch.wraps_cols |= unwrapped[ni].1 != reached.1;
+                } else if labels[ni] == UNLABELLED && grid.data[ni] == value {
+                    labels[ni] = label;
+                    unwrapped[ni] = reached;
+                    stack.push(ni);
+                }
+            }
+        }
+        patches.push(patch);
+    }
+
+    let data = labels
+        .into_iter()
+        .map(|l| if l == UNLABELLED { f64::NAN } else { l as f64 })
+        .collect();
+    (Grid { data, rows, cols }, patches)
+}
+
+/// Marks cells outside `mask` as NoData.
+///
+/// A mask cell is inside the study area when it is non-zero and not NaN, so
+/// binary 0/1 rasters and grids read from an ASCII grid with a NoData value
+/// both work as masks. Follow with [`scale`] to rescale the remaining cells
+/// over the study area only. Panics if the grid shapes differ.
+pub fn apply_mask<T: Element, M: Element>(grid: &mut Grid<T>, mask: &Grid<M>) {
+    assert!(
+        grid.rows == mask.rows && grid.cols == mask.cols,
+        "mask shape {}x{} does not match grid shape {}x{}",
+        mask.rows, mask.cols, grid.rows, grid.cols
+    );
+    let op = |(v, &m): (&mut T, &M)| {
+        if m == M::ZERO || m.is_nan() {
+            *v = T::NAN;
+        }
+    };
+    #[cfg(feature = "parallel")]
+    grid.data.par_iter_mut().zip(mask.data.par_iter()).for_each(op);
+    #[cfg(not(feature = "parallel"))]
+    grid.data.iter_mut().zip(mask.data.iter()).for_each(op);
+}
+
+/// Fallible [`apply_mask`]: errors if the mask shape differs from the grid's.
+pub fn try_apply_mask<T: Element, M: Element>(grid: &mut Grid<T>, mask: &Grid<M>) -> error::Result<()> {
+    check_shape((grid.rows, grid.cols), (mask.rows, mask.cols))?;
+    apply_mask(grid, mask);
+    Ok(())
+}
+
+/// Returns a (rows x cols) mask with `1.0` inside `polygon` and `0.0` outside.
+///
+/// `polygon` lists `(row, col)` vertices in cell units, where cell (i, j)
+/// spans `[i, i + 1) x [j, j + 1)`; a cell is inside when its centre is. The
+/// polygon is closed implicitly and may be concave. Uses the even-odd rule.
+pub fn polygon_mask(rows: usize, cols: usize, polygon: &[(f64, f64)]) -> Grid {
+    let mut grid = Grid::new(rows, cols);
+    if polygon.len() < 3 {
+        return grid;
+    }
+    let fill = |(i, out_row): (usize, &mut [f64])| {
+        let y = i as f64 + 0.5;
+        // Column crossings of the polygon edges along this row's centre line.
+        let mut xs: Vec<f64> = Vec::new();
+        for k in 0..polygon.len() {
+            let (y0, x0) = polygon[k];
+            let (y1, x1) = polygon[(k + 1) % polygon.len()];
+            if (y0 <= y) != (y1 <= y) {
+                xs.push(x0 + (y - y0) / (y1 - y0) * (x1 - x0));
+            }
+        }
+        xs.sort_by(|a, b| a.total_cmp(b));
+        for pair in xs.chunks_exact(2) {
+            let start = (pair[0] - 0.5).ceil().max(0.0) as usize;
+            let end = ((pair[1] - 0.5).ceil().max(0.0) as usize).min(cols);
+            for v in out_row.iter_mut().take(end).skip(start) {
+                *v = 1.0;
+            }
+        }
+    };
+    if cols > 0 {
+        #[cfg(feature = "parallel")]
+        grid.data.par_chunks_mut(cols).enumerate().for_each(fill);
+        #[cfg(not(feature = "parallel"))]
+        grid.data.chunks_mut(cols).enumerate().for_each(fill);
+    }
+    grid
+}
+
 /// Maps every cell to `0.0` if its value is strictly below `t`, or `1.0` otherwise.
-pub fn threshold(grid: &mut Grid, t: f64) {
-    let op = |v: &mut f64| {
-        *v = if *v < t { 0.0 } else { 1.0 };
+/// NoData cells are left untouched.
+pub fn threshold<T: Element>(grid: &mut Grid<T>, t: T) {
+    let op = |v: &mut T| {
+        if v.is_nan() {
+            return;
+        }
+        *v = if *v < t { T::ZERO } else { T::ONE };
     };
     #[cfg(feature = "parallel")]
     grid.data.par_iter_mut().for_each(op);
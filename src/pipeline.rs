@@ -0,0 +1,1221 @@
+//! Declarative multi-step NLM recipes.
+//!
+//! A [`Pipeline`] names a set of generated layers, the [`Step`]s that combine
+//! and post-process them, and the [`Output`]s to write, so a whole study design
+//! lives in one TOML or JSON file that can be versioned and re-run with
+//! `nlmrs run`:
+//!
+//! ```toml
+//! rows = 200
+//! cols = 200
+//! seed = 42
+//!
+//! [[layers]]
+//! name = "terrain"
+//! algorithm = "fbm_noise"
+//! params = { scale = 4.0, octaves = 6 }
+//!
+//! [[layers]]
+//! name = "slope"
+//! algorithm = "planar_gradient"
+//! params = { direction = 90.0 }
+//!
+//! [[steps]]
+//! op = "multiply"
+//! target = "terrain"
+//! with = "slope"
+//!
+//! [[steps]]
+//! op = "classify_weighted"
+//! target = "terrain"
+//! weights = [0.6, 0.3, 0.1]
+//!
+//! [[outputs]]
+//! layer = "terrain"
+//! path = "habitat.png"
+//! ```
+//!
+//! Layers are generated in order through the [registry](crate::registry),
+//! then steps run in order, each modifying its `target` layer in place.
+//! Unknown keys are rejected so that a misspelt option fails loudly instead of
+//! silently falling back to a default.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+
+use serde_json::{Map, Value as Json};
+
+use crate::algorithms::sub_seed64;
+use crate::error::{check_dimensions, check_shape, NlmError, Result};
+use crate::export::{self, Colormap, Metadata, TiffSampleFormat, WriteOptions};
+use crate::focal::{self, Kernel, Statistic};
+use crate::grid::{Boundary, GeoGrid, GeoTransform, Grid};
+use crate::morphology::{self, Morphology};
+use crate::operation::{self, Connectivity};
+use crate::registry::{self, Params, Value};
+
+/// A complete recipe: grid size, seed, layers, steps and outputs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pipeline {
+    pub rows: usize,
+    pub cols: usize,
+    /// Base seed. Layer `i` without its own seed uses a seed derived from it and
+    /// `i`, see [`layer_seed`](Pipeline::layer_seed).
+    pub seed: Option<u64>,
+    /// ESRI ASCII grid or GeoTIFF whose georeferencing is given to the outputs.
+    pub like: Option<String>,
+    pub layers: Vec<Layer>,
+    pub steps: Vec<Step>,
+    pub outputs: Vec<Output>,
+}
+
+/// A named grid produced by one registry generator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layer {
+    pub name: String,
+    /// Registry name of the generator, e.g. `"fbm_noise"`.
+    pub algorithm: String,
+    pub params: Params,
+    /// Overrides the seed derived from the pipeline seed.
+    pub seed: Option<u64>,
+}
+
+/// The second argument of an arithmetic step: another layer or a constant.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operand {
+    Layer(String),
+    Value(f64),
+}
+
+/// Where a mask step reads its mask from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MaskSource {
+    /// Another layer: its zero and NoData cells are masked out.
+    Layer(String),
+    /// A raster file in any format [`export::read`] recognises: its zero and
+    /// NODATA cells are masked out.
+    File(String),
+}
+
+/// One operation applied in place to the `target` layer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    /// `op = "add"`, `with` = layer name or number.
+    Add { target: String, with: Operand },
+    /// `op = "multiply"`, `with` = layer name or number.
+    Multiply { target: String, with: Operand },
+    /// `op = "invert"`.
+    Invert { target: String },
+    /// `op = "abs"`.
+    Abs { target: String },
+    /// `op = "scale"`: rescale to [0, 1].
+    Scale { target: String },
+    /// `op = "classify"`, `classes` = number of equal-width classes.
+    Classify { target: String, classes: usize },
+    /// `op = "classify_weighted"`, `weights` = class proportions.
+    ClassifyWeighted { target: String, weights: Vec<f64> },
+    /// `op = "threshold"`, `value` = cut-off.
+    Threshold { target: String, value: f64 },
+    /// `op = "mask"`, with either `with` = layer name or `path` = raster file.
+    /// Masked cells become NoData; follow with `scale` to rescale the rest.
+    Mask { target: String, mask: MaskSource },
+    /// `op = "copy"`, `from` = layer name: creates or replaces `target`.
+    Copy { target: String, from: String },
+    /// `op = "focal"`, `statistic` = a [`Statistic::name`], `kernel` =
+    /// `"square"` (the default) or `"circle"` with a `radius`, or a nested
+    /// array of weights, and optionally `periodic = true` to wrap at the edges.
+    Focal {
+        target: String,
+        statistic: Statistic,
+        kernel: Kernel,
+        boundary: Boundary,
+    },
+    /// `op = "erode"`, `"dilate"`, `"open"` or `"close"`, with `connectivity`
+    /// = `"rook"` (the default) or `"queen"` and optionally `periodic = true`.
+    Morphology {
+        target: String,
+        op: Morphology,
+        connectivity: Connectivity,
+        boundary: Boundary,
+    },
+    /// `op = "fill_holes"`, with `connectivity` as for erosion.
+    FillHoles { target: String, connectivity: Connectivity },
+    /// `op = "skeletonize"`, optionally with `periodic = true`.
+    Skeletonize { target: String, boundary: Boundary },
+    /// `op = "sieve"`, `min_area` = smallest patch kept, with `connectivity`
+    /// and `periodic` as for erosion.
+    Sieve {
+        target: String,
+        min_area: usize,
+        connectivity: Connectivity,
+        boundary: Boundary,
+    },
+}
+
+/// A layer to write once all steps have run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Output {
+    pub layer: String,
+    /// Destination; the extension selects the format as in [`export::write`].
+    pub path: String,
+    pub options: WriteOptions,
+    /// Also write a [`Metadata`] sidecar so the file can be regenerated.
+    pub metadata: bool,
+}
+
+impl Pipeline {
+    /// Parses a pipeline from TOML.
+    pub fn from_toml(source: &str) -> Result<Self> {
+        let json: Json = toml::from_str(source).map_err(|e| NlmError::Config(e.to_string()))?;
+        Self::from_value(&json)
+    }
+
+    /// Parses a pipeline from JSON.
+    pub fn from_json(source: &str) -> Result<Self> {
+        let json: Json =
+            serde_json::from_str(source).map_err(|e| NlmError::Config(e.to_string()))?;
+        Self::from_value(&json)
+    }
+
+    /// Reads a pipeline file, parsing `.json` files as JSON and anything else as TOML.
+    pub fn load(path: &str) -> Result<Self> {
+        let source = std::fs::read_to_string(path)?;
+        if path.ends_with(".json") {
+            Self::from_json(&source)
+        } else {
+            Self::from_toml(&source)
+        }
+    }
+
+    /// Serialises the pipeline as pretty-printed JSON that [`from_json`](Pipeline::from_json) reads back.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_value()).expect("JSON values always serialise")
+    }
+
+    pub(crate) fn to_value(&self) -> Json {
+        let mut root = Map::new();
+        root.insert("rows".into(), self.rows.into());
+        root.insert("cols".into(), self.cols.into());
+        if let Some(seed) = self.seed {
+            root.insert("seed".into(), seed.into());
+        }
+        if let Some(like) = &self.like {
+            root.insert("like".into(), like.as_str().into());
+        }
+        root.insert("layers".into(), self.layers.iter().map(Layer::to_value).collect());
+        if !self.steps.is_empty() {
+            root.insert("steps".into(), self.steps.iter().map(Step::to_value).collect());
+        }
+        if !self.outputs.is_empty() {
+            root.insert("outputs".into(), self.outputs.iter().map(Output::to_value).collect());
+        }
+        Json::Object(root)
+    }
+
+    pub(crate) fn from_value(json: &Json) -> Result<Self> {
+        let root = Table::new(json, "pipeline")?;
+        root.allow(&["rows", "cols", "seed", "like", "layers", "steps", "outputs"])?;
+        Ok(Pipeline {
+            rows: root.usize("rows")?,
+            cols: root.usize("cols")?,
+            seed: root.opt_u64("seed")?,
+            like: root.opt_string("like")?,
+            layers: root.list("layers", Layer::parse)?,
+            steps: root.list("steps", Step::parse)?,
+            outputs: root.list("outputs", Output::parse)?,
+        })
+    }
+
+    /// Seed used to generate layer `index`: its own seed if it has one, otherwise
+    /// the first value of stream `index + 1` of [`crate::RNG`] seeded with the
+    /// pipeline seed.
+    ///
+    /// Using `seed + index` instead would give layer 1 of seed 42 the same seed
+    /// as layer 0 of seed 43.
+    pub fn layer_seed(&self, index: usize) -> Option<u64> {
+        self.layers[index]
+            .seed
+            .or_else(|| self.seed.map(|s| sub_seed64(s, index)))
+    }
+
+    /// Checks dimensions, generator names and parameters, and that every step
+    /// and output refers to a layer that exists by the time it runs.
+    ///
+    /// Runs before anything is generated, so a mistake late in a long recipe
+    /// is reported without waiting for the early layers.
+    pub fn validate(&self) -> Result<()> {
+        check_dimensions(self.rows, self.cols)?;
+        let mut defined = BTreeSet::new();
+        for (i, layer) in self.layers.iter().enumerate() {
+            let at = format!("layers[{i}]");
+            if !defined.insert(layer.name.as_str()) {
+                return Err(config_err(
+                    &at,
+                    format!("duplicate layer name '{}'", layer.name),
+                ));
+            }
+            registry::try_get(&layer.algorithm)
+                .and_then(|g| g.validate_size(self.rows, self.cols, &layer.params))
+                .map_err(|e| config_err(&at, e))?;
+        }
+        let exists = |defined: &BTreeSet<&str>, at: &str, name: &str| {
+            if defined.contains(name) {
+                Ok(())
+            } else {
+                Err(config_err(at, format!("unknown layer '{name}'")))
+            }
+        };
+        for (i, step) in self.steps.iter().enumerate() {
+            let at = format!("steps[{i}]");
+            if let Some(name) = step.input() {
+                exists(&defined, &at, name)?;
+            }
+            match step {
+                Step::Copy { target, .. } => {
+                    defined.insert(target);
+                }
+                _ => exists(&defined, &at, step.target())?,
+            }
+        }
+        for (i, output) in self.outputs.iter().enumerate() {
+            exists(&defined, &format!("outputs[{i}]"), &output.layer)?;
+        }
+        Ok(())
+    }
+
+    /// Generates every layer and applies every step, returning the final layers by name.
+    pub fn run(&self) -> Result<BTreeMap<String, Grid>> {
+        self.validate()?;
+        let mut layers = BTreeMap::new();
+        for (i, layer) in self.layers.iter().enumerate() {
+            let generator = registry::try_get(&layer.algorithm)?;
+            let grid =
+                generator.try_generate(self.rows, self.cols, &layer.params, self.layer_seed(i))?;
+            layers.insert(layer.name.clone(), grid);
+        }
+        for step in &self.steps {
+            step.apply(&mut layers)?;
+        }
+        Ok(layers)
+    }
+
+    /// [`run`](Pipeline::run)s the pipeline and writes every output.
+    ///
+    /// If an output asks for metadata and the pipeline has no seed, a random
+    /// one is chosen so that the sidecar can reproduce the output.
+    pub fn execute(&self) -> Result<BTreeMap<String, Grid>> {
+        if self.seed.is_none() && self.outputs.iter().any(|o| o.metadata) {
+            let seeded = Pipeline { seed: Some(rand::random()), ..self.clone() };
+            return seeded.execute();
+        }
+        self.validate()?;
+        let transform = match &self.like {
+            Some(path) => {
+                let template = read_raster(path)?;
+                check_shape(
+                    (self.rows, self.cols),
+                    (template.grid.rows, template.grid.cols),
+                )?;
+                template.transform
+            }
+            None => GeoTransform::default(),
+        };
+        let layers = self.run()?;
+        for output in &self.outputs {
+            let geo = GeoGrid {
+                grid: layers[&output.layer].clone(),
+                transform: transform.clone(),
+            };
+            export::write(&geo, &output.path, &output.options)?;
+            if output.metadata {
+                let recipe = Pipeline { outputs: Vec::new(), ..self.clone() };
+                export::write_metadata(&output.path, &Metadata::new(recipe, &output.layer))?;
+            }
+        }
+        Ok(layers)
+    }
+}
+
+impl Layer {
+    fn parse(table: &Table) -> Result<Self> {
+        table.allow(&["name", "algorithm", "params", "seed"])?;
+        let mut params = Params::new();
+        if let Some(json) = table.get("params") {
+            let at = format!("{}.params", table.at);
+            let Json::Object(entries) = json else {
+                return Err(config_err(&at, "expected a table"));
+            };
+            for (key, value) in entries {
+                params.set(
+                    key,
+                    param_value(value).ok_or_else(|| {
+                        config_err(&at, format!("'{key}' must be a number, boolean or kernel"))
+                    })?,
+                );
+            }
+        }
+        Ok(Layer {
+            name: table.string("name")?,
+            algorithm: table.string("algorithm")?,
+            params,
+            seed: table.opt_u64("seed")?,
+        })
+    }
+
+    fn to_value(&self) -> Json {
+        let params: Map<String, Json> = self
+            .params
+            .iter()
+            .map(|(name, value)| (name.to_string(), param_json(value)))
+            .collect();
+        let mut layer = Map::new();
+        layer.insert("name".into(), self.name.as_str().into());
+        layer.insert("algorithm".into(), self.algorithm.as_str().into());
+        if !params.is_empty() {
+            layer.insert("params".into(), Json::Object(params));
+        }
+        if let Some(seed) = self.seed {
+            layer.insert("seed".into(), seed.into());
+        }
+        Json::Object(layer)
+    }
+}
+
+/// Converts a config value to a registry value the way the Python `generate` does:
+/// booleans, whole non-negative numbers as integers, other numbers as floats,
+/// and nested arrays as kernels.
+fn param_value(json: &Json) -> Option<Value> {
+    match json {
+        Json::Bool(b) => Some(Value::Bool(*b)),
+        Json::Number(n) => match n.as_u64() {
+            Some(i) => Some(Value::Int(i as usize)),
+            None => n.as_f64().map(Value::Float),
+        },
+        Json::Array(rows) => rows
+            .iter()
+            .map(|row| row.as_array()?.iter().map(Json::as_f64).collect())
+            .collect::<Option<_>>()
+            .map(Value::Kernel),
+        _ => None,
+    }
+}
+
+/// Inverse of [`param_value`]. Floats keep a fractional part (`4.0`) so they
+/// read back as floats.
+fn param_json(value: &Value) -> Json {
+    match value {
+        Value::Float(v) => (*v).into(),
+        Value::Int(v) => (*v).into(),
+        Value::Bool(v) => (*v).into(),
+        Value::Kernel(k) => k.iter().map(|row| Json::from(row.clone())).collect(),
+    }
+}
+
+impl Step {
+    fn parse(table: &Table) -> Result<Self> {
+        let op = table.string("op")?;
+        let target = table.string("target")?;
+        let (keys, step): (&[&str], _) = match op.as_str() {
+            "add" => (
+                &["with"],
+                Step::Add {
+                    target,
+                    with: table.operand("with")?,
+                },
+            ),
+            "multiply" => (
+                &["with"],
+                Step::Multiply {
+                    target,
+                    with: table.operand("with")?,
+                },
+            ),
+            "invert" => (&[], Step::Invert { target }),
+            "abs" => (&[], Step::Abs { target }),
+            "scale" => (&[], Step::Scale { target }),
+            "classify" => (
+                &["classes"],
+                Step::Classify {
+                    target,
+                    classes: table.usize("classes")?,
+                },
+            ),
+            "classify_weighted" => (
+                &["weights"],
+                Step::ClassifyWeighted {
+                    target,
+                    weights: table.floats("weights")?,
+                },
+            ),
+            "threshold" => (
+                &["value"],
+                Step::Threshold {
+                    target,
+                    value: table.f64("value")?,
+                },
+            ),
+            "mask" => {
+                let mask = match (table.opt_string("with")?, table.opt_string("path")?) {
+                    (Some(layer), None) => MaskSource::Layer(layer),
+                    (None, Some(path)) => MaskSource::File(path),
+                    _ => {
+                        return Err(config_err(
+                            &table.at,
+                            "mask needs exactly one of 'with' or 'path'",
+                        ))
+                    }
+                };
+                (&["with", "path"], Step::Mask { target, mask })
+            }
+            "copy" => (
+                &["from"],
+                Step::Copy {
+                    target,
+                    from: table.string("from")?,
+                },
+            ),
+            "focal" => {
+                let name = table.string("statistic")?;
+                let statistic = Statistic::from_name(&name).ok_or_else(|| {
+                    let names: Vec<&str> = Statistic::ALL.iter().map(|s| s.name()).collect();
+                    config_err(
+                        &table.at,
+                        format!("unknown statistic '{name}'; expected one of {}", names.join(", ")),
+                    )
+                })?;
+                let kernel = match table.get("kernel") {
+                    Some(Json::Array(_)) if table.get("radius").is_some() => {
+                        return Err(config_err(&table.at, "'radius' does not apply to a weights kernel"))
+                    }
+                    Some(Json::Array(_)) => Kernel::Custom(table.weights("kernel")?),
+                    _ => match table.opt_string("kernel")?.as_deref().unwrap_or("square") {
+                        "square" => Kernel::Square(table.usize("radius")?),
+                        "circle" => Kernel::Circle(table.f64("radius")?),
+                        other => {
+                            return Err(config_err(
+                                &table.at,
+                                format!("kernel must be \"square\", \"circle\" or an array of weights, got \"{other}\""),
+                            ))
+                        }
+                    },
+                };
+                kernel.check().map_err(|e| config_err(&table.at, e))?;
+                (
+                    &["statistic", "kernel", "radius", "periodic"],
+                    Step::Focal { target, statistic, kernel, boundary: table.boundary()? },
+                )
+            }
+            "fill_holes" => (
+                &["connectivity"],
+                Step::FillHoles {
+                    target,
+                    connectivity: table.connectivity()?,
+                },
+            ),
+            "skeletonize" => (
+                &["periodic"],
+                Step::Skeletonize {
+                    target,
+                    boundary: table.boundary()?,
+                },
+            ),
+            "sieve" => (
+                &["min_area", "connectivity", "periodic"],
+                Step::Sieve {
+                    target,
+                    min_area: table.usize("min_area")?,
+                    connectivity: table.connectivity()?,
+                    boundary: table.boundary()?,
+                },
+            ),
+            name if Morphology::from_name(name).is_some() => (
+                &["connectivity", "periodic"],
+                Step::Morphology {
+                    target,
+                    op: Morphology::from_name(name).unwrap(),
+                    connectivity: table.connectivity()?,
+                    boundary: table.boundary()?,
+                },
+            ),
+            other => return Err(config_err(&table.at, format!("unknown op '{other}'"))),
+        };
+        table.allow(&[&["op", "target"], keys].concat())?;
+        Ok(step)
+    }
+
+    fn to_value(&self) -> Json {
+        let operand = |with: &Operand| match with {
+            Operand::Layer(name) => Json::from(name.as_str()),
+            Operand::Value(v) => Json::from(*v),
+        };
+        let (op, fields): (&str, Vec<(&str, Json)>) = match self {
+            Step::Add { with, .. } => ("add", vec![("with", operand(with))]),
+            Step::Multiply { with, .. } => ("multiply", vec![("with", operand(with))]),
+            Step::Invert { .. } => ("invert", vec![]),
+            Step::Abs { .. } => ("abs", vec![]),
+            Step::Scale { .. } => ("scale", vec![]),
+            Step::Classify { classes, .. } => ("classify", vec![("classes", (*classes).into())]),
+            Step::ClassifyWeighted { weights, .. } => {
+                ("classify_weighted", vec![("weights", weights.clone().into())])
+            }
+            Step::Threshold { value, .. } => ("threshold", vec![("value", (*value).into())]),
+            Step::Mask { mask: MaskSource::Layer(name), .. } => {
+                ("mask", vec![("with", name.as_str().into())])
+            }
+            Step::Mask { mask: MaskSource::File(path), .. } => {
+                ("mask", vec![("path", path.as_str().into())])
+            }
+            Step::Copy { from, .. } => ("copy", vec![("from", from.as_str().into())]),
+            Step::Focal { statistic, kernel, boundary, .. } => {
+                let mut fields = vec![("statistic", statistic.name().into())];
+                match kernel {
+                    Kernel::Square(r) => {
+                        fields.extend([("kernel", "square".into()), ("radius", (*r).into())])
+                    }
+                    Kernel::Circle(r) => {
+                        fields.extend([("kernel", "circle".into()), ("radius", (*r).into())])
+                    }
+                    Kernel::Custom(k) => {
+                        let rows = (0..k.rows).map(|i| Json::from(k[i].to_vec())).collect();
+                        fields.push(("kernel", rows))
+                    }
+                }
+                if boundary.is_periodic() {
+                    fields.push(("periodic", true.into()));
+                }
+                ("focal", fields)
+            }
+            Step::Morphology { op, connectivity, boundary, .. } => {
+                let mut fields = vec![("connectivity", connectivity.name().into())];
+                if boundary.is_periodic() {
+                    fields.push(("periodic", true.into()));
+                }
+                (op.name(), fields)
+            }
+            Step::FillHoles { connectivity, .. } => {
+                ("fill_holes", vec![("connectivity", connectivity.name().into())])
+            }
+            Step::Skeletonize { boundary, .. } => {
+                let periodic = boundary.is_periodic().then(|| ("periodic", true.into()));
+                ("skeletonize", periodic.into_iter().collect())
+            }
+            Step::Sieve { min_area, connectivity, boundary, .. } => {
+                let mut fields = vec![
+                    ("min_area", (*min_area).into()),
+                    ("connectivity", connectivity.name().into()),
+                ];
+                if boundary.is_periodic() {
+                    fields.push(("periodic", true.into()));
+                }
+                ("sieve", fields)
+            }
+        };
+        let mut step = Map::new();
+        step.insert("op".into(), op.into());
+        step.insert("target".into(), self.target().into());
+        for (key, value) in fields {
+            step.insert(key.into(), value);
+        }
+        Json::Object(step)
+    }
+
+    /// The layer this step modifies or creates.
+    pub fn target(&self) -> &str {
+        match self {
+            Step::Add { target, .. }
+            | Step::Multiply { target, .. }
+            | Step::Invert { target }
+            | Step::Abs { target }
+            | Step::Scale { target }
+            | Step::Classify { target, .. }
+            | Step::ClassifyWeighted { target, .. }
+            | Step::Threshold { target, .. }
+            | Step::Mask { target, .. }
+            | Step::Copy { target, .. }
+            | Step::Focal { target, .. }
+            | Step::Morphology { target, .. }
+            | Step::FillHoles { target, .. }
+            | Step::Skeletonize { target, .. }
+            | Step::Sieve { target, .. } => target,
+        }
+    }
+
+    /// The layer this step reads besides its target, if any.
+    fn input(&self) -> Option<&str> {
+        match self {
+            Step::Add {
+                with: Operand::Layer(name),
+                ..
+            }
+            | Step::Multiply {
+                with: Operand::Layer(name),
+                ..
+            }
+            | Step::Mask {
+                mask: MaskSource::Layer(name),
+                ..
+            }
+            | Step::Copy { from: name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    fn apply(&self, layers: &mut BTreeMap<String, Grid>) -> Result<()> {
+        if let Step::Copy { target, from } = self {
+            let grid = layers[from].clone();
+            layers.insert(target.clone(), grid);
+            return Ok(());
+        }
+        let operand = self.input().map(|name| layers[name].clone());
+        let grid = layers.get_mut(self.target()).expect("validated layer name");
+        match self {
+            Step::Add {
+                with: Operand::Value(v),
+                ..
+            } => operation::add_value(grid, *v),
+            Step::Multiply {
+                with: Operand::Value(v),
+                ..
+            } => operation::multiply_value(grid, *v),
+            Step::Add { .. } => operation::try_add(grid, &operand.unwrap())?,
+            Step::Multiply { .. } => operation::try_multiply(grid, &operand.unwrap())?,
+            Step::Invert { .. } => operation::invert(grid),
+            Step::Abs { .. } => operation::abs(grid),
+            Step::Scale { .. } => operation::scale(grid),
+            Step::Classify { classes, .. } => operation::try_classify(grid, *classes)?,
+            Step::ClassifyWeighted { weights, .. } => {
+                operation::try_classify_weighted(grid, weights)?
+            }
+            Step::Threshold { value, .. } => operation::threshold(grid, *value),
+            Step::Mask {
+                mask: MaskSource::Layer(_),
+                ..
+            } => operation::try_apply_mask(grid, &operand.unwrap())?,
+            Step::Mask {
+                mask: MaskSource::File(path),
+                ..
+            } => operation::try_apply_mask(grid, &read_raster(path)?.grid)?,
+            Step::Focal { statistic, kernel, boundary, .. } => {
+                focal::try_focal(grid, *statistic, kernel, *boundary)?
+            }
+            Step::Morphology { op, connectivity, boundary, .. } => {
+                op.apply(grid, *connectivity, *boundary)
+            }
+            Step::FillHoles { connectivity, .. } => morphology::fill_holes(grid, *connectivity),
+            Step::Skeletonize { boundary, .. } => morphology::skeletonize(grid, *boundary),
+            Step::Sieve { min_area, connectivity, boundary, .. } => {
+                morphology::sieve(grid, *min_area, *connectivity, *boundary)
+            }
+            Step::Copy { .. } => unreachable!(),
+        }
+        Ok(())
+    }
+}
+
+impl Output {
+    fn parse(table: &Table) -> Result<Self> {
+        table.allow(&[
+            "layer",
+            "path",
+            "grayscale",
+            "colormap",
+            "legend",
+            "tiff_format",
+            "labels",
+            "metadata",
+        ])?;
+        let colormap = match table.opt_string("colormap")? {
+            Some(spec) => Colormap::parse(&spec).map_err(|e| config_err(&table.at, e))?,
+            None => Colormap::default(),
+        };
+        let tiff_format = match table.opt_string("tiff_format")?.as_deref() {
+            None | Some("u16") => None,
+            Some("f32") => Some(TiffSampleFormat::Float32),
+            Some("f64") => Some(TiffSampleFormat::Float64),
+            Some(other) => {
+                return Err(config_err(
+                    &table.at,
+                    format!("tiff_format must be \"u16\", \"f32\" or \"f64\", got \"{other}\""),
+                ))
+            }
+        };
+        Ok(Output {
+            layer: table.string("layer")?,
+            path: table.string("path")?,
+            options: WriteOptions {
+                grayscale: table.opt_bool("grayscale")?.unwrap_or(false),
+                colormap,
+                legend: table.opt_bool("legend")?.unwrap_or(false),
+                tiff_format,
+                labels: table.opt_bool("labels")?.unwrap_or(false),
+            },
+            metadata: table.opt_bool("metadata")?.unwrap_or(false),
+        })
+    }
+
+    fn to_value(&self) -> Json {
+        let mut output = Map::new();
+        output.insert("layer".into(), self.layer.as_str().into());
+        output.insert("path".into(), self.path.as_str().into());
+        if self.options.grayscale {
+            output.insert("grayscale".into(), true.into());
+        }
+        if self.options.colormap != Colormap::default() {
+            output.insert("colormap".into(), self.options.colormap.to_string().into());
+        }
+        if self.options.legend {
+            output.insert("legend".into(), true.into());
+        }
+        if let Some(format) = self.options.tiff_format {
+            let name = match format {
+                TiffSampleFormat::Float32 => "f32",
+                TiffSampleFormat::Float64 => "f64",
+            };
+            output.insert("tiff_format".into(), name.into());
+        }
+        if self.options.labels {
+            output.insert("labels".into(), true.into());
+        }
+        if self.metadata {
+            output.insert("metadata".into(), true.into());
+        }
+        Json::Object(output)
+    }
+}
+
+/// Reads a raster in any format [`export::read`] recognises.
+fn read_raster(path: &str) -> Result<GeoGrid> {
+    Ok(export::read(path)?)
+}
+
+fn config_err(at: &str, reason: impl Display) -> NlmError {
+    NlmError::Config(format!("{at}: {reason}"))
+}
+
+/// A JSON object being parsed, with its location for error messages.
+struct Table<'a> {
+    entries: &'a Map<String, Json>,
+    at: String,
+}
+
+impl<'a> Table<'a> {
+    fn new(json: &'a Json, at: &str) -> Result<Self> {
+        match json {
+            Json::Object(entries) => Ok(Table {
+                entries,
+                at: at.to_string(),
+            }),
+            _ => Err(config_err(at, "expected a table")),
+        }
+    }
+
+    /// Rejects keys outside `allowed`.
+    fn allow(&self, allowed: &[&str]) -> Result<()> {
+        match self.entries.keys().find(|k| !allowed.contains(&k.as_str())) {
+            Some(key) => Err(config_err(&self.at, format!("unknown key '{key}'"))),
+            None => Ok(()),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&'a Json> {
+        self.entries.get(key)
+    }
+
+    fn expected(&self, key: &str, what: &str) -> NlmError {
+        config_err(&format!("{}.{key}", self.at), format!("expected {what}"))
+    }
+
+    fn required(&self, key: &str) -> Result<&'a Json> {
+        self.get(key)
+            .ok_or_else(|| config_err(&self.at, format!("missing '{key}'")))
+    }
+
+    fn string(&self, key: &str) -> Result<String> {
+        self.opt_string(key)?
+            .ok_or_else(|| config_err(&self.at, format!("missing '{key}'")))
+    }
+
+    fn opt_string(&self, key: &str) -> Result<Option<String>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Json::String(s)) => Ok(Some(s.clone())),
+            Some(_) => Err(self.expected(key, "a string")),
+        }
+    }
+
+    fn usize(&self, key: &str) -> Result<usize> {
+        self.required(key)?
+            .as_u64()
+            .map(|n| n as usize)
+            .ok_or_else(|| self.expected(key, "a non-negative integer"))
+    }
+
+    fn opt_u64(&self, key: &str) -> Result<Option<u64>> {
+        self.get(key)
+            .map(|v| {
+                v.as_u64()
+                    .ok_or_else(|| self.expected(key, "a non-negative integer"))
+            })
+            .transpose()
+    }
+
+    fn f64(&self, key: &str) -> Result<f64> {
+        self.required(key)?
+            .as_f64()
+            .ok_or_else(|| self.expected(key, "a number"))
+    }
+
+    fn opt_bool(&self, key: &str) -> Result<Option<bool>> {
+        self.get(key)
+            .map(|v| {
+                v.as_bool()
+                    .ok_or_else(|| self.expected(key, "true or false"))
+            })
+            .transpose()
+    }
+
+    /// `periodic = true` as [`Boundary::Periodic`], and anything else as
+    /// [`Boundary::Clamped`].
+    fn boundary(&self) -> Result<Boundary> {
+        Ok(match self.opt_bool("periodic")? {
+            Some(true) => Boundary::Periodic,
+            _ => Boundary::Clamped,
+        })
+    }
+
+    /// An optional `connectivity` name, rook by default.
+    fn connectivity(&self) -> Result<Connectivity> {
+        match self.opt_string("connectivity")? {
+            None => Ok(Connectivity::Rook),
+            Some(name) => Connectivity::from_name(&name)
+                .ok_or_else(|| self.expected("connectivity", "\"rook\" or \"queen\"")),
+        }
+    }
+
+    /// Parses a nested array of numbers with rows of equal length.
+    fn weights(&self, key: &str) -> Result<Grid> {
+        let rows: Vec<Vec<f64>> = match param_value(self.required(key)?) {
+            Some(Value::Kernel(rows)) if rows.iter().all(|r| r.len() == rows[0].len()) => rows,
+            _ => return Err(self.expected(key, "an array of equal-length arrays of numbers")),
+        };
+        let cols = rows.first().map_or(0, Vec::len);
+        Ok(Grid { rows: rows.len(), cols, data: rows.concat() })
+    }
+
+    fn floats(&self, key: &str) -> Result<Vec<f64>> {
+        self.required(key)?
+            .as_array()
+            .and_then(|items| items.iter().map(Json::as_f64).collect())
+            .ok_or_else(|| self.expected(key, "an array of numbers"))
+    }
+
+    /// Parses an optional array of tables; a missing key is an empty list.
+    fn list<T>(&self, key: &str, parse: fn(&Table) -> Result<T>) -> Result<Vec<T>> {
+        let items: &[Json] = match self.get(key) {
+            None => &[],
+            Some(Json::Array(items)) => items,
+            Some(_) => return Err(self.expected(key, "an array")),
+        };
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| parse(&Table::new(item, &format!("{key}[{i}]"))?))
+            .collect()
+    }
+
+    fn operand(&self, key: &str) -> Result<Operand> {
+        match self.required(key)? {
+            Json::String(name) => Ok(Operand::Layer(name.clone())),
+            json => json
+                .as_f64()
+                .map(Operand::Value)
+                .ok_or_else(|| self.expected(key, "a layer name or a number")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPE: &str = r#"
+        rows = 40
+        cols = 30
+        seed = 7
+
+        [[layers]]
+        name = "terrain"
+        algorithm = "fbm_noise"
+        params = { scale = 3.0, octaves = 4 }
+
+        [[layers]]
+        name = "slope"
+        algorithm = "planar_gradient"
+        params = { direction = 90 }
+
+        [[steps]]
+        op = "multiply"
+        target = "terrain"
+        with = "slope"
+
+        [[steps]]
+        op = "copy"
+        target = "habitat"
+        from = "terrain"
+
+        [[steps]]
+        op = "classify_weighted"
+        target = "habitat"
+        weights = [0.6, 0.3, 0.1]
+    "#;
+
+    #[test]
+    fn test_pipeline_parses_toml() {
+        let p = Pipeline::from_toml(RECIPE).unwrap();
+        assert_eq!((p.rows, p.cols, p.seed), (40, 30, Some(7)));
+        assert_eq!(p.layers.len(), 2);
+        assert_eq!(p.layers[0].params.get("octaves"), Some(&Value::Int(4)));
+        assert_eq!(p.layers[1].params.get("direction"), Some(&Value::Int(90)));
+        assert_eq!(
+            p.steps[0],
+            Step::Multiply {
+                target: "terrain".into(),
+                with: Operand::Layer("slope".into())
+            }
+        );
+        assert_eq!(p.layer_seed(0), Some(sub_seed64(7, 0)));
+        assert_eq!(p.layer_seed(1), Some(sub_seed64(7, 1)));
+        assert_ne!(p.layer_seed(0), p.layer_seed(1));
+    }
+
+    #[test]
+    fn test_pipeline_json_matches_toml() {
+        let json = r#"{
+            "rows": 40, "cols": 30, "seed": 7,
+            "layers": [
+                {"name": "terrain", "algorithm": "fbm_noise", "params": {"scale": 3.0, "octaves": 4}},
+                {"name": "slope", "algorithm": "planar_gradient", "params": {"direction": 90}}
+            ],
+            "steps": [
+                {"op": "multiply", "target": "terrain", "with": "slope"},
+                {"op": "copy", "target": "habitat", "from": "terrain"},
+                {"op": "classify_weighted", "target": "habitat", "weights": [0.6, 0.3, 0.1]}
+            ]
+        }"#;
+        assert_eq!(
+            Pipeline::from_json(json).unwrap(),
+            Pipeline::from_toml(RECIPE).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pipeline_json_round_trip() {
+        let p = Pipeline::from_toml(RECIPE).unwrap();
+        assert_eq!(Pipeline::from_json(&p.to_json()).unwrap(), p);
+
+        let every_step = Pipeline::from_toml(
+            r#"
+            rows = 4
+            cols = 4
+            like = "site.asc"
+            layers = [
+                { name = "a", algorithm = "random", seed = 3 },
+                { name = "b", algorithm = "hill_grow", params = { kernel = [[0, 1, 0], [1, 1, 1], [0, 1, 0]], n = 10, runaway = true } },
+            ]
+            steps = [
+                { op = "add", target = "a", with = "b" },
+                { op = "add", target = "a", with = 0.5 },
+                { op = "multiply", target = "a", with = -2 },
+                { op = "invert", target = "a" },
+                { op = "abs", target = "a" },
+                { op = "scale", target = "a" },
+                { op = "classify", target = "a", classes = 3 },
+                { op = "classify_weighted", target = "b", weights = [1, 2] },
+                { op = "threshold", target = "b", value = 0.5 },
+                { op = "mask", target = "a", with = "b" },
+                { op = "mask", target = "a", path = "area.tif" },
+                { op = "copy", target = "c", from = "a" },
+                { op = "focal", target = "c", statistic = "std_dev", radius = 2 },
+                { op = "focal", target = "c", statistic = "majority", kernel = "circle", radius = 1.5, periodic = true },
+                { op = "focal", target = "b", statistic = "diversity", kernel = [[0, 1, 0], [1, 2, 1], [0, 1, 0]] },
+                { op = "open", target = "b", connectivity = "queen" },
+                { op = "erode", target = "b", periodic = true },
+                { op = "fill_holes", target = "b" },
+                { op = "skeletonize", target = "c", periodic = true },
+                { op = "sieve", target = "b", min_area = 4, connectivity = "queen" },
+            ]
+            outputs = [
+                { layer = "c", path = "c.png", grayscale = true, metadata = true },
+                { layer = "c", path = "c.tif", tiff_format = "f64" },
+                { layer = "b", path = "b.asc", labels = true },
+                { layer = "b", path = "b.png", colormap = "rdbu_r@4", legend = true },
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(Pipeline::from_json(&every_step.to_json()).unwrap(), every_step);
+    }
+
+    #[test]
+    fn test_pipeline_run_matches_manual_calls() {
+        let layers = Pipeline::from_toml(RECIPE).unwrap().run().unwrap();
+        let mut terrain = crate::fbm_noise(40, 30, 3.0, 4, 0.5, 2.0, Some(sub_seed64(7, 0)));
+        operation::multiply(
+            &mut terrain,
+            &crate::planar_gradient(40, 30, Some(90.0), Some(sub_seed64(7, 1))),
+        );
+        assert_eq!(layers["terrain"], terrain);
+
+        let mut habitat = terrain;
+        operation::classify_weighted(&mut habitat, &[0.6, 0.3, 0.1]);
+        assert_eq!(layers["habitat"], habitat);
+    }
+
+    #[test]
+    fn test_pipeline_constant_operands_and_mask() {
+        let p = Pipeline::from_toml(
+            r#"
+            rows = 5
+            cols = 5
+            layers = [
+                { name = "a", algorithm = "random", seed = 1 },
+                { name = "m", algorithm = "planar_gradient", params = { direction = 0.0 } },
+            ]
+            steps = [
+                { op = "multiply", target = "a", with = 0 },
+                { op = "add", target = "a", with = 0.25 },
+                { op = "threshold", target = "m", value = 0.5 },
+                { op = "mask", target = "a", with = "m" },
+            ]
+            "#,
+        )
+        .unwrap();
+        let layers = p.run().unwrap();
+        let a = &layers["a"];
+        assert!(a.nodata_count() > 0);
+        assert!(a.iter().all(|&v| v.is_nan() || v == 0.25));
+    }
+
+    #[test]
+    fn test_pipeline_focal_step() {
+        let p = Pipeline::from_toml(
+            r#"
+            rows = 12
+            cols = 9
+            layers = [{ name = "a", algorithm = "random", seed = 2 }]
+            steps = [{ op = "focal", target = "a", statistic = "median", kernel = "circle", radius = 2 }]
+            "#,
+        )
+        .unwrap();
+        let mut a = crate::random(12, 9, Some(2));
+        focal::focal(&mut a, Statistic::Median, &Kernel::Circle(2.0), Boundary::Clamped);
+        assert_eq!(p.run().unwrap()["a"], a);
+
+        for (step, message) in [
+            ("statistic = \"mode\", radius = 1", "unknown statistic 'mode'"),
+            ("statistic = \"mean\"", "missing 'radius'"),
+            ("statistic = \"mean\", kernel = [[1, 1]]", "odd number of rows and columns"),
+            ("statistic = \"mean\", kernel = [[1]], radius = 1", "'radius' does not apply"),
+        ] {
+            let source = format!("rows = 5\ncols = 5\nsteps = [{{ op = \"focal\", target = \"a\", {step} }}]");
+            let err = Pipeline::from_toml(&source).unwrap_err();
+            assert!(err.to_string().contains(message), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_pipeline_morphology_steps() {
+        let p = Pipeline::from_toml(
+            r#"
+            rows = 20
+            cols = 20
+            layers = [{ name = "a", algorithm = "percolation", params = { p = 0.55 }, seed = 4 }]
+            steps = [
+                { op = "close", target = "a", connectivity = "queen" },
+                { op = "fill_holes", target = "a" },
+                { op = "sieve", target = "a", min_area = 6, periodic = true },
+            ]
+            "#,
+        )
+        .unwrap();
+        let mut a = crate::percolation(20, 20, 0.55, Some(4));
+        morphology::close(&mut a, Connectivity::Queen, Boundary::Clamped);
+        morphology::fill_holes(&mut a, Connectivity::Rook);
+        morphology::sieve(&mut a, 6, Connectivity::Rook, Boundary::Periodic);
+        assert_eq!(p.run().unwrap()["a"], a);
+
+        for (step, message) in [
+            ("op = \"dilate\", connectivity = \"hex\"", "expected \"rook\" or \"queen\""),
+            ("op = \"sieve\"", "missing 'min_area'"),
+            ("op = \"skeletonize\", connectivity = \"rook\"", "unknown key 'connectivity'"),
+        ] {
+            let source = format!("rows = 5\ncols = 5\nsteps = [{{ target = \"a\", {step} }}]");
+            let err = Pipeline::from_toml(&source).unwrap_err();
+            assert!(err.to_string().contains(message), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_pipeline_rejects_unknown_key() {
+        let err = Pipeline::from_toml(
+            "rows = 5\ncols = 5\n[[steps]]\nop = \"classify\"\ntarget = \"a\"\nclasses = 3\nweigths = [1]\n",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("steps[0]: unknown key 'weigths'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_pipeline_rejects_unknown_layer() {
+        let p = Pipeline::from_toml(
+            "rows = 5\ncols = 5\n[[layers]]\nname = \"a\"\nalgorithm = \"random\"\n\
+             [[steps]]\nop = \"add\"\ntarget = \"a\"\nwith = \"b\"\n",
+        )
+        .unwrap();
+        let err = p.validate().unwrap_err();
+        assert!(
+            err.to_string().contains("steps[0]: unknown layer 'b'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_pipeline_rejects_bad_layers() {
+        let unknown = Pipeline::from_toml(
+            "rows = 5\ncols = 5\n[[layers]]\nname = \"a\"\nalgorithm = \"nope\"\n",
+        )
+        .unwrap();
+        assert!(matches!(unknown.validate(), Err(NlmError::Config(_))));
+
+        let out_of_range = Pipeline::from_toml(
+            "rows = 5\ncols = 5\n[[layers]]\nname = \"a\"\nalgorithm = \"fbm_noise\"\nparams = { scale = -1.0 }\n",
+        )
+        .unwrap();
+        let err = out_of_range.validate().unwrap_err();
+        assert!(
+            err.to_string().contains("layers[0]") && err.to_string().contains("scale"),
+            "{err}"
+        );
+
+        let empty = Pipeline::from_toml("rows = 0\ncols = 5\n").unwrap();
+        assert!(matches!(
+            empty.validate(),
+            Err(NlmError::InvalidDimensions { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pipeline_reports_syntax_errors() {
+        assert!(matches!(
+            Pipeline::from_toml("rows = "),
+            Err(NlmError::Config(_))
+        ));
+        assert!(matches!(Pipeline::from_json("{"), Err(NlmError::Config(_))));
+        assert!(matches!(
+            Pipeline::from_toml("cols = 5"),
+            Err(NlmError::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,1718 @@
+//! Runtime discovery of the NLM generators.
+//!
+//! Every algorithm in [`algorithms`](crate) is exposed here as a [`Generator`]:
+//! a name, a short description, a parameter schema with defaults and valid
+//! ranges, and a `generate` method taking parameters by name. Tools can list
+//! [`generators`] and build UIs or configs from [`Generator::params`] instead of
+//! hard-coding each function's positional signature.
+//!
+//! ```
+//! use nlmrs::registry::{self, Params};
+//!
+//! let fbm = registry::get("fbm_noise").unwrap();
+//! let params = Params::new().with("octaves", 4usize).with("scale", 2.0);
+//! let grid = fbm.generate(50, 50, &params, Some(42));
+//! assert_eq!(grid.rows, 50);
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::error::{check_dimensions, NlmError, Result};
+use crate::grid::{Boundary, Grid};
+
+const INFINITY: f64 = f64::INFINITY;
+
+/// The type of a generator parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamKind {
+    /// A real number.
+    Float,
+    /// A non-negative integer (count, iterations, size in cells).
+    Int,
+    /// A boolean switch.
+    Bool,
+    /// A 2-D convolution kernel (square, odd side length).
+    Kernel,
+    /// Observed cells as `[row, col, value]` triples, held as a
+    /// [`Value::Kernel`] with three columns.
+    Observations,
+}
+
+/// A parameter value passed to [`Generator::generate`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Float(f64),
+    Int(usize),
+    Bool(bool),
+    Kernel(Vec<Vec<f64>>),
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Self {
+        Value::Float(v)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(v: usize) -> Self {
+        Value::Int(v)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Bool(v)
+    }
+}
+
+impl From<Vec<Vec<f64>>> for Value {
+    fn from(v: Vec<Vec<f64>>) -> Self {
+        Value::Kernel(v)
+    }
+}
+
+impl Value {
+    /// The value as a number, if it is one. Integers are widened to `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Float(v) => Some(v),
+            Value::Int(v) => Some(v as f64),
+            _ => None,
+        }
+    }
+
+    /// The value as a count, if it is an integer or a non-negative whole float.
+    pub fn as_usize(&self) -> Option<usize> {
+        match *self {
+            Value::Int(v) => Some(v),
+            Value::Float(v) if v >= 0.0 && v.fract() == 0.0 => Some(v as usize),
+            _ => None,
+        }
+    }
+}
+
+/// Formats numbers and booleans as Rust literals and kernels as nested JSON arrays,
+/// the forms accepted by [`Param::parse`].
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Float(v) => write!(f, "{v}"),
+            Value::Int(v) => write!(f, "{v}"),
+            Value::Bool(v) => write!(f, "{v}"),
+            Value::Kernel(k) => {
+                let rows: Vec<String> = k
+                    .iter()
+                    .map(|row| {
+                        let cells: Vec<String> = row.iter().map(f64::to_string).collect();
+                        format!("[{}]", cells.join(", "))
+                    })
+                    .collect();
+                write!(f, "[{}]", rows.join(", "))
+            }
+        }
+    }
+}
+
+/// Schema entry describing one generator parameter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    /// Value used when the parameter is not given. `None` marks an optional
+    /// parameter whose absence has its own meaning (e.g. a random direction).
+    pub default: Option<Value>,
+    /// Smallest valid value for numeric parameters; inclusive unless `min_exclusive`.
+    pub min: f64,
+    /// Whether values must be strictly greater than `min`.
+    pub min_exclusive: bool,
+    /// Largest valid value (inclusive) for numeric parameters.
+    pub max: f64,
+    pub description: &'static str,
+}
+
+impl Param {
+    const fn float(
+        name: &'static str,
+        default: f64,
+        min: f64,
+        max: f64,
+        description: &'static str,
+    ) -> Self {
+        Param {
+            name,
+            kind: ParamKind::Float,
+            default: Some(Value::Float(default)),
+            min,
+            min_exclusive: false,
+            max,
+            description,
+        }
+    }
+
+    const fn int(
+        name: &'static str,
+        default: usize,
+        min: f64,
+        max: f64,
+        description: &'static str,
+    ) -> Self {
+        Param {
+            name,
+            kind: ParamKind::Int,
+            default: Some(Value::Int(default)),
+            min,
+            min_exclusive: false,
+            max,
+            description,
+        }
+    }
+
+    const fn opt_float(name: &'static str, min: f64, max: f64, description: &'static str) -> Self {
+        Param {
+            name,
+            kind: ParamKind::Float,
+            default: None,
+            min,
+            min_exclusive: false,
+            max,
+            description,
+        }
+    }
+
+    const fn flag(name: &'static str, default: bool, description: &'static str) -> Self {
+        Param {
+            name,
+            kind: ParamKind::Bool,
+            default: Some(Value::Bool(default)),
+            min: 0.0,
+            min_exclusive: false,
+            max: 1.0,
+            description,
+        }
+    }
+
+    /// A float parameter that must be strictly positive.
+    const fn positive(name: &'static str, default: f64, description: &'static str) -> Self {
+        Param {
+            name,
+            kind: ParamKind::Float,
+            default: Some(Value::Float(default)),
+            min: 0.0,
+            min_exclusive: true,
+            max: INFINITY,
+            description,
+        }
+    }
+
+    /// Parses a value of this parameter's kind from text (`"0.5"`, `"200"`,
+    /// `"true"`, `"[[0, 1, 0], [1, 1, 1], [0, 1, 0]]"`) and checks its range.
+    pub fn parse(&self, text: &str) -> Result<Value> {
+        let text = text.trim();
+        let value = match self.kind {
+            ParamKind::Float => text.parse().ok().map(Value::Float),
+            ParamKind::Int => text.parse().ok().map(Value::Int),
+            ParamKind::Bool => text.parse().ok().map(Value::Bool),
+            ParamKind::Kernel | ParamKind::Observations => {
+                serde_json::from_str(text).ok().map(Value::Kernel)
+            }
+        };
+        let value = value.ok_or_else(|| {
+            NlmError::invalid(self.name, format!("'{text}' is not a {:?} value", self.kind))
+        })?;
+        self.check(&value)?;
+        Ok(value)
+    }
+
+    /// Checks `value` against this parameter's kind and range.
+    pub fn check(&self, value: &Value) -> Result<()> {
+        let number = match (self.kind, value) {
+            (ParamKind::Float, v) => v.as_f64(),
+            (ParamKind::Int, v) => v.as_usize().map(|n| n as f64),
+            (ParamKind::Bool, Value::Bool(_)) => return Ok(()),
+            (ParamKind::Kernel, Value::Kernel(k)) => {
+                let n = k.len();
+                return if n % 2 == 1 && k.iter().all(|row| row.len() == n) {
+                    Ok(())
+                } else {
+                    Err(NlmError::invalid(
+                        self.name,
+                        "kernel must be square with an odd side length",
+                    ))
+                };
+            }
+            (ParamKind::Observations, Value::Kernel(k)) => {
+                let cell = |x: f64| x >= 0.0 && x.fract() == 0.0;
+                return match k.iter().find(|o| !matches!(o[..], [i, j, _] if cell(i) && cell(j))) {
+                    None => Ok(()),
+                    Some(o) => Err(NlmError::invalid(
+                        self.name,
+                        format!("{o:?} is not a [row, col, value] observation"),
+                    )),
+                };
+            }
+            _ => None,
+        };
+        let above_min = |x: f64| if self.min_exclusive { x > self.min } else { x >= self.min };
+        match number {
+            Some(x) if x.is_finite() && above_min(x) && x <= self.max => Ok(()),
+            Some(x) => Err(NlmError::invalid(
+                self.name,
+                format!(
+                    "{x} is outside the valid range {}{}, {}]",
+                    if self.min_exclusive { "(" } else { "[" },
+                    self.min,
+                    self.max
+                ),
+            )),
+            None => Err(NlmError::invalid(
+                self.name,
+                format!("expected a {:?} value, got {value:?}", self.kind),
+            )),
+        }
+    }
+}
+
+/// Named parameter values for [`Generator::generate`].
+///
+/// Parameters that are not set fall back to the schema default.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Params(BTreeMap<String, Value>);
+
+impl Params {
+    pub fn new() -> Self {
+        Params::default()
+    }
+
+    /// Sets `name` to `value`, returning `self` for chaining.
+    pub fn with(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    pub fn set(&mut self, name: &str, value: impl Into<Value>) {
+        self.0.insert(name.to_string(), value.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+
+    /// Iterates over the set parameters in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Returns the float parameter `name`. Panics if it is missing or not a number.
+    pub fn float(&self, name: &str) -> f64 {
+        self.opt_float(name)
+            .unwrap_or_else(|| panic!("missing parameter '{name}'"))
+    }
+
+    /// Returns the float parameter `name`, or `None` if it is not set.
+    pub fn opt_float(&self, name: &str) -> Option<f64> {
+        self.get(name).map(|v| {
+            v.as_f64()
+                .unwrap_or_else(|| panic!("'{name}' is not a number"))
+        })
+    }
+
+    /// Returns the integer parameter `name`. Panics if it is missing or not a count.
+    pub fn int(&self, name: &str) -> usize {
+        match self.get(name) {
+            Some(v) => v
+                .as_usize()
+                .unwrap_or_else(|| panic!("'{name}' is not an integer")),
+            None => panic!("missing parameter '{name}'"),
+        }
+    }
+
+    /// Returns the boolean parameter `name`. Panics if it is missing or not a bool.
+    pub fn flag(&self, name: &str) -> bool {
+        match self.get(name) {
+            Some(Value::Bool(b)) => *b,
+            _ => panic!("missing boolean parameter '{name}'"),
+        }
+    }
+
+    /// Returns the observations parameter `name` as `(row, col, value)`
+    /// triples. Panics if it is missing or not a list of triples.
+    pub fn observations(&self, name: &str) -> Vec<(usize, usize, f64)> {
+        match self.get(name) {
+            Some(Value::Kernel(k)) => k
+                .iter()
+                .map(|o| match o[..] {
+                    [i, j, v] => (i as usize, j as usize, v),
+                    _ => panic!("'{name}' holds {o:?}, not a [row, col, value] observation"),
+                })
+                .collect(),
+            _ => panic!("missing observations parameter '{name}'"),
+        }
+    }
+
+    /// Returns the kernel parameter `name`, or `None` if it is not set.
+    pub fn opt_kernel(&self, name: &str) -> Option<Vec<Vec<f64>>> {
+        match self.get(name) {
+            Some(Value::Kernel(k)) => Some(k.clone()),
+            Some(v) => panic!("'{name}' is not a kernel: {v:?}"),
+            None => None,
+        }
+    }
+}
+
+/// A neutral landscape model generator that can be discovered and driven at runtime.
+pub trait Generator: Send + Sync {
+    /// Snake-case name matching the library function, e.g. `"fbm_noise"`.
+    fn name(&self) -> &str;
+
+    /// Algorithm family: `"gradient"`, `"noise"`, `"patch"` or `"hill_grow"`.
+    fn category(&self) -> &str;
+
+    /// One-line description.
+    fn description(&self) -> &str;
+
+    /// Parameter schema, excluding `rows`, `cols` and `seed`.
+    fn params(&self) -> &[Param];
+
+    /// True if the generator has a periodic mode, switched on by its
+    /// `periodic` flag. The others handle edges their own fixed way, mostly as
+    /// hard edges, so tiled copies of their output show seams.
+    fn supports_periodic(&self) -> bool {
+        self.params().iter().any(|p| p.name == PERIODIC.name)
+    }
+
+    /// Generates a (rows x cols) grid. Unset parameters take their defaults.
+    ///
+    /// Panics if `params` fails [`validate`](Generator::validate).
+    fn generate(&self, rows: usize, cols: usize, params: &Params, seed: Option<u64>) -> Grid;
+
+    /// Checks that every parameter in `params` is known, of the right kind and in range.
+    fn validate(&self, params: &Params) -> Result<()> {
+        check_params(self, params)
+    }
+
+    /// Like [`validate`](Generator::validate), and also checks that `params`
+    /// can generate a (rows x cols) grid: it must not be empty, and some
+    /// parameters only work at certain sizes.
+    fn validate_size(&self, rows: usize, cols: usize, params: &Params) -> Result<()> {
+        check_dimensions(rows, cols)?;
+        self.validate(params)
+    }
+
+    /// Fallible [`generate`](Generator::generate): checks `params` and the
+    /// grid size with [`validate_size`](Generator::validate_size) instead of
+    /// panicking.
+    fn try_generate(
+        &self,
+        rows: usize,
+        cols: usize,
+        params: &Params,
+        seed: Option<u64>,
+    ) -> Result<Grid> {
+        self.validate_size(rows, cols, params)?;
+        Ok(self.generate(rows, cols, params, seed))
+    }
+}
+
+/// Checks each entry of `params` against the schema of `generator`.
+fn check_params<G: Generator + ?Sized>(generator: &G, params: &Params) -> Result<()> {
+    for (name, value) in params.iter() {
+        match generator.params().iter().find(|p| p.name == name) {
+            Some(p) => p.check(value)?,
+            None => {
+                return Err(NlmError::invalid(
+                    name,
+                    format!("not a parameter of {}", generator.name()),
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Highest noise frequency (`scale * lacunarity^(octaves - 1)`) the noise
+/// backends can sample without their lattice coordinates overflowing.
+const MAX_FREQUENCY: f64 = 1e9;
+
+/// A built-in generator: schema plus a thunk onto the library function.
+struct Algorithm {
+    name: &'static str,
+    category: &'static str,
+    description: &'static str,
+    params: &'static [Param],
+    run: fn(usize, usize, &Params, Option<u64>) -> Grid,
+}
+
+impl Generator for Algorithm {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn category(&self) -> &str {
+        self.category
+    }
+
+    fn description(&self) -> &str {
+        self.description
+    }
+
+    fn params(&self) -> &[Param] {
+        self.params
+    }
+
+    fn validate(&self, params: &Params) -> Result<()> {
+        check_params(self, params)?;
+        let is_float_scale = |p: &Param| p.name == "scale" && p.kind == ParamKind::Float;
+        if !self.params.iter().any(is_float_scale) {
+            return Ok(());
+        }
+        let resolved = self.resolve(params);
+        let mut frequency = resolved.float("scale");
+        if let (Some(octaves), Some(lacunarity)) =
+            (resolved.get("octaves"), resolved.opt_float("lacunarity"))
+        {
+            let octaves = octaves.as_usize().unwrap_or(1);
+            frequency *= lacunarity.powi(octaves.saturating_sub(1).min(i32::MAX as usize) as i32);
+        }
+        if frequency <= MAX_FREQUENCY {
+            Ok(())
+        } else {
+            Err(NlmError::invalid(
+                "scale",
+                format!("highest octave frequency {frequency:e} exceeds {MAX_FREQUENCY:e}"),
+            ))
+        }
+    }
+
+    fn validate_size(&self, rows: usize, cols: usize, params: &Params) -> Result<()> {
+        check_dimensions(rows, cols)?;
+        self.validate(params)?;
+        match self.name {
+            "midpoint_displacement" if boundary(&self.resolve(params)).is_periodic() => {
+                crate::check_periodic_midpoint(rows, cols)
+            }
+            "conditional_gaussian_field" | "conditional_spectral_synthesis" => {
+                let observations = self.resolve(params).observations("observations");
+                crate::check_observations(rows, cols, &observations)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn generate(&self, rows: usize, cols: usize, params: &Params, seed: Option<u64>) -> Grid {
+        if let Err(e) = self.validate(params) {
+            panic!("{e}");
+        }
+        (self.run)(rows, cols, &self.resolve(params), seed)
+    }
+}
+
+impl Algorithm {
+    /// Returns `params` with every unset parameter filled in from its default.
+    fn resolve(&self, params: &Params) -> Params {
+        let mut resolved = params.clone();
+        for p in self.params {
+            if let (None, Some(default)) = (params.get(p.name), &p.default) {
+                resolved.set(p.name, default.clone());
+            }
+        }
+        resolved
+    }
+}
+
+/// Returns every built-in generator, grouped by category.
+pub fn generators() -> impl Iterator<Item = &'static dyn Generator> {
+    ALGORITHMS.iter().map(|a| a as &dyn Generator)
+}
+
+/// Looks up a built-in generator by name (e.g. `"perlin_noise"`).
+pub fn get(name: &str) -> Option<&'static dyn Generator> {
+    generators().find(|g| g.name() == name)
+}
+
+/// Like [`get`], but returns [`NlmError::UnknownAlgorithm`] for unknown names.
+pub fn try_get(name: &str) -> Result<&'static dyn Generator> {
+    get(name).ok_or_else(|| NlmError::UnknownAlgorithm(name.to_string()))
+}
+
+// ── Shared parameter definitions ─────────────────────────────────────────────
+
+const DIRECTION: Param = Param::opt_float(
+    "direction",
+    0.0,
+    360.0,
+    "Direction in degrees [0, 360). Random if omitted.",
+);
+const SCALE: Param = Param::positive("scale", 4.0, "Base noise frequency (higher = more features)");
+const OCTAVES: Param = Param::int("octaves", 6, 1.0, INFINITY, "Number of octaves");
+const PERSISTENCE: Param = Param::positive("persistence", 0.5, "Amplitude scaling per octave");
+const LACUNARITY: Param = Param::float(
+    "lacunarity",
+    2.0,
+    0.0,
+    INFINITY,
+    "Frequency scaling per octave",
+);
+const SIGMA: Param = Param::positive(
+    "sigma",
+    10.0,
+    "Gaussian kernel standard deviation in cells (correlation length)",
+);
+
+const FRACTAL: &[Param] = &[SCALE, OCTAVES, PERSISTENCE, LACUNARITY];
+const PERIODIC: Param = Param::flag(
+    "periodic",
+    false,
+    "Wrap opposite edges so the grid is a torus",
+);
+/// Grid area per random nucleus or infection focus, so larger grids get more.
+const SITE_AREA: Param = Param::int(
+    "site_area",
+    2500,
+    1.0,
+    INFINITY,
+    "Grid cells per random starting site (at least one site)",
+);
+const OBSERVATIONS: Param = Param {
+    name: "observations",
+    kind: ParamKind::Observations,
+    default: Some(Value::Kernel(Vec::new())),
+    min: -INFINITY,
+    min_exclusive: false,
+    max: INFINITY,
+    description: "Cells the grid must pass through, as [[row, col, value], ...] on the [0, 1] scale",
+};
+const SCALE_ONLY: &[Param] = &[SCALE];
+const NONE: &[Param] = &[];
+
+/// Reads the shared `periodic` flag as a [`Boundary`].
+fn boundary(params: &Params) -> Boundary {
+    if params.flag("periodic") {
+        Boundary::Periodic
+    } else {
+        Boundary::Clamped
+    }
+}
+
+const fn count(default: usize, description: &'static str) -> Param {
+    Param::int("n", default, 0.0, INFINITY, description)
+}
+
+/// Number of seed points or features; zero would leave the grid featureless.
+const fn points(default: usize, description: &'static str) -> Param {
+    Param::int("n", default, 1.0, INFINITY, description)
+}
+
+const fn iterations(default: usize, description: &'static str) -> Param {
+    Param::int("iterations", default, 0.0, INFINITY, description)
+}
+
+// ── Registry ─────────────────────────────────────────────────────────────────
+
+static ALGORITHMS: &[Algorithm] = &[
+    // Gradient
+    Algorithm {
+        name: "planar_gradient",
+        category: "gradient",
+        description: "Linear gradient at a given angle",
+        params: &[DIRECTION],
+        run: |r, c, p, s| crate::planar_gradient(r, c, p.opt_float("direction"), s),
+    },
+    Algorithm {
+        name: "edge_gradient",
+        category: "gradient",
+        description: "Symmetric gradient (zero at edges, peak in middle)",
+        params: &[DIRECTION],
+        run: |r, c, p, s| crate::edge_gradient(r, c, p.opt_float("direction"), s),
+    },
+    Algorithm {
+        name: "distance_gradient",
+        category: "gradient",
+        description: "Radial gradient from a random centre point",
+        params: &[PERIODIC],
+        run: |r, c, p, s| crate::distance_gradient_with_boundary(r, c, boundary(p), s),
+    },
+    Algorithm {
+        name: "wave_gradient",
+        category: "gradient",
+        description: "Sinusoidal wave gradient",
+        params: &[
+            Param::float(
+                "period",
+                2.5,
+                0.0,
+                INFINITY,
+                "Wave period (smaller = larger waves)",
+            ),
+            DIRECTION,
+        ],
+        run: |r, c, p, s| {
+            crate::wave_gradient(r, c, p.float("period"), p.opt_float("direction"), s)
+        },
+    },
+    Algorithm {
+        name: "landscape_gradient",
+        category: "gradient",
+        description: "Elliptical landscape gradient centred at the grid midpoint",
+        params: &[
+            DIRECTION,
+            Param::float(
+                "aspect",
+                1.0,
+                1.0,
+                INFINITY,
+                "Major-to-minor axis ratio (1.0 = circular)",
+            ),
+        ],
+        run: |r, c, p, s| {
+            crate::landscape_gradient(r, c, p.opt_float("direction"), p.float("aspect"), s)
+        },
+    },
+    Algorithm {
+        name: "concentric_rings",
+        category: "gradient",
+        description: "Concentric sinusoidal rings around a random centre",
+        params: &[Param::float(
+            "frequency",
+            5.0,
+            0.0,
+            INFINITY,
+            "Number of ring oscillations across half the grid diagonal",
+        )],
+        run: |r, c, p, s| crate::concentric_rings(r, c, p.float("frequency"), s),
+    },
+    Algorithm {
+        name: "checkerboard",
+        category: "gradient",
+        description: "Alternating checkerboard at a random offset",
+        params: &[Param::int(
+            "scale",
+            10,
+            1.0,
+            INFINITY,
+            "Side length of each square in cells",
+        )],
+        run: |r, c, p, s| crate::checkerboard(r, c, p.int("scale"), s),
+    },
+    Algorithm {
+        name: "spiral_gradient",
+        category: "gradient",
+        description: "Archimedean spiral gradient emanating from a random centre",
+        params: &[Param::float(
+            "turns",
+            3.0,
+            0.0,
+            INFINITY,
+            "Number of full spiral rotations across half the grid diagonal",
+        )],
+        run: |r, c, p, s| crate::spiral_gradient(r, c, p.float("turns"), s),
+    },
+    Algorithm {
+        name: "radial_sweep",
+        category: "gradient",
+        description: "Radial sweep — clockwise angle around a random centre",
+        params: NONE,
+        run: |r, c, _, s| crate::radial_sweep(r, c, s),
+    },
+    // Noise
+    Algorithm {
+        name: "perlin_noise",
+        category: "noise",
+        description: "Perlin noise",
+        params: SCALE_ONLY,
+        run: |r, c, p, s| crate::perlin_noise(r, c, p.float("scale"), s),
+    },
+    Algorithm {
+        name: "fbm_noise",
+        category: "noise",
+        description: "Fractal Brownian motion (layered Perlin noise)",
+        params: FRACTAL,
+        run: |r, c, p, s| {
+            crate::fbm_noise(
+                r,
+                c,
+                p.float("scale"),
+                p.int("octaves"),
+                p.float("persistence"),
+                p.float("lacunarity"),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "ridged_noise",
+        category: "noise",
+        description: "Ridged multifractal noise — sharp ridges and mountain-like terrain",
+        params: FRACTAL,
+        run: |r, c, p, s| {
+            crate::ridged_noise(
+                r,
+                c,
+                p.float("scale"),
+                p.int("octaves"),
+                p.float("persistence"),
+                p.float("lacunarity"),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "billow_noise",
+        category: "noise",
+        description: "Billow noise — rounded cloud- and hill-like patterns",
+        params: FRACTAL,
+        run: |r, c, p, s| {
+            crate::billow_noise(
+                r,
+                c,
+                p.float("scale"),
+                p.int("octaves"),
+                p.float("persistence"),
+                p.float("lacunarity"),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "worley_noise",
+        category: "noise",
+        description: "Worley (cellular) noise — territory / patch patterns",
+        params: SCALE_ONLY,
+        run: |r, c, p, s| crate::worley_noise(r, c, p.float("scale"), s),
+    },
+    Algorithm {
+        name: "hybrid_noise",
+        category: "noise",
+        description: "Hybrid multifractal noise — blends smooth and ridged characteristics",
+        params: FRACTAL,
+        run: |r, c, p, s| {
+            crate::hybrid_noise(
+                r,
+                c,
+                p.float("scale"),
+                p.int("octaves"),
+                p.float("persistence"),
+                p.float("lacunarity"),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "value_noise",
+        category: "noise",
+        description: "Value noise — interpolated lattice noise",
+        params: SCALE_ONLY,
+        run: |r, c, p, s| crate::value_noise(r, c, p.float("scale"), s),
+    },
+    Algorithm {
+        name: "turbulence",
+        category: "noise",
+        description: "Turbulence — fBm with absolute-value fold per octave",
+        params: FRACTAL,
+        run: |r, c, p, s| {
+            crate::turbulence(
+                r,
+                c,
+                p.float("scale"),
+                p.int("octaves"),
+                p.float("persistence"),
+                p.float("lacunarity"),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "domain_warp",
+        category: "noise",
+        description: "Domain-warped Perlin noise — organic, swirling patterns",
+        params: &[
+            SCALE,
+            Param::float(
+                "warp_strength",
+                1.0,
+                -INFINITY,
+                INFINITY,
+                "Displacement magnitude applied to sample coordinates",
+            ),
+        ],
+        run: |r, c, p, s| crate::domain_warp(r, c, p.float("scale"), p.float("warp_strength"), s),
+    },
+    Algorithm {
+        name: "spectral_synthesis",
+        category: "noise",
+        description: "Spectral synthesis — 1/f^beta noise generated in the frequency domain",
+        params: &[Param::float(
+            "beta",
+            2.0,
+            -INFINITY,
+            INFINITY,
+            "Spectral exponent: 0 = white noise, 1 = pink, 2 = brown",
+        )],
+        run: |r, c, p, s| crate::spectral_synthesis(r, c, p.float("beta"), s),
+    },
+    Algorithm {
+        name: "conditional_spectral_synthesis",
+        category: "noise",
+        description: "Spectral synthesis passing through observed cell values (kriging of residuals)",
+        params: &[
+            Param::float(
+                "beta",
+                2.0,
+                -INFINITY,
+                INFINITY,
+                "Spectral exponent: 0 = white noise, 1 = pink, 2 = brown",
+            ),
+            OBSERVATIONS,
+        ],
+        run: |r, c, p, s| {
+            crate::conditional_spectral_synthesis(
+                r,
+                c,
+                p.float("beta"),
+                &p.observations("observations"),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "fractal_brownian_surface",
+        category: "noise",
+        description: "Fractal Brownian surface parameterised by the Hurst exponent",
+        params: &[Param::float(
+            "h",
+            0.5,
+            0.0,
+            1.0,
+            "Hurst exponent: 0 = rough, 1 = smooth",
+        )],
+        run: |r, c, p, s| crate::fractal_brownian_surface(r, c, p.float("h"), s),
+    },
+    Algorithm {
+        name: "simplex_noise",
+        category: "noise",
+        description: "OpenSimplex noise",
+        params: SCALE_ONLY,
+        run: |r, c, p, s| crate::simplex_noise(r, c, p.float("scale"), s),
+    },
+    Algorithm {
+        name: "voronoi_distance",
+        category: "noise",
+        description: "Voronoi distance field from random feature points",
+        params: &[points(50, "Number of feature points")],
+        run: |r, c, p, s| crate::voronoi_distance(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "sine_composite",
+        category: "noise",
+        description: "Superposition of sinusoidal plane waves",
+        params: &[Param::int(
+            "waves",
+            8,
+            0.0,
+            INFINITY,
+            "Number of sinusoidal waves to superpose",
+        )],
+        run: |r, c, p, s| crate::sine_composite(r, c, p.int("waves"), s),
+    },
+    Algorithm {
+        name: "curl_noise",
+        category: "noise",
+        description: "Divergence-free curl-warped Perlin noise",
+        params: SCALE_ONLY,
+        run: |r, c, p, s| crate::curl_noise(r, c, p.float("scale"), s),
+    },
+    Algorithm {
+        name: "gabor_noise",
+        category: "noise",
+        description: "Gabor noise — oriented sinusoidal kernel superposition",
+        params: &[SCALE, points(500, "Number of Gabor kernels to place")],
+        run: |r, c, p, s| crate::gabor_noise(r, c, p.float("scale"), p.int("n"), s),
+    },
+    Algorithm {
+        name: "spot_noise",
+        category: "noise",
+        description: "Spot noise — random anisotropic elliptical Gaussian blobs",
+        params: &[points(200, "Number of spots to place")],
+        run: |r, c, p, s| crate::spot_noise(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "anisotropic_noise",
+        category: "noise",
+        description: "Anisotropic noise — fBm stretched along a dominant axis",
+        params: &[
+            SCALE,
+            OCTAVES,
+            Param::float(
+                "direction",
+                45.0,
+                0.0,
+                360.0,
+                "Orientation of elongation in degrees [0, 360)",
+            ),
+            Param::float(
+                "stretch",
+                4.0,
+                1.0,
+                INFINITY,
+                "Compression ratio for the perpendicular axis",
+            ),
+        ],
+        run: |r, c, p, s| {
+            crate::anisotropic_noise(
+                r,
+                c,
+                p.float("scale"),
+                p.int("octaves"),
+                p.float("direction"),
+                p.float("stretch"),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "tiled_noise",
+        category: "noise",
+        description: "Tiled noise — seamlessly repeating Perlin noise via 4-D torus mapping",
+        params: SCALE_ONLY,
+        run: |r, c, p, s| crate::tiled_noise(r, c, p.float("scale"), s),
+    },
+    Algorithm {
+        name: "voronoi_crease",
+        category: "noise",
+        description: "Voronoi crease (F2-F1) — highlights cell boundaries",
+        params: &[points(30, "Number of Voronoi seed points")],
+        run: |r, c, p, s| crate::voronoi_crease(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "perlin_worley",
+        category: "noise",
+        description: "Combined Perlin-Worley noise",
+        params: SCALE_ONLY,
+        run: |r, c, p, s| crate::perlin_worley(r, c, p.float("scale"), s),
+    },
+    Algorithm {
+        name: "lognormal_field",
+        category: "noise",
+        description: "Lognormal random field — exponential-transformed Gaussian field",
+        params: &[SIGMA, PERIODIC],
+        run: |r, c, p, s| {
+            crate::lognormal_field_with_boundary(r, c, p.float("sigma"), boundary(p), s)
+        },
+    },
+    Algorithm {
+        name: "multifractal_terrain",
+        category: "noise",
+        description: "Musgrave heterogeneous multifractal terrain",
+        params: &[SCALE, OCTAVES],
+        run: |r, c, p, s| crate::multifractal_terrain(r, c, p.float("scale"), p.int("octaves"), s),
+    },
+    Algorithm {
+        name: "blue_noise",
+        category: "noise",
+        description: "Spectral blue noise (high-frequency energy, uniform point distribution)",
+        params: NONE,
+        run: |r, c, _, s| crate::blue_noise(r, c, s),
+    },
+    // Patch
+    Algorithm {
+        name: "random",
+        category: "patch",
+        description: "Spatially random noise",
+        params: NONE,
+        run: |r, c, _, s| crate::random(r, c, s),
+    },
+    Algorithm {
+        name: "random_element",
+        category: "patch",
+        description: "Random element nearest-neighbour interpolation",
+        params: &[
+            Param::float(
+                "n",
+                50000.0,
+                1.0,
+                INFINITY,
+                "Number of seed elements to place",
+            ),
+            PERIODIC,
+        ],
+        run: |r, c, p, s| crate::random_element_with_boundary(r, c, p.float("n"), boundary(p), s),
+    },
+    Algorithm {
+        name: "midpoint_displacement",
+        category: "patch",
+        description: "Diamond-square fractal terrain (midpoint displacement)",
+        params: &[
+            Param::float(
+                "h",
+                1.0,
+                0.0,
+                1.0,
+                "Spatial autocorrelation (0 = rough, 1 = smooth)",
+            ),
+            PERIODIC,
+        ],
+        run: |r, c, p, s| {
+            crate::midpoint_displacement_with_boundary(r, c, p.float("h"), boundary(p), s)
+        },
+    },
+    Algorithm {
+        name: "gaussian_field",
+        category: "patch",
+        description: "Gaussian random field — spatially correlated noise",
+        params: &[SIGMA, PERIODIC],
+        run: |r, c, p, s| {
+            crate::gaussian_field_with_boundary(r, c, p.float("sigma"), boundary(p), s)
+        },
+    },
+    Algorithm {
+        name: "conditional_gaussian_field",
+        category: "patch",
+        description: "Gaussian random field passing through observed cell values (kriging of residuals)",
+        params: &[SIGMA, OBSERVATIONS, PERIODIC],
+        run: |r, c, p, s| {
+            crate::conditional_gaussian_field_with_boundary(
+                r,
+                c,
+                p.float("sigma"),
+                &p.observations("observations"),
+                boundary(p),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "random_cluster",
+        category: "patch",
+        description: "Random cluster via fault-line cuts",
+        params: &[points(200, "Number of fault-line cuts"), PERIODIC],
+        run: |r, c, p, s| crate::random_cluster_with_boundary(r, c, p.int("n"), boundary(p), s),
+    },
+    Algorithm {
+        name: "mosaic",
+        category: "patch",
+        description: "Mosaic — discrete Voronoi patch map with flat-coloured regions",
+        params: &[points(200, "Number of Voronoi seed points"), PERIODIC],
+        run: |r, c, p, s| crate::mosaic_with_boundary(r, c, p.int("n"), boundary(p), s),
+    },
+    Algorithm {
+        name: "rectangular_cluster",
+        category: "patch",
+        description: "Rectangular cluster — overlapping random axis-aligned rectangles",
+        params: &[points(200, "Number of rectangles to place")],
+        run: |r, c, p, s| crate::rectangular_cluster(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "percolation",
+        category: "patch",
+        description: "Percolation — binary Bernoulli lattice (Gardner 1987)",
+        params: &[Param::float(
+            "p",
+            0.5,
+            0.0,
+            1.0,
+            "Probability a cell is habitat",
+        )],
+        run: |r, c, p, s| crate::percolation(r, c, p.float("p"), s),
+    },
+    Algorithm {
+        name: "binary_space_partitioning",
+        category: "patch",
+        description: "Binary space partitioning — hierarchical rectilinear partition",
+        params: &[points(100, "Number of rectangles in the final partition")],
+        run: |r, c, p, s| crate::binary_space_partitioning(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "neighbourhood_clustering",
+        category: "patch",
+        description: "Neighbourhood clustering — iterative majority-vote patch clustering",
+        params: &[
+            Param::int("k", 5, 1.0, INFINITY, "Number of distinct patch classes"),
+            iterations(10, "Number of majority-vote iterations"),
+            PERIODIC,
+        ],
+        run: |r, c, p, s| {
+            crate::neighbourhood_clustering_with_boundary(
+                r,
+                c,
+                p.int("k"),
+                p.int("iterations"),
+                boundary(p),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "cellular_automaton",
+        category: "patch",
+        description: "Cellular automaton — binary cave-like patterns from birth/survival rules",
+        params: &[
+            Param::float(
+                "p",
+                0.45,
+                0.0,
+                1.0,
+                "Initial probability of a cell being alive",
+            ),
+            iterations(5, "Number of rule iterations"),
+            Param::int(
+                "birth_threshold",
+                5,
+                0.0,
+                8.0,
+                "Min live neighbours for a dead cell to become alive",
+            ),
+            Param::int(
+                "survival_threshold",
+                4,
+                0.0,
+                8.0,
+                "Min live neighbours for a live cell to stay alive",
+            ),
+        ],
+        run: |r, c, p, s| {
+            crate::cellular_automaton(
+                r,
+                c,
+                p.float("p"),
+                p.int("iterations"),
+                p.int("birth_threshold"),
+                p.int("survival_threshold"),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "diffusion_limited_aggregation",
+        category: "patch",
+        description: "Diffusion-limited aggregation — branching fractal cluster grown from centre",
+        params: &[count(2000, "Number of particles to release")],
+        run: |r, c, p, s| crate::diffusion_limited_aggregation(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "reaction_diffusion",
+        category: "patch",
+        description: "Gray-Scott reaction-diffusion — Turing-pattern spots, stripes and labyrinths",
+        params: &[
+            iterations(1000, "Number of simulation steps"),
+            Param::float(
+                "feed",
+                0.055,
+                0.0,
+                1.0,
+                "Feed rate for chemical A (controls pattern type)",
+            ),
+            Param::float(
+                "kill",
+                0.062,
+                0.0,
+                1.0,
+                "Kill rate for chemical B (controls pattern type)",
+            ),
+        ],
+        run: |r, c, p, s| {
+            crate::reaction_diffusion(
+                r,
+                c,
+                p.int("iterations"),
+                p.float("feed"),
+                p.float("kill"),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "eden_growth",
+        category: "patch",
+        description: "Eden growth model — compact fractal blob grown from the centre",
+        params: &[count(2000, "Number of cells to add to the cluster")],
+        run: |r, c, p, s| crate::eden_growth(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "invasion_percolation",
+        category: "patch",
+        description: "Invasion percolation (lowest-weight boundary growth from centre)",
+        params: &[count(2000, "Number of cells to invade")],
+        run: |r, c, p, s| crate::invasion_percolation(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "gaussian_blobs",
+        category: "patch",
+        description: "Sum of random Gaussian blob kernels",
+        params: &[
+            points(50, "Number of blob centres"),
+            Param::positive("sigma", 5.0, "Gaussian width (in cells)"),
+        ],
+        run: |r, c, p, s| crate::gaussian_blobs(r, c, p.int("n"), p.float("sigma"), s),
+    },
+    Algorithm {
+        name: "ising_model",
+        category: "patch",
+        description: "Ising model via Glauber dynamics (binary spin lattice)",
+        params: &[
+            Param::float(
+                "beta",
+                0.4,
+                0.0,
+                INFINITY,
+                "Inverse temperature (near 0.44 = critical point)",
+            ),
+            iterations(
+                1000,
+                "Number of sweeps (each sweep = rows × cols spin-flip attempts)",
+            ),
+        ],
+        run: |r, c, p, s| crate::ising_model(r, c, p.float("beta"), p.int("iterations"), s),
+    },
+    Algorithm {
+        name: "levy_flight",
+        category: "patch",
+        description: "Levy flight random walk density map",
+        params: &[count(1000, "Number of flight steps")],
+        run: |r, c, p, s| crate::levy_flight(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "hydraulic_erosion",
+        category: "patch",
+        description: "Hydraulic erosion simulation on a random heightmap",
+        params: &[count(500, "Number of erosion droplets to simulate")],
+        run: |r, c, p, s| crate::hydraulic_erosion(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "poisson_disk",
+        category: "patch",
+        description: "Poisson disk sampling (binary inhibition pattern)",
+        params: &[Param::float(
+            "min_dist",
+            5.0,
+            0.0,
+            INFINITY,
+            "Minimum distance in cells between any two sample points",
+        )],
+        run: |r, c, p, s| crate::poisson_disk(r, c, p.float("min_dist"), s),
+    },
+    Algorithm {
+        name: "brownian_motion",
+        category: "patch",
+        description: "Brownian motion — Gaussian random-walk visit density",
+        params: &[count(5000, "Number of walk steps")],
+        run: |r, c, p, s| crate::brownian_motion(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "forest_fire",
+        category: "patch",
+        description: "Forest fire — Drossel-Schwabl cellular automaton burn-history map",
+        params: &[
+            Param::float(
+                "p_tree",
+                0.02,
+                0.0,
+                1.0,
+                "Per-step probability an empty cell becomes a tree",
+            ),
+            Param::float(
+                "p_lightning",
+                0.001,
+                0.0,
+                1.0,
+                "Per-step probability a tree ignites spontaneously",
+            ),
+            iterations(500, "Number of simulation steps"),
+        ],
+        run: |r, c, p, s| {
+            crate::forest_fire(
+                r,
+                c,
+                p.float("p_tree"),
+                p.float("p_lightning"),
+                p.int("iterations"),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "river_network",
+        category: "patch",
+        description: "River network — D8 flow accumulation on fBm terrain",
+        params: NONE,
+        run: |r, c, _, s| crate::river_network(r, c, s),
+    },
+    Algorithm {
+        name: "hexagonal_voronoi",
+        category: "patch",
+        description: "Hexagonal Voronoi — BFS mosaic from a regular hexagonal seed lattice",
+        params: &[Param::int(
+            "n",
+            50,
+            1.0,
+            INFINITY,
+            "Approximate number of hexagonal cells",
+        )],
+        run: |r, c, p, s| crate::hexagonal_voronoi(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "fault_uplift",
+        category: "patch",
+        description: "Fault uplift ridges from random fault lines",
+        params: &[count(50, "Number of fault lines")],
+        run: |r, c, p, s| crate::fault_uplift(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "triangular_tessellation",
+        category: "patch",
+        description: "Triangular tessellation via Delaunay triangulation",
+        params: &[points(30, "Number of seed points (triangles ≈ 2n)")],
+        run: |r, c, p, s| crate::triangular_tessellation(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "physarum",
+        category: "patch",
+        description: "Physarum slime mould transport network",
+        params: &[
+            points(1000, "Number of agents"),
+            iterations(300, "Number of simulation steps"),
+        ],
+        run: |r, c, p, s| crate::physarum(r, c, p.int("n"), p.int("iterations"), s),
+    },
+    Algorithm {
+        name: "cahn_hilliard",
+        category: "patch",
+        description: "Cahn-Hilliard spinodal decomposition",
+        params: &[iterations(2000, "Number of PDE time steps")],
+        run: |r, c, p, s| crate::cahn_hilliard(r, c, p.int("iterations"), s),
+    },
+    Algorithm {
+        name: "crystal_growth",
+        category: "patch",
+        description: "Crystal growth (Reiter's snowflake model)",
+        params: &[
+            iterations(300, "Number of growth steps"),
+            SITE_AREA,
+        ],
+        run: |r, c, p, s| {
+            crate::crystal_growth(r, c, p.int("iterations"), p.int("site_area"), s)
+        },
+    },
+    Algorithm {
+        name: "predator_prey",
+        category: "patch",
+        description: "Predator-prey spatial pattern (Lotka-Volterra PDE)",
+        params: &[iterations(500, "Number of PDE time steps")],
+        run: |r, c, p, s| crate::predator_prey(r, c, p.int("iterations"), s),
+    },
+    Algorithm {
+        name: "sandpile",
+        category: "patch",
+        description: "Bak-Tang-Wiesenfeld sandpile — self-organized criticality grain-count map",
+        params: &[count(5000, "Number of grains to drop")],
+        run: |r, c, p, s| crate::sandpile(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "correlated_walk",
+        category: "patch",
+        description: "Correlated random walk visit-density map",
+        params: &[
+            count(5000, "Number of walk steps"),
+            Param::float(
+                "kappa",
+                2.0,
+                0.0,
+                INFINITY,
+                "Directional persistence (0 = isotropic, higher = straighter)",
+            ),
+        ],
+        run: |r, c, p, s| crate::correlated_walk(r, c, p.int("n"), p.float("kappa"), s),
+    },
+    Algorithm {
+        name: "schelling",
+        category: "patch",
+        description: "Schelling segregation — self-organising binary spatial patches",
+        params: &[
+            Param::float(
+                "tolerance",
+                0.5,
+                0.0,
+                1.0,
+                "Minimum fraction of same-type neighbours for a cell to be happy",
+            ),
+            iterations(50, "Number of relocation sweeps"),
+        ],
+        run: |r, c, p, s| crate::schelling(r, c, p.float("tolerance"), p.int("iterations"), s),
+    },
+    Algorithm {
+        name: "sir_epidemic",
+        category: "patch",
+        description: "Spatial SIR epidemic model (reaction-diffusion PDE)",
+        params: &[
+            Param::float("beta", 0.3, 0.0, INFINITY, "Infection rate"),
+            Param::float("gamma", 0.1, 0.0, INFINITY, "Recovery rate"),
+            iterations(200, "Number of PDE time steps"),
+            SITE_AREA,
+            Param::float(
+                "noise",
+                1.0,
+                0.0,
+                1.0,
+                "Transmission noise: each step scales infection by a factor in [1 - noise, 1 + noise)",
+            ),
+        ],
+        run: |r, c, p, s| {
+            crate::sir_epidemic(
+                r,
+                c,
+                p.float("beta"),
+                p.float("gamma"),
+                p.int("iterations"),
+                p.int("site_area"),
+                p.float("noise"),
+                s,
+            )
+        },
+    },
+    Algorithm {
+        name: "thermal_erosion",
+        category: "patch",
+        description: "Thermal erosion of a Perlin heightmap via talus-slope diffusion",
+        params: &[count(50, "Number of erosion passes")],
+        run: |r, c, p, s| crate::thermal_erosion(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "space_colonization",
+        category: "patch",
+        description: "Space colonisation — auxin-based vascular branching network",
+        params: &[points(200, "Number of auxin attractors")],
+        run: |r, c, p, s| crate::space_colonization(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "substrate",
+        category: "patch",
+        description: "Substrate — Jared Tarbell crack propagation",
+        params: &[count(10, "Number of simulation steps")],
+        run: |r, c, p, s| crate::substrate(r, c, p.int("n"), s),
+    },
+    Algorithm {
+        name: "game_of_life",
+        category: "patch",
+        description: "Conway's Game of Life visit-density map",
+        params: &[iterations(200, "Number of generations")],
+        run: |r, c, p, s| crate::game_of_life(r, c, p.int("iterations"), s),
+    },
+    Algorithm {
+        name: "rock_paper_scissors",
+        category: "patch",
+        description: "Cyclic dominance (rock-paper-scissors) spiral domains",
+        params: &[iterations(200, "Number of synchronous update steps")],
+        run: |r, c, p, s| crate::rock_paper_scissors(r, c, p.int("iterations"), s),
+    },
+    Algorithm {
+        name: "excitable_media",
+        category: "patch",
+        description: "Greenberg-Hastings excitable media — spiral waves and target patterns",
+        params: &[iterations(200, "Number of synchronous update steps")],
+        run: |r, c, p, s| crate::excitable_media(r, c, p.int("iterations"), s),
+    },
+    Algorithm {
+        name: "truchet",
+        category: "patch",
+        description: "Truchet quarter-circle tile pattern",
+        params: &[Param::int(
+            "n",
+            10,
+            1.0,
+            INFINITY,
+            "Tile side length in cells",
+        )],
+        run: |r, c, p, s| crate::truchet(r, c, p.int("n"), s),
+    },
+    // Hill grow
+    Algorithm {
+        name: "hill_grow",
+        category: "hill_grow",
+        description: "Hill-grow algorithm",
+        params: &[
+            Param::int("n", 10000, 0.0, INFINITY, "Number of iterations"),
+            Param::flag(
+                "runaway",
+                true,
+                "Hills grow in clusters (weighted random selection)",
+            ),
+            Param {
+                name: "kernel",
+                kind: ParamKind::Kernel,
+                default: None,
+                min: -INFINITY,
+                min_exclusive: false,
+                max: INFINITY,
+                description: "Hill shape added each iteration. Defaults to a 3x3 cross if omitted.",
+            },
+            Param::flag("only_grow", false, "Surface only grows, never shrinks"),
+            PERIODIC,
+        ],
+        run: |r, c, p, s| {
+            crate::hill_grow_with_boundary(
+                r,
+                c,
+                p.int("n"),
+                p.flag("runaway"),
+                p.opt_kernel("kernel"),
+                p.flag("only_grow"),
+                boundary(p),
+                s,
+            )
+        },
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_names_unique() {
+        let mut names: Vec<&str> = generators().map(|g| g.name()).collect();
+        let n = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), n);
+        assert_eq!(n, 77);
+    }
+
+    #[test]
+    fn test_every_generator_runs_with_defaults() {
+        for g in generators() {
+            let grid = g.generate(20, 20, &Params::new(), Some(1));
+            assert_eq!((grid.rows, grid.cols), (20, 20), "{}", g.name());
+        }
+    }
+
+    #[test]
+    fn test_generate_matches_free_function() {
+        let params = Params::new().with("scale", 2.0).with("octaves", 3usize);
+        let a = get("fbm_noise").unwrap().generate(30, 30, &params, Some(5));
+        let b = crate::fbm_noise(30, 30, 2.0, 3, 0.5, 2.0, Some(5));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_try_generate_rejects_periodic_midpoint_of_other_sizes() {
+        let g = get("midpoint_displacement").unwrap();
+        let periodic = Params::new().with("periodic", true);
+        let err = g.try_generate(100, 64, &periodic, Some(1)).unwrap_err();
+        assert!(matches!(&err, NlmError::InvalidParameter { name, .. } if name == "periodic"));
+        assert!(g.try_generate(128, 64, &periodic, Some(1)).is_ok());
+        assert!(g.try_generate(100, 64, &Params::new(), Some(1)).is_ok());
+    }
+
+    #[test]
+    fn test_supports_periodic_follows_the_periodic_flag() {
+        let periodic: Vec<&str> = generators()
+            .filter(|g| g.supports_periodic())
+            .map(|g| g.name())
+            .collect();
+        assert_eq!(
+            periodic,
+            [
+                "distance_gradient",
+                "lognormal_field",
+                "random_element",
+                "midpoint_displacement",
+                "gaussian_field",
+                "conditional_gaussian_field",
+                "random_cluster",
+                "mosaic",
+                "neighbourhood_clustering",
+                "hill_grow",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_conditional_generators_take_observations() {
+        let g = get("conditional_gaussian_field").unwrap();
+        let observed = Params::new().with("observations", vec![vec![3.0, 4.0, 0.7]]);
+        let grid = g.try_generate(20, 20, &observed, Some(1)).unwrap();
+        assert_eq!(grid[3][4], 0.7);
+        assert_eq!(
+            g.generate(20, 20, &Params::new(), Some(1)),
+            get("gaussian_field").unwrap().generate(20, 20, &Params::new(), Some(1))
+        );
+
+        let outside = Params::new().with("observations", vec![vec![20.0, 4.0, 0.7]]);
+        assert!(g.try_generate(20, 20, &outside, Some(1)).is_err());
+        let fractional = Params::new().with("observations", vec![vec![3.5, 4.0, 0.7]]);
+        assert!(g.validate(&fractional).is_err());
+        let param = &get("conditional_spectral_synthesis").unwrap().params()[1];
+        assert_eq!(
+            param.parse("[[1, 2, 0.5]]").unwrap(),
+            Value::Kernel(vec![vec![1.0, 2.0, 0.5]])
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_params() {
+        let p = get("percolation").unwrap();
+        assert!(p.validate(&Params::new().with("p", 0.3)).is_ok());
+        assert!(p.validate(&Params::new().with("p", 1.5)).is_err());
+        assert!(p.validate(&Params::new().with("q", 0.5)).is_err());
+        assert!(p.validate(&Params::new().with("p", true)).is_err());
+        let mosaic = get("mosaic").unwrap();
+        assert!(mosaic.validate(&Params::new().with("n", 10.0)).is_ok());
+        assert!(mosaic.validate(&Params::new().with("n", 10.5)).is_err());
+        assert!(mosaic.validate(&Params::new().with("n", 0usize)).is_err());
+        let fbm = get("fbm_noise").unwrap();
+        assert!(fbm.validate(&Params::new().with("scale", 0.0)).is_err());
+        assert!(fbm.validate(&Params::new().with("scale", f64::NAN)).is_err());
+        assert!(fbm.validate(&Params::new().with("lacunarity", 1e6)).is_err());
+    }
+
+    #[test]
+    fn test_try_generate_reports_errors() {
+        let g = get("gaussian_field").unwrap();
+        assert!(g.try_generate(10, 10, &Params::new(), Some(1)).is_ok());
+        assert!(matches!(
+            g.try_generate(10, 10, &Params::new().with("sigma", -1.0), None),
+            Err(NlmError::InvalidParameter { .. })
+        ));
+        assert!(matches!(
+            g.try_generate(0, 10, &Params::new(), None),
+            Err(NlmError::InvalidDimensions { rows: 0, cols: 10 })
+        ));
+        assert!(matches!(try_get("nope"), Err(NlmError::UnknownAlgorithm(_))));
+    }
+
+    #[test]
+    fn test_defaults_within_ranges() {
+        for g in generators() {
+            for p in g.params() {
+                if let Some(default) = &p.default {
+                    assert!(p.check(default).is_ok(), "{}.{}", g.name(), p.name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_param_parse_round_trips_display() {
+        let hill_grow = get("hill_grow").unwrap();
+        for p in hill_grow.params() {
+            if let Some(default) = &p.default {
+                assert_eq!(p.parse(&default.to_string()).unwrap(), *default, "{}", p.name);
+            }
+        }
+        let kernel = hill_grow.params().iter().find(|p| p.name == "kernel").unwrap();
+        let cross = Value::Kernel(vec![vec![0.0, 1.0, 0.0], vec![1.0; 3], vec![0.0, 1.0, 0.0]]);
+        assert_eq!(kernel.parse(&cross.to_string()).unwrap(), cross);
+
+        let p = &get("percolation").unwrap().params()[0];
+        assert_eq!(p.parse(" 0.25 ").unwrap(), Value::Float(0.25));
+        assert!(matches!(p.parse("1.5"), Err(NlmError::InvalidParameter { .. })));
+        assert!(matches!(p.parse("half"), Err(NlmError::InvalidParameter { .. })));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_generate_panics_on_invalid_params() {
+        get("percolation")
+            .unwrap()
+            .generate(5, 5, &Params::new().with("p", -1.0), None);
+    }
+}
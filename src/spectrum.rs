@@ -0,0 +1,318 @@
+//! Radially averaged power spectra and spectral exponent estimation.
+//!
+//! [`power_spectrum`] is the inverse check of `spectral_synthesis`: it
+//! returns the power of a grid's 2D Fourier transform averaged over rings of
+//! equal frequency, and [`PowerSpectrum::fit`] fits the power law
+//! `P(f) ∝ 1/f^beta` to it. For a fractal surface `beta = 2h + 2`, as in
+//! `fractal_brownian_surface`, so the fit also gives the Hurst exponent.
+//!
+//! ```
+//! use nlmrs::spectral_synthesis;
+//! use nlmrs::spectrum::spectral_exponent;
+//!
+//! let grid = spectral_synthesis(128, 128, 2.5, Some(42));
+//! let fit = spectral_exponent(&grid);
+//! assert!((fit.beta - 2.5).abs() < 0.2);
+//! ```
+//!
+//! To calibrate against a DEM, which unlike these NLMs does not wrap at its
+//! edges, remove the regional slope and taper the edges first with
+//! [`SpectrumOptions`], and fit over the frequencies where the spectrum
+//! follows a straight line on log-log axes.
+
+use std::sync::Arc;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+
+use crate::grid::Grid;
+
+/// Preprocessing applied by [`power_spectrum`] before the transform.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SpectrumOptions {
+    /// Subtract the least-squares plane through the values rather than only
+    /// their mean, so a regional slope does not swamp the low frequencies.
+    pub detrend: bool,
+    /// Multiply by a 2D Hann window, tapering the values to zero at the
+    /// edges to limit the leakage a non-periodic grid's edges cause.
+    pub window: bool,
+}
+
+/// Radially averaged power spectral density returned by [`power_spectrum`].
+///
+/// Bin `k` holds the Fourier components whose radial frequency rounds to
+/// `k / n` cycles per cell, where `n` is the longer side of the grid, for
+/// `k` from 1 up to the Nyquist frequency of 0.5 cycles per cell. Bins
+/// without components are left out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PowerSpectrum {
+    /// Mean radial frequency of each bin's components, in cycles per cell.
+    pub frequencies: Vec<f64>,
+    /// Mean power of each bin's components, `|F|²` divided by the sum of
+    /// squared window weights (the number of cells without a window).
+    pub power: Vec<f64>,
+    /// Number of Fourier components averaged in each bin.
+    pub counts: Vec<usize>,
+}
+
+/// A power law `P(f) ∝ 1/f^beta` fitted by [`PowerSpectrum::fit`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectralFit {
+    /// Spectral exponent: 0 for white noise, 2 for a Brownian surface.
+    pub beta: f64,
+    /// Hurst exponent `(beta - 2) / 2`, meaningful for `beta` in (2, 4).
+    pub hurst: f64,
+    /// Coefficient of determination of the fit on log-log axes.
+    pub r_squared: f64,
+    /// Number of bins fitted.
+    pub bins: usize,
+}
+
+impl PowerSpectrum {
+    /// Fits `log P = c - beta log f` by least squares to the bins with
+    /// positive power and frequencies in `[min, max]` cycles per cell.
+    ///
+    /// Every field but `bins` is `NaN` if fewer than two bins qualify.
+    pub fn fit(&self, min: f64, max: f64) -> SpectralFit {
+        let points: Vec<(f64, f64)> = self
+            .frequencies
+            .iter()
+            .zip(&self.power)
+            .filter(|&(&f, &p)| f >= min && f <= max && p > 0.0)
+            .map(|(f, p)| (f.ln(), p.ln()))
+            .collect();
+        let n = points.len() as f64;
+        let x_mean = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let y_mean = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|p| (p.0 - x_mean).powi(2)).sum();
+        let sxy: f64 = points.iter().map(|p| (p.0 - x_mean) * (p.1 - y_mean)).sum();
+        let syy: f64 = points.iter().map(|p| (p.1 - y_mean).powi(2)).sum();
+        let (beta, r_squared) = if points.len() < 2 || sxx == 0.0 {
+            (f64::NAN, f64::NAN)
+        } else {
+            (-sxy / sxx, if syy == 0.0 { 1.0 } else { sxy * sxy / (sxx * syy) })
+        };
+        SpectralFit { beta, hurst: (beta - 2.0) / 2.0, r_squared, bins: points.len() }
+    }
+
+    /// The spectrum as CSV with a header row: `frequency,wavelength,power,count`,
+    /// with wavelengths in cells.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("frequency,wavelength,power,count\n");
+        for ((f, p), n) in self.frequencies.iter().zip(&self.power).zip(&self.counts) {
+            csv += &format!("{f},{},{p},{n}\n", 1.0 / f);
+        }
+        csv
+    }
+
+    /// Writes [`to_csv`](PowerSpectrum::to_csv) to `path`.
+    pub fn write_csv(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_csv())
+    }
+}
+
+/// Computes the radially averaged power spectral density of `grid`.
+///
+/// The mean (or with `detrend` the fitted plane) is subtracted first and
+/// NoData cells are then set to zero, so they add no power of their own but
+/// do blur the spectrum in proportion to how many there are.
+pub fn power_spectrum(grid: &Grid, options: &SpectrumOptions) -> PowerSpectrum {
+    let (rows, cols) = (grid.rows, grid.cols);
+    if rows == 0 || cols == 0 {
+        return PowerSpectrum { frequencies: Vec::new(), power: Vec::new(), counts: Vec::new() };
+    }
+    let trend = trend(grid, options.detrend);
+    let hann = |i: usize, n: usize| {
+        if options.window && n > 1 {
+            0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / (n - 1) as f64).cos()
+        } else {
+            1.0
+        }
+    };
+    let mut weights = 0.0;
+    let mut data: Vec<Complex<f64>> = Vec::with_capacity(rows * cols);
+    for i in 0..rows {
+        for j in 0..cols {
+            let w = hann(i, rows) * hann(j, cols);
+            weights += w * w;
+            let v = grid[i][j];
+            let v = if v.is_nan() { 0.0 } else { (v - trend(i, j)) * w };
+            data.push(Complex::new(v, 0.0));
+        }
+    }
+
+    let transformed = fft_2d(data, rows, cols, false);
+
+    let n = rows.max(cols);
+    let bins = n / 2;
+    let mut frequency = vec![0.0; bins + 1];
+    let mut power = vec![0.0; bins + 1];
+    let mut counts = vec![0usize; bins + 1];
+    for i in 0..rows {
+        let fi = frequency_of(i, rows);
+        for j in 0..cols {
+            let fj = frequency_of(j, cols);
+            let f = (fi * fi + fj * fj).sqrt();
+            let k = (f * n as f64).round() as usize;
+            if k == 0 || k > bins {
+                continue;
+            }
+            frequency[k] += f;
+            power[k] += transformed[i * cols + j].norm_sqr() / weights;
+            counts[k] += 1;
+        }
+    }
+    let kept: Vec<usize> = (1..=bins).filter(|&k| counts[k] > 0).collect();
+    PowerSpectrum {
+        frequencies: kept.iter().map(|&k| frequency[k] / counts[k] as f64).collect(),
+        power: kept.iter().map(|&k| power[k] / counts[k] as f64).collect(),
+        counts: kept.iter().map(|&k| counts[k]).collect(),
+    }
+}
+
+/// Fits the spectral exponent of `grid` over every frequency of its
+/// [`power_spectrum`] without detrending or windowing, which suits
+/// periodic NLMs such as `spectral_synthesis` output.
+pub fn spectral_exponent(grid: &Grid) -> SpectralFit {
+    power_spectrum(grid, &SpectrumOptions::default()).fit(0.0, f64::INFINITY)
+}
+
+/// Signed frequency of FFT index `i` of `n`, in cycles per cell.
+fn frequency_of(i: usize, n: usize) -> f64 {
+    let i = if i <= n / 2 { i as f64 } else { i as f64 - n as f64 };
+    i / n as f64
+}
+
+/// The unnormalised forward, or `inverse`, 2D FFT of a row-major
+/// (rows x cols) array.
+pub(crate) fn fft_2d(mut data: Vec<Complex<f64>>, rows: usize, cols: usize, inverse: bool) -> Vec<Complex<f64>> {
+    // Transform the rows, transpose, transform the columns and transpose back.
+    let mut planner = FftPlanner::<f64>::new();
+    let mut plan = |len| {
+        if inverse {
+            planner.plan_fft_inverse(len)
+        } else {
+            planner.plan_fft_forward(len)
+        }
+    };
+    fft_rows(&mut data, plan(cols));
+    let mut transposed = transpose(&data, rows, cols);
+    fft_rows(&mut transposed, plan(rows));
+    transpose(&transposed, cols, rows)
+}
+
+fn transpose(data: &[Complex<f64>], rows: usize, cols: usize) -> Vec<Complex<f64>> {
+    let mut out = vec![Complex::new(0.0, 0.0); rows * cols];
+    for i in 0..rows {
+        for j in 0..cols {
+            out[j * rows + i] = data[i * cols + j];
+        }
+    }
+    out
+}
+
+fn fft_rows(data: &mut [Complex<f64>], fft: Arc<dyn Fft<f64>>) {
+    let len = fft.len();
+    #[cfg(feature = "parallel")]
+    data.par_chunks_mut(len).for_each(|row| fft.process(row));
+    #[cfg(not(feature = "parallel"))]
+    data.chunks_mut(len).for_each(|row| fft.process(row));
+}
+
+/// The value to subtract at each cell: the mean of the valid cells, or the
+/// least-squares plane `a + b i + c j` through them.
+fn trend(grid: &Grid, plane: bool) -> impl Fn(usize, usize) -> f64 {
+    let cells = || {
+        (0..grid.data.len())
+            .filter(|&k| !grid.data[k].is_nan())
+            .map(|k| ((k / grid.cols) as f64, (k % grid.cols) as f64, grid.data[k]))
+    };
+    let n = cells().count() as f64;
+    let (mi, mj, mv) = cells().fold((0.0, 0.0, 0.0), |(a, b, c), (i, j, v)| (a + i, b + j, c + v));
+    let (mi, mj, mv) = (mi / n, mj / n, mv / n);
+    let (mut b, mut c) = (0.0, 0.0);
+    if plane {
+        // Normal equations of the centred regression on i and j.
+        let (mut sii, mut sjj, mut sij, mut siv, mut sjv) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for (i, j, v) in cells() {
+            let (i, j, v) = (i - mi, j - mj, v - mv);
+            sii += i * i;
+            sjj += j * j;
+            sij += i * j;
+            siv += i * v;
+            sjv += j * v;
+        }
+        let det = sii * sjj - sij * sij;
+        if det != 0.0 {
+            b = (siv * sjj - sjv * sij) / det;
+            c = (sjv * sii - siv * sij) / det;
+        } else if sii != 0.0 {
+            b = siv / sii;
+        } else if sjj != 0.0 {
+            c = sjv / sjj;
+        }
+    }
+    move |i, j| mv + b * (i as f64 - mi) + c * (j as f64 - mj)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovers_synthesis_beta() {
+        for beta in [0.0, 1.0, 2.0, 3.0] {
+            let grid = crate::spectral_synthesis(128, 128, beta, Some(7));
+            let fit = spectral_exponent(&grid);
+            assert!((fit.beta - beta).abs() < 0.15, "beta {beta}: fitted {}", fit.beta);
+            assert_eq!(fit.bins, 64);
+        }
+    }
+
+    #[test]
+    fn test_recovers_hurst_exponent() {
+        let grid = crate::fractal_brownian_surface(128, 96, 0.7, Some(8));
+        let fit = spectral_exponent(&grid);
+        assert!((fit.hurst - 0.7).abs() < 0.1, "fitted {}", fit.hurst);
+        assert!(fit.r_squared > 0.9);
+    }
+
+    #[test]
+    fn test_sine_peaks_at_its_frequency() {
+        let mut grid = Grid::new(64, 64);
+        for k in 0..64 * 64 {
+            grid.data[k] = (2.0 * std::f64::consts::PI * 8.0 * (k % 64) as f64 / 64.0).sin();
+        }
+        let spectrum = power_spectrum(&grid, &SpectrumOptions::default());
+        let peak = (0..spectrum.power.len())
+            .max_by(|&a, &b| spectrum.power[a].total_cmp(&spectrum.power[b]))
+            .unwrap();
+        assert!((spectrum.frequencies[peak] - 0.125).abs() < 1e-3);
+        let total: f64 = spectrum.power.iter().zip(&spectrum.counts).map(|(p, &n)| p * n as f64).sum();
+        // Parseval: the sine's variance of 1/2 times the number of cells.
+        assert!((total - 0.5 * 4096.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_detrend_removes_a_plane() {
+        let grid = crate::planar_gradient(40, 50, Some(30.0), Some(1));
+        let raw = power_spectrum(&grid, &SpectrumOptions::default());
+        let flat = power_spectrum(&grid, &SpectrumOptions { detrend: true, window: true });
+        assert!(raw.power[0] > 1.0);
+        assert!(flat.power.iter().all(|&p| p < 1e-20));
+        assert_eq!(flat.to_csv().lines().next(), Some("frequency,wavelength,power,count"));
+    }
+
+    #[test]
+    fn test_nodata_and_degenerate_grids() {
+        let mut grid = crate::spectral_synthesis(64, 64, 2.0, Some(9));
+        grid[3][4] = f64::NAN;
+        assert!(spectral_exponent(&grid).beta.is_finite());
+
+        let flat = spectral_exponent(&Grid::filled(16, 16, 3.0));
+        assert!(flat.beta.is_nan());
+        assert_eq!(flat.bins, 0);
+        assert!(power_spectrum(&Grid::new(0, 0), &SpectrumOptions::default()).power.is_empty());
+    }
+}
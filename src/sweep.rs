@@ -0,0 +1,385 @@
+//! Replicate batches and parameter sweeps.
+//!
+//! A neutral-model experiment usually needs many replicates of one generator
+//! across a grid of parameter values. [`Sweep::parse`] reads a sweep of one
+//! parameter (`h=0.1:0.9:0.2` or `p=0.3,0.5,0.6`), [`runs`] expands sweeps and
+//! replicates into the full list of [`Run`]s, [`output_paths`] names one file
+//! per run from a template, and [`write_manifest`] records the seed and
+//! parameters behind every file.
+//!
+//! ```
+//! use nlmrs::registry::{self, Params};
+//! use nlmrs::sweep::{self, Sweep};
+//!
+//! let md = registry::get("midpoint_displacement").unwrap();
+//! let h = Sweep::parse("h=0.1:0.9:0.4", md).unwrap();
+//! let runs = sweep::runs(md, &Params::new(), &[h], 2, 42).unwrap();
+//! assert_eq!(runs.len(), 6); // h = 0.1, 0.5, 0.9, two replicates each
+//! assert_eq!(runs[1].replicate, 1);
+//! assert_eq!(runs[3].seed, runs[1].seed); // replicate 1 of h = 0.5 and of h = 0.1
+//!
+//! let paths = sweep::output_paths("md_h{h}_{replicate}.png", md, &runs).unwrap();
+//! assert_eq!(paths[1], "md_h0.1_1.png");
+//! ```
+
+use std::collections::HashSet;
+use std::io;
+
+use crate::algorithms::sub_seed64;
+use crate::error::{NlmError, Result};
+use crate::registry::{Generator, Param, ParamKind, Params, Value};
+
+/// A list of values to try for one generator parameter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sweep {
+    pub param: String,
+    pub values: Vec<Value>,
+}
+
+impl Sweep {
+    /// Parses `name=values`, where `values` is either a comma-separated list
+    /// (`0.3,0.5,0.6`, `true,false`) or an inclusive range `start:stop:step`.
+    /// Integer ranges may omit the step, which defaults to 1.
+    ///
+    /// Every value is checked against the parameter's schema in `generator`.
+    pub fn parse(spec: &str, generator: &dyn Generator) -> Result<Self> {
+        let (name, values) = spec.split_once('=').ok_or_else(|| {
+            NlmError::invalid("sweep", format!("'{spec}' is not of the form name=values"))
+        })?;
+        let name = name.trim();
+        let param = generator
+            .params()
+            .iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| {
+                NlmError::invalid(name, format!("not a parameter of {}", generator.name()))
+            })?;
+        let values = if values.contains(':') {
+            range(param, values)?
+        } else {
+            values
+                .split(',')
+                .map(|v| param.parse(v))
+                .collect::<Result<_>>()?
+        };
+        Ok(Sweep {
+            param: name.to_string(),
+            values,
+        })
+    }
+}
+
+/// Expands an inclusive `start:stop[:step]` range of a numeric parameter.
+fn range(param: &Param, spec: &str) -> Result<Vec<Value>> {
+    let parts: Vec<&str> = spec.split(':').map(str::trim).collect();
+    let bad = |reason: &str| NlmError::invalid(param.name, format!("range '{spec}' {reason}"));
+    let (start, stop, step) = match (param.kind, parts.as_slice()) {
+        (ParamKind::Float | ParamKind::Int, [start, stop, step]) => (*start, *stop, *step),
+        (ParamKind::Int, [start, stop]) => (*start, *stop, "1"),
+        (ParamKind::Float, [_, _]) => return Err(bad("needs a step: start:stop:step")),
+        (ParamKind::Float | ParamKind::Int, _) => return Err(bad("must be start:stop:step")),
+        _ => return Err(bad("is only valid for numeric parameters")),
+    };
+    let number = |text: &str| -> Result<f64> {
+        text.parse()
+            .ok()
+            .filter(|x: &f64| x.is_finite())
+            .ok_or_else(|| bad(&format!("has a non-numeric bound '{text}'")))
+    };
+    let (start, stop, step) = (number(start)?, number(stop)?, number(step)?);
+    if step <= 0.0 || stop < start {
+        return Err(bad("must have start <= stop and a positive step"));
+    }
+    // Tolerate rounding so that 0.1:0.9:0.2 includes 0.9.
+    let n = ((stop - start) / step + 1e-9).floor() as usize;
+    (0..=n)
+        .map(|i| {
+            // Round away the error accumulated by start + i * step (0.30000000000000004).
+            let x = format!("{:.12}", start + i as f64 * step);
+            param.parse(x.trim_end_matches('0').trim_end_matches('.'))
+        })
+        .collect()
+}
+
+/// One generator call in a batch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    /// Position in the batch, from 0.
+    pub index: usize,
+    /// Replicate number within this parameter combination, from 0.
+    pub replicate: usize,
+    pub seed: u64,
+    /// Every parameter with a value, swept or not, including defaults.
+    pub params: Params,
+}
+
+/// Lists every combination of the `sweeps` values, each `replicates` times.
+///
+/// Later sweeps vary fastest and replicates fastest of all. Replicate `r`
+/// uses the same seed in every combination, so combinations are compared on
+/// the same random draws: the first value of stream `r + 1` of [`crate::RNG`]
+/// seeded with `seed`, which keeps the replicates of nearby base seeds apart. `base` supplies the parameters that are not
+/// swept; anything unset in both takes its schema default.
+pub fn runs(
+    generator: &dyn Generator,
+    base: &Params,
+    sweeps: &[Sweep],
+    replicates: usize,
+    seed: u64,
+) -> Result<Vec<Run>> {
+    let mut seen = HashSet::new();
+    if let Some(dup) = sweeps.iter().find(|s| !seen.insert(&s.param)) {
+        return Err(NlmError::invalid(&dup.param, "swept more than once"));
+    }
+    let mut defaults = Params::new();
+    for p in generator.params() {
+        match (base.get(p.name), &p.default) {
+            (Some(value), _) | (None, Some(value)) => defaults.set(p.name, value.clone()),
+            (None, None) => {}
+        }
+    }
+    let mut combinations = vec![defaults];
+    for sweep in sweeps {
+        combinations = combinations
+            .iter()
+            .flat_map(|params| {
+                sweep.values.iter().map(|value| {
+                    let mut params = params.clone();
+                    params.set(&sweep.param, value.clone());
+                    params
+                })
+            })
+            .collect();
+    }
+    let mut runs = Vec::with_capacity(combinations.len() * replicates);
+    for params in combinations {
+        generator.validate(&params)?;
+        for replicate in 0..replicates {
+            runs.push(Run {
+                index: runs.len(),
+                replicate,
+                seed: sub_seed64(seed, replicate),
+                params: params.clone(),
+            });
+        }
+    }
+    Ok(runs)
+}
+
+/// Names the output file of each run by filling in `template`.
+///
+/// `{run}` (zero-padded), `{replicate}`, `{seed}`, `{algorithm}` and
+/// `{<parameter>}` are replaced by that run's values; unset optional
+/// parameters become `none`. A template without placeholders gets `_{run}`
+/// inserted before its extension. Templates that give two runs the same path
+/// are rejected.
+pub fn output_paths(
+    template: &str,
+    generator: &dyn Generator,
+    runs: &[Run],
+) -> Result<Vec<String>> {
+    let template = if template.contains('{') {
+        template.to_string()
+    } else {
+        let stem_end = template
+            .rfind('.')
+            .filter(|&dot| !template[dot..].contains(['/', '\\']))
+            .unwrap_or(template.len());
+        format!("{}_{{run}}{}", &template[..stem_end], &template[stem_end..])
+    };
+    let width = runs.len().saturating_sub(1).to_string().len();
+    let mut paths = Vec::with_capacity(runs.len());
+    let mut seen = HashSet::new();
+    for run in runs {
+        let mut path = String::new();
+        let mut rest = template.as_str();
+        while let Some(open) = rest.find('{') {
+            let close = rest[open..].find('}').map(|i| open + i).ok_or_else(|| {
+                NlmError::invalid("output", format!("unclosed '{{' in '{template}'"))
+            })?;
+            path.push_str(&rest[..open]);
+            let field = match &rest[open + 1..close] {
+                "run" => format!("{:0width$}", run.index),
+                "replicate" => run.replicate.to_string(),
+                "seed" => run.seed.to_string(),
+                "algorithm" => generator.name().to_string(),
+                name => match run.params.get(name) {
+                    Some(value) => value.to_string(),
+                    None if generator.params().iter().any(|p| p.name == name) => "none".to_string(),
+                    None => {
+                        return Err(NlmError::invalid(
+                            "output",
+                            format!("unknown placeholder '{{{name}}}' in '{template}'"),
+                        ))
+                    }
+                },
+            };
+            path.push_str(&field);
+            rest = &rest[close + 1..];
+        }
+        path.push_str(rest);
+        if !seen.insert(path.clone()) {
+            return Err(NlmError::invalid(
+                "output",
+                format!(
+                    "'{template}' names more than one run '{path}'; \
+                     add {{replicate}} or the swept parameters"
+                ),
+            ));
+        }
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Writes a CSV with one row per run: its output path, the algorithm, grid
+/// size, replicate, seed and the value of every parameter of `generator`
+/// (empty when unset).
+pub fn write_manifest(
+    path: &str,
+    generator: &dyn Generator,
+    rows: usize,
+    cols: usize,
+    runs: &[Run],
+    outputs: &[String],
+) -> io::Result<()> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    let mut header = vec!["path", "algorithm", "rows", "cols", "replicate", "seed"];
+    header.extend(generator.params().iter().map(|p| p.name));
+    wtr.write_record(&header)?;
+    for (run, output) in runs.iter().zip(outputs) {
+        let mut record = vec![
+            output.clone(),
+            generator.name().to_string(),
+            rows.to_string(),
+            cols.to_string(),
+            run.replicate.to_string(),
+            run.seed.to_string(),
+        ];
+        record.extend(generator.params().iter().map(|p| {
+            run.params
+                .get(p.name)
+                .map_or(String::new(), Value::to_string)
+        }));
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry;
+
+    fn generator(name: &str) -> &'static dyn Generator {
+        registry::get(name).unwrap()
+    }
+
+    #[test]
+    fn test_sweep_parses_ranges_and_lists() {
+        let md = generator("midpoint_displacement");
+        let h = Sweep::parse("h=0.1:0.9:0.2", md).unwrap();
+        let expected: Vec<Value> = [0.1, 0.3, 0.5, 0.7, 0.9].map(Value::Float).to_vec();
+        assert_eq!(h.values, expected);
+
+        let p = Sweep::parse("p = 0.3, 0.55", generator("percolation")).unwrap();
+        assert_eq!((p.param.as_str(), p.values.len()), ("p", 2));
+
+        let n = Sweep::parse("n=2:4", generator("mosaic")).unwrap();
+        assert_eq!(n.values, vec![Value::Int(2), Value::Int(3), Value::Int(4)]);
+        let n = Sweep::parse("n=100:500:200", generator("mosaic")).unwrap();
+        assert_eq!(
+            n.values,
+            vec![Value::Int(100), Value::Int(300), Value::Int(500)]
+        );
+
+        let periodic = Sweep::parse("periodic=false,true", md).unwrap();
+        assert_eq!(periodic.values, vec![Value::Bool(false), Value::Bool(true)]);
+    }
+
+    #[test]
+    fn test_sweep_rejects_bad_specs() {
+        let md = generator("midpoint_displacement");
+        for spec in [
+            "h",
+            "q=0.5",
+            "h=0.5,2.0",
+            "h=0.1:0.9",
+            "h=0.9:0.1:0.1",
+            "h=0.1:0.9:0",
+            "h=a:b:c",
+            "periodic=0:1",
+        ] {
+            assert!(
+                matches!(
+                    Sweep::parse(spec, md),
+                    Err(NlmError::InvalidParameter { .. })
+                ),
+                "{spec}"
+            );
+        }
+        assert!(Sweep::parse("n=1.5:3:1", generator("mosaic")).is_err());
+    }
+
+    #[test]
+    fn test_runs_expand_sweeps_and_replicates() {
+        let md = generator("midpoint_displacement");
+        let sweeps = [
+            Sweep::parse("h=0.2,0.8", md).unwrap(),
+            Sweep::parse("periodic=false,true", md).unwrap(),
+        ];
+        let runs = runs(md, &Params::new(), &sweeps, 3, 10).unwrap();
+        assert_eq!(runs.len(), 12);
+        assert_eq!(runs[0].params.get("h"), Some(&Value::Float(0.2)));
+        assert_eq!(runs[3].params.get("periodic"), Some(&Value::Bool(true)));
+        assert_eq!(runs[6].params.get("h"), Some(&Value::Float(0.8)));
+        for (i, run) in runs.iter().enumerate() {
+            assert_eq!(run.index, i);
+            assert_eq!(run.replicate, i % 3);
+            assert_eq!(run.seed, sub_seed64(10, i % 3));
+        }
+    }
+
+    #[test]
+    fn test_runs_fill_defaults_and_reject_duplicates() {
+        let fbm = generator("fbm_noise");
+        let base = Params::new().with("octaves", 3usize);
+        let runs = runs(fbm, &base, &[], 2, u64::MAX).unwrap();
+        assert_eq!(runs[0].params.get("octaves"), Some(&Value::Int(3)));
+        assert_eq!(runs[0].params.get("scale"), Some(&Value::Float(4.0)));
+        assert_eq!(runs[1].seed, sub_seed64(u64::MAX, 1));
+
+        let twice = [
+            Sweep::parse("scale=1,2", fbm).unwrap(),
+            Sweep::parse("scale=3", fbm).unwrap(),
+        ];
+        assert!(super::runs(fbm, &Params::new(), &twice, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_output_paths_fill_template() {
+        let md = generator("midpoint_displacement");
+        let sweeps = [Sweep::parse("h=0.25,0.75", md).unwrap()];
+        let runs = runs(md, &Params::new(), &sweeps, 6, 1).unwrap();
+        let paths = output_paths("out/{algorithm}_h{h}_r{replicate}.png", md, &runs).unwrap();
+        assert_eq!(paths[0], "out/midpoint_displacement_h0.25_r0.png");
+        assert_eq!(paths[11], "out/midpoint_displacement_h0.75_r5.png");
+
+        let paths = output_paths("out.v1/grid.csv", md, &runs).unwrap();
+        assert_eq!(paths[0], "out.v1/grid_00.csv");
+        assert_eq!(paths[11], "out.v1/grid_11.csv");
+        assert_eq!(output_paths("grid", md, &runs).unwrap()[3], "grid_03");
+
+        assert!(output_paths("h{h}.png", md, &runs).is_err());
+        assert!(output_paths("{nope}_{run}.png", md, &runs).is_err());
+        assert!(output_paths("{run.png", md, &runs).is_err());
+    }
+
+    #[test]
+    fn test_output_paths_unset_optional_param() {
+        let planar = generator("planar_gradient");
+        let runs = runs(planar, &Params::new(), &[], 2, 0).unwrap();
+        let paths = output_paths("{direction}_{replicate}.csv", planar, &runs).unwrap();
+        assert_eq!(paths, vec!["none_0.csv", "none_1.csv"]);
+    }
+}
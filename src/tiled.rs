@@ -0,0 +1,276 @@
+//! Tiled generation for grids too large to hold in memory.
+//!
+//! The coordinate-based noise generators evaluate each cell from its position
+//! alone, so any window of a grid can be generated on its own and adjacent
+//! windows join seamlessly. [`TiledNoise`] samples such windows in global
+//! coordinates and [`write`](TiledNoise::write) streams them to disk, giving
+//! the same values as the in-memory generator without materialising the grid.
+
+use std::io;
+
+use crate::algorithms::noise::{
+    fbm_window, perlin_window, ridged_window, simplex_window, value_window, worley_window, Octaves,
+};
+use crate::algorithms::perlin_seed;
+use crate::error::{check_dimensions, NlmError, Result};
+use crate::export::{self, TileOptions};
+use crate::grid::{GeoTransform, Grid};
+use crate::operation::{min_and_max, scale_from};
+use crate::registry::{self, Params};
+
+/// Registry names of the generators that [`TiledNoise`] can generate.
+pub const TILED_ALGORITHMS: &[&str] = &[
+    "perlin_noise",
+    "fbm_noise",
+    "ridged_noise",
+    "simplex_noise",
+    "worley_noise",
+    "value_noise",
+];
+
+/// A `rows` x `cols` block of cells whose top-left cell is (`row`, `col`) of a
+/// larger grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Window {
+    pub row: usize,
+    pub col: usize,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl Window {
+    /// The whole of a (rows x cols) grid.
+    pub fn all(rows: usize, cols: usize) -> Self {
+        Window {
+            row: 0,
+            col: 0,
+            rows,
+            cols,
+        }
+    }
+
+    /// Windows of at most `size` x `size` cells covering a (rows x cols) grid,
+    /// in row-major order. Windows along the bottom and right edges are clipped.
+    ///
+    /// Panics if `size` is 0.
+    pub fn tiles(rows: usize, cols: usize, size: usize) -> impl Iterator<Item = Window> {
+        assert!(size > 0, "tile size must be positive");
+        (0..rows).step_by(size).flat_map(move |row| {
+            (0..cols).step_by(size).map(move |col| Window {
+                row,
+                col,
+                rows: size.min(rows - row),
+                cols: size.min(cols - col),
+            })
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Source {
+    Perlin(f64),
+    Fbm(Octaves),
+    Ridged(Octaves),
+    Simplex(f64),
+    Worley(f64),
+    Value(f64),
+}
+
+/// One of the [`TILED_ALGORITHMS`] over a (rows x cols) grid, sampled a window
+/// at a time.
+///
+/// A window of raw values is independent of how the grid is split up. The
+/// in-memory generators rescale the whole grid to [0, 1] by its minimum and
+/// maximum, so reproducing them exactly needs that range first: [`range`]
+/// finds it with one pass over the grid, and [`window`] applies it.
+///
+/// [`range`]: TiledNoise::range
+/// [`window`]: TiledNoise::window
+#[derive(Clone, Debug, PartialEq)]
+pub struct TiledNoise {
+    pub rows: usize,
+    pub cols: usize,
+    /// Seed shared by every window; a random one is chosen when none is given.
+    pub seed: u64,
+    source: Source,
+}
+
+impl TiledNoise {
+    /// Tiled form of the registry generator `algorithm` with `params` (unset
+    /// parameters take their defaults).
+    ///
+    /// Fails if `algorithm` is not one of [`TILED_ALGORITHMS`], or on invalid
+    /// parameters or dimensions.
+    pub fn new(
+        algorithm: &str,
+        rows: usize,
+        cols: usize,
+        params: &Params,
+        seed: Option<u64>,
+    ) -> Result<Self> {
+        check_dimensions(rows, cols)?;
+        let generator = registry::try_get(algorithm)?;
+        if !TILED_ALGORITHMS.contains(&algorithm) {
+            return Err(NlmError::invalid(
+                "algorithm",
+                format!(
+                    "{algorithm} cannot be generated in tiles; use one of {}",
+                    TILED_ALGORITHMS.join(", ")
+                ),
+            ));
+        }
+        generator.validate(params)?;
+        let mut resolved = Params::new();
+        for p in generator.params() {
+            if let Some(value) = params.get(p.name).or(p.default.as_ref()) {
+                resolved.set(p.name, value.clone());
+            }
+        }
+        let scale_factor = resolved.float("scale");
+        let octaves = || Octaves {
+            scale_factor,
+            octaves: resolved.int("octaves"),
+            persistence: resolved.float("persistence"),
+            lacunarity: resolved.float("lacunarity"),
+        };
+        let source = match algorithm {
+            "perlin_noise" => Source::Perlin(scale_factor),
+            "fbm_noise" => Source::Fbm(octaves()),
+            "ridged_noise" => Source::Ridged(octaves()),
+            "simplex_noise" => Source::Simplex(scale_factor),
+            "worley_noise" => Source::Worley(scale_factor),
+            "value_noise" => Source::Value(scale_factor),
+            _ => unreachable!("checked against TILED_ALGORITHMS"),
+        };
+        Ok(TiledNoise {
+            rows,
+            cols,
+            seed: seed.unwrap_or_else(rand::random),
+            source,
+        })
+    }
+
+    /// Raw, unscaled values over `window`.
+    ///
+    /// Panics if `window` extends beyond the grid.
+    pub fn sample(&self, window: Window) -> Grid {
+        assert!(
+            window.row + window.rows <= self.rows && window.col + window.cols <= self.cols,
+            "window {window:?} extends beyond the {}x{} grid",
+            self.rows,
+            self.cols
+        );
+        let (rows, cols, seed) = (self.rows, self.cols, perlin_seed(Some(self.seed)));
+        match self.source {
+            Source::Perlin(s) => perlin_window(rows, cols, s, seed, window),
+            Source::Fbm(o) => fbm_window(rows, cols, o, seed, window),
+            Source::Ridged(o) => ridged_window(rows, cols, o, seed, window),
+            Source::Simplex(s) => simplex_window(rows, cols, s, seed, window),
+            Source::Worley(s) => worley_window(rows, cols, s, seed, window),
+            Source::Value(s) => value_window(rows, cols, s, seed, window),
+        }
+    }
+
+    /// Minimum and maximum raw value over the whole grid, sampled in
+    /// `tile_size` x `tile_size` windows.
+    pub fn range(&self, tile_size: usize) -> (f64, f64) {
+        Window::tiles(self.rows, self.cols, tile_size)
+            .map(|w| min_and_max(&self.sample(w)))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(mn, mx), (lo, hi)| {
+                (mn.min(lo), mx.max(hi))
+            })
+    }
+
+    /// Values over `window` rescaled by the grid's [`range`](TiledNoise::range),
+    /// identical to the same cells of the in-memory generator's output.
+    pub fn window(&self, window: Window, range: (f64, f64)) -> Grid {
+        let mut grid = self.sample(window);
+        scale_from(&mut grid, range);
+        grid
+    }
+
+    /// Streams the scaled grid to `path` with [`export::write_tiled`], after a
+    /// first pass to find its range. Memory use is bounded by the tile size.
+    pub fn write(
+        &self,
+        path: &str,
+        transform: &GeoTransform,
+        options: TileOptions,
+    ) -> io::Result<()> {
+        let mut range = None;
+        export::write_tiled(path, self.rows, self.cols, transform, options, |w| {
+            let range = *range.get_or_insert_with(|| self.range(options.tile_size));
+            self.window(w, range)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiles_cover_grid() {
+        let tiles: Vec<Window> = Window::tiles(5, 7, 3).collect();
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(
+            tiles[0],
+            Window {
+                row: 0,
+                col: 0,
+                rows: 3,
+                cols: 3
+            }
+        );
+        assert_eq!(
+            tiles[2],
+            Window {
+                row: 0,
+                col: 6,
+                rows: 3,
+                cols: 1
+            }
+        );
+        assert_eq!(
+            tiles[5],
+            Window {
+                row: 3,
+                col: 6,
+                rows: 2,
+                cols: 1
+            }
+        );
+        assert_eq!(tiles.iter().map(|w| w.rows * w.cols).sum::<usize>(), 35);
+    }
+
+    #[test]
+    fn test_tiles_match_in_memory_generators() {
+        let (rows, cols) = (37, 53);
+        let params = Params::new().with("scale", 3.0);
+        for &name in TILED_ALGORITHMS {
+            let whole = registry::get(name)
+                .unwrap()
+                .generate(rows, cols, &params, Some(9));
+            let noise = TiledNoise::new(name, rows, cols, &params, Some(9)).unwrap();
+            let range = noise.range(16);
+            for w in Window::tiles(rows, cols, 16) {
+                let tile = noise.window(w, range);
+                for i in 0..w.rows {
+                    assert_eq!(
+                        tile[i],
+                        whole[w.row + i][w.col..w.col + w.cols],
+                        "{name} {w:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_rejects_other_algorithms() {
+        assert!(TiledNoise::new("random", 10, 10, &Params::new(), None).is_err());
+        assert!(TiledNoise::new("perlin_noise", 0, 10, &Params::new(), None).is_err());
+        let bad = Params::new().with("octaves", 0.5);
+        assert!(TiledNoise::new("fbm_noise", 10, 10, &bad, None).is_err());
+    }
+}
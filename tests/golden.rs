@@ -0,0 +1,132 @@
+//! Golden hashes of seeded output, one per built-in generator.
+//!
+//! The same table must pass with and without the `parallel` feature and on
+//! wasm, as `.github/workflows/golden.yml` checks:
+//!
+//! ```sh
+//! cargo test --test golden
+//! cargo test --test golden --no-default-features
+//! cargo test --test golden --no-default-features --target wasm32-wasip1
+//! ```
+//!
+//! A failure means a seed no longer reproduces a published grid. If that is
+//! intended, bump `nlmrs::RNG` and regenerate the table.
+
+use nlmrs::registry::{self, Params};
+use nlmrs::Grid;
+
+const ROWS: usize = 24;
+const COLS: usize = 32;
+const SEED: u64 = 42;
+
+/// FNV-1a over each cell rounded to `f32`, so that last-bit differences
+/// between platform maths libraries do not count as a change.
+fn hash(grid: &Grid) -> u64 {
+    let mut h = 0xcbf2_9ce4_8422_2325u64;
+    for &v in grid.iter() {
+        for byte in (v as f32).to_bits().to_le_bytes() {
+            h = (h ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    h
+}
+
+const GOLDEN: &[(&str, u64)] = &[
+    ("planar_gradient", 0xdb5532618d594ed6),
+    ("edge_gradient", 0xda89dd3c30bb5681),
+    ("distance_gradient", 0xcf08edc7801c97d8),
+    ("wave_gradient", 0x611a4d2524605dd6),
+    ("landscape_gradient", 0xc06b6c61fdc8f7b5),
+    ("concentric_rings", 0x97fdcb8c08dcdda5),
+    ("checkerboard", 0x75101f20d6b9dba5),
+    ("spiral_gradient", 0x489955c04bddb005),
+    ("radial_sweep", 0xc2122a9434fe314a),
+    ("perlin_noise", 0x281330b1a0e4dd57),
+    ("fbm_noise", 0x32e82f49320b5688),
+    ("ridged_noise", 0xecabef5c7ca91adf),
+    ("billow_noise", 0x9b6d1a602206b57a),
+    ("worley_noise", 0x1dbd970e7bb41198),
+    ("hybrid_noise", 0x9179e7bd77dad282),
+    ("value_noise", 0x3ba47ed103e97f15),
+    ("turbulence", 0x63162318f79c2c17),
+    ("domain_warp", 0x7a1e0753aabb3189),
+    ("spectral_synthesis", 0xd1efc5d19840fb21),
+    ("conditional_spectral_synthesis", 0xd1efc5d19840fb21),
+    ("fractal_brownian_surface", 0x3b2b91a4313e3a8b),
+    ("simplex_noise", 0x136e8d4d8483953e),
+    ("voronoi_distance", 0xc6947f2634ea4c4e),
+    ("sine_composite", 0x4e2c460e065504cb),
+    ("curl_noise", 0x2fb28f0c553fc8ea),
+    ("gabor_noise", 0x748516946d6f71d8),
+    ("spot_noise", 0xebc5d62e08651dde),
+    ("anisotropic_noise", 0xee7f6339b41717c5),
+    ("tiled_noise", 0xd0ef4c40ec2488a7),
+    ("voronoi_crease", 0x801357041526cfb9),
+    ("perlin_worley", 0xffebeb1bd8db506b),
+    ("lognormal_field", 0x5f3f3cea5cbf4fd9),
+    ("multifractal_terrain", 0x339c894bb7c6f83e),
+    ("blue_noise", 0xd3942b33d406f2c6),
+    ("random", 0xa1d07c0ccf6f631a),
+    ("random_element", 0x81db1c4ae5f704c7),
+    ("midpoint_displacement", 0x48a3c42f8ef355c8),
+    ("gaussian_field", 0x58f4e3843eb530bd),
+    ("conditional_gaussian_field", 0x58f4e3843eb530bd),
+    ("random_cluster", 0x108371a24470ba91),
+    ("mosaic", 0x3b8e4dd232ced4ec),
+    ("rectangular_cluster", 0x190fea71dfecc87b),
+    ("percolation", 0x2b01e7a559d51b58),
+    ("binary_space_partitioning", 0x84c767891bf0cb75),
+    ("neighbourhood_clustering", 0x593097f2b2054235),
+    ("cellular_automaton", 0x82ec8797a52d9188),
+    ("diffusion_limited_aggregation", 0x336c13b928803618),
+    ("reaction_diffusion", 0x23e50be12d8d5d6b),
+    ("eden_growth", 0x6d895d9b172fbab8),
+    ("invasion_percolation", 0x72c4138bc70f1715),
+    ("gaussian_blobs", 0x424b07c117dbafe1),
+    ("ising_model", 0x6c250419dcfcb325),
+    ("levy_flight", 0x5f5f3803efd7b438),
+    ("hydraulic_erosion", 0xbc8b1ccba596e976),
+    ("poisson_disk", 0x8bf799e92a5d6c38),
+    ("brownian_motion", 0xa574a395cf0cdf0b),
+    ("forest_fire", 0xa2143e1631ab5f62),
+    ("river_network", 0xbfa1923ce264e0f3),
+    ("hexagonal_voronoi", 0xbdc040890e729b5e),
+    ("fault_uplift", 0xf8e580c958e5086b),
+    ("triangular_tessellation", 0x50f2742f934f2e00),
+    ("physarum", 0xfe1804c2b52ef67e),
+    ("cahn_hilliard", 0xb51ffd1f7ee2bd72),
+    ("crystal_growth", 0xb8fe502d260cffbd),
+    ("predator_prey", 0xc43de1510de4e852),
+    ("sandpile", 0x54223b8148096ec8),
+    ("correlated_walk", 0x7586b92020b3b1a5),
+    ("schelling", 0x6da1bdc96de8add5),
+    ("sir_epidemic", 0x14de2c7e35ef0f27),
+    ("thermal_erosion", 0xdf441269354a3fed),
+    ("space_colonization", 0xabbccd5c8fcaa70f),
+    ("substrate", 0x1241c1351c229485),
+    ("game_of_life", 0x597cc01854179abc),
+    ("rock_paper_scissors", 0xb4253e384f9b7465),
+    ("excitable_media", 0x2d41c660add13102),
+    ("truchet", 0x19e3d17aad958b25),
+    ("hill_grow", 0x4409e356cd78e128),
+];
+
+#[test]
+fn test_rng_name() {
+    assert_eq!(nlmrs::RNG, "chacha12-v2");
+}
+
+#[test]
+fn test_golden_hashes() {
+    let mut failures = Vec::new();
+    for g in registry::generators() {
+        let grid = g.generate(ROWS, COLS, &Params::new(), Some(SEED));
+        let actual = hash(&grid);
+        match GOLDEN.iter().find(|(name, _)| *name == g.name()) {
+            Some(&(_, expected)) if expected == actual => {}
+            _ => failures.push(format!("    (\"{}\", {actual:#018x}),", g.name())),
+        }
+    }
+    assert!(failures.is_empty(), "hashes changed:\n{}", failures.join("\n"));
+    assert_eq!(GOLDEN.len(), registry::generators().count());
+}
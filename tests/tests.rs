@@ -94,3 +94,522 @@ fn test_ascii_grid_round_trip() {
         assert!((a - b).abs() < 1e-5, "value mismatch: {a} vs {b}");
     }
 }
+
+#[test]
+fn test_ascii_grid_round_trip_nodata() {
+    ensure_examples_dir();
+    let mut original = nlmrs::random(20, 30, Some(8));
+    let mask = nlmrs::polygon_mask(20, 30, &[(0.0, 0.0), (20.0, 5.0), (10.0, 30.0)]);
+    nlmrs::apply_mask(&mut original, &mask);
+    nlmrs::export::write_to_ascii_grid(&original, "examples/roundtrip_nodata.asc").unwrap();
+    let loaded = nlmrs::export::read_from_ascii_grid("examples/roundtrip_nodata.asc").unwrap();
+
+    assert!(original.nodata_count() > 0);
+    assert_eq!(loaded.nodata_count(), original.nodata_count());
+    for (a, b) in original.data.iter().zip(loaded.data.iter()) {
+        assert_eq!(a.is_nan(), b.is_nan());
+    }
+}
+
+#[test]
+fn test_json_round_trip() {
+    ensure_examples_dir();
+    let mut original = nlmrs::random(12, 17, Some(3));
+    original[2][5] = f64::NAN;
+    nlmrs::export::write_to_json(&original, "examples/roundtrip.json").unwrap();
+    let loaded = nlmrs::export::read_from_json("examples/roundtrip.json").unwrap();
+    assert_eq!(loaded.to_string(), original.to_string());
+
+    std::fs::write("examples/roundtrip_ragged.json", "[[0.1, 0.2], [0.3]]").unwrap();
+    assert!(nlmrs::export::read_from_json("examples/roundtrip_ragged.json").is_err());
+}
+
+#[test]
+fn test_png_and_tiff_round_trip() {
+    ensure_examples_dir();
+    let mut original = nlmrs::fbm_noise(30, 40, 4.0, 6, 0.5, 2.0, Some(11));
+    original[3][4] = f64::NAN;
+    nlmrs::export::write_to_png_grayscale(&original, "examples/roundtrip_gray.png").unwrap();
+    let png = nlmrs::export::read_from_png("examples/roundtrip_gray.png").unwrap();
+    assert_eq!((png.rows, png.cols), (30, 40));
+    assert_eq!(png.nodata_count(), 1);
+    assert!(png[3][4].is_nan());
+    for (a, b) in original.iter().zip(png.iter()).filter(|(a, _)| !a.is_nan()) {
+        assert!((a - b).abs() <= 0.5 / 255.0 + 1e-12, "value mismatch: {a} vs {b}");
+    }
+
+    // 16-bit TIFF keeps ~5 digits; NoData takes the reserved level 0, so a
+    // value of 0 moves up one level.
+    original[5][6] = 0.0;
+    nlmrs::export::write_to_tiff(&original, "examples/roundtrip_gray.tif").unwrap();
+    let tif = nlmrs::export::read_from_tiff("examples/roundtrip_gray.tif").unwrap();
+    assert_eq!(tif.nodata_count(), 1);
+    assert!(tif[3][4].is_nan());
+    assert_eq!(tif[5][6], 1.0 / 65535.0);
+    for (a, b) in original.iter().zip(tif.iter()).filter(|(a, _)| !a.is_nan()) {
+        assert!((a - b).abs() <= 1.0 / 65535.0 + 1e-12, "value mismatch: {a} vs {b}");
+    }
+
+    let geo = nlmrs::GeoGrid { grid: original.clone(), transform: Default::default() };
+    let format = nlmrs::export::TiffSampleFormat::Float64;
+    nlmrs::export::write_to_geotiff(&geo, "examples/roundtrip_gray_f64.tif", format).unwrap();
+    let tif = nlmrs::export::read_from_tiff("examples/roundtrip_gray_f64.tif").unwrap();
+    assert_eq!(tif.to_string(), original.to_string());
+}
+
+#[test]
+fn test_npy_and_npz_round_trip() {
+    ensure_examples_dir();
+    let mut original = nlmrs::fbm_noise(21, 34, 4.0, 6, 0.5, 2.0, Some(19));
+    original[6][2] = f64::NAN;
+    nlmrs::export::write_to_npy(&original, "examples/roundtrip.npy").unwrap();
+    let loaded = nlmrs::export::read_from_npy("examples/roundtrip.npy").unwrap();
+    assert_eq!(loaded.to_string(), original.to_string());
+
+    let single = original.to_f32();
+    nlmrs::export::write_to_npy(&single, "examples/roundtrip_f32.npy").unwrap();
+    let loaded = nlmrs::export::read_from_npy("examples/roundtrip_f32.npy").unwrap();
+    assert_eq!(loaded.to_string(), single.to_f64().to_string());
+
+    let mut classes = original.clone();
+    nlmrs::classify(&mut classes, 4);
+    let labels = nlmrs::LabelGrid::<u16>::from_grid(&classes).unwrap();
+    nlmrs::export::write_labels_to_npy(&labels, "examples/roundtrip_labels.npy").unwrap();
+    let loaded = nlmrs::export::read_from_npy("examples/roundtrip_labels.npy").unwrap();
+    assert_eq!(loaded[6][2], f64::from(u16::MAX));
+    assert_eq!(loaded[0][0], f64::from(labels[0][0]));
+
+    let replicates: Vec<nlmrs::Grid> = (0..3).map(|i| nlmrs::random(8, 5, Some(i))).collect();
+    let arrays: Vec<(&str, &nlmrs::Grid)> =
+        ["a", "b", "c"].into_iter().zip(&replicates).collect();
+    nlmrs::export::write_to_npz(&arrays, "examples/roundtrip.npz").unwrap();
+    let loaded = nlmrs::export::read_from_npz("examples/roundtrip.npz").unwrap();
+    let names: Vec<&str> = loaded.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["a", "b", "c"]);
+    for ((_, a), b) in loaded.iter().zip(&replicates) {
+        assert_eq!(a, b);
+    }
+    let duplicate = [("a", &replicates[0]), ("a", &replicates[1])];
+    assert!(nlmrs::export::write_to_npz(&duplicate, "examples/roundtrip_dup.npz").is_err());
+    assert!(nlmrs::export::read("examples/roundtrip.npz").is_err());
+
+    let geo = nlmrs::GeoGrid { grid: original.clone(), transform: Default::default() };
+    let options = nlmrs::export::WriteOptions::default();
+    nlmrs::export::write(&geo, "examples/roundtrip_single.npz", &options).unwrap();
+    let loaded = nlmrs::export::read("examples/roundtrip_single.npz").unwrap();
+    assert_eq!(loaded.grid.to_string(), original.to_string());
+    let loaded = nlmrs::export::read("examples/roundtrip.npy").unwrap();
+    assert_eq!(loaded.grid.to_string(), original.to_string());
+}
+
+#[test]
+fn test_read_sniffs_format() {
+    ensure_examples_dir();
+    let mut grid = nlmrs::random(9, 13, Some(5));
+    grid[1][1] = f64::NAN;
+    let transform = nlmrs::GeoTransform { x_min: 500.0, y_min: 100.0, cell_size: 30.0, crs: None };
+    let geo = nlmrs::GeoGrid { grid: grid.clone(), transform: transform.clone() };
+    let options = nlmrs::export::WriteOptions::default();
+    // Extensions that do not match the contents, to show they are ignored.
+    for (ext, path) in [
+        ("csv", "examples/roundtrip_sniff_csv.dat"),
+        ("json", "examples/roundtrip_sniff_json.dat"),
+        ("asc", "examples/roundtrip_sniff_asc.dat"),
+        ("tif", "examples/roundtrip_sniff_tif.dat"),
+    ] {
+        let written = format!("examples/roundtrip_sniff.{ext}");
+        let options = nlmrs::export::WriteOptions {
+            tiff_format: Some(nlmrs::export::TiffSampleFormat::Float64),
+            ..options.clone()
+        };
+        nlmrs::export::write(&geo, &written, &options).unwrap();
+        std::fs::rename(&written, path).unwrap();
+        let loaded = nlmrs::export::read(path).unwrap();
+        assert_eq!(loaded.grid.nodata_count(), 1, "{ext}");
+        for (a, b) in grid.iter().zip(loaded.grid.iter()).filter(|(a, _)| !a.is_nan()) {
+            assert!((a - b).abs() < 1e-6, "{ext}: value mismatch: {a} vs {b}");
+        }
+        let georeferenced = matches!(ext, "asc" | "tif");
+        assert_eq!(loaded.transform == transform, georeferenced, "{ext}");
+    }
+
+    nlmrs::export::write_to_png_grayscale(&grid, "examples/roundtrip_sniff.png").unwrap();
+    let png = nlmrs::export::read("examples/roundtrip_sniff.png").unwrap();
+    let direct = nlmrs::export::read_from_png("examples/roundtrip_sniff.png").unwrap();
+    assert_eq!(png.grid.to_string(), direct.to_string());
+
+    // Georeferenced integer GeoTIFFs keep their labels; plain ones are scaled.
+    let mut classes = grid.clone();
+    nlmrs::classify(&mut classes, 3);
+    let labels = nlmrs::LabelGrid::<u8>::from_grid(&classes).unwrap();
+    nlmrs::export::write_labels_to_geotiff(&labels, &transform, "examples/roundtrip_sniff_labels.tif")
+        .unwrap();
+    let loaded = nlmrs::export::read("examples/roundtrip_sniff_labels.tif").unwrap();
+    assert_eq!(loaded.grid[0][0], f64::from(labels[0][0]));
+    nlmrs::export::write_to_tiff(&grid, "examples/roundtrip_sniff_u16.tif").unwrap();
+    let loaded = nlmrs::export::read("examples/roundtrip_sniff_u16.tif").unwrap();
+    assert!((loaded.grid[0][0] - grid[0][0]).abs() < 1e-4);
+
+    std::fs::write("examples/roundtrip_sniff_bad.dat", "{\"rows\": 1}").unwrap();
+    assert!(nlmrs::export::read("examples/roundtrip_sniff_bad.dat").is_err());
+}
+
+#[test]
+fn test_ascii_grid_round_trip_georeferenced() {
+    ensure_examples_dir();
+    let transform = nlmrs::GeoTransform {
+        x_min: 523_400.0,
+        y_min: 181_250.5,
+        cell_size: 25.0,
+        crs: Some(r#"PROJCS["OSGB 1936 / British National Grid",GEOGCS["OSGB 1936"]]"#.to_string()),
+    };
+    let geo = nlmrs::GeoGrid { grid: nlmrs::random(12, 8, Some(9)), transform };
+    nlmrs::export::write_to_ascii_grid_geo(&geo, "examples/roundtrip_geo.asc").unwrap();
+    let loaded = nlmrs::export::read_from_ascii_grid_geo("examples/roundtrip_geo.asc").unwrap();
+
+    assert_eq!(loaded.transform, geo.transform);
+    assert_eq!((loaded.grid.rows, loaded.grid.cols), (12, 8));
+    let prj = std::fs::read_to_string("examples/roundtrip_geo.prj").unwrap();
+    assert_eq!(Some(prj), geo.transform.crs);
+}
+
+#[test]
+fn test_ascii_grid_skips_prj_for_authority_code() {
+    ensure_examples_dir();
+    let transform =
+        nlmrs::GeoTransform { crs: Some("EPSG:4326".to_string()), ..Default::default() };
+    let geo = nlmrs::GeoGrid { grid: nlmrs::random(4, 4, Some(9)), transform };
+    let _ = std::fs::remove_file("examples/roundtrip_epsg.prj");
+    nlmrs::export::write_to_ascii_grid_geo(&geo, "examples/roundtrip_epsg.asc").unwrap();
+    assert!(!std::path::Path::new("examples/roundtrip_epsg.prj").exists());
+}
+
+#[test]
+fn test_read_ascii_grid_rows_starting_with_nan_or_inf() {
+    ensure_examples_dir();
+    std::fs::write(
+        "examples/roundtrip_nan_rows.asc",
+        "ncols 2\nnrows 3\nxllcorner 0\nyllcorner 0\ncellsize 1\nnan 1\ninf 2\n3 -inf\n",
+    )
+    .unwrap();
+    let grid = nlmrs::export::read_from_ascii_grid("examples/roundtrip_nan_rows.asc").unwrap();
+    assert_eq!((grid.rows, grid.cols), (3, 2));
+    assert!(grid[0][0].is_nan());
+    assert_eq!(grid[1][0], f64::INFINITY);
+    assert_eq!(grid[2][1], f64::NEG_INFINITY);
+}
+
+#[test]
+fn test_read_ascii_grid_cell_centre_header() {
+    ensure_examples_dir();
+    std::fs::write(
+        "examples/roundtrip_center.asc",
+        "NCOLS 3\nNROWS 2\nXLLCENTER 105\nYLLCENTER 205\nCELLSIZE 10\n1 2 3\n4 5 6\n",
+    )
+    .unwrap();
+    let geo = nlmrs::export::read_from_ascii_grid_geo("examples/roundtrip_center.asc").unwrap();
+
+    assert_eq!((geo.transform.x_min, geo.transform.y_min), (100.0, 200.0));
+    assert_eq!(geo.transform.crs, None);
+    assert_eq!(geo.grid[1][2], 6.0);
+    assert_eq!(geo.transform.cell_centre(2, 0, 0), (105.0, 215.0));
+}
+
+#[test]
+fn test_geotiff_round_trip_float64() {
+    ensure_examples_dir();
+    let mut grid = nlmrs::fbm_noise(30, 40, 4.0, 6, 0.5, 2.0, Some(10));
+    grid[3][7] = f64::NAN;
+    let transform = nlmrs::GeoTransform {
+        x_min: 423_000.0,
+        y_min: 112_250.0,
+        cell_size: 50.0,
+        crs: Some("EPSG:27700".to_string()),
+    };
+    let geo = nlmrs::GeoGrid { grid, transform };
+    nlmrs::export::write_to_geotiff(
+        &geo,
+        "examples/roundtrip_f64.tif",
+        nlmrs::export::TiffSampleFormat::Float64,
+    )
+    .unwrap();
+    let loaded = nlmrs::export::read_from_geotiff("examples/roundtrip_f64.tif").unwrap();
+
+    assert_eq!(loaded.transform, geo.transform);
+    assert_eq!((loaded.grid.rows, loaded.grid.cols), (30, 40));
+    assert!(loaded.grid.is_nodata(3, 7));
+    assert_eq!(loaded.grid.nodata_count(), 1);
+    for (a, b) in geo.grid.iter().zip(loaded.grid.iter()) {
+        assert!(a == b || (a.is_nan() && b.is_nan()), "value mismatch: {a} vs {b}");
+    }
+}
+
+#[test]
+fn test_geotiff_round_trip_float32() {
+    ensure_examples_dir();
+    let transform = nlmrs::GeoTransform {
+        x_min: -10.5,
+        y_min: 50.25,
+        cell_size: 0.01,
+        crs: Some("EPSG:4326".to_string()),
+    };
+    let geo = nlmrs::GeoGrid { grid: nlmrs::random(20, 10, Some(11)), transform };
+    nlmrs::export::write_to_geotiff(
+        &geo,
+        "examples/roundtrip_f32.tif",
+        nlmrs::export::TiffSampleFormat::Float32,
+    )
+    .unwrap();
+    let loaded = nlmrs::export::read_from_geotiff("examples/roundtrip_f32.tif").unwrap();
+
+    assert_eq!(loaded.transform.crs.as_deref(), Some("EPSG:4326"));
+    assert!((loaded.transform.x_min - -10.5).abs() < 1e-9);
+    assert!((loaded.transform.y_min - 50.25).abs() < 1e-9);
+    for (a, b) in geo.grid.iter().zip(loaded.grid.iter()) {
+        assert!((a - b).abs() < 1e-6, "value mismatch: {a} vs {b}");
+    }
+}
+
+#[test]
+fn test_geotiff_from_f32_grid() {
+    ensure_examples_dir();
+    let grid = nlmrs::fbm_noise(20, 10, 4.0, 6, 0.5, 2.0, Some(13)).to_f32();
+    let geo = nlmrs::GeoGrid { grid, transform: nlmrs::GeoTransform::default() };
+    nlmrs::export::write_to_geotiff(
+        &geo,
+        "examples/roundtrip_grid_f32.tif",
+        nlmrs::export::TiffSampleFormat::Float32,
+    )
+    .unwrap();
+    let loaded = nlmrs::export::read_from_geotiff("examples/roundtrip_grid_f32.tif").unwrap();
+    assert_eq!(loaded.grid.to_f32(), geo.grid);
+}
+
+#[test]
+fn test_label_grid_writers() {
+    ensure_examples_dir();
+    let mut grid = nlmrs::fbm_noise(30, 40, 4.0, 6, 0.5, 2.0, Some(14));
+    nlmrs::classify(&mut grid, 5);
+    grid[2][3] = f64::NAN;
+    let labels = nlmrs::LabelGrid::<u8>::from_grid(&grid).unwrap();
+    let transform = nlmrs::GeoTransform {
+        x_min: 500.0,
+        y_min: 1000.0,
+        cell_size: 30.0,
+        crs: Some("EPSG:32633".to_string()),
+    };
+    let expected: Vec<f64> = labels
+        .iter()
+        .map(|&l| if l == u8::MAX { f64::NAN } else { l as f64 })
+        .collect();
+    let same = |loaded: &nlmrs::Grid| {
+        loaded
+            .iter()
+            .zip(&expected)
+            .all(|(a, b)| a == b || (a.is_nan() && b.is_nan()))
+    };
+
+    nlmrs::export::write_labels_to_geotiff(&labels, &transform, "examples/roundtrip_labels.tif")
+        .unwrap();
+    let loaded = nlmrs::export::read_from_geotiff("examples/roundtrip_labels.tif").unwrap();
+    assert_eq!(loaded.transform, transform);
+    assert!(same(&loaded.grid));
+
+    nlmrs::export::write_labels_to_ascii_grid(&labels, &transform, "examples/roundtrip_labels.asc")
+        .unwrap();
+    let loaded = nlmrs::export::read_from_ascii_grid("examples/roundtrip_labels.asc").unwrap();
+    assert!(same(&loaded));
+
+    let palette = [[0, 0, 0], [50, 50, 50], [100, 100, 100], [150, 150, 150], [200, 200, 200]];
+    let path = "examples/roundtrip_labels.png";
+    nlmrs::export::write_labels_to_png(&labels, path, Some(&palette), true).unwrap();
+    let png = image::open(path).unwrap().to_rgba8();
+    let rgba = |c: [u8; 3]| [c[0], c[1], c[2], 255];
+    assert_eq!(png.dimensions(), (40 + 4 + 8, 30));
+    assert_eq!(png.get_pixel(3, 2).0[3], 0);
+    assert_eq!(png.get_pixel(7, 5).0, rgba(palette[labels[5][7] as usize]));
+    assert_eq!(png.get_pixel(42, 0).0[3], 0);
+    assert_eq!(png.get_pixel(51, 0).0, rgba(palette[4]));
+    assert_eq!(png.get_pixel(51, 29).0, rgba(palette[0]));
+    assert!(nlmrs::export::write_labels_to_png(&labels, path, Some(&palette[..2]), false).is_err());
+}
+
+#[test]
+fn test_png_colormap_and_legend() {
+    ensure_examples_dir();
+    let mut grid = nlmrs::planar_gradient(20, 32, Some(90.0), Some(15));
+    grid[4][4] = f64::NAN;
+    let viridis = nlmrs::export::Colormap::parse("viridis").unwrap();
+    let path = "examples/roundtrip_colormap.png";
+    nlmrs::export::write_to_png_colormap(&grid, path, &viridis, true).unwrap();
+    let png = image::open(path).unwrap().to_rgba8();
+    let rgba = |c: [u8; 3]| [c[0], c[1], c[2], 255];
+    assert_eq!(png.dimensions(), (32 + 4 + 8, 20));
+    assert_eq!(png.get_pixel(4, 4).0[3], 0);
+    assert_eq!(png.get_pixel(9, 2).0, rgba(viridis.color(grid[2][9])));
+    assert_eq!(png.get_pixel(43, 0).0, rgba(viridis.color(1.0)));
+    assert_eq!(png.get_pixel(43, 19).0, rgba(viridis.color(0.0)));
+
+    let options = nlmrs::export::WriteOptions {
+        colormap: nlmrs::export::Colormap::parse("set1").unwrap(),
+        labels: true,
+        ..Default::default()
+    };
+    let mut classes = grid.clone();
+    nlmrs::classify(&mut classes, 3);
+    let geo = nlmrs::GeoGrid { grid: classes, transform: Default::default() };
+    nlmrs::export::write(&geo, path, &options).unwrap();
+    let png = image::open(path).unwrap().to_rgba8();
+    assert_eq!(png.dimensions(), (32, 20));
+    assert_eq!(png.get_pixel(0, 0).0, [0xe4, 0x1a, 0x1c, 255]);
+}
+
+#[test]
+fn test_geotiff_custom_crs_and_plain_tiff() {
+    ensure_examples_dir();
+    let transform = nlmrs::GeoTransform {
+        crs: Some("+proj=utm +zone=33 +datum=WGS84".to_string()),
+        ..Default::default()
+    };
+    let geo = nlmrs::GeoGrid { grid: nlmrs::random(5, 5, Some(12)), transform };
+    nlmrs::export::write_to_geotiff(
+        &geo,
+        "examples/roundtrip_crs.tif",
+        nlmrs::export::TiffSampleFormat::Float32,
+    )
+    .unwrap();
+    let loaded = nlmrs::export::read_from_geotiff("examples/roundtrip_crs.tif").unwrap();
+    assert_eq!(loaded.transform, geo.transform);
+
+    // A 16-bit TIFF without geotags reads back with the default transform.
+    nlmrs::export::write_to_tiff(&geo.grid, "examples/roundtrip_u16.tif").unwrap();
+    let plain = nlmrs::export::read_from_geotiff("examples/roundtrip_u16.tif").unwrap();
+    assert_eq!(plain.transform, nlmrs::GeoTransform::default());
+    assert_eq!(plain.grid.nodata_count(), 0);
+}
+
+#[test]
+fn test_pipeline_writes_outputs() {
+    ensure_examples_dir();
+    let pipeline = nlmrs::pipeline::Pipeline::from_toml(
+        r#"
+        rows = 20
+        cols = 25
+        seed = 3
+
+        [[layers]]
+        name = "terrain"
+        algorithm = "fbm_noise"
+
+        [[steps]]
+        op = "classify"
+        target = "terrain"
+        classes = 4
+
+        [[outputs]]
+        layer = "terrain"
+        path = "examples/roundtrip_pipeline.csv"
+        "#,
+    )
+    .unwrap();
+    let layers = pipeline.execute().unwrap();
+    let loaded = nlmrs::export::read_from_csv("examples/roundtrip_pipeline.csv").unwrap();
+    assert_eq!((loaded.rows, loaded.cols), (20, 25));
+    for (a, b) in loaded.iter().zip(layers["terrain"].iter()) {
+        assert!((a - b).abs() < 1e-9, "value mismatch: {a} vs {b}");
+    }
+}
+
+#[test]
+fn test_sweep_manifest() {
+    ensure_examples_dir();
+    let percolation = nlmrs::registry::get("percolation").unwrap();
+    let sweeps = [nlmrs::sweep::Sweep::parse("p=0.4,0.6", percolation).unwrap()];
+    let params = nlmrs::registry::Params::new();
+    let runs = nlmrs::sweep::runs(percolation, &params, &sweeps, 2, 7).unwrap();
+    let paths = nlmrs::sweep::output_paths("p{p}_{replicate}.csv", percolation, &runs).unwrap();
+    nlmrs::sweep::write_manifest("examples/roundtrip_manifest.csv", percolation, 5, 6, &runs, &paths)
+        .unwrap();
+    let manifest = std::fs::read_to_string("examples/roundtrip_manifest.csv").unwrap();
+    let lines: Vec<&str> = manifest.lines().collect();
+    assert_eq!(lines[0], "path,algorithm,rows,cols,replicate,seed,p");
+    assert_eq!(lines[1], format!("p0.4_0.csv,percolation,5,6,0,{},0.4", runs[0].seed));
+    assert_eq!(lines[4], format!("p0.6_1.csv,percolation,5,6,1,{},0.6", runs[3].seed));
+}
+
+#[test]
+fn test_metadata_sidecar_reproduces_grid() {
+    ensure_examples_dir();
+    let pipeline = nlmrs::pipeline::Pipeline::from_toml(
+        r#"
+        rows = 12
+        cols = 9
+
+        [[layers]]
+        name = "terrain"
+        algorithm = "midpoint_displacement"
+        params = { h = 0.6 }
+
+        [[steps]]
+        op = "classify"
+        target = "terrain"
+        classes = 3
+
+        [[outputs]]
+        layer = "terrain"
+        path = "examples/roundtrip_metadata.csv"
+        metadata = true
+        "#,
+    )
+    .unwrap();
+    let layers = pipeline.execute().unwrap();
+
+    let metadata = nlmrs::export::read_metadata("examples/roundtrip_metadata.csv").unwrap();
+    assert_eq!(metadata.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(metadata.rng, nlmrs::RNG);
+    assert_eq!(metadata.layer, "terrain");
+    assert!(metadata.pipeline.seed.is_some(), "an unseeded pipeline gets a recorded seed");
+    assert!(metadata.pipeline.outputs.is_empty());
+    assert_eq!(metadata.reproduce().unwrap(), layers["terrain"]);
+
+    let sidecar = nlmrs::export::sidecar_path("examples/roundtrip_metadata.csv");
+    assert_eq!(nlmrs::export::read_metadata(&sidecar).unwrap(), metadata);
+    assert!(matches!(
+        nlmrs::export::Metadata::from_json("{\"layer\": \"a\"}"),
+        Err(nlmrs::NlmError::Config(_))
+    ));
+}
+
+#[test]
+fn test_tiled_writes_match_in_memory_grid() {
+    use nlmrs::export::{TileOptions, TiffSampleFormat};
+    use nlmrs::registry::Params;
+    use nlmrs::tiled::TiledNoise;
+
+    ensure_examples_dir();
+    let (rows, cols) = (70, 45);
+    let params = Params::new().with("octaves", 4);
+    let expected = nlmrs::fbm_noise(rows, cols, 4.0, 4, 0.5, 2.0, Some(3));
+    let noise = TiledNoise::new("fbm_noise", rows, cols, &params, Some(3)).unwrap();
+    let transform = nlmrs::GeoTransform {
+        x_min: 100.0,
+        y_min: 200.0,
+        cell_size: 30.0,
+        crs: Some("EPSG:32633".into()),
+    };
+    let options = TileOptions { tile_size: 32, tiff_format: TiffSampleFormat::Float64 };
+
+    noise.write("examples/roundtrip_tiled.tif", &transform, options).unwrap();
+    let tif = nlmrs::export::read_from_geotiff("examples/roundtrip_tiled.tif").unwrap();
+    assert_eq!(tif.grid, expected);
+    assert_eq!(tif.transform, transform);
+
+    noise.write("examples/roundtrip_tiled.asc", &transform, options).unwrap();
+    let asc = nlmrs::export::read_from_ascii_grid_geo("examples/roundtrip_tiled.asc").unwrap();
+    assert_eq!((asc.grid.rows, asc.grid.cols), (rows, cols));
+    assert!(asc.grid.iter().zip(expected.iter()).all(|(a, b)| (a - b).abs() < 1e-6));
+
+    let odd = TileOptions { tile_size: 20, ..options };
+    assert!(noise.write("examples/roundtrip_tiled.tif", &transform, odd).is_err());
+    assert!(noise.write("examples/roundtrip_tiled.png", &transform, options).is_err());
+}